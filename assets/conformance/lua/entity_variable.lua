@@ -0,0 +1 @@
+record(entity)
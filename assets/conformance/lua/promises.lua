@@ -0,0 +1,3 @@
+answer():and_then(function(value)
+	record(value)
+end)
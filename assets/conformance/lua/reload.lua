@@ -0,0 +1 @@
+record("evaluated")
@@ -0,0 +1,3 @@
+function fail()
+	error("failed on purpose")
+end
@@ -0,0 +1,3 @@
+function broken()
+	local x = = 1
+end
@@ -0,0 +1,33 @@
+-- Every turret runs in a Lua state of its own, so these globals belong to this turret only.
+-- `entity` is not available within promise callbacks, so it is kept in a local variable.
+local turret = entity
+local config = nil
+local cooldown = 0
+kills = 0
+
+turret_config():and_then(function(value)
+	config = value
+	log.info("Lua turret ready, range " .. config.range)
+end)
+
+function on_fixed_update(dt)
+	cooldown = cooldown - dt
+	if config == nil or cooldown > 0 then
+		return
+	end
+	cooldown = config.reload_time
+
+	transform.position(turret):and_then(function(position)
+		nearest_enemy(position, config.range):and_then(function(enemy)
+			if enemy then
+				transform.look_at(turret, enemy)
+				fire(turret, enemy, config.damage)
+			end
+		end)
+	end)
+end
+
+function on_enemy_destroyed(enemy)
+	kills = kills + 1
+	log.info("Lua turret destroyed enemy " .. enemy.index .. ", " .. kills .. " in total")
+end
@@ -0,0 +1,4 @@
+bind_action("dash", "Space")
+bind_action("jump", "KeyW")
+bind_action("jump", "ArrowUp")
+bind_action("fly", "NotAKey")
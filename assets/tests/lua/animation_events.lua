@@ -0,0 +1,3 @@
+function on_animation_event(event)
+	record(event)
+end
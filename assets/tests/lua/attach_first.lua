@@ -0,0 +1,5 @@
+record("attached first")
+
+function on_detach()
+	record("detached first")
+end
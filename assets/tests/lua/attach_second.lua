@@ -0,0 +1,5 @@
+record("attached second")
+
+function on_detach()
+	record("detached second")
+end
@@ -0,0 +1,2 @@
+takes_numbers("not a list")
+record("after")
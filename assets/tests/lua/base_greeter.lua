@@ -0,0 +1,9 @@
+greeting = "hello"
+
+function describe()
+	return "base"
+end
+
+function greet()
+	record(greeting .. " from " .. describe())
+end
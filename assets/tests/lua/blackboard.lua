@@ -0,0 +1,10 @@
+blackboard.set("alarm", true)
+blackboard.set("patrol", { speed = 2 })
+blackboard.get("target"):and_then(function(target)
+	record(target)
+end)
+blackboard.get("missing"):and_then(function(missing)
+	if missing == nil then
+		record("missing")
+	end
+end)
@@ -0,0 +1,2 @@
+define_item("sword")
+define_item("shield")
@@ -0,0 +1,15 @@
+State = {
+	sum = 0,
+	total = 0
+}
+
+function test_func()
+	rust_sum({ 1, 2, 3 })
+	rust_scores({ a = 1.5, b = 2.5 })
+	rust_vec():and_then(function(values)
+		State.sum = values[1] + values[2] + values[3]
+	end)
+	rust_map():and_then(function(map)
+		State.total = map.a + map.b
+	end)
+end
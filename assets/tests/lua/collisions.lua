@@ -0,0 +1,7 @@
+function on_collision_start(other)
+	record("start", other.index)
+end
+
+function on_collision_stop(other)
+	record("stop", other.index)
+end
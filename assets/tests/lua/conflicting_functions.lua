@@ -0,0 +1,6 @@
+greet():and_then(function(greeting)
+    record(greeting)
+end)
+plugin.greet():and_then(function(greeting)
+    record(greeting)
+end)
@@ -0,0 +1,3 @@
+data.load("tests/shared/balance.json"):and_then(function(balance)
+    record(balance)
+end)
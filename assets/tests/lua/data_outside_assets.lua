@@ -0,0 +1,5 @@
+for _, path in ipairs({ "../assets/tests/shared/balance.json", "tests/../../assets/tests/shared/balance.json", "embedded://tests/shared/balance.json" }) do
+    data.load(path):and_then(function(value)
+        record(value)
+    end)
+end
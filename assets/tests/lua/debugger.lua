@@ -0,0 +1,4 @@
+function test_func()
+	local x = 5
+	rust_func(x)
+end
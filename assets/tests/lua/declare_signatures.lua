@@ -0,0 +1,3 @@
+declare("give_item", {"string", "int"})
+declare("give_item", {"string", "string"})
+declare("take_item", {})
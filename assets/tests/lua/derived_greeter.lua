@@ -0,0 +1,5 @@
+function describe()
+	return "derived"
+end
+
+greet()
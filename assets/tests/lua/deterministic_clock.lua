@@ -0,0 +1,3 @@
+function test_func()
+	os.time()
+end
@@ -0,0 +1,3 @@
+function test_func()
+	rust_func(math.random(1, 1000000))
+end
@@ -0,0 +1,14 @@
+counter = 0
+
+function add(a, b)
+	return a + b
+end
+
+function bump()
+	counter = counter + 1
+	return counter
+end
+
+function notify(value)
+	record(value)
+end
@@ -0,0 +1 @@
+record(self_entity ~= nil and entity == nil)
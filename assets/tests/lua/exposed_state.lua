@@ -0,0 +1,6 @@
+function test_func()
+	state.get("GameState"):and_then(function(current)
+		record(current)
+		state.set("GameState", "Paused")
+	end)
+end
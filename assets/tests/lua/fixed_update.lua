@@ -0,0 +1,3 @@
+function on_fixed_update(dt)
+	record(dt)
+end
@@ -0,0 +1,6 @@
+log_event("loaded", 0)
+
+function test_func()
+	log_event("tick", 1)
+	log_event("tick", 2)
+end
@@ -0,0 +1,12 @@
+hierarchy.parent(entity):and_then(function(parent)
+	record(parent)
+end)
+hierarchy.children(entity):and_then(function(children)
+	record(children)
+end)
+hierarchy.find_child_by_name(entity, "Hand"):and_then(function(hand)
+	record(hand)
+end)
+hierarchy.find_child_by_name(entity, "Foot"):and_then(function(foot)
+	record(foot)
+end)
@@ -0,0 +1,9 @@
+http.get(SERVER_URL .. "/scores"):and_then(function(response)
+    record(response)
+end)
+http.post(SERVER_URL .. "/scores", "42"):and_then(function(response)
+    record(response)
+end)
+http.get("http://example.com/"):and_then(function(response)
+    record(response)
+end)
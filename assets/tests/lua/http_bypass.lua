@@ -0,0 +1,12 @@
+http.get("http://allowed.example.com:x@evil.com/"):and_then(function(response)
+    record(response)
+end)
+http.get("http://evil.com\\.example.com/"):and_then(function(response)
+    record(response)
+end)
+http.get("https://allowed.example.com/"):and_then(function(response)
+    record(response)
+end)
+http.get("http://allowed.example.com/"):and_then(function(response)
+    record(response)
+end)
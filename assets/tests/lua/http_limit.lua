@@ -0,0 +1,9 @@
+http.get("http://allowed.example.com/1"):and_then(function(response)
+    record(response)
+end)
+http.get("http://allowed.example.com/2"):and_then(function(response)
+    record(response)
+end)
+http.get("http://allowed.example.com/3"):and_then(function(response)
+    record(response)
+end)
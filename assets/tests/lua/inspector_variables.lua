@@ -0,0 +1,5 @@
+State = {
+	name = "player",
+	speed = 1.5,
+	alive = true,
+}
@@ -0,0 +1,8 @@
+for _, value in ipairs({ 255, 256, -1, 2.5 }) do
+	local ok, message = pcall(takes_byte, value)
+	if ok then
+		record("ok")
+	else
+		record(tostring(message))
+	end
+end
@@ -0,0 +1,3 @@
+function test_func()
+	rust_func() -- 
+end
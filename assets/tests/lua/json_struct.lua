@@ -0,0 +1,11 @@
+State = {
+	health = 0,
+	name = ""
+}
+
+function test_func()
+	heal({ health = 5, name = "hero" }):and_then(function(stats)
+		State.health = stats.health
+		State.name = stats.name
+	end)
+end
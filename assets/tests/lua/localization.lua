@@ -0,0 +1,10 @@
+localization.load("tests/shared/strings.json")
+t("menu.start", {}):and_then(function(text)
+	record(text)
+end)
+t("hud.score", { points = 10 }):and_then(function(text)
+	record(text)
+end)
+t("menu.quit", {}):and_then(function(text)
+	record(text)
+end)
@@ -0,0 +1,7 @@
+function test_func()
+	log.debug("debug message")
+	log.info("info message")
+	log.warn("warn message")
+	log.error("error message")
+	rust_func()
+end
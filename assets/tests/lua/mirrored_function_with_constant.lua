@@ -0,0 +1 @@
+rust_func(MAGIC)
@@ -0,0 +1,5 @@
+function on_init()
+end
+
+function on_updat()
+end
@@ -0,0 +1,10 @@
+local constants = require('shared.constants')
+
+return {
+	double = function(value)
+		return value * 2
+	end,
+	increment = function(value)
+		return value + constants.ONE
+	end,
+}
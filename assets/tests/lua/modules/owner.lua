@@ -0,0 +1,5 @@
+Owner = entity
+
+return {
+	index = entity.index,
+}
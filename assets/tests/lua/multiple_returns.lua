@@ -0,0 +1,15 @@
+State = {
+	x = nil,
+	y = nil
+}
+
+function test_func()
+	rust_func():and_then(function(x, y)
+		State.x = x
+		State.y = y
+	end)
+end
+
+function pair()
+	return 1, "two"
+end
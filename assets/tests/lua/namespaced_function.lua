@@ -0,0 +1,4 @@
+function test_func()
+	game.give_item(5)
+	game.take_item()
+end
@@ -0,0 +1,8 @@
+local function record_type(value)
+	record(math.type(value))
+end
+
+integer():and_then(record_type)
+float():and_then(record_type)
+echo(2):and_then(record_type)
+echo(2.0):and_then(record_type)
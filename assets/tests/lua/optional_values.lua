@@ -0,0 +1,17 @@
+State = {
+	found = 0,
+	missing = 0
+}
+
+function test_func()
+	find("a"):and_then(function(value)
+		State.found = value
+	end)
+	find("b"):and_then(function(value)
+		if value == nil then
+			State.missing = 1
+		end
+	end)
+	record(nil)
+	record(7)
+end
@@ -0,0 +1,2 @@
+record(tostring(EXACT))
+record(tostring(ROUNDED))
@@ -0,0 +1,5 @@
+function test_func()
+	move_to(1, 2)
+	move_to(3)
+	move_to("home")
+end
@@ -0,0 +1,2 @@
+panicking()
+record("after")
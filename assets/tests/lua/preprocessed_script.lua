@@ -0,0 +1,3 @@
+function test_func()
+	__FUNC__()
+end
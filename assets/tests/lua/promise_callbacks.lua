@@ -0,0 +1,5 @@
+for _ = 1, 4 do
+	next_step():and_then(function(step)
+		record(step)
+	end)
+end
@@ -0,0 +1,11 @@
+function test_func()
+	local promise = rust_func()
+	promise:and_then(function()
+		rust_callback()
+	end)
+	promise:cancel()
+
+	rust_func():timeout(0):and_then(function()
+		rust_callback()
+	end)
+end
@@ -0,0 +1,6 @@
+local function step(value)
+	record(value)
+	return value + 1
+end
+
+first_step():and_then(step):and_then(step):and_then(step):and_then(step)
@@ -0,0 +1,3 @@
+wait():and_then(function()
+	record_entity(entity)
+end)
@@ -0,0 +1,2 @@
+add(1, 2)
+game.greet("hero")
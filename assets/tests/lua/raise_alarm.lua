@@ -0,0 +1,2 @@
+blackboard.set("alarm", "raised")
+blackboard.set("patrol", { points = { 1, 2, 3 }, speed = 1.5 })
@@ -0,0 +1,11 @@
+function call_greet()
+    greet():and_then(function(greeting)
+        record(greeting)
+    end)
+end
+
+function call_plugin_greet()
+    plugin.greet():and_then(function(greeting)
+        record(greeting)
+    end)
+end
@@ -0,0 +1,9 @@
+local arithmetic = require("modules.arithmetic")
+local found, message = pcall(require, "modules.missing")
+
+State = {
+	doubled = arithmetic.double(21),
+	incremented = arithmetic.increment(41),
+	missing_blocked = (not found and string.find(message, "among script assets", 1, true) ~= nil) and 1 or 0,
+	loadlib_removed = package.loadlib == nil and 1 or 0,
+}
@@ -0,0 +1,7 @@
+-- Modules required in comments, like require("modules.missing"), are not read.
+local owner = require("modules.owner")
+
+State = {
+	owner_index = owner.index,
+	owner_global_index = Owner.index,
+}
@@ -0,0 +1,5 @@
+record("evaluated", 0)
+register_system("Update", function(delta)
+	record("tick", delta)
+end)
+declare("missing", {})
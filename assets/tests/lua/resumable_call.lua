@@ -0,0 +1,7 @@
+function test_func()
+	local sum = 0
+	for i = 1, 100000 do
+		sum = sum + i
+	end
+	rust_func(sum)
+end
@@ -0,0 +1,6 @@
+exact():and_then(function(value)
+	record("exact")
+end)
+rounded():and_then(function(value)
+	record("rounded")
+end)
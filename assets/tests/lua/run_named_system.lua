@@ -0,0 +1,4 @@
+function test_func()
+	run_system("respawn_all")
+	run_system("respawn_all")
+end
@@ -0,0 +1,17 @@
+State = {
+	times_suspended = 0,
+	times_resumed = 0,
+	times_updated = 0
+}
+
+function on_suspend()
+	State.times_suspended = State.times_suspended + 1
+end
+
+function on_resume()
+	State.times_resumed = State.times_resumed + 1
+end
+
+function on_update()
+	State.times_updated = State.times_updated + 1
+end
@@ -0,0 +1,9 @@
+define_component("Burning", { duration = 3 })
+components.add(entity, "Burning")
+components.has(entity, "Burning"):and_then(function(burning)
+	record(burning)
+end)
+
+function extinguish()
+	components.remove(entity, "Burning")
+end
@@ -0,0 +1,9 @@
+State = {
+	received = 0
+}
+
+function test_func()
+	on_event(function(value)
+		State.received = value
+	end)
+end
@@ -0,0 +1,3 @@
+function test_func()
+	rust_func(script.path, script.mod_name == nil)
+end
@@ -0,0 +1,7 @@
+function on_play(pressed)
+	record("play", pressed.index)
+end
+
+function on_quit(pressed)
+	record("quit", pressed.index)
+end
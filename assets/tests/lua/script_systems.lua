@@ -0,0 +1,12 @@
+function test_func()
+	register_system("Update", function(delta)
+		record("repeating", delta)
+	end)
+	local once
+	register_system("Update", function(delta)
+		record("once", delta)
+		unregister_system(once)
+	end):and_then(function(id)
+		once = id
+	end)
+end
@@ -0,0 +1,13 @@
+store({
+	alive = true,
+	level = 3,
+	ratio = 0.5,
+	name = "hero",
+	tags = { "brave", "tall" },
+	position = Vec3(1, 2, 3),
+	owner = entity,
+})
+
+function get_stats()
+	return { health = 10, items = { "sword" } }
+end
@@ -0,0 +1,9 @@
+physics.raycast(Vec3(0, 0, 0), Vec3(0, 0, -2), 10):and_then(function(hit)
+	record(hit)
+end)
+physics.raycast(Vec3(0, 0, 0), Vec3(0, 0, 1), 10):and_then(function(hit)
+	record(hit)
+end)
+physics.overlap_sphere(Vec3(0, 0, 0), 1):and_then(function(entities)
+	record(entities)
+end)
@@ -0,0 +1,5 @@
+record("entered")
+
+function on_exit()
+	record("exited")
+end
@@ -0,0 +1,3 @@
+function test_func()
+	local x = = 1
+end
@@ -0,0 +1,7 @@
+function on_ui()
+	record("ui")
+end
+
+function on_sim()
+	record("sim")
+end
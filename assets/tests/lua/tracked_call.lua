@@ -0,0 +1,5 @@
+function test_func()
+	rust_func():and_then(function()
+		rust_callback()
+	end)
+end
@@ -0,0 +1,9 @@
+transform.set_position(entity, Vec3(1, 2, 3))
+transform.rotate_y(entity, 0.5)
+transform.position(entity):and_then(function(position)
+	record(position)
+end)
+
+function face()
+	transform.look_at(entity, Vec3(1, 2, 10))
+end
@@ -0,0 +1,2 @@
+local ok, message = pcall(takes_number, "ten")
+record(tostring(message))
@@ -0,0 +1 @@
+mod_loaded("addon")
@@ -0,0 +1 @@
+mod_loaded("outdated")
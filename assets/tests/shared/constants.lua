@@ -0,0 +1,3 @@
+return {
+	ONE = 1,
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use bevy_scriptum::{prelude::*, runtimes::rhai::prelude::*, ScriptingSettings};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const SCRIPT: &str = r#"
+let sum = 0;
+for i in 0..2000 {
+    sum += i * i;
+}
+"#;
+
+fn build_app(scripts: usize, parallel_eval: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins((AssetPlugin::default(), TaskPoolPlugin::default()))
+        .add_scripting::<RhaiRuntime>(|_| {});
+    app.world_mut()
+        .resource_mut::<ScriptingSettings<RhaiRuntime>>()
+        .parallel_eval = parallel_eval;
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<RhaiScript>>()
+        .add(RhaiScript::from(SCRIPT.to_string()));
+    for _ in 0..scripts {
+        app.world_mut().spawn(Script::new(handle.clone()));
+    }
+    app
+}
+
+fn parallel_eval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eval_rhai");
+    for scripts in [100, 500] {
+        for parallel_eval in [false, true] {
+            let name = if parallel_eval { "parallel" } else { "serial" };
+            group.bench_with_input(BenchmarkId::new(name, scripts), &scripts, |b, &scripts| {
+                b.iter_batched(
+                    || build_app(scripts, parallel_eval),
+                    |mut app| app.update(),
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parallel_eval);
+criterion_main!(benches);
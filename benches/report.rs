@@ -0,0 +1,79 @@
+//! Prints a Markdown table of mean times measured by the last run of `runtimes` benchmarks,
+//! with a column for every runtime. Changes against the run before are included, so running the
+//! benchmarks before and after a change shows its effect on performance.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+/// Benchmarks of `runtimes.rs`, in the order they get printed.
+const BENCHMARKS: [&str; 5] = [
+    "eval",
+    "call_fn",
+    "callback_dispatch",
+    "nullary_dispatch",
+    "promise_resolution",
+];
+
+/// Returns the point estimate of provided statistic in a criterion estimates file.
+fn estimate(path: &Path, statistic: &str) -> Option<f64> {
+    let estimates: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    estimates[statistic]["point_estimate"].as_f64()
+}
+
+fn format_time(nanoseconds: f64) -> String {
+    if nanoseconds >= 1_000_000.0 {
+        format!("{:.2} ms", nanoseconds / 1_000_000.0)
+    } else if nanoseconds >= 1_000.0 {
+        format!("{:.2} µs", nanoseconds / 1_000.0)
+    } else {
+        format!("{nanoseconds:.0} ns")
+    }
+}
+
+fn main() {
+    let criterion = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/criterion");
+    let mut cells = BTreeMap::new();
+    let mut runtimes = BTreeSet::new();
+    for benchmark in BENCHMARKS {
+        let Ok(entries) = fs::read_dir(criterion.join(benchmark)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let runtime = entry.file_name().to_string_lossy().to_string();
+            let Some(mean) = estimate(&entry.path().join("new/estimates.json"), "mean") else {
+                continue;
+            };
+            let mut cell = format_time(mean);
+            if let Some(change) = estimate(&entry.path().join("change/estimates.json"), "mean") {
+                cell.push_str(&format!(" ({:+.1}%)", change * 100.0));
+            }
+            cells.insert((benchmark, runtime.clone()), cell);
+            runtimes.insert(runtime);
+        }
+    }
+    if cells.is_empty() {
+        println!("No results found, run `cargo bench --bench runtimes` first.");
+        return;
+    }
+
+    println!(
+        "| benchmark | {} |",
+        runtimes.iter().cloned().collect::<Vec<_>>().join(" | ")
+    );
+    println!("|---|{}", "---|".repeat(runtimes.len()));
+    for benchmark in BENCHMARKS {
+        let row = runtimes
+            .iter()
+            .map(|runtime| {
+                cells
+                    .get(&(benchmark, runtime.clone()))
+                    .map_or("-", String::as_str)
+            })
+            .collect::<Vec<_>>();
+        println!("| {benchmark} | {} |", row.join(" | "));
+    }
+}
@@ -0,0 +1,191 @@
+//! Benchmarks of every enabled runtime: evaluating scripts, calling script functions from Rust,
+//! dispatching calls that scripts make to registered functions and resolving promises of calls.
+//! Run `cargo bench --bench report` afterwards to print a table comparing the runtimes.
+
+use bevy::prelude::*;
+use bevy_scriptum::{apply_script_calls, prelude::*, FuncArgs, Runtime, ScriptingRuntimeBuilder};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+/// Number of scripts evaluated in a single benchmark iteration.
+const SCRIPTS: usize = 100;
+
+/// A runtime along with a script defining functions that the benchmarks call.
+trait BenchedRuntime: Runtime
+where
+    Self::ScriptAsset: From<String>,
+    (): for<'a> FuncArgs<'a, Self::Value, Self>,
+    (i64, i64): for<'a> FuncArgs<'a, Self::Value, Self>,
+{
+    const NAME: &'static str;
+    /// Defines `add(a, b)`, `call_rust()` calling `rust_fn` 100 times, `call_nullary()` calling
+    /// `tick` 100 times and `resolve_promises()` calling `rust_fn` with a callback attached to
+    /// every call.
+    const SCRIPT: &'static str;
+
+    /// Registers `rust_fn` returning a number and `tick` taking and returning nothing.
+    fn add_functions(runtime: ScriptingRuntimeBuilder<Self>);
+}
+
+#[cfg(any(feature = "lua", feature = "lua54", feature = "luau"))]
+impl BenchedRuntime for bevy_scriptum::runtimes::lua::LuaRuntime {
+    const NAME: &'static str = "lua";
+    const SCRIPT: &'static str = r#"
+function add(a, b)
+    return a + b
+end
+
+function call_rust()
+    for i = 1, 100 do
+        rust_fn()
+    end
+end
+
+function call_nullary()
+    for i = 1, 100 do
+        tick()
+    end
+end
+
+function resolve_promises()
+    for i = 1, 100 do
+        rust_fn():and_then(function(value) end)
+    end
+end
+"#;
+
+    fn add_functions(runtime: ScriptingRuntimeBuilder<Self>) {
+        runtime
+            .add_function(String::from("rust_fn"), || 1)
+            .add_function(String::from("tick"), || {});
+    }
+}
+
+#[cfg(feature = "rhai")]
+impl BenchedRuntime for bevy_scriptum::runtimes::rhai::RhaiRuntime {
+    const NAME: &'static str = "rhai";
+    const SCRIPT: &'static str = r#"
+fn add(a, b) {
+    a + b
+}
+
+fn call_rust() {
+    for i in 0..100 {
+        rust_fn();
+    }
+}
+
+fn call_nullary() {
+    for i in 0..100 {
+        tick();
+    }
+}
+
+fn resolve_promises() {
+    for i in 0..100 {
+        rust_fn().then(|value| {});
+    }
+}
+"#;
+
+    fn add_functions(runtime: ScriptingRuntimeBuilder<Self>) {
+        runtime
+            .add_function(String::from("rust_fn"), || 1)
+            .add_function(String::from("tick"), || {});
+    }
+}
+
+/// Builds an app with provided number of entities running the benchmark script, which have not
+/// been evaluated yet.
+fn build_app<R: BenchedRuntime>(scripts: usize) -> App
+where
+    R::ScriptAsset: From<String>,
+    (): for<'a> FuncArgs<'a, R::Value, R>,
+    (i64, i64): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    let mut app = App::new();
+    app.add_plugins((AssetPlugin::default(), TaskPoolPlugin::default()))
+        .add_scripting::<R>(R::add_functions);
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<R::ScriptAsset>>()
+        .add(R::ScriptAsset::from(R::SCRIPT.to_string()));
+    for _ in 0..scripts {
+        app.world_mut().spawn(Script::new(handle.clone()));
+    }
+    app
+}
+
+/// Calls provided function of the only script of the app.
+fn call<R: BenchedRuntime>(
+    world: &mut World,
+    name: &str,
+    args: impl for<'a> FuncArgs<'a, R::Value, R>,
+) where
+    R::ScriptAsset: From<String>,
+    (): for<'a> FuncArgs<'a, R::Value, R>,
+    (i64, i64): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    world.resource_scope(|world, runtime: Mut<R>| {
+        let mut scripts = world.query::<(Entity, &mut R::ScriptData)>();
+        let (entity, mut script_data) = scripts.single_mut(world);
+        runtime
+            .call_fn(name, &mut script_data, entity, args)
+            .expect("Failed to call script function");
+    });
+}
+
+fn bench_runtime<R: BenchedRuntime>(c: &mut Criterion)
+where
+    R::ScriptAsset: From<String>,
+    (): for<'a> FuncArgs<'a, R::Value, R>,
+    (i64, i64): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    c.benchmark_group("eval").bench_function(R::NAME, |b| {
+        b.iter_batched(
+            || build_app::<R>(SCRIPTS),
+            |mut app| app.update(),
+            BatchSize::LargeInput,
+        );
+    });
+
+    let mut app = build_app::<R>(1);
+    app.update();
+
+    c.benchmark_group("call_fn").bench_function(R::NAME, |b| {
+        b.iter(|| call::<R>(app.world_mut(), "add", (1, 2)));
+    });
+
+    c.benchmark_group("callback_dispatch")
+        .bench_function(R::NAME, |b| {
+            b.iter(|| {
+                call::<R>(app.world_mut(), "call_rust", ());
+                apply_script_calls::<R>(app.world_mut());
+            });
+        });
+
+    c.benchmark_group("nullary_dispatch")
+        .bench_function(R::NAME, |b| {
+            b.iter(|| {
+                call::<R>(app.world_mut(), "call_nullary", ());
+                apply_script_calls::<R>(app.world_mut());
+            });
+        });
+
+    c.benchmark_group("promise_resolution")
+        .bench_function(R::NAME, |b| {
+            b.iter(|| {
+                call::<R>(app.world_mut(), "resolve_promises", ());
+                apply_script_calls::<R>(app.world_mut());
+            });
+        });
+}
+
+fn runtimes(c: &mut Criterion) {
+    #[cfg(any(feature = "lua", feature = "lua54", feature = "luau"))]
+    bench_runtime::<bevy_scriptum::runtimes::lua::LuaRuntime>(c);
+    #[cfg(feature = "rhai")]
+    bench_runtime::<bevy_scriptum::runtimes::rhai::RhaiRuntime>(c);
+}
+
+criterion_group!(benches, runtimes);
+criterion_main!(benches);
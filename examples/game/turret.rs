@@ -0,0 +1,270 @@
+//! A small tower defense game in which turrets controlled by scripts shoot enemies marching
+//! towards the base. One turret is controlled by a Lua script and the other by a Rhai script,
+//! both of which keep state of their own, use promises returned by registered functions,
+//! receive game events and get a custom configuration type from Rust.
+//!
+//! The game runs without a window and exits once all enemies are destroyed, failing if any of
+//! them reached the base. With `file_watcher` feature of Bevy enabled, scripts in
+//! `assets/examples/game` can be edited while the game runs to change how turrets behave.
+
+use std::{collections::HashSet, time::Duration};
+
+use bevy::{app::ScheduleRunnerPlugin, prelude::*};
+use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::{lua::prelude::*, rhai::prelude::*};
+use bevy_scriptum::{EntityArgs, Json, Runtime, ScriptReloaded, ScriptValue};
+use serde::Serialize;
+
+/// Name of the script function called on a turret once a shot of it destroyed an enemy.
+const ON_ENEMY_DESTROYED_FN_NAME: &str = "on_enemy_destroyed";
+
+/// Distance from the base at which enemies appear.
+const SPAWN_DISTANCE: f32 = 14.0;
+
+#[derive(Component)]
+struct Enemy {
+    health: i64,
+}
+
+#[derive(Component)]
+struct Turret {
+    name: &'static str,
+    kills: u32,
+}
+
+/// Configuration of turrets, passed to scripts as a table in Lua and an object map in Rhai.
+#[derive(Serialize)]
+struct TurretConfig {
+    range: f32,
+    damage: i64,
+    reload_time: f32,
+}
+
+#[derive(Event)]
+struct Shot {
+    turret: Entity,
+    target: Entity,
+    damage: i64,
+}
+
+#[derive(Event)]
+struct EnemyDestroyed {
+    turret: Entity,
+    enemy: Entity,
+}
+
+/// Number of enemies that reached the base.
+#[derive(Resource, Default)]
+struct Breaches(u32);
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / 60.0,
+        )))
+        .add_event::<Shot>()
+        .add_event::<EnemyDestroyed>()
+        .init_resource::<Breaches>()
+        .add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .enable_fixed_update()
+                .enable_transform()
+                .add_function(String::from("turret_config"), turret_config)
+                .add_function(String::from("nearest_enemy"), nearest_enemy)
+                .add_function(String::from("fire"), fire)
+                .on_script_reloaded(report_reload::<LuaRuntime>);
+        })
+        .add_scripting::<RhaiRuntime>(|runtime| {
+            runtime
+                .enable_fixed_update()
+                .enable_transform()
+                .add_function(String::from("turret_config"), turret_config)
+                .add_function(String::from("nearest_enemy"), nearest_enemy)
+                .add_function(String::from("fire"), fire)
+                .on_script_reloaded(report_reload::<RhaiRuntime>);
+        })
+        // Every Lua turret gets a Lua state of its own, so their globals are kept separate.
+        .insert_resource(LuaRuntimeSettings {
+            isolation: IsolationMode::PerEntity,
+            ..Default::default()
+        })
+        .add_systems(Startup, startup)
+        .add_systems(
+            Update,
+            (
+                move_enemies,
+                apply_shots,
+                notify_turrets::<LuaRuntime>,
+                notify_turrets::<RhaiRuntime>,
+                end_game,
+            )
+                .chain(),
+        )
+        .run()
+}
+
+fn startup(mut commands: Commands, assets_server: Res<AssetServer>) {
+    commands.spawn((
+        Turret {
+            name: "Lua turret",
+            kills: 0,
+        },
+        Transform::from_xyz(0.0, 0.0, 3.0),
+        Script::<LuaScript>::new(assets_server.load("examples/game/turret.lua")),
+    ));
+    commands.spawn((
+        Turret {
+            name: "Rhai turret",
+            kills: 0,
+        },
+        Transform::from_xyz(0.0, 0.0, -3.0),
+        Script::<RhaiScript>::new(assets_server.load("examples/game/turret.rhai")),
+    ));
+    for i in 0..6 {
+        commands.spawn((
+            Enemy { health: 3 },
+            Transform::from_xyz(SPAWN_DISTANCE + i as f32 * 2.0, 0.0, 0.0),
+        ));
+    }
+}
+
+fn turret_config() -> Json<TurretConfig> {
+    Json(TurretConfig {
+        range: 10.0,
+        damage: 1,
+        reload_time: 0.4,
+    })
+}
+
+/// Returns the enemy closest to provided position within provided range, or nil if there is
+/// none. Entities are passed as [ScriptValue], so that the function works for every runtime.
+fn nearest_enemy(
+    In((position, range)): In<(ScriptValue, f64)>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+) -> ScriptValue {
+    let Some(position) = position.as_vec3() else {
+        return ScriptValue::Nil;
+    };
+    enemies
+        .iter()
+        .map(|(enemy, transform)| (enemy, transform.translation.distance(position)))
+        .filter(|(_, distance)| *distance <= range as f32)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(enemy, _)| ScriptValue::Entity(enemy))
+        .unwrap_or_default()
+}
+
+fn fire(
+    In((turret, target, damage)): In<(ScriptValue, ScriptValue, i64)>,
+    mut shots: EventWriter<Shot>,
+) {
+    if let (Some(turret), Some(target)) = (turret.as_entity(), target.as_entity()) {
+        shots.send(Shot {
+            turret,
+            target,
+            damage,
+        });
+    }
+}
+
+fn move_enemies(
+    mut commands: Commands,
+    mut enemies: Query<(Entity, &mut Transform), With<Enemy>>,
+    mut breaches: ResMut<Breaches>,
+    time: Res<Time>,
+) {
+    for (enemy, mut transform) in &mut enemies {
+        transform.translation.x -= 1.5 * time.delta_seconds();
+        if transform.translation.x <= 0.0 {
+            info!("an enemy reached the base");
+            breaches.0 += 1;
+            commands.entity(enemy).despawn();
+        }
+    }
+}
+
+fn apply_shots(
+    mut commands: Commands,
+    mut shots: EventReader<Shot>,
+    mut destroyed: EventWriter<EnemyDestroyed>,
+    mut enemies: Query<&mut Enemy>,
+    mut turrets: Query<&mut Turret>,
+) {
+    let mut despawned = HashSet::new();
+    for shot in shots.read() {
+        let Ok(mut enemy) = enemies.get_mut(shot.target) else {
+            continue;
+        };
+        if despawned.contains(&shot.target) {
+            continue;
+        }
+        enemy.health -= shot.damage;
+        if enemy.health <= 0 {
+            despawned.insert(shot.target);
+            commands.entity(shot.target).despawn();
+            if let Ok(mut turret) = turrets.get_mut(shot.turret) {
+                turret.kills += 1;
+            }
+            destroyed.send(EnemyDestroyed {
+                turret: shot.turret,
+                enemy: shot.target,
+            });
+        }
+    }
+}
+
+/// Bridges [EnemyDestroyed] events to scripts of runtime `R`, calling `on_enemy_destroyed` of
+/// the turret that destroyed the enemy.
+fn notify_turrets<R: EntityArgs>(
+    mut events: EventReader<EnemyDestroyed>,
+    mut turrets: Query<&mut R::ScriptData, With<Turret>>,
+    scripting_runtime: Res<R>,
+) {
+    for event in events.read() {
+        let Ok(mut script_data) = turrets.get_mut(event.turret) else {
+            continue;
+        };
+        if let Err(e) = scripting_runtime.call_fn_if_exists(
+            ON_ENEMY_DESTROYED_FN_NAME,
+            &mut script_data,
+            event.turret,
+            R::entity_args(event.enemy),
+        ) {
+            error!("error notifying turret: {}", e);
+        }
+    }
+}
+
+fn report_reload<R: Runtime>(In(event): In<ScriptReloaded<R>>) {
+    info!(
+        "script of turret {:?} reloaded, version {}",
+        event.entity, event.new_version
+    );
+}
+
+fn end_game(
+    enemies: Query<(), With<Enemy>>,
+    turrets: Query<&Turret>,
+    breaches: Res<Breaches>,
+    time: Res<Time>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if time.elapsed_seconds() > 60.0 {
+        error!("turrets did not destroy all enemies in time");
+        exit.send(AppExit::error());
+    }
+    if !enemies.is_empty() {
+        return;
+    }
+    for turret in &turrets {
+        info!("{} destroyed {} enemies", turret.name, turret.kills);
+    }
+    if breaches.0 > 0 {
+        error!("{} enemies reached the base", breaches.0);
+        exit.send(AppExit::error());
+    } else {
+        info!("the base is safe");
+        exit.send(AppExit::Success);
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::lua::mlua::{UserData, UserDataMethods};
 use bevy_scriptum::runtimes::lua::prelude::*;
-use mlua::{UserData, UserDataMethods};
 
 fn main() {
     App::new()
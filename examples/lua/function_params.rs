@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::lua::mlua;
 use bevy_scriptum::runtimes::lua::prelude::*;
 
 fn main() {
@@ -6,12 +6,10 @@ fn main() {
     App::new()
         // This is just needed for headless console app, not needed for a regular bevy game
         // that uses a winit window
-        .set_runner(move |mut app: App| {
-            loop {
-                app.update();
-                if let Some(exit) = app.should_exit() {
-                    return exit;
-                }
+        .set_runner(move |mut app: App| loop {
+            app.update();
+            if let Some(exit) = app.should_exit() {
+                return exit;
             }
         })
         .add_plugins(DefaultPlugins)
@@ -0,0 +1,38 @@
+//! Runs a mod on a mobile device. Mods are read through the asset server, from assets packaged
+//! into the app. The Android asset source can't list directories, so the mod is listed
+//! explicitly and quits the app once it runs. Build it for Android with
+//! `cargo build --example mobile_rhai --features rhai --target aarch64-linux-android`, using a
+//! linker from the Android NDK.
+
+use std::time::Duration;
+
+use bevy::{
+    app::{AppExit, ScheduleRunnerPlugin},
+    log::LogPlugin,
+    prelude::*,
+};
+use bevy_scriptum::mods::ModManagerPlugin;
+use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::rhai::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                1.0 / 60.0,
+            ))),
+            AssetPlugin::default(),
+            LogPlugin::default(),
+        ))
+        .add_scripting::<RhaiRuntime>(|runtime| {
+            runtime.add_function(String::from("quit"), |mut exit: EventWriter<AppExit>| {
+                exit.send(AppExit::Success);
+            });
+        })
+        .add_plugins(
+            ModManagerPlugin::new("examples/rhai/mods")
+                .with_mods(["hello"])
+                .with_language::<RhaiRuntime>("rhai"),
+        )
+        .run();
+}
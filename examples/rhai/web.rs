@@ -0,0 +1,42 @@
+//! Runs a script in a web browser. Build it with
+//! `cargo run --example web_rhai --features rhai --target wasm32-unknown-unknown`, using a runner
+//! such as `wasm-server-runner`. The script is fetched from the `assets` directory over HTTP and
+//! logs to the browser console, then quits.
+
+use std::time::Duration;
+
+use bevy::{
+    app::{AppExit, ScheduleRunnerPlugin},
+    log::LogPlugin,
+    prelude::*,
+};
+use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::rhai::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                1.0 / 60.0,
+            ))),
+            AssetPlugin::default(),
+            LogPlugin::default(),
+        ))
+        .add_scripting::<RhaiRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("greeting"), || {
+                    String::from("hello browser, returned from Rust")
+                })
+                .add_function(String::from("quit"), |mut exit: EventWriter<AppExit>| {
+                    exit.send(AppExit::Success);
+                });
+        })
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(mut commands: Commands, assets_server: Res<AssetServer>) {
+    commands.spawn(Script::<RhaiScript>::new(
+        assets_server.load("examples/rhai/web.rhai"),
+    ));
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant},
+    utils::tracing,
+};
+
+/// Name of the function that scripts use to bind input actions to keys.
+pub(crate) const BIND_ACTION_FN_NAME: &str = "bind_action";
+
+/// A resource mapping names of input actions to keys they are bound to, so that scripts, e.g.
+/// ones of mods, can add controls without changes to the host, which checks whether actions are
+/// pressed with [ScriptActions::pressed] and [ScriptActions::just_pressed].
+///
+/// Scripts bind actions with `bind_action("dash", "Space")`, where the key is the name of a
+/// [KeyCode] variant, and binding an action again rebinds it to the new key. Requires
+/// [ScriptingRuntimeBuilder::enable_action_bindings](crate::ScriptingRuntimeBuilder::enable_action_bindings).
+#[derive(Resource, Default, Debug)]
+pub struct ScriptActions {
+    bindings: HashMap<String, KeyCode>,
+}
+
+impl ScriptActions {
+    /// Binds the action with provided name to a key, replacing its previous binding.
+    pub fn bind(&mut self, action: impl Into<String>, key: KeyCode) {
+        self.bindings.insert(action.into(), key);
+    }
+
+    /// Removes the binding of the action with provided name, returning its key.
+    pub fn unbind(&mut self, action: &str) -> Option<KeyCode> {
+        self.bindings.remove(action)
+    }
+
+    /// Returns the key the action with provided name is bound to, or `None` if it is not bound.
+    pub fn key(&self, action: &str) -> Option<KeyCode> {
+        self.bindings.get(action).copied()
+    }
+
+    /// Returns whether the key of the action with provided name is pressed.
+    pub fn pressed(&self, action: &str, input: &ButtonInput<KeyCode>) -> bool {
+        self.key(action).is_some_and(|key| input.pressed(key))
+    }
+
+    /// Returns whether the key of the action with provided name has been pressed this frame.
+    pub fn just_pressed(&self, action: &str, input: &ButtonInput<KeyCode>) -> bool {
+        self.key(action).is_some_and(|key| input.just_pressed(key))
+    }
+
+    /// Returns an iterator over names of bound actions and their keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &KeyCode)> {
+        self.bindings.iter()
+    }
+}
+
+/// Binds an action to the key with provided name, registered as `bind_action` function.
+pub(crate) fn bind_action(
+    In((action, key)): In<(String, String)>,
+    mut actions: ResMut<ScriptActions>,
+) {
+    match KeyCode::from_reflect(&DynamicEnum::new(key.as_str(), DynamicVariant::Unit)) {
+        Some(key) => actions.bind(action, key),
+        None => tracing::error!("can not bind action `{}` to unknown key `{}`", action, key),
+    }
+}
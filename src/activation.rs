@@ -0,0 +1,96 @@
+//! Suspending scripts of entities far from the player.
+//!
+//! Once enabled with
+//! [ScriptingRuntimeBuilder::enable_script_activation](crate::ScriptingRuntimeBuilder::enable_script_activation),
+//! scripted entities with [ScriptActivationRange] component get [ScriptSuspended] component
+//! inserted while no entity with [ScriptActivator] component, e.g. the player or the camera, is
+//! within their range. Their script gets its `on_suspend` function called when they get
+//! suspended and `on_resume` when they get resumed, if it defines them, and systems created with
+//! [run_script_fn_on_all](crate::run_script_fn_on_all) skip suspended entities.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//! use bevy_scriptum::{run_script_fn_on_all, ScriptActivationRange, ScriptActivator};
+//!
+//! fn setup(mut commands: Commands, assets_server: Res<AssetServer>) {
+//!     commands.spawn((TransformBundle::default(), ScriptActivator));
+//!     commands.spawn((
+//!         Script::<LuaScript>::new(assets_server.load("scripts/prop.lua")),
+//!         TransformBundle::default(),
+//!         ScriptActivationRange(50.0),
+//!     ));
+//! }
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.enable_script_activation();
+//!     })
+//!     .add_systems(Startup, setup)
+//!     .add_systems(Update, run_script_fn_on_all::<LuaRuntime, _>("on_update", ()));
+//! ```
+
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{FuncArgs, Runtime};
+
+/// Name of the function called on scripted entities that no activator is within range of anymore.
+pub const ON_SUSPEND_FN_NAME: &str = "on_suspend";
+
+/// Name of the function called on suspended scripted entities that an activator got within range of.
+pub const ON_RESUME_FN_NAME: &str = "on_resume";
+
+/// A component making the script of its entity suspended while no [ScriptActivator] is within
+/// provided distance of it.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ScriptActivationRange(pub f32);
+
+/// A component marking entities, like the player or the camera, that keep scripts of entities
+/// within their [ScriptActivationRange] active.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct ScriptActivator;
+
+/// A component inserted into scripted entities that are suspended because no [ScriptActivator]
+/// is within their [ScriptActivationRange].
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct ScriptSuspended;
+
+/// Suspends and resumes scripts of entities with [ScriptActivationRange], depending on whether any
+/// [ScriptActivator] is within their range.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_script_activation<R: Runtime>(
+    mut commands: Commands,
+    activators: Query<&GlobalTransform, With<ScriptActivator>>,
+    mut scripted_entities: Query<(
+        Entity,
+        &ScriptActivationRange,
+        &GlobalTransform,
+        &mut R::ScriptData,
+        Has<ScriptSuspended>,
+    )>,
+    scripting_runtime: Res<R>,
+) where
+    (): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    for (entity, range, transform, mut script_data, suspended) in &mut scripted_entities {
+        let active = activators
+            .iter()
+            .any(|activator| activator.translation().distance(transform.translation()) <= range.0);
+        let name = match (active, suspended) {
+            (false, false) => {
+                commands.entity(entity).insert(ScriptSuspended);
+                ON_SUSPEND_FN_NAME
+            }
+            (true, true) => {
+                commands.entity(entity).remove::<ScriptSuspended>();
+                ON_RESUME_FN_NAME
+            }
+            _ => continue,
+        };
+        if let Err(e) = scripting_runtime.call_fn_if_exists(name, &mut script_data, entity, ()) {
+            tracing::error!("error calling '{}' on entity {:?}: {}", name, entity, e);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::{animation::AnimationPlayer, prelude::*, utils::tracing};
+
+use crate::{FuncArgs, Runtime, ScriptAnimationEvents};
+
+/// Name of the function called when an animation passes a marker.
+pub(crate) const ON_ANIMATION_EVENT_FN_NAME: &str = "on_animation_event";
+
+/// Returns `true` if an animation moving from `previous` to `current` seek time passed `time`.
+/// `wrapped` tells whether the animation completed in between, getting back to its other end.
+fn passed(time: f32, previous: f32, current: f32, wrapped: bool, reversed: bool) -> bool {
+    match (wrapped, reversed) {
+        (false, false) => previous < time && time <= current,
+        (true, false) => previous < time || time <= current,
+        (false, true) => current <= time && time < previous,
+        (true, true) => time < previous || current <= time,
+    }
+}
+
+/// Calls script functions for markers that animations have passed since the previous frame.
+pub(crate) fn trigger_animation_events<R: Runtime>(
+    mut animated_entities: Query<(Entity, &AnimationPlayer, &mut ScriptAnimationEvents<R>)>,
+    mut scripted_entities: Query<&mut R::ScriptData>,
+    scripting_runtime: Res<R>,
+) where
+    (String,): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    for (entity, player, mut events) in &mut animated_entities {
+        let events = &mut *events;
+        let mut triggered = vec![];
+        for (&animation, active) in player.playing_animations() {
+            let reversed = active.is_playback_reversed();
+            let (previous, completions) = events
+                .previous
+                .get(&animation)
+                .copied()
+                .unwrap_or((if reversed { f32::INFINITY } else { -1.0 }, 0));
+            let current = active.seek_time();
+            let wrapped = active.completions() > completions;
+            triggered.extend(
+                events
+                    .markers
+                    .iter()
+                    .filter(|marker| marker.animation == animation)
+                    .filter(|marker| passed(marker.time, previous, current, wrapped, reversed))
+                    .map(|marker| marker.event.clone()),
+            );
+            events
+                .previous
+                .insert(animation, (current, active.completions()));
+        }
+        events
+            .previous
+            .retain(|animation, _| player.is_playing_animation(*animation));
+        if triggered.is_empty() {
+            continue;
+        }
+
+        let script = events.script.unwrap_or(entity);
+        let Ok(mut script_data) = scripted_entities.get_mut(script) else {
+            tracing::error!(
+                "animation of entity {:?} triggered events but {:?} has no script",
+                entity,
+                script
+            );
+            continue;
+        };
+        for event in triggered {
+            if let Err(e) = scripting_runtime.call_fn(
+                ON_ANIMATION_EVENT_FN_NAME,
+                &mut script_data,
+                script,
+                (event,),
+            ) {
+                tracing::error!(
+                    "error calling '{}' on entity {:?}: {}",
+                    ON_ANIMATION_EVENT_FN_NAME,
+                    script,
+                    e
+                );
+            }
+        }
+    }
+}
@@ -1,21 +1,328 @@
-use std::marker::PhantomData;
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+};
 
 use bevy::{
-    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt as _, LoadContext},
+    asset::{io::Reader, Asset, AssetLoader, AssetPath, AsyncReadExt as _, LoadContext},
+    ecs::system::Resource,
     utils::ConditionalSendFuture,
 };
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{decoders::ScriptDecoder, Runtime};
+
+/// A function that transforms the source of a script before it is compiled.
+/// It receives the source text and the path of the script asset.
+pub(crate) type ScriptPreprocessor = dyn Fn(String, &Path) -> String + Send + Sync;
+
+/// A resource that stores preprocessors applied to script assets of type `A`.
+/// The list is shared with [ScriptLoader], so preprocessors added later still apply
+/// to scripts loaded afterwards.
+#[derive(Resource)]
+pub(crate) struct ScriptPreprocessors<A: Asset> {
+    pub(crate) preprocessors: Arc<RwLock<Vec<Box<ScriptPreprocessor>>>>,
+    _phantom_data: PhantomData<A>,
+}
+
+impl<A: Asset> Default for ScriptPreprocessors<A> {
+    fn default() -> Self {
+        Self {
+            preprocessors: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// A resource that stores decoders for script assets of type `A`, keyed by file name suffix.
+#[derive(Resource)]
+pub(crate) struct ScriptDecoders<A: Asset> {
+    pub(crate) decoders: Vec<(&'static str, Arc<dyn ScriptDecoder>)>,
+    _phantom_data: PhantomData<A>,
+}
+
+impl<A: Asset> Default for ScriptDecoders<A> {
+    fn default() -> Self {
+        Self {
+            decoders: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// Byte order mark that some editors put at the start of UTF-8 files.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Settings of loading a script asset, which can be set for every asset in its `.meta` file or
+/// with [AssetServer::load_with_settings](bevy::asset::AssetServer::load_with_settings).
+/// Modules imported by a script are read with the settings of the script.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptLoaderSettings {
+    /// Replace invalid UTF-8 sequences with `U+FFFD` instead of failing to load the script, e.g.
+    /// for scripts saved in a legacy encoding.
+    pub lossy_utf8: bool,
+    /// Strip a UTF-8 byte order mark at the start of the script instead of failing to load it.
+    pub accept_bom: bool,
+}
+
+/// An error of loading a script asset, sent as a [ScriptError](crate::ScriptError) event for
+/// every entity that the script is attached to.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ScriptLoadError {
+    #[error("error reading script: {0}")]
+    Read(String),
+    #[error("error decoding script: {0}")]
+    Decode(String),
+    #[error("script is not valid UTF-8 at line {line}, column {column}")]
+    InvalidUtf8 {
+        /// Offset of the first invalid byte.
+        offset: usize,
+        /// Line of the first invalid byte, starting at 1.
+        line: usize,
+        /// Column of the first invalid byte, in bytes and starting at 1.
+        column: usize,
+    },
+    #[error(
+        "script starts with a byte order mark, which is only accepted with `accept_bom` setting"
+    )]
+    ByteOrderMark,
+}
+
+impl ScriptLoadError {
+    fn invalid_utf8(bytes: &[u8], offset: usize) -> Self {
+        let valid = &bytes[..offset];
+        let line_start = valid
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        Self::InvalidUtf8 {
+            offset,
+            line: valid.iter().filter(|byte| **byte == b'\n').count() + 1,
+            column: offset - line_start + 1,
+        }
+    }
+}
+
+/// Errors of script assets of type `A` that failed to load, along with their paths. The list is
+/// shared with [ScriptLoader], which pushes errors to it, so that they can be sent as events.
+#[derive(Resource)]
+pub(crate) struct ScriptLoadFailures<A: Asset> {
+    pub(crate) failures: Arc<Mutex<Vec<(AssetPath<'static>, ScriptLoadError)>>>,
+    _phantom_data: PhantomData<A>,
+}
+
+impl<A: Asset> Default for ScriptLoadFailures<A> {
+    fn default() -> Self {
+        Self {
+            failures: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// Returns provided extension as a string living as long as the program, which
+/// [AssetLoader::extensions] requires. Every distinct extension is allocated once, so loaders
+/// created again when runtimes are added back after
+/// [remove_scripting](crate::BuildScriptingRuntime::remove_scripting) reuse it.
+pub(crate) fn intern_extension(extension: String) -> &'static str {
+    static EXTENSIONS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut extensions = EXTENSIONS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("Failed to lock interned extensions");
+    if let Some(interned) = extensions.get(extension.as_str()) {
+        return interned;
+    }
+    let interned: &'static str = extension.leak();
+    extensions.insert(interned);
+    interned
+}
 
 /// A loader for script assets.
 pub struct ScriptLoader<A: Asset + From<String>> {
+    extensions: Vec<&'static str>,
+    preprocessors: Arc<RwLock<Vec<Box<ScriptPreprocessor>>>>,
+    decoders: Vec<(&'static str, Arc<dyn ScriptDecoder>)>,
+    failures: Arc<Mutex<Vec<(AssetPath<'static>, ScriptLoadError)>>>,
     _phantom_data: PhantomData<A>,
 }
 
-impl<A: Asset + From<String>> Default for ScriptLoader<A> {
-    fn default() -> Self {
+impl<A: Asset + From<String>> ScriptLoader<A> {
+    /// Create a loader that handles provided file extensions.
+    pub(crate) fn with_extensions(extensions: Vec<&'static str>) -> Self {
         Self {
+            extensions,
+            preprocessors: Default::default(),
+            decoders: Default::default(),
+            failures: Default::default(),
             _phantom_data: Default::default(),
         }
     }
+
+    /// Apply preprocessors from provided resource to every loaded script.
+    pub(crate) fn with_preprocessors(mut self, preprocessors: &ScriptPreprocessors<A>) -> Self {
+        self.preprocessors = preprocessors.preprocessors.clone();
+        self
+    }
+
+    /// Push errors of scripts that fail to load to provided resource.
+    pub(crate) fn with_failures(mut self, failures: &ScriptLoadFailures<A>) -> Self {
+        self.failures = failures.failures.clone();
+        self
+    }
+
+    /// Handle files with suffixes of provided decoders appended to loader extensions.
+    pub(crate) fn with_decoders(mut self, decoders: &ScriptDecoders<A>) -> Self {
+        let base_extensions = self.extensions.clone();
+        for (suffix, _) in &decoders.decoders {
+            for extension in &base_extensions {
+                self.extensions
+                    .push(intern_extension(format!("{extension}.{suffix}")));
+            }
+        }
+        self.decoders = decoders.decoders.clone();
+        self
+    }
+}
+
+impl<A: Asset + From<String>> ScriptLoader<A> {
+    /// Reads a script file, decoding it if its suffix has a registered decoder.
+    pub(crate) async fn read_source(
+        &self,
+        reader: &mut Reader<'_>,
+        path: &Path,
+        settings: &ScriptLoaderSettings,
+    ) -> Result<String, ScriptLoadError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| ScriptLoadError::Read(e.to_string()))?;
+        self.decode(bytes, path, settings)
+    }
+
+    /// Decodes contents of a script file if its suffix has a registered decoder, and turns them
+    /// into text according to provided settings.
+    fn decode(
+        &self,
+        mut bytes: Vec<u8>,
+        path: &Path,
+        settings: &ScriptLoaderSettings,
+    ) -> Result<String, ScriptLoadError> {
+        let suffix = path.extension().and_then(|extension| extension.to_str());
+        if let Some((_, decoder)) = self
+            .decoders
+            .iter()
+            .find(|(decoder_suffix, _)| Some(*decoder_suffix) == suffix)
+        {
+            bytes = decoder
+                .decode(bytes)
+                .map_err(|e| ScriptLoadError::Decode(e.to_string()))?;
+        }
+
+        if bytes.starts_with(UTF8_BOM) {
+            if !settings.accept_bom {
+                return Err(ScriptLoadError::ByteOrderMark);
+            }
+            bytes.drain(..UTF8_BOM.len());
+        }
+        if settings.lossy_utf8 {
+            return Ok(String::from_utf8_lossy(&bytes).into_owned());
+        }
+        String::from_utf8(bytes)
+            .map_err(|e| ScriptLoadError::invalid_utf8(e.as_bytes(), e.utf8_error().valid_up_to()))
+    }
+
+    /// Applies all registered preprocessors to the source of a script.
+    pub(crate) fn preprocess(&self, mut script_text: String, path: &Path) -> String {
+        for preprocessor in self
+            .preprocessors
+            .read()
+            .expect("Failed to lock preprocessors")
+            .iter()
+        {
+            script_text = preprocessor(script_text, path);
+        }
+        script_text
+    }
+}
+
+impl<A: Asset + ResolveImports> ScriptLoader<A> {
+    /// Reads a script file and turns it into a script asset, as loading it as an asset would.
+    /// Modules that the script imports, and modules imported by them, are read with `modules`.
+    pub(crate) async fn load_script(
+        &self,
+        reader: &mut Reader<'_>,
+        path: &Path,
+        modules: &mut impl ModuleReader,
+        settings: &ScriptLoaderSettings,
+    ) -> Result<A, ScriptLoadError> {
+        let script_text = self.read_source(reader, path, settings).await?;
+        let script_text = self.preprocess(script_text, path);
+
+        let mut imports = A::imports(&script_text, path);
+        let mut sources: Vec<(String, String)> = Vec::new();
+        while let Some((name, candidates)) = imports.pop() {
+            if sources.iter().any(|(loaded, _)| *loaded == name) {
+                continue;
+            }
+            // Modules that can't be read are left for the runtime to report, they may be
+            // provided in another way, e.g. by a script pack.
+            for module_path in candidates {
+                let Ok(bytes) = modules.read_module(&module_path).await else {
+                    continue;
+                };
+                let source = self.decode(bytes, module_path.path(), settings)?;
+                let source = self.preprocess(source, module_path.path());
+                imports.extend(A::imports(&source, path));
+                sources.push((name, source));
+                break;
+            }
+        }
+
+        Ok(A::with_imports(script_text, sources))
+    }
+}
+
+impl<A: Asset + From<String> + GetExtensions> Default for ScriptLoader<A> {
+    fn default() -> Self {
+        Self::with_extensions(A::extensions().to_vec())
+    }
+}
+
+/// Allows scripts to import other scripts as modules. Imported modules are read through the
+/// asset server while the importing script loads, so imports work with any asset source, e.g.
+/// embedded or web assets, and the script gets reloaded whenever a module it imports changes.
+pub trait ResolveImports: From<String> {
+    /// Returns names of modules imported by provided source, along with asset paths to read
+    /// every module from, in order of preference. `path` is the path of the script being loaded,
+    /// also when provided source is of a module it imports indirectly. By default scripts import
+    /// nothing.
+    fn imports(_source: &str, _path: &Path) -> Vec<(String, Vec<AssetPath<'static>>)> {
+        Vec::new()
+    }
+
+    /// Creates a script asset from the source of a script and sources of modules it imports,
+    /// keyed by their names. By default modules are ignored.
+    fn with_imports(source: String, _modules: Vec<(String, String)>) -> Self {
+        source.into()
+    }
+}
+
+/// Reads modules imported by scripts, see [ResolveImports].
+pub(crate) trait ModuleReader {
+    async fn read_module(&mut self, path: &AssetPath<'static>) -> anyhow::Result<Vec<u8>>;
+}
+
+impl ModuleReader for LoadContext<'_> {
+    async fn read_module(&mut self, path: &AssetPath<'static>) -> anyhow::Result<Vec<u8>> {
+        // Reading through the load context makes the script reload when the module changes.
+        Ok(self.read_asset_bytes(path.clone()).await?)
+    }
 }
 
 /// Allows providing an allow-list for extensions of AssetLoader for a Script
@@ -24,28 +331,75 @@ pub trait GetExtensions {
     fn extensions() -> &'static [&'static str];
 }
 
-impl<A: Asset + From<String> + GetExtensions> AssetLoader for ScriptLoader<A> {
+/// A resource that decides which runtime loads scripts with a given file extension
+/// when more than one runtime is enabled. It has to be inserted before calling
+/// `add_scripting` for the runtimes it affects.
+///
+/// Extensions that are not assigned explicitly are handled by the runtime whose
+/// script asset lists them in [GetExtensions].
+#[derive(Resource, Default, Clone)]
+pub struct ScriptExtensionPolicy {
+    assignments: HashMap<&'static str, TypeId>,
+}
+
+impl ScriptExtensionPolicy {
+    /// Assign provided extension to runtime `R`, taking it away from any other runtime.
+    pub fn assign<R: Runtime>(mut self, extension: &'static str) -> Self {
+        self.assignments.insert(extension, TypeId::of::<R>());
+        self
+    }
+
+    /// Returns `true` if scripts with provided extension are loaded by runtime `R`.
+    pub fn handles<R: Runtime>(&self, extension: &str) -> bool {
+        match self.assignments.get(extension) {
+            Some(runtime) => *runtime == TypeId::of::<R>(),
+            None => R::ScriptAsset::extensions().contains(&extension),
+        }
+    }
+
+    /// Returns all extensions that runtime `R` loads under this policy.
+    pub fn extensions_for<R: Runtime>(&self) -> Vec<&'static str> {
+        let mut extensions = R::ScriptAsset::extensions()
+            .iter()
+            .copied()
+            .filter(|extension| self.handles::<R>(extension))
+            .collect::<Vec<_>>();
+        for (extension, runtime) in &self.assignments {
+            if *runtime == TypeId::of::<R>() && !extensions.contains(extension) {
+                extensions.push(extension);
+            }
+        }
+        extensions
+    }
+}
+
+impl<A: Asset + ResolveImports + GetExtensions> AssetLoader for ScriptLoader<A> {
     type Asset = A;
-    type Settings = ();
-    type Error = anyhow::Error;
+    type Settings = ScriptLoaderSettings;
+    type Error = ScriptLoadError;
 
     fn load<'a>(
         &'a self,
         reader: &'a mut Reader,
-        _settings: &'a Self::Settings,
-        _load_context: &'a mut LoadContext,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
     ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
         Box::pin(async move {
-            let mut bytes = Vec::new();
-            reader.read_to_end(&mut bytes).await?;
-
-            let script_text = String::from_utf8(bytes.to_vec())?;
-            let rhai_script: A = script_text.into();
-            Ok(rhai_script)
+            let path = load_context.path().to_owned();
+            let result = self
+                .load_script(reader, &path, load_context, settings)
+                .await;
+            if let Err(e) = &result {
+                self.failures
+                    .lock()
+                    .expect("Failed to lock script load failures")
+                    .push((load_context.asset_path().clone_owned(), e.clone()));
+            }
+            result
         })
     }
 
     fn extensions(&self) -> &[&str] {
-        A::extensions()
+        &self.extensions
     }
 }
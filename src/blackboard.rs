@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::ScriptValue;
+
+/// Namespace of functions that scripts use to get and set values of the [Blackboard].
+pub(crate) const BLACKBOARD_NAMESPACE: &str = "blackboard";
+
+/// A resource storing values shared by all scripts, regardless of their entity and runtime, so
+/// that they can coordinate, e.g. by raising an alarm that every guard reacts to.
+///
+/// Scripts set values with `blackboard.set("alarm", true)` and get them with
+/// `blackboard.get("alarm")`, which returns a promise, in Rhai called as `blackboard::set` and
+/// `blackboard::get`. Values that are not set are `nil` in Lua and `()` in Rhai, and setting a
+/// value to them removes it. Values are stored as [ScriptValue], so that numbers, strings,
+/// booleans, tables and maps set from one language can be read from another. Requires
+/// [ScriptingRuntimeBuilder::enable_blackboard](crate::ScriptingRuntimeBuilder::enable_blackboard).
+#[derive(Resource, Default, Debug)]
+pub struct Blackboard {
+    values: HashMap<String, ScriptValue>,
+    /// Keys whose values changed since [BlackboardChanged] events were last sent.
+    changed: Vec<String>,
+}
+
+impl Blackboard {
+    /// Returns the value with provided key, or `None` if it is not set.
+    pub fn get(&self, key: &str) -> Option<&ScriptValue> {
+        self.values.get(key)
+    }
+
+    /// Sets the value with provided key, removing it if the value is [ScriptValue::Nil].
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<ScriptValue>) {
+        let key = key.into();
+        let value = value.into();
+        let previous = if value.is_nil() {
+            self.values.remove(&key)
+        } else {
+            self.values.insert(key.clone(), value.clone())
+        };
+        if previous.unwrap_or_default() != value && !self.changed.contains(&key) {
+            self.changed.push(key);
+        }
+    }
+
+    /// Removes the value with provided key, returning it.
+    pub fn remove(&mut self, key: &str) -> Option<ScriptValue> {
+        let previous = self.values.remove(key);
+        if previous.is_some() && !self.changed.iter().any(|changed| changed == key) {
+            self.changed.push(key.to_string());
+        }
+        previous
+    }
+
+    /// Returns an iterator over keys and values that are set.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ScriptValue)> {
+        self.values.iter()
+    }
+}
+
+/// An event sent in [PostUpdate] for every key of the [Blackboard] whose value changed during
+/// the frame, either from a script or from Rust.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct BlackboardChanged {
+    pub key: String,
+    /// The new value, [ScriptValue::Nil] if it was removed.
+    pub value: ScriptValue,
+}
+
+/// Sends [BlackboardChanged] events for values changed since the last run.
+pub(crate) fn send_blackboard_events(
+    mut blackboard: ResMut<Blackboard>,
+    mut events: EventWriter<BlackboardChanged>,
+) {
+    if blackboard.changed.is_empty() {
+        return;
+    }
+    let blackboard = &mut *blackboard;
+    for key in blackboard.changed.drain(..) {
+        let value = blackboard.values.get(&key).cloned().unwrap_or_default();
+        events.send(BlackboardChanged { key, value });
+    }
+}
+
+/// Returns the value with provided key, registered as `blackboard.get` function.
+pub(crate) fn get_blackboard_value(
+    In((key,)): In<(String,)>,
+    blackboard: Res<Blackboard>,
+) -> ScriptValue {
+    blackboard.get(&key).cloned().unwrap_or_default()
+}
+
+/// Sets the value with provided key, registered as `blackboard.set` function.
+pub(crate) fn set_blackboard_value(
+    In((key, value)): In<(String, ScriptValue)>,
+    mut blackboard: ResMut<Blackboard>,
+) {
+    blackboard.set(key, value);
+}
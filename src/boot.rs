@@ -0,0 +1,125 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, AssetPath, AsyncReadExt as _},
+    prelude::*,
+    tasks::block_on,
+    utils::tracing,
+};
+
+use crate::{
+    assets::{ModuleReader, ResolveImports, ScriptDecoders, ScriptLoader, ScriptLoaderSettings},
+    systems::{init_callbacks, process_calls},
+    Runtime, Script, ScriptInfo, ScriptingError,
+};
+
+/// A resource that stores paths of scripts added with
+/// [ScriptingRuntimeBuilder::add_boot_script](crate::ScriptingRuntimeBuilder::add_boot_script).
+#[derive(Resource)]
+pub(crate) struct BootScripts<R: Runtime> {
+    pub(crate) paths: Vec<AssetPath<'static>>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for BootScripts<R> {
+    fn default() -> Self {
+        Self {
+            paths: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// Reads boot scripts directly from their asset source and evaluates them, then runs calls
+/// they made to registered functions, so that their effects are visible before the first
+/// [Update]. Each script gets an entity of its own, just like a spawned [Script].
+pub(crate) fn eval_boot_scripts<R: Runtime>(world: &mut World) -> Result<(), ScriptingError> {
+    let paths = std::mem::take(&mut world.resource_mut::<BootScripts<R>>().paths);
+    if paths.is_empty() {
+        return Ok(());
+    }
+    init_callbacks::<R>(world)?;
+    for path in paths {
+        eval_script_blocking::<R>(world, &path);
+    }
+    process_calls::<R>(world)
+}
+
+/// Reads a script directly from its asset source and evaluates it on a new entity, blocking
+/// until it is done. Errors are logged and the entity is only kept if evaluation succeeds.
+pub(crate) fn eval_script_blocking<R: Runtime>(
+    world: &mut World,
+    path: &AssetPath<'static>,
+) -> Option<Entity> {
+    let loader = ScriptLoader::<R::ScriptAsset>::with_extensions(vec![])
+        .with_preprocessors(world.resource())
+        .with_decoders(world.resource::<ScriptDecoders<R::ScriptAsset>>());
+    let asset_server = world.resource::<AssetServer>().clone();
+    let script = match block_on(load_script(&asset_server, &loader, path)) {
+        Ok(script) => script,
+        Err(e) => {
+            tracing::error!("error loading script {}: {:?}", path, e);
+            return None;
+        }
+    };
+    let handle = world.resource_mut::<Assets<R::ScriptAsset>>().add(script);
+    let entity = world.spawn(Script::new(handle.clone())).id();
+    let info = ScriptInfo {
+        path: path.to_string(),
+        asset_id: handle.id().to_string(),
+        mod_name: None,
+    };
+    let script = world
+        .resource::<Assets<R::ScriptAsset>>()
+        .get(&handle)
+        .expect("Script asset has just been added");
+    match world.resource::<R>().eval(script, entity, &info) {
+        Ok(script_data) => {
+            world.entity_mut(entity).insert(script_data);
+            Some(entity)
+        }
+        Err(e) => {
+            tracing::error!("error running script {} {:?}", info.path, e);
+            world.despawn(entity);
+            None
+        }
+    }
+}
+
+async fn load_script<A: Asset + ResolveImports>(
+    asset_server: &AssetServer,
+    loader: &ScriptLoader<A>,
+    path: &AssetPath<'static>,
+) -> anyhow::Result<A> {
+    let mut reader = read_asset(asset_server, path).await?;
+    Ok(loader
+        .load_script(
+            &mut reader,
+            path.path(),
+            &mut SourceModuleReader(asset_server),
+            &ScriptLoaderSettings::default(),
+        )
+        .await?)
+}
+
+async fn read_asset<'a>(
+    asset_server: &'a AssetServer,
+    path: &'a AssetPath<'static>,
+) -> anyhow::Result<Box<Reader<'a>>> {
+    let source = asset_server.get_source(path.source())?;
+    Ok(source.reader().read(path.path()).await?)
+}
+
+/// Reads modules imported by scripts directly from their asset source.
+struct SourceModuleReader<'a>(&'a AssetServer);
+
+impl ModuleReader for SourceModuleReader<'_> {
+    async fn read_module(&mut self, path: &AssetPath<'static>) -> anyhow::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        read_asset(self.0, path)
+            .await?
+            .read_to_end(&mut bytes)
+            .await?;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use bevy::prelude::*;
+
+use crate::Runtime;
+
+type Entries = HashMap<(String, blake3::Hash), Arc<dyn Any + Send + Sync>>;
+
+/// A resource caching scripts compiled by runtime `R`, such as Rhai ASTs and Lua bytecode, keyed
+/// by the asset id of the script and a hash of the sources it has been compiled from. Entities
+/// spawned with a script that has already been compiled share the compiled script instead of
+/// compiling it again, while the script still runs for each of them, so every entity keeps its
+/// own state. Entries of a script are invalidated when it or one of its bases gets modified,
+/// removed or is no longer used.
+///
+/// The cache is enabled by default and can be disabled with
+/// [ScriptingRuntimeBuilder::disable_compiled_script_cache](crate::ScriptingRuntimeBuilder::disable_compiled_script_cache)
+/// or [CompiledScriptCache::set_enabled], in which case scripts get compiled every time they are
+/// evaluated.
+#[derive(Resource)]
+pub struct CompiledScriptCache<R: Runtime> {
+    entries: Arc<Mutex<Entries>>,
+    enabled: Arc<AtomicBool>,
+    compilations: Arc<AtomicUsize>,
+    _phantom_data: PhantomData<fn() -> R>,
+}
+
+impl<R: Runtime> Clone for CompiledScriptCache<R> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            enabled: self.enabled.clone(),
+            compilations: self.compilations.clone(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<R: Runtime> Default for CompiledScriptCache<R> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+            enabled: Arc::new(AtomicBool::new(true)),
+            compilations: Default::default(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<R: Runtime> CompiledScriptCache<R> {
+    /// Returns the number of compiled scripts in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether the cache holds no compiled scripts.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns how many times scripts have been compiled because they were not cached.
+    pub fn compilations(&self) -> usize {
+        self.compilations.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether compiled scripts get cached.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables caching compiled scripts. Disabling the cache removes all compiled
+    /// scripts from it.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    /// Removes all compiled scripts of the script with provided asset id.
+    pub fn invalidate(&self, id: AssetId<R::ScriptAsset>) {
+        let id = id.to_string();
+        self.lock().retain(|(asset_id, _), _| *asset_id != id);
+    }
+
+    /// Removes all compiled scripts.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the script compiled from sources with provided hash for the script with provided
+    /// asset id, compiling and caching it first if it is not cached yet, or just compiling it if
+    /// the cache is disabled.
+    pub(crate) fn get_or_compile<T: Clone + Send + Sync + 'static, E>(
+        &self,
+        asset_id: &str,
+        hash: blake3::Hash,
+        compile: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if !self.is_enabled() {
+            self.compilations.fetch_add(1, Ordering::Relaxed);
+            return compile();
+        }
+        let key = (asset_id.to_string(), hash);
+        let cached = self
+            .lock()
+            .get(&key)
+            .and_then(|compiled| compiled.downcast_ref::<T>().cloned());
+        if let Some(compiled) = cached {
+            return Ok(compiled);
+        }
+        self.compilations.fetch_add(1, Ordering::Relaxed);
+        let compiled = compile()?;
+        self.lock().insert(key, Arc::new(compiled.clone()));
+        Ok(compiled)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries
+            .lock()
+            .expect("Failed to lock compiled script cache")
+    }
+}
+
+/// Hashes sources that a script is compiled from, for [CompiledScriptCache].
+#[derive(Default)]
+pub(crate) struct SourceHasher(blake3::Hasher);
+
+impl SourceHasher {
+    /// Adds provided part of the sources. Parts are length-prefixed, so that moving bytes from one
+    /// part to another changes the hash.
+    pub(crate) fn update(&mut self, part: impl AsRef<[u8]>) -> &mut Self {
+        let part = part.as_ref();
+        self.0.update(&(part.len() as u64).to_le_bytes());
+        self.0.update(part);
+        self
+    }
+
+    pub(crate) fn finalize(&self) -> blake3::Hash {
+        self.0.finalize()
+    }
+}
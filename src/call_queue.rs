@@ -0,0 +1,220 @@
+//! Calling script functions from threads other than the one running the scripting schedule.
+//!
+//! Engines of runtimes are not meant to be used outside of systems: the Lua state of
+//! [LuaRuntime](crate::runtimes::lua::LuaRuntime), for example, is kept behind a mutex that the
+//! runtime locks without expecting other threads to hold it, and script data components can only
+//! be reached through the [World]. Threads that need to call scripts, like audio callbacks or
+//! network tasks, can instead queue calls through a [ScriptCallSender], taken from the
+//! [ScriptCallQueue] resource once the queue is enabled with
+//! [ScriptingRuntimeBuilder::enable_call_queue](crate::ScriptingRuntimeBuilder::enable_call_queue).
+//! Senders can be cloned and moved to other threads, and queued calls run in the runtime's
+//! schedule, in the order they were queued, right after new scripts get evaluated. Their
+//! arguments are passed as [ScriptValue]s, their results are discarded and errors are logged.
+//! Calls of scripts of suspended entities, see [ScriptSuspended], are dropped.
+//!
+//! The queue holds at most the number of calls it has been enabled with, so that threads queuing
+//! calls faster than the schedule runs them can't grow it without limit. Queuing a call while it
+//! is full fails with [ScriptCallError::Full], leaving it up to the thread to drop or retry it.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//! use bevy_scriptum::call_queue::DEFAULT_CALL_QUEUE_CAPACITY;
+//! use bevy_scriptum::{ScriptCallQueue, ScriptValue};
+//!
+//! fn start_network_task(queue: Res<ScriptCallQueue<LuaRuntime>>) {
+//!     let sender = queue.sender();
+//!     std::thread::spawn(move || {
+//!         // Received from the network.
+//!         let message = String::from("hello");
+//!         if let Err(e) = sender.call_all("on_message", vec![ScriptValue::from(message.as_str())]) {
+//!             eprintln!("dropped message: {e}");
+//!         }
+//!     });
+//! }
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.enable_call_queue(DEFAULT_CALL_QUEUE_CAPACITY);
+//!     })
+//!     .add_systems(Startup, start_network_task);
+//! ```
+
+use std::{
+    marker::PhantomData,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Mutex,
+    },
+};
+
+use bevy::{prelude::*, utils::tracing};
+use thiserror::Error;
+
+use crate::{FuncArgs, Runtime, ScriptSuspended, ScriptValue};
+
+/// A suggested capacity of the queue, for calls queued about as often as frames are run.
+pub const DEFAULT_CALL_QUEUE_CAPACITY: usize = 1024;
+
+/// An error that can occur when queuing a call with [ScriptCallSender].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptCallError {
+    #[error("script call queue is full")]
+    Full,
+    #[error("script call queue has been removed along with its runtime")]
+    Disconnected,
+}
+
+/// A call of a script function queued with [ScriptCallSender].
+struct QueuedCall {
+    /// Entity whose script gets called, or `None` to call scripts of all entities.
+    entity: Option<Entity>,
+    name: String,
+    args: Vec<ScriptValue>,
+}
+
+/// A handle queuing calls of script functions of runtime `R` from any thread, see the [module
+/// documentation](self).
+pub struct ScriptCallSender<R: Runtime> {
+    sender: SyncSender<QueuedCall>,
+    _phantom_data: PhantomData<fn() -> R>,
+}
+
+impl<R: Runtime> Clone for ScriptCallSender<R> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+impl<R: Runtime> ScriptCallSender<R> {
+    /// Queues a call of the function with provided name of the script attached to provided
+    /// entity, which is skipped if the entity is suspended by the time the call runs. Fails if
+    /// the queue is full or the runtime, along with its queue, has been removed.
+    pub fn call(
+        &self,
+        entity: Entity,
+        name: impl Into<String>,
+        args: Vec<ScriptValue>,
+    ) -> Result<(), ScriptCallError> {
+        self.send(Some(entity), name.into(), args)
+    }
+
+    /// Queues a call of the function with provided name of every script that defines it, skipping
+    /// suspended entities like [run_script_fn_on_all](crate::run_script_fn_on_all) does. Fails if
+    /// the queue is full or the runtime, along with its queue, has been removed.
+    pub fn call_all(
+        &self,
+        name: impl Into<String>,
+        args: Vec<ScriptValue>,
+    ) -> Result<(), ScriptCallError> {
+        self.send(None, name.into(), args)
+    }
+
+    fn send(
+        &self,
+        entity: Option<Entity>,
+        name: String,
+        args: Vec<ScriptValue>,
+    ) -> Result<(), ScriptCallError> {
+        self.sender
+            .try_send(QueuedCall { entity, name, args })
+            .map_err(|e| match e {
+                TrySendError::Full(_) => ScriptCallError::Full,
+                TrySendError::Disconnected(_) => ScriptCallError::Disconnected,
+            })
+    }
+}
+
+/// A resource holding calls of script functions of runtime `R` queued from other threads, see the
+/// [module documentation](self).
+#[derive(Resource)]
+pub struct ScriptCallQueue<R: Runtime> {
+    sender: SyncSender<QueuedCall>,
+    receiver: Mutex<Receiver<QueuedCall>>,
+    _phantom_data: PhantomData<fn() -> R>,
+}
+
+impl<R: Runtime> ScriptCallQueue<R> {
+    /// Creates a queue holding at most provided number of calls.
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Returns a handle queuing calls, which can be sent to other threads.
+    pub fn sender(&self) -> ScriptCallSender<R> {
+        ScriptCallSender {
+            sender: self.sender.clone(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+/// Runs calls queued since it last ran.
+#[allow(clippy::type_complexity)]
+pub(crate) fn run_queued_calls<R: Runtime>(
+    queue: Res<ScriptCallQueue<R>>,
+    runtime: Res<R>,
+    mut scripts: Query<(Entity, &mut R::ScriptData, Has<ScriptSuspended>)>,
+) where
+    Vec<ScriptValue>: for<'a> FuncArgs<'a, R::Value, R>,
+{
+    let receiver = queue
+        .receiver
+        .lock()
+        .expect("Failed to lock script call queue");
+    for call in receiver.try_iter() {
+        match call.entity {
+            Some(entity) => {
+                let Ok((entity, mut script_data, suspended)) = scripts.get_mut(entity) else {
+                    tracing::error!(
+                        "error calling queued '{}' on entity {:?}: entity has no evaluated script",
+                        call.name,
+                        entity
+                    );
+                    continue;
+                };
+                if suspended {
+                    continue;
+                }
+                if let Err(e) = runtime.call_fn(&call.name, &mut script_data, entity, call.args) {
+                    tracing::error!(
+                        "error calling queued '{}' on entity {:?}: {}",
+                        call.name,
+                        entity,
+                        e
+                    );
+                }
+            }
+            None => {
+                for (entity, mut script_data, suspended) in &mut scripts {
+                    if suspended {
+                        continue;
+                    }
+                    if let Err(e) = runtime.call_fn_if_exists(
+                        &call.name,
+                        &mut script_data,
+                        entity,
+                        call.args.clone(),
+                    ) {
+                        tracing::error!(
+                            "error calling queued '{}' on entity {:?}: {}",
+                            call.name,
+                            entity,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
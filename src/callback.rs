@@ -1,13 +1,25 @@
 use bevy::prelude::*;
-use core::any::TypeId;
-use std::sync::{Arc, Mutex};
+use core::any::{Any, TypeId};
+use std::{
+    borrow::Cow,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+};
 
-use crate::{promise::Promise, Runtime};
+use crate::{promise::Promise, Runtime, ScriptingError};
+
+/// A boxed callback system that takes the context of the call and arguments passed from script,
+/// failing if its return value can't be converted into a value of the runtime.
+type BoxedCallbackSystem<C, V> = Box<dyn System<In = (C, Vec<V>), Out = Result<V, ScriptingError>>>;
 
 /// A system that can be used to call a script function.
 pub struct CallbackSystem<R: Runtime> {
-    pub(crate) system: Box<dyn System<In = Vec<R::Value>, Out = R::Value>>,
+    pub(crate) system: BoxedCallbackSystem<R::CallContext, R::Value>,
     pub(crate) arg_types: Vec<TypeId>,
+    /// Names of Rust types of the arguments, for tooling describing registered functions.
+    pub(crate) arg_type_names: Vec<&'static str>,
+    /// Name of the Rust type the function returns.
+    pub(crate) return_type_name: &'static str,
 }
 
 pub(crate) struct FunctionCallEvent<C: Send, V: Send> {
@@ -17,42 +29,155 @@ pub(crate) struct FunctionCallEvent<C: Send, V: Send> {
 
 type Calls<C, V> = Arc<Mutex<Vec<FunctionCallEvent<C, V>>>>;
 
+/// An identifier of a registered function, unique within its runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct FunctionId(pub(crate) u32);
+
+/// Identifiers of functions that scripts have made calls to since calls were last processed,
+/// each pushed by the first call queued for its function.
+pub(crate) type PendingFunctions = Arc<Mutex<Vec<FunctionId>>>;
+
 /// A struct representing a Bevy system that can be called from a script.
 pub(crate) struct Callback<R: Runtime> {
+    pub(crate) id: FunctionId,
     pub(crate) name: String,
+    /// Namespace that scripts access the function through, if any.
+    pub(crate) namespace: Option<String>,
     pub(crate) system: Arc<Mutex<CallbackSystem<R>>>,
     pub(crate) calls: Calls<R::CallContext, R::Value>,
+    /// Capabilities a script needs to be granted to call this function.
+    pub(crate) capabilities: Vec<String>,
+    /// Name of the plugin that registered the function, if set with
+    /// [ScriptingRuntimeBuilder::set_function_source](crate::ScriptingRuntimeBuilder::set_function_source).
+    pub(crate) source: Option<String>,
 }
 
 impl<R: Runtime> Clone for Callback<R> {
     fn clone(&self) -> Self {
         Callback {
+            id: self.id,
             name: self.name.clone(),
+            namespace: self.namespace.clone(),
             system: self.system.clone(),
             calls: self.calls.clone(),
+            capabilities: self.capabilities.clone(),
+            source: self.source.clone(),
+        }
+    }
+}
+
+impl<R: Runtime> Callback<R> {
+    /// Returns the name of the function prefixed with its namespace, e.g. `game.give_item`.
+    /// Only allocates for functions registered within a namespace.
+    pub(crate) fn qualified_name(&self) -> Cow<'_, str> {
+        match &self.namespace {
+            Some(namespace) => Cow::Owned(format!("{namespace}.{}", self.name)),
+            None => Cow::Borrowed(&self.name),
         }
     }
 }
 
 impl<R: Runtime> CallbackSystem<R> {
+    /// Runs the system for provided call, moving its arguments out of it. A panic of the system,
+    /// or of conversions of its arguments and return value, is caught and returned as
+    /// [ScriptingError::FunctionPanicked], so that a script passing bad arguments can't crash
+    /// the app.
     pub(crate) fn call(
         &mut self,
-        call: &FunctionCallEvent<R::CallContext, R::Value>,
+        function: &str,
+        call: &mut FunctionCallEvent<R::CallContext, R::Value>,
         world: &mut World,
-    ) -> R::Value {
-        self.system.run(call.params.clone(), world)
+    ) -> Result<R::Value, ScriptingError> {
+        // Functions without parameters ignore arguments, so they are dropped in place instead of
+        // being handed over to the system.
+        let params = if self.arg_types.is_empty() {
+            Vec::new()
+        } else {
+            std::mem::take(&mut call.params)
+        };
+        catch_unwind(AssertUnwindSafe(|| {
+            self.system.run((call.promise.context(), params), world)
+        }))
+        .map_err(|panic| panicked(function, panic))?
+    }
+}
+
+/// Runs provided conversion with the engine of provided call context. A panic of the conversion
+/// unwinds only once the engine has been released, so that it doesn't poison the engine of
+/// runtimes that keep it behind a lock.
+fn convert<R: Runtime, T>(
+    runtime: &R,
+    context: &R::CallContext,
+    f: impl FnOnce(&R::RawEngine) -> T,
+) -> T {
+    runtime
+        .with_context_engine(context, |engine| {
+            catch_unwind(AssertUnwindSafe(|| f(engine)))
+        })
+        .unwrap_or_else(|panic| resume_unwind(panic))
+}
+
+/// Converts a caught panic of provided function into [ScriptingError::FunctionPanicked].
+pub(crate) fn panicked(function: &str, panic: Box<dyn Any + Send>) -> ScriptingError {
+    let message = match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => panic
+            .downcast_ref::<&str>()
+            .map_or_else(|| String::from("unknown panic"), ToString::to_string),
+    };
+    ScriptingError::FunctionPanicked {
+        function: function.to_string(),
+        message,
     }
 }
 
 /// Allows converting to a wrapper type that the library uses internally for data
-pub(crate) trait IntoRuntimeValueWithEngine<'a, V, R: Runtime> {
+pub trait IntoRuntimeValueWithEngine<'a, V, R: Runtime> {
     fn into_runtime_value_with_engine(value: V, engine: &'a R::RawEngine) -> R::Value;
+
+    /// Converts provided value the same way as
+    /// [IntoRuntimeValueWithEngine::into_runtime_value_with_engine], but returns an error instead
+    /// of panicking if the runtime can't hold the value, e.g. an integer out of range of its
+    /// numbers. Runtimes whose conversions can't fail don't need to override it.
+    fn try_into_runtime_value_with_engine(
+        value: V,
+        engine: &'a R::RawEngine,
+    ) -> Result<R::Value, ScriptingError> {
+        Ok(Self::into_runtime_value_with_engine(value, engine))
+    }
 }
 
 /// Allows converting from a wrapper type that the library uses internally for data to underlying
-/// concrete type.
-pub(crate) trait FromRuntimeValueWithEngine<'a, R: Runtime> {
-    fn from_runtime_value_with_engine(value: R::Value, engine: &'a R::RawEngine) -> Self;
+/// concrete type. Provided context is the one of the call that the value has been passed to.
+pub trait FromRuntimeValueWithEngine<'a, R: Runtime> {
+    fn from_runtime_value_with_engine(
+        value: R::Value,
+        engine: &'a R::RawEngine,
+        context: &R::CallContext,
+    ) -> Self;
+
+    /// Converts provided value the same way as
+    /// [FromRuntimeValueWithEngine::from_runtime_value_with_engine], but returns an error instead
+    /// of panicking if the value doesn't fit this type, e.g. an integer out of its range.
+    /// Runtimes whose conversions can't fail don't need to override it.
+    fn try_from_runtime_value_with_engine(
+        value: R::Value,
+        engine: &'a R::RawEngine,
+        context: &R::CallContext,
+    ) -> Result<Self, ScriptingError>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_runtime_value_with_engine(value, engine, context))
+    }
+
+    /// Type that the runtime uses to decide which values can be passed as this argument.
+    fn arg_type_id() -> TypeId
+    where
+        Self: 'static,
+    {
+        TypeId::of::<Self>()
+    }
 }
 
 /// Trait that alllows to convert a script callback function into a Bevy [`System`].
@@ -70,16 +195,20 @@ where
     fn into_callback_system(self, world: &mut World) -> CallbackSystem<R> {
         let mut inner_system = IntoSystem::into_system(self);
         inner_system.initialize(world);
-        let system_fn = move |_args: In<Vec<R::Value>>, world: &mut World| {
+        let system_fn = move |In((context, _args)): In<(R::CallContext, Vec<R::Value>)>,
+                              world: &mut World| {
             let result = inner_system.run((), world);
             inner_system.apply_deferred(world);
-            let mut runtime = world.get_resource_mut::<R>().expect("No runtime resource");
-            runtime
-                .with_engine_mut(move |engine| Out::into_runtime_value_with_engine(result, engine))
+            let runtime = world.get_resource::<R>().expect("No runtime resource");
+            convert(runtime, &context, move |engine| {
+                Out::try_into_runtime_value_with_engine(result, engine)
+            })
         };
         let system = IntoSystem::into_system(system_fn);
         CallbackSystem {
             arg_types: vec![],
+            arg_type_names: vec![],
+            return_type_name: std::any::type_name::<Out>(),
             system: Box::new(system),
         }
     }
@@ -97,23 +226,25 @@ macro_rules! impl_tuple {
             fn into_callback_system(self, world: &mut World) -> CallbackSystem<RN> {
                 let mut inner_system = IntoSystem::into_system(self);
                 inner_system.initialize(world);
-                let system_fn = move |args: In<Vec<RN::Value>>, world: &mut World| {
-                    let mut runtime = world.get_resource_mut::<RN>().expect("No runtime resource");
-                    let args  = runtime.with_engine_mut(move |engine| {
-                        (
-                            $($t::from_runtime_value_with_engine(args.get($idx).expect(&format!("Failed to get function argument for index {}", $idx)).clone(), engine), )+
-                        )
-                    });
+                let system_fn = move |In((context, args)): In<(RN::CallContext, Vec<RN::Value>)>, world: &mut World| {
+                    let runtime = world.get_resource::<RN>().expect("No runtime resource");
+                    let args = convert(runtime, &context, |engine| {
+                        Ok::<_, ScriptingError>((
+                            $($t::try_from_runtime_value_with_engine(args.get($idx).expect(&format!("Failed to get function argument for index {}", $idx)).clone(), engine, &context)?, )+
+                        ))
+                    })?;
                     let result = inner_system.run(args, world);
                     inner_system.apply_deferred(world);
-                    let mut runtime = world.get_resource_mut::<RN>().expect("No runtime resource");
-                    runtime.with_engine_mut(move |engine| {
-                        Out::into_runtime_value_with_engine(result, engine)
+                    let runtime = world.get_resource::<RN>().expect("No runtime resource");
+                    convert(runtime, &context, move |engine| {
+                        Out::try_into_runtime_value_with_engine(result, engine)
                     })
                 };
                 let system = IntoSystem::into_system(system_fn);
                 CallbackSystem {
-                    arg_types: vec![$(TypeId::of::<$t>(),)+],
+                    arg_types: vec![$(<$t as FromRuntimeValueWithEngine<'static, RN>>::arg_type_id(),)+],
+                    arg_type_names: vec![$(std::any::type_name::<$t>(),)+],
+                    return_type_name: std::any::type_name::<Out>(),
                     system: Box::new(system),
                 }
             }
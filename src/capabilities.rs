@@ -0,0 +1,102 @@
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use bevy::prelude::*;
+
+thread_local! {
+    static CURRENT_ENTITY: Cell<Option<Entity>> = const { Cell::new(None) };
+}
+
+/// Marks provided entity as the one whose script is currently running on this thread,
+/// until the returned guard is dropped.
+pub fn enter_entity(entity: Option<Entity>) -> EntityGuard {
+    EntityGuard(CURRENT_ENTITY.replace(entity))
+}
+
+/// Returns the entity whose script is currently running on this thread.
+pub fn current_entity() -> Option<Entity> {
+    CURRENT_ENTITY.get()
+}
+
+/// Restores previously running entity when dropped.
+pub struct EntityGuard(Option<Entity>);
+
+impl Drop for EntityGuard {
+    fn drop(&mut self) {
+        CURRENT_ENTITY.set(self.0);
+    }
+}
+
+/// A component that grants capabilities to the script of an entity. Functions registered
+/// with [FunctionRegistration::requires](crate::FunctionRegistration::requires) can only be
+/// called by scripts that have been granted all capabilities they require.
+#[derive(Component, Default, Clone, Debug)]
+pub struct ScriptCapabilities(HashSet<String>);
+
+impl ScriptCapabilities {
+    /// Creates a set of provided capabilities.
+    pub fn new<S: Into<String>>(capabilities: impl IntoIterator<Item = S>) -> Self {
+        Self(capabilities.into_iter().map(Into::into).collect())
+    }
+
+    /// Grants provided capability.
+    pub fn grant(&mut self, capability: impl Into<String>) {
+        self.0.insert(capability.into());
+    }
+
+    /// Revokes provided capability.
+    pub fn revoke(&mut self, capability: &str) {
+        self.0.remove(capability);
+    }
+
+    /// Returns `true` if provided capability has been granted.
+    pub fn has(&self, capability: &str) -> bool {
+        self.0.contains(capability)
+    }
+}
+
+/// A resource mirroring [ScriptCapabilities] of all entities, so they can be checked
+/// while a script runs, without access to the [World].
+#[derive(Resource, Default, Clone)]
+pub(crate) struct CapabilityGrants {
+    pub(crate) grants: Arc<RwLock<HashMap<Entity, ScriptCapabilities>>>,
+}
+
+impl CapabilityGrants {
+    /// Returns the first of provided capabilities that has not been granted to provided entity.
+    pub(crate) fn missing<'a>(
+        &self,
+        entity: Option<Entity>,
+        capabilities: &'a [String],
+    ) -> Option<&'a str> {
+        let grants = self
+            .grants
+            .read()
+            .expect("Failed to lock capability grants");
+        let granted = entity.and_then(|entity| grants.get(&entity));
+        capabilities
+            .iter()
+            .find(|capability| !granted.is_some_and(|granted| granted.has(capability)))
+            .map(String::as_str)
+    }
+}
+
+pub(crate) fn sync_capabilities(
+    grants: Res<CapabilityGrants>,
+    changed: Query<(Entity, &ScriptCapabilities), Changed<ScriptCapabilities>>,
+    mut removed: RemovedComponents<ScriptCapabilities>,
+) {
+    let mut grants = grants
+        .grants
+        .write()
+        .expect("Failed to lock capability grants");
+    for entity in removed.read() {
+        grants.remove(&entity);
+    }
+    for (entity, capabilities) in &changed {
+        grants.insert(entity, capabilities.clone());
+    }
+}
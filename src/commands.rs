@@ -0,0 +1,84 @@
+use bevy::{asset::AssetPath, ecs::system::EntityCommands, prelude::*, utils::tracing};
+
+use crate::{FuncArgs, Runtime, Script};
+
+/// Name of the function that a script can define to run when it is detached from its entity.
+pub(crate) const ON_DETACH_FN_NAME: &str = "on_detach";
+
+/// An extension trait for [EntityCommands] that allows swapping scripts of entities at runtime.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Guard;
+///
+/// fn alert_guards(mut commands: Commands, guards: Query<Entity, With<Guard>>) {
+///     for guard in &guards {
+///         commands
+///             .entity(guard)
+///             .attach_script::<LuaRuntime>("ai/alerted_guard.lua");
+///     }
+/// }
+/// ```
+pub trait ScriptEntityCommands {
+    /// Loads the script with provided path and evaluates it for the entity once it is loaded.
+    /// A script of the same runtime that the entity already runs is detached first.
+    fn attach_script<R: Runtime>(&mut self, path: impl Into<AssetPath<'static>>) -> &mut Self
+    where
+        (): for<'a> FuncArgs<'a, R::Value, R>;
+
+    /// Calls `on_detach` function of the script that the entity runs, if it defines one, then
+    /// removes the script along with its data. The entity itself is kept.
+    fn detach_script<R: Runtime>(&mut self) -> &mut Self
+    where
+        (): for<'a> FuncArgs<'a, R::Value, R>;
+}
+
+impl ScriptEntityCommands for EntityCommands<'_> {
+    fn attach_script<R: Runtime>(&mut self, path: impl Into<AssetPath<'static>>) -> &mut Self
+    where
+        (): for<'a> FuncArgs<'a, R::Value, R>,
+    {
+        let path = path.into();
+        self.add(move |entity: Entity, world: &mut World| {
+            detach_script::<R>(entity, world);
+            let handle = world.resource::<AssetServer>().load::<R::ScriptAsset>(path);
+            world.entity_mut(entity).insert(Script::new(handle));
+        })
+    }
+
+    fn detach_script<R: Runtime>(&mut self) -> &mut Self
+    where
+        (): for<'a> FuncArgs<'a, R::Value, R>,
+    {
+        self.add(detach_script::<R>)
+    }
+}
+
+fn detach_script<R: Runtime>(entity: Entity, world: &mut World)
+where
+    (): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    world.resource_scope(|world, runtime: Mut<R>| {
+        let Some(mut script_data) = world.get_mut::<R::ScriptData>(entity) else {
+            return;
+        };
+        if let Err(e) = runtime.call_fn_if_exists(ON_DETACH_FN_NAME, &mut script_data, entity, ()) {
+            tracing::error!(
+                "error calling '{}' on entity {:?}: {}",
+                ON_DETACH_FN_NAME,
+                entity,
+                e
+            );
+        }
+    });
+    let Some(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    entity.remove::<(Script<R::ScriptAsset>, R::ScriptData)>();
+    #[cfg(feature = "inspector")]
+    entity.remove::<crate::inspector::ScriptVariables>();
+}
@@ -1,14 +1,169 @@
+#[cfg(feature = "animation")]
+use std::collections::HashMap;
+#[cfg(any(feature = "ui", feature = "animation"))]
+use std::marker::PhantomData;
+
 use bevy::prelude::*;
+use smallvec::SmallVec;
+
+#[cfg(feature = "animation")]
+use bevy::animation::graph::AnimationNodeIndex;
+
+#[cfg(any(feature = "ui", feature = "animation"))]
+use crate::Runtime;
 
 /// A component that represents a script.
 #[derive(Component)]
 pub struct Script<A: Asset> {
     pub script: Handle<A>,
+    /// Base scripts evaluated in order before the script, see [Script::with_base].
+    pub bases: Vec<Handle<A>>,
 }
 
 impl<A: Asset> Script<A> {
     /// Create a new script component from a handle to a [Script] obtained using [AssetServer].
     pub fn new(script: Handle<A>) -> Self {
-        Self { script }
+        Self {
+            script,
+            bases: Vec::new(),
+        }
+    }
+
+    /// Adds a base script that is evaluated before the script in the same environment of the
+    /// entity, so that the script can use its variables and functions and redefine them.
+    /// The script is evaluated once all of its bases are loaded.
+    pub fn with_base(mut self, base: Handle<A>) -> Self {
+        self.bases.push(base);
+        self
+    }
+}
+
+/// A component naming the mod that a script belongs to. It is inserted by
+/// [ModManager](crate::mods::ModManager) and exposed to scripts as `script.mod_name`.
+#[derive(Component, Clone, Debug)]
+pub struct ScriptMod {
+    pub name: String,
+}
+
+/// Information about a script being evaluated, exposed to scripts as the `script` global variable.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScriptInfo {
+    /// Asset path of the script.
+    pub path: String,
+    /// Asset id of the script.
+    pub asset_id: String,
+    /// Name of the mod that the script belongs to, see [ScriptMod].
+    pub mod_name: Option<String>,
+}
+
+/// A component assigning tags to a scripted entity, e.g. `"ui"` or `"sim"`, so that a single
+/// runtime can run different kinds of scripts in different schedules. Systems created with
+/// [run_script_fn_on_tagged](crate::run_script_fn_on_tagged), or added with
+/// [ScriptingRuntimeBuilder::run_tagged_scripts_in](crate::ScriptingRuntimeBuilder::run_tagged_scripts_in),
+/// only call functions of scripts with their tag.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScriptTags(pub SmallVec<[String; 2]>);
+
+impl ScriptTags {
+    /// Creates a component with provided tags.
+    pub fn new(tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self(tags.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns whether provided tag is one of the tags.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|t| t == tag)
+    }
+}
+
+/// A component that calls a function of a script of runtime `R` when [Interaction] of its entity
+/// becomes [Interaction::Pressed], passing it the pressed entity. The function is called on the
+/// script of the entity itself, unless another scripted entity is provided with
+/// [ScriptOnClick::on_script]. Requires
+/// [ScriptingRuntimeBuilder::enable_script_on_click](crate::ScriptingRuntimeBuilder::enable_script_on_click).
+#[cfg(feature = "ui")]
+#[derive(Component)]
+pub struct ScriptOnClick<R: Runtime> {
+    pub function: String,
+    pub script: Option<Entity>,
+    _phantom_data: PhantomData<R>,
+}
+
+#[cfg(feature = "ui")]
+impl<R: Runtime> ScriptOnClick<R> {
+    /// Creates a component calling the function with provided name.
+    pub fn new(function: impl Into<String>) -> Self {
+        Self {
+            function: function.into(),
+            script: None,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Calls the function on the script of provided entity, e.g. one managing the whole menu.
+    pub fn on_script(mut self, script: Entity) -> Self {
+        self.script = Some(script);
+        self
+    }
+}
+
+/// A component that calls `on_animation_event` function of a script of runtime `R` with the name
+/// of a marker, when an animation played by [AnimationPlayer](bevy::animation::AnimationPlayer)
+/// of its entity passes the marker. The function is called on the script of the entity itself,
+/// unless another scripted entity is provided with [ScriptAnimationEvents::on_script]. Requires
+/// [ScriptingRuntimeBuilder::enable_animation_events](crate::ScriptingRuntimeBuilder::enable_animation_events).
+#[cfg(feature = "animation")]
+#[derive(Component)]
+pub struct ScriptAnimationEvents<R: Runtime> {
+    pub markers: Vec<AnimationMarker>,
+    pub script: Option<Entity>,
+    /// Seek time and number of completions of every playing animation in the previous frame.
+    pub(crate) previous: HashMap<AnimationNodeIndex, (f32, u32)>,
+    _phantom_data: PhantomData<R>,
+}
+
+/// A point in time of an animation clip that triggers an event with provided name.
+#[cfg(feature = "animation")]
+#[derive(Clone, Debug)]
+pub struct AnimationMarker {
+    pub animation: AnimationNodeIndex,
+    /// Seek time of the marker in seconds.
+    pub time: f32,
+    pub event: String,
+}
+
+#[cfg(feature = "animation")]
+impl<R: Runtime> Default for ScriptAnimationEvents<R> {
+    fn default() -> Self {
+        Self {
+            markers: Default::default(),
+            script: None,
+            previous: Default::default(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "animation")]
+impl<R: Runtime> ScriptAnimationEvents<R> {
+    /// Adds a marker triggering provided event once `animation` reaches `time` seconds.
+    pub fn with_marker(
+        mut self,
+        animation: AnimationNodeIndex,
+        time: f32,
+        event: impl Into<String>,
+    ) -> Self {
+        self.markers.push(AnimationMarker {
+            animation,
+            time,
+            event: event.into(),
+        });
+        self
+    }
+
+    /// Calls the function on the script of provided entity instead of the animated one.
+    pub fn on_script(mut self, script: Entity) -> Self {
+        self.script = Some(script);
+        self
     }
 }
@@ -0,0 +1,126 @@
+use std::{any::TypeId, fmt};
+
+use bevy::utils::tracing;
+
+use crate::{Callbacks, Runtime, ScriptingRuntimeBuilder};
+
+/// Decides what happens when a function is registered with the same name, namespace and
+/// argument types as a function registered before in the same runtime. Functions with the same
+/// name taking different arguments are overloads and never conflict.
+/// Set with [ScriptingRuntimeBuilder::set_function_conflict_policy].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FunctionConflictPolicy {
+    /// Logs a warning and replaces the function registered before.
+    #[default]
+    Replace,
+    /// Keeps the function registered before and fails the registration with
+    /// [FunctionConflict], which [ScriptingRuntimeBuilder::add_function] logs as an error.
+    Error,
+    /// Registers the function within provided namespace instead, e.g. named after the plugin
+    /// registering it, so that scripts access it as `my_plugin.name` in Lua and
+    /// `my_plugin::name` in Rhai. Fails with [FunctionConflict] if it conflicts there as well.
+    Namespace(String),
+}
+
+/// An error returned by [ScriptingRuntimeBuilder::try_add_function] when a function conflicts
+/// with a function registered before and [FunctionConflictPolicy] does not allow replacing it.
+/// The builder can be taken back with [FunctionConflict::into_builder] to continue registering
+/// other functions.
+pub struct FunctionConflict<'a, R: Runtime> {
+    builder: ScriptingRuntimeBuilder<'a, R>,
+    name: String,
+}
+
+impl<'a, R: Runtime> FunctionConflict<'a, R> {
+    /// Returns the name of the conflicting function, prefixed with its namespace.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the builder, so more functions can be registered.
+    pub fn into_builder(self) -> ScriptingRuntimeBuilder<'a, R> {
+        self.builder
+    }
+}
+
+impl<R: Runtime> fmt::Display for FunctionConflict<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "function `{}` is already registered", self.name)
+    }
+}
+
+impl<R: Runtime> fmt::Debug for FunctionConflict<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionConflict")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<R: Runtime> std::error::Error for FunctionConflict<'_, R> {}
+
+/// Namespace, name and argument types of a registered function.
+pub(crate) type FunctionSignature = (Option<String>, String, Vec<TypeId>);
+
+fn qualified_name(namespace: Option<&str>, name: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{namespace}.{name}"),
+        None => name.to_string(),
+    }
+}
+
+impl<R: Runtime> Callbacks<R> {
+    /// Records a function about to be registered and returns the namespace it should be
+    /// registered within according to the conflict policy, or the qualified name of the function
+    /// if it can't be registered.
+    pub(crate) fn resolve_conflict(
+        &mut self,
+        namespace: Option<String>,
+        name: &str,
+        arg_types: &[TypeId],
+    ) -> Result<Option<String>, String> {
+        let signature = (namespace, name.to_string(), arg_types.to_vec());
+        if self.signatures.insert(signature.clone()) {
+            return Ok(signature.0);
+        }
+        let qualified = qualified_name(signature.0.as_deref(), name);
+        match self.conflict_policy.clone() {
+            FunctionConflictPolicy::Replace => {
+                tracing::warn!("function `{}` registered again, replacing it", qualified);
+                self.uninitialized_callbacks.retain(|callback| {
+                    callback.namespace != signature.0
+                        || callback.name != signature.1
+                        || callback
+                            .system
+                            .lock()
+                            .expect("Failed to lock callback system mutex")
+                            .arg_types
+                            != signature.2
+                });
+                Ok(signature.0)
+            }
+            FunctionConflictPolicy::Error => Err(qualified),
+            FunctionConflictPolicy::Namespace(namespace) => {
+                let signature = (Some(namespace), signature.1, signature.2);
+                if !self.signatures.insert(signature.clone()) {
+                    return Err(qualified_name(signature.0.as_deref(), name));
+                }
+                tracing::warn!(
+                    "function `{}` is already registered, registering it as `{}`",
+                    qualified,
+                    qualified_name(signature.0.as_deref(), name)
+                );
+                Ok(signature.0)
+            }
+        }
+    }
+}
+
+impl<'a, R: Runtime> ScriptingRuntimeBuilder<'a, R> {
+    pub(crate) fn conflict(self, name: String) -> FunctionConflict<'a, R> {
+        FunctionConflict {
+            builder: self,
+            name,
+        }
+    }
+}
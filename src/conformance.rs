@@ -0,0 +1,340 @@
+//! Conformance suite of scripting runtimes.
+//!
+//! The suite checks that a [Runtime] implementation behaves the way the rest of the library
+//! expects, covering promises, the `entity` variable, errors, hot reload and conversions of
+//! values. Implementors of runtimes for other languages can run it against their own runtime
+//! from an integration test with [conformance_tests](crate::conformance_tests), which generates
+//! a test for every check of [ConformanceTest]:
+//!
+//! ```ignore
+//! mod conformance {
+//!     use my_runtime::MyRuntime;
+//!
+//!     bevy_scriptum::conformance_tests!(MyRuntime, "conformance/my_language", "ml");
+//! }
+//! ```
+//!
+//! Every check evaluates a script written in the language of the runtime, found in provided
+//! directory of the asset folder and named after the check, with provided extension. What the
+//! scripts have to do is described by every check, scripts of this repository found in
+//! `assets/conformance` can be used as a reference. Scripts can call these functions:
+//!
+//! - `record(value)`, recording provided value, checked by the test afterwards,
+//! - `answer()`, returning a promise resolving with `42`.
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::{
+    callback::{FromRuntimeValueWithEngine, IntoRuntimeValueWithEngine},
+    BuildScriptingRuntime, FuncArgs, Runtime, Script, ScriptDiagnostic, ScriptError, ScriptValue,
+    ScriptingError,
+};
+
+/// Values recorded by scripts with `record` function, in the order they were recorded.
+#[derive(Resource, Default)]
+struct Records(Vec<ScriptValue>);
+
+/// Compile errors sent as [ScriptError] events.
+#[derive(Resource, Default)]
+struct Diagnostics(Vec<(Option<Entity>, ScriptDiagnostic)>);
+
+/// Returns `true` if provided values are equal, treating integers and floats with the same value
+/// as equal, as some languages, e.g. LuaJIT, have a single number type.
+fn same_value(a: &ScriptValue, b: &ScriptValue) -> bool {
+    match (a, b) {
+        (
+            ScriptValue::Int(_) | ScriptValue::Float(_),
+            ScriptValue::Int(_) | ScriptValue::Float(_),
+        ) => a.as_float() == b.as_float(),
+        (ScriptValue::Array(a), ScriptValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b))
+        }
+        (ScriptValue::Map(a), ScriptValue::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_value(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// A harness running checks of the conformance suite against runtime `R`, see [module
+/// documentation](self).
+pub struct ConformanceTest<R: Runtime> {
+    app: App,
+    directory: String,
+    extension: String,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> ConformanceTest<R>
+where
+    Vec<ScriptValue>: for<'a> FuncArgs<'a, R::Value, R>,
+    ScriptValue: for<'a> FromRuntimeValueWithEngine<'a, R>
+        + for<'a> IntoRuntimeValueWithEngine<'a, ScriptValue, R>,
+{
+    /// Maximum number of updates to wait for something to happen, e.g. for a script to load.
+    const MAX_UPDATES: usize = 100;
+
+    /// Creates a harness with runtime `R`, evaluating scripts from provided directory of the
+    /// asset folder with provided extension.
+    pub fn new(directory: impl Into<String>, extension: impl Into<String>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((AssetPlugin::default(), TaskPoolPlugin::default()))
+            .init_resource::<Records>()
+            .init_resource::<Diagnostics>()
+            .add_scripting::<R>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((value,)): In<(ScriptValue,)>, mut records: ResMut<Records>| {
+                            records.0.push(value);
+                            ScriptValue::Nil
+                        },
+                    )
+                    .add_function(String::from("answer"), || ScriptValue::Int(42));
+            })
+            .add_systems(
+                Last,
+                |mut events: EventReader<ScriptError<R>>, mut diagnostics: ResMut<Diagnostics>| {
+                    for event in events.read() {
+                        if let ScriptingError::CompileError(diagnostic) = &event.error {
+                            diagnostics.0.push((event.entity, diagnostic.clone()));
+                        }
+                    }
+                },
+            );
+        Self {
+            app,
+            directory: directory.into(),
+            extension: extension.into(),
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Returns the asset path of the script of check with provided name.
+    fn path(&self, check: &str) -> String {
+        format!("{}/{check}.{}", self.directory, self.extension)
+    }
+
+    /// Spawns an entity with the script of check with provided name, without waiting for it to
+    /// be evaluated.
+    fn spawn(&mut self, check: &str) -> Entity {
+        let handle = self
+            .app
+            .world()
+            .resource::<AssetServer>()
+            .load::<R::ScriptAsset>(self.path(check));
+        self.app.world_mut().spawn(Script::new(handle)).id()
+    }
+
+    /// Updates the app until provided condition holds, panicking with provided message if it
+    /// doesn't within [Self::MAX_UPDATES] updates.
+    fn update_until(&mut self, message: &str, condition: impl Fn(&World) -> bool) {
+        for _ in 0..Self::MAX_UPDATES {
+            self.app.update();
+            if condition(self.app.world()) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        panic!("{message}");
+    }
+
+    /// Spawns an entity with the script of check with provided name and waits for the script to
+    /// be evaluated.
+    fn run_script(&mut self, check: &str) -> Entity {
+        let entity = self.spawn(check);
+        let message = format!("script {} has not been evaluated", self.path(check));
+        self.update_until(&message, |world| {
+            world.get::<R::ScriptData>(entity).is_some()
+        });
+        entity
+    }
+
+    fn call_fn(
+        &mut self,
+        entity: Entity,
+        name: &str,
+        args: Vec<ScriptValue>,
+    ) -> Result<ScriptValue, ScriptingError> {
+        self.app
+            .world_mut()
+            .resource_scope(|world, runtime: Mut<R>| {
+                let mut script_data = world
+                    .get_mut::<R::ScriptData>(entity)
+                    .expect("script data has to be present");
+                let value = runtime.call_fn(name, &mut script_data, entity, args)?;
+                Ok(runtime.to_script_value(&value, &script_data))
+            })
+    }
+
+    fn records(&self) -> &[ScriptValue] {
+        &self.app.world().resource::<Records>().0
+    }
+
+    /// Checks that scripts see the entity they are attached to as `entity` variable.
+    /// `entity_variable` script has to call `record(entity)` when evaluated.
+    pub fn entity_variable_holds_entity(mut self) {
+        let entity = self.run_script("entity_variable");
+        self.update_until("entity has not been recorded", |world| {
+            !world.resource::<Records>().0.is_empty()
+        });
+
+        assert_eq!(self.records(), [ScriptValue::Entity(entity)]);
+    }
+
+    /// Checks that functions defined by scripts can be called with arguments, returning values,
+    /// and that calling functions that scripts don't define fails with
+    /// [ScriptingError::FunctionNotFound]. `script_functions` script has to define `add(a, b)`
+    /// returning the sum of its arguments, and no `missing` function.
+    pub fn script_functions_get_called(mut self) {
+        let entity = self.run_script("script_functions");
+
+        let sum = self
+            .call_fn(
+                entity,
+                "add",
+                vec![ScriptValue::Int(2), ScriptValue::Int(3)],
+            )
+            .expect("calling add failed");
+        assert!(
+            same_value(&sum, &ScriptValue::Int(5)),
+            "add returned {sum:?}"
+        );
+        assert!(matches!(
+            self.call_fn(entity, "missing", vec![]),
+            Err(ScriptingError::FunctionNotFound(_))
+        ));
+    }
+
+    /// Checks that calling registered functions returns promises, which call their callbacks
+    /// with the values returned by the functions. `promises` script has to call `answer()` when
+    /// evaluated and `record` the value its promise resolves with.
+    pub fn promises_resolve_with_returned_values(mut self) {
+        self.run_script("promises");
+        self.update_until("promise has not been resolved", |world| {
+            !world.resource::<Records>().0.is_empty()
+        });
+
+        assert_eq!(self.records().len(), 1);
+        assert!(
+            same_value(&self.records()[0], &ScriptValue::Int(42)),
+            "promise resolved with {:?}",
+            self.records()[0]
+        );
+    }
+
+    /// Checks that errors raised by scripts are returned as [ScriptingError::RuntimeError].
+    /// `runtime_errors` script has to define `fail()` raising an error.
+    pub fn runtime_errors_are_returned(mut self) {
+        let entity = self.run_script("runtime_errors");
+
+        let result = self.call_fn(entity, "fail", vec![]);
+        assert!(
+            matches!(result, Err(ScriptingError::RuntimeError(_))),
+            "fail returned {result:?}"
+        );
+    }
+
+    /// Checks that scripts that fail to compile are reported with a [ScriptError] event holding
+    /// a [ScriptDiagnostic] pointing at the line of the error. `syntax_error` script has to have
+    /// a syntax error in its second line.
+    pub fn syntax_errors_are_reported_with_diagnostics(mut self) {
+        let entity = self.spawn("syntax_error");
+        self.update_until("syntax error has not been reported", |world| {
+            !world.resource::<Diagnostics>().0.is_empty()
+        });
+
+        let diagnostics = &self.app.world().resource::<Diagnostics>().0;
+        let (error_entity, diagnostic) = &diagnostics[0];
+        assert_eq!(*error_entity, Some(entity));
+        assert_eq!(diagnostic.file, self.path("syntax_error"));
+        assert_eq!(diagnostic.line, Some(2));
+        assert!(self.app.world().get::<R::ScriptData>(entity).is_none());
+    }
+
+    /// Checks that scripts get evaluated again once their asset changes. `reload` script has to
+    /// call `record("evaluated")` when evaluated.
+    pub fn modified_scripts_get_reloaded(mut self) {
+        let entity = self.run_script("reload");
+        self.update_until("script has not been evaluated", |world| {
+            world.resource::<Records>().0.len() == 1
+        });
+
+        let handle = self
+            .app
+            .world()
+            .get::<Script<R::ScriptAsset>>(entity)
+            .expect("script has to be present")
+            .script
+            .clone();
+        self.app
+            .world_mut()
+            .resource_mut::<Assets<R::ScriptAsset>>()
+            .get_mut(&handle);
+        self.update_until("script has not been reloaded", |world| {
+            world.resource::<Records>().0.len() == 2
+        });
+
+        let evaluated = ScriptValue::from("evaluated");
+        assert_eq!(self.records(), [evaluated.clone(), evaluated]);
+    }
+
+    /// Checks that every kind of [ScriptValue] can be passed to scripts and returned back
+    /// unchanged, except that integers can turn into floats of the same value.
+    /// `conversions` script has to define `echo(value)` returning its argument.
+    pub fn values_round_trip_through_scripts(mut self) {
+        let entity = self.run_script("conversions");
+
+        let values = [
+            ScriptValue::Bool(true),
+            ScriptValue::Int(7),
+            ScriptValue::Float(1.5),
+            ScriptValue::from("text"),
+            ScriptValue::from(vec![ScriptValue::Int(1), ScriptValue::from("two")]),
+            ScriptValue::Map([(String::from("key"), ScriptValue::Bool(false))].into()),
+            ScriptValue::Entity(entity),
+            ScriptValue::Vec3(Vec3::new(1.0, 2.0, 3.0)),
+        ];
+        for value in values {
+            let echoed = self
+                .call_fn(entity, "echo", vec![value.clone()])
+                .expect("calling echo failed");
+            assert!(
+                same_value(&echoed, &value),
+                "{value:?} got converted into {echoed:?}"
+            );
+        }
+    }
+}
+
+/// Generates a test for every check of [ConformanceTest](crate::conformance::ConformanceTest)
+/// of provided runtime, evaluating scripts from provided directory of the asset folder with
+/// provided extension, see [conformance](crate::conformance).
+#[macro_export]
+macro_rules! conformance_tests {
+    ($runtime:ty, $directory:expr, $extension:expr) => {
+        $crate::conformance_tests!(
+            @checks $runtime, $directory, $extension,
+            entity_variable_holds_entity,
+            script_functions_get_called,
+            promises_resolve_with_returned_values,
+            runtime_errors_are_returned,
+            syntax_errors_are_reported_with_diagnostics,
+            modified_scripts_get_reloaded,
+            values_round_trip_through_scripts
+        );
+    };
+    (@checks $runtime:ty, $directory:expr, $extension:expr, $($check:ident),+) => {
+        $(
+            #[test]
+            fn $check() {
+                $crate::conformance::ConformanceTest::<$runtime>::new($directory, $extension)
+                    .$check();
+            }
+        )+
+    };
+}
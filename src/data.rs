@@ -0,0 +1,136 @@
+use std::path::{Component, Path};
+
+use bevy::{
+    asset::{io::AssetSourceId, AssetPath, AsyncReadExt as _},
+    prelude::*,
+    tasks::IoTaskPool,
+    utils::tracing,
+};
+
+use crate::{promise::DeferredCall, ScriptValue};
+
+/// Namespace of functions that scripts use to read data files.
+pub(crate) const DATA_NAMESPACE: &str = "data";
+
+/// Reads the data file with provided asset path in the background and resolves the promise of
+/// the call with its contents, registered as `data.load` function. JSON files are always
+/// supported and TOML files with `toml` feature. Only files of the default asset source can be
+/// read, with paths relative to its root. Errors are logged and result in [ScriptValue::Nil].
+pub(crate) fn load_data(
+    In((path,)): In<(String,)>,
+    asset_server: Res<AssetServer>,
+    mut deferred: ResMut<DeferredCall>,
+) {
+    let sender = deferred.defer();
+    let asset_path = match data_path(&path) {
+        Ok(asset_path) => asset_path,
+        Err(e) => {
+            tracing::error!("error loading data file {}: {}", path, e);
+            let _ = sender.send(ScriptValue::Nil);
+            return;
+        }
+    };
+    let asset_server = asset_server.clone();
+    IoTaskPool::get()
+        .spawn(async move {
+            let value = match read_data(&asset_server, &asset_path).await {
+                Ok(value) => value,
+                Err(e) => {
+                    tracing::error!("error loading data file {}: {}", path, e);
+                    ScriptValue::Nil
+                }
+            };
+            let _ = sender.send(value);
+        })
+        .detach();
+}
+
+/// Parses the path of a data file requested by a script, rejecting paths that could reach files
+/// outside of the root of the default asset source.
+fn data_path(path: &str) -> anyhow::Result<AssetPath<'static>> {
+    let asset_path = AssetPath::try_parse(path)?.into_owned();
+    if *asset_path.source() != AssetSourceId::Default {
+        anyhow::bail!("data files can only be read from the default asset source");
+    }
+    if !asset_path
+        .path()
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        anyhow::bail!("data file paths have to be relative to the asset directory");
+    }
+    Ok(asset_path)
+}
+
+pub(crate) async fn read_data(
+    asset_server: &AssetServer,
+    path: &AssetPath<'static>,
+) -> anyhow::Result<ScriptValue> {
+    let source = asset_server.get_source(path.source())?;
+    let mut text = String::new();
+    source
+        .reader()
+        .read(path.path())
+        .await?
+        .read_to_string(&mut text)
+        .await?;
+    parse_data(path.path(), &text)
+}
+
+fn parse_data(path: &Path, text: &str) -> anyhow::Result<ScriptValue> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => Ok(serde_json::from_str::<serde_json::Value>(text)?.into()),
+        #[cfg(feature = "toml")]
+        Some("toml") => Ok(toml_table(
+            text.parse::<toml_edit::DocumentMut>()?.as_table(),
+        )),
+        extension => Err(anyhow::anyhow!(
+            "unsupported data file extension {:?}",
+            extension.unwrap_or_default()
+        )),
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_table<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a toml_edit::Item)>,
+) -> ScriptValue {
+    ScriptValue::Map(
+        entries
+            .into_iter()
+            .map(|(key, item)| (key.to_string(), toml_item(item)))
+            .collect(),
+    )
+}
+
+#[cfg(feature = "toml")]
+fn toml_item(item: &toml_edit::Item) -> ScriptValue {
+    match item {
+        toml_edit::Item::None => ScriptValue::Nil,
+        toml_edit::Item::Value(value) => toml_value(value),
+        toml_edit::Item::Table(table) => toml_table(table),
+        toml_edit::Item::ArrayOfTables(tables) => {
+            ScriptValue::Array(tables.iter().map(toml_table).collect())
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_value(value: &toml_edit::Value) -> ScriptValue {
+    use toml_edit::Value;
+
+    match value {
+        Value::String(value) => value.value().as_str().into(),
+        Value::Integer(value) => (*value.value()).into(),
+        Value::Float(value) => (*value.value()).into(),
+        Value::Boolean(value) => (*value.value()).into(),
+        Value::Datetime(value) => value.value().to_string().into(),
+        Value::Array(values) => ScriptValue::Array(values.iter().map(toml_value).collect()),
+        Value::InlineTable(table) => ScriptValue::Map(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value(value)))
+                .collect(),
+        ),
+    }
+}
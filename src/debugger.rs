@@ -0,0 +1,285 @@
+//! A debugging bridge allowing to set breakpoints in scripts, step through them and inspect
+//! their variables from an external tool connected over TCP.
+//!
+//! Inserting [ScriptDebugger] resource before calling `add_scripting` attaches the debugger to
+//! the runtime being added. The debugger speaks a simple line based protocol, which can be
+//! used directly (e.g. with `telnet`) or wrapped by an editor extension.
+//!
+//! Commands sent by the client:
+//!
+//! - `break <path> <line>` - sets a breakpoint at provided line of the script with provided asset path
+//! - `clear <path> <line>` - removes a breakpoint, `clear` without arguments removes all of them
+//! - `pause` - stops at the next line that gets executed
+//!
+//! and, while a script is stopped:
+//!
+//! - `variables` - lists variables visible at the current line as `variable <name> <value>`
+//!   lines, followed by `end`
+//! - `step` - resumes execution, stopping at the next line
+//! - `continue` - resumes execution
+//!
+//! When a script stops, `stopped <path> <line>` is sent to the client. Stopping blocks the
+//! thread running the script, so the app stays frozen until execution is resumed.
+//! Disconnecting the client removes all breakpoints and resumes execution.
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::debugger::ScriptDebugger;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .insert_resource(ScriptDebugger::listen("127.0.0.1:9276").unwrap())
+//!     .add_scripting::<LuaRuntime>(|_| {})
+//!     .run();
+//! ```
+
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread,
+};
+
+use bevy::{prelude::*, utils::tracing};
+
+/// A command that resumes a stopped script or inspects it.
+enum PausedCommand {
+    Continue,
+    Step,
+    Variables,
+}
+
+#[derive(Default)]
+struct DebuggerState {
+    breakpoints: HashSet<(String, u32)>,
+    stepping: bool,
+    client: Option<TcpStream>,
+}
+
+struct DebuggerShared {
+    /// Set when there are breakpoints or stepping is requested, so that lines can be skipped
+    /// without locking the state.
+    active: AtomicBool,
+    state: Mutex<DebuggerState>,
+    connected: Condvar,
+    commands: Mutex<Receiver<PausedCommand>>,
+    sender: Sender<PausedCommand>,
+    local_addr: SocketAddr,
+}
+
+/// A resource that attaches a debugger listening for a client on a TCP socket, see
+/// [module documentation](self).
+#[derive(Resource, Clone)]
+pub struct ScriptDebugger {
+    shared: Arc<DebuggerShared>,
+}
+
+impl ScriptDebugger {
+    /// Creates a debugger accepting clients on provided address.
+    pub fn listen(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let (sender, receiver) = mpsc::channel();
+        let debugger = Self {
+            shared: Arc::new(DebuggerShared {
+                active: AtomicBool::new(false),
+                state: Mutex::new(DebuggerState::default()),
+                connected: Condvar::new(),
+                commands: Mutex::new(receiver),
+                sender,
+                local_addr: listener.local_addr()?,
+            }),
+        };
+        let accepting = debugger.clone();
+        thread::Builder::new()
+            .name("script debugger".to_string())
+            .spawn(move || accepting.accept(listener))?;
+        Ok(debugger)
+    }
+
+    /// Returns the address that the debugger listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.shared.local_addr
+    }
+
+    /// Sets a breakpoint at provided line of the script with provided asset path.
+    /// A script reaching a breakpoint waits for a client to connect.
+    pub fn set_breakpoint(&self, path: impl Into<String>, line: u32) {
+        let mut state = self.lock();
+        state.breakpoints.insert((path.into(), line));
+        self.update_active(&state);
+    }
+
+    /// Removes a breakpoint at provided line of the script with provided asset path.
+    pub fn clear_breakpoint(&self, path: &str, line: u32) {
+        let mut state = self.lock();
+        state.breakpoints.remove(&(path.to_string(), line));
+        self.update_active(&state);
+    }
+
+    /// Returns `true` if a script should stop at provided line.
+    pub(crate) fn should_stop(&self, path: &str, line: u32) -> bool {
+        if !self.shared.active.load(Ordering::Relaxed) {
+            return false;
+        }
+        let state = self.lock();
+        state.stepping || state.breakpoints.contains(&(path.to_string(), line))
+    }
+
+    /// Stops the calling script at provided line until the client resumes it. Provided function
+    /// lists variables of the script as pairs of names and formatted values.
+    pub(crate) fn stop(
+        &self,
+        path: &str,
+        line: u32,
+        variables: impl Fn() -> Vec<(String, String)>,
+    ) {
+        let commands = self
+            .shared
+            .commands
+            .lock()
+            .expect("Failed to lock debugger commands");
+        {
+            let mut state = self.lock();
+            while state.client.is_none() {
+                state = self
+                    .shared
+                    .connected
+                    .wait(state)
+                    .expect("Failed to lock debugger state");
+            }
+            while commands.try_recv().is_ok() {}
+            Self::send(&mut state, &format!("stopped {path} {line}"));
+        }
+        loop {
+            match commands.recv() {
+                Ok(PausedCommand::Variables) => {
+                    let variables = variables();
+                    let mut state = self.lock();
+                    for (name, value) in variables {
+                        Self::send(&mut state, &format!("variable {name} {value}"));
+                    }
+                    Self::send(&mut state, "end");
+                }
+                Ok(PausedCommand::Step) => {
+                    let mut state = self.lock();
+                    state.stepping = true;
+                    self.update_active(&state);
+                    return;
+                }
+                Ok(PausedCommand::Continue) | Err(_) => {
+                    let mut state = self.lock();
+                    state.stepping = false;
+                    self.update_active(&state);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn accept(&self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::error!("error accepting debugger client: {}", e);
+                    continue;
+                }
+            };
+            match stream.try_clone() {
+                Ok(writer) => {
+                    self.lock().client = Some(writer);
+                    self.shared.connected.notify_all();
+                }
+                Err(e) => {
+                    tracing::error!("error accepting debugger client: {}", e);
+                    continue;
+                }
+            }
+            for line in BufReader::new(stream).lines() {
+                match line {
+                    Ok(line) => self.handle_command(line.trim()),
+                    Err(_) => break,
+                }
+            }
+            let mut state = self.lock();
+            state.client = None;
+            state.breakpoints.clear();
+            state.stepping = false;
+            self.update_active(&state);
+            let _ = self.shared.sender.send(PausedCommand::Continue);
+        }
+    }
+
+    fn handle_command(&self, command: &str) {
+        let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+        match name {
+            "break" | "clear" => {
+                let mut state = self.lock();
+                if name == "clear" && args.is_empty() {
+                    state.breakpoints.clear();
+                } else {
+                    let Some(breakpoint) = parse_location(args) else {
+                        Self::send(&mut state, "error expected <path> <line>");
+                        return;
+                    };
+                    if name == "break" {
+                        state.breakpoints.insert(breakpoint);
+                    } else {
+                        state.breakpoints.remove(&breakpoint);
+                    }
+                }
+                self.update_active(&state);
+            }
+            "pause" => {
+                let mut state = self.lock();
+                state.stepping = true;
+                self.update_active(&state);
+            }
+            "continue" => {
+                let _ = self.shared.sender.send(PausedCommand::Continue);
+            }
+            "step" => {
+                let _ = self.shared.sender.send(PausedCommand::Step);
+            }
+            "variables" => {
+                let _ = self.shared.sender.send(PausedCommand::Variables);
+            }
+            "" => {}
+            _ => Self::send(&mut self.lock(), &format!("error unknown command {name}")),
+        }
+    }
+
+    fn update_active(&self, state: &DebuggerState) {
+        self.shared.active.store(
+            state.stepping || !state.breakpoints.is_empty(),
+            Ordering::Relaxed,
+        );
+    }
+
+    fn send(state: &mut DebuggerState, message: &str) {
+        if let Some(client) = &mut state.client {
+            if let Err(e) = writeln!(client, "{message}") {
+                tracing::error!("error sending message to debugger client: {}", e);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DebuggerState> {
+        self.shared
+            .state
+            .lock()
+            .expect("Failed to lock debugger state")
+    }
+}
+
+fn parse_location(args: &str) -> Option<(String, u32)> {
+    let (path, line) = args.rsplit_once(' ')?;
+    Some((path.to_string(), line.parse().ok()?))
+}
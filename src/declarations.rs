@@ -0,0 +1,153 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use bevy::{math::Vec3, prelude::*, utils::tracing};
+
+use crate::{
+    capabilities::current_entity, Runtime, Script, ScriptApiRegistry, ScriptError, ScriptValue,
+    ScriptingError,
+};
+
+/// Name of the function that scripts use to declare signatures of functions they call.
+pub(crate) const DECLARE_FN_NAME: &str = "declare";
+
+/// Names of types that scripts can declare arguments as, along with Rust types of arguments that
+/// they match. Arguments of other Rust types, e.g. [ScriptValue], match every declared type.
+fn declared_types() -> [(&'static str, Vec<TypeId>); 8] {
+    let ints = vec![
+        TypeId::of::<i8>(),
+        TypeId::of::<i16>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<i64>(),
+        TypeId::of::<i128>(),
+        TypeId::of::<isize>(),
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<u128>(),
+        TypeId::of::<usize>(),
+    ];
+    let floats = vec![TypeId::of::<f32>(), TypeId::of::<f64>()];
+    [
+        ("string", vec![TypeId::of::<String>()]),
+        ("int", ints.clone()),
+        ("float", floats.clone()),
+        ("number", [ints, floats].concat()),
+        ("bool", vec![TypeId::of::<bool>()]),
+        ("entity", vec![TypeId::of::<Entity>()]),
+        ("vec3", vec![TypeId::of::<Vec3>()]),
+        ("any", vec![]),
+    ]
+}
+
+/// Returns a description of why provided argument types, declared by a script, don't match
+/// arguments of the registered function, or `None` if they match.
+fn mismatch(declared: &[String], arg_types: &[TypeId], arg_type_names: &[&str]) -> Option<String> {
+    if declared.len() != arg_types.len() {
+        return Some(format!(
+            "declared {} arguments, but it takes {}",
+            declared.len(),
+            arg_types.len()
+        ));
+    }
+    let types = declared_types();
+    for (position, (declared, arg_type)) in declared.iter().zip(arg_types).enumerate() {
+        let Some((_, matching)) = types.iter().find(|(name, _)| name == declared) else {
+            let names = types.map(|(name, _)| name).join(", ");
+            return Some(format!(
+                "argument {} is declared as unknown type `{declared}`, known types are {names}",
+                position + 1
+            ));
+        };
+        let known = types.iter().any(|(_, types)| types.contains(arg_type));
+        if declared != "any" && known && !matching.contains(arg_type) {
+            return Some(format!(
+                "argument {} is declared as {declared}, but it takes {}",
+                position + 1,
+                arg_type_names[position]
+            ));
+        }
+    }
+    None
+}
+
+/// Signatures declared by scripts of runtime `R` that have not been checked yet.
+#[derive(Resource)]
+pub(crate) struct SignatureDeclarations<R: Runtime> {
+    declarations: Vec<(Option<Entity>, String, ScriptValue)>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for SignatureDeclarations<R> {
+    fn default() -> Self {
+        Self {
+            declarations: Vec::new(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+/// Queues a signature declared by a script for checking by [check_signature_declarations],
+/// registered as `declare` function. Signatures are not checked right away, as registered
+/// functions can't be listed while one of them is running.
+pub(crate) fn declare_signature<R: Runtime>(
+    In((function, declared)): In<(String, ScriptValue)>,
+    mut declarations: ResMut<SignatureDeclarations<R>>,
+) {
+    declarations
+        .declarations
+        .push((current_entity(), function, declared));
+}
+
+/// Checks queued signatures declared by scripts against registered functions, logging
+/// mismatches and sending them as [ScriptError].
+pub(crate) fn check_signature_declarations<R: Runtime>(
+    mut declarations: ResMut<SignatureDeclarations<R>>,
+    registry: ScriptApiRegistry<R>,
+    scripts: Query<&Script<R::ScriptAsset>>,
+    asset_server: Res<AssetServer>,
+    mut errors: EventWriter<ScriptError<R>>,
+) {
+    for (entity, function, declared) in declarations.declarations.drain(..) {
+        let declared = match declared {
+            ScriptValue::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    ScriptValue::String(name) => Some(name),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>(),
+            ScriptValue::Map(values) if values.is_empty() => Some(Vec::new()),
+            _ => None,
+        };
+        let reason = match declared {
+            None => Some(String::from(
+                "argument types have to be an array of type names",
+            )),
+            Some(declared) => match registry
+                .iter()
+                .find(|info| info.qualified_name() == function)
+            {
+                None => Some(String::from("function is not registered")),
+                Some(info) => mismatch(&declared, &info.arg_types, &info.arg_type_names),
+            },
+        };
+        let Some(reason) = reason else {
+            continue;
+        };
+
+        let error = ScriptingError::SignatureMismatch { function, reason };
+        tracing::error!("{}", error);
+        if let Some(script) = entity.and_then(|entity| scripts.get(entity).ok()) {
+            errors.send(ScriptError {
+                entity,
+                script: script.script.clone(),
+                path: asset_server
+                    .get_path(&script.script)
+                    .map(|path| path.to_string())
+                    .unwrap_or_default(),
+                error,
+            });
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Decoders that allow shipping script assets compressed or obfuscated.
+//!
+//! A decoder is registered for a file name suffix using
+//! [ScriptingRuntimeBuilder::add_script_decoder](crate::ScriptingRuntimeBuilder::add_script_decoder).
+//! For example a decoder registered for `"gz"` with Lua runtime makes files named `script.lua.gz`
+//! loadable as [LuaScript](crate::runtimes::lua::LuaScript) assets.
+
+/// Trait that allows transforming raw bytes of a script asset file into plain script source bytes.
+pub trait ScriptDecoder: Send + Sync + 'static {
+    /// Decode raw bytes read from an asset file.
+    fn decode(&self, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>>;
+}
+
+impl<F> ScriptDecoder for F
+where
+    F: Fn(Vec<u8>) -> anyhow::Result<Vec<u8>> + Send + Sync + 'static,
+{
+    fn decode(&self, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        self(bytes)
+    }
+}
+
+/// A decoder for files obfuscated by XOR-ing every byte with a repeating key.
+/// Since XOR is symmetric, the same decoder can be used for encoding files.
+pub struct XorDecoder {
+    key: Vec<u8>,
+}
+
+impl XorDecoder {
+    /// Creates a decoder using provided key. Key must not be empty.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        let key = key.into();
+        assert!(!key.is_empty(), "XOR key must not be empty");
+        Self { key }
+    }
+}
+
+impl ScriptDecoder for XorDecoder {
+    fn decode(&self, mut bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        for (byte, key) in bytes.iter_mut().zip(self.key.iter().cycle()) {
+            *byte ^= key;
+        }
+        Ok(bytes)
+    }
+}
+
+/// A decoder for gzip compressed files.
+#[cfg(feature = "gzip")]
+pub struct GzipDecoder;
+
+#[cfg(feature = "gzip")]
+impl ScriptDecoder for GzipDecoder {
+    fn decode(&self, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        use std::io::Read as _;
+
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+        Ok(decoded)
+    }
+}
+
+/// A decoder for zstd compressed files.
+#[cfg(feature = "zstd")]
+pub struct ZstdDecoder;
+
+#[cfg(feature = "zstd")]
+impl ScriptDecoder for ZstdDecoder {
+    fn decode(&self, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        use std::io::Read as _;
+
+        let mut decoded = Vec::new();
+        ruzstd::StreamingDecoder::new(bytes.as_slice())
+            .map_err(|e| anyhow::anyhow!("invalid zstd frame: {}", e))?
+            .read_to_end(&mut decoded)?;
+        Ok(decoded)
+    }
+}
+
+/// A decoder for files encrypted with AES-256 in CTR mode. The first 16 bytes of a file
+/// are the initialization vector, the rest is the ciphertext. Since CTR mode is symmetric,
+/// [Aes256CtrDecoder::encode] can be used to produce such files.
+#[cfg(feature = "aes")]
+pub struct Aes256CtrDecoder {
+    key: [u8; 32],
+}
+
+#[cfg(feature = "aes")]
+impl Aes256CtrDecoder {
+    const IV_LEN: usize = 16;
+
+    /// Creates a decoder using provided 256-bit key.
+    pub fn new(key: [u8; 32]) -> Self {
+        Self { key }
+    }
+
+    /// Encrypts provided bytes using provided initialization vector, producing data
+    /// that can be decoded by this decoder.
+    pub fn encode(&self, iv: [u8; 16], mut bytes: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(&iv, &mut bytes);
+        let mut encoded = iv.to_vec();
+        encoded.append(&mut bytes);
+        encoded
+    }
+
+    fn apply_keystream(&self, iv: &[u8; 16], bytes: &mut [u8]) {
+        use ctr::cipher::{KeyIvInit as _, StreamCipher as _};
+
+        ctr::Ctr128BE::<aes::Aes256>::new(&self.key.into(), iv.into()).apply_keystream(bytes);
+    }
+}
+
+#[cfg(feature = "aes")]
+impl ScriptDecoder for Aes256CtrDecoder {
+    fn decode(&self, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if bytes.len() < Self::IV_LEN {
+            anyhow::bail!("encrypted script is shorter than the initialization vector");
+        }
+        let (iv, ciphertext) = bytes.split_at(Self::IV_LEN);
+        let iv: [u8; 16] = iv.try_into()?;
+        let mut decoded = ciphertext.to_vec();
+        self.apply_keystream(&iv, &mut decoded);
+        Ok(decoded)
+    }
+}
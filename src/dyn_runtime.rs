@@ -0,0 +1,291 @@
+//! Access to scripting runtimes without knowing their types.
+//!
+//! Every runtime added with [ScriptingRuntimeBuilder::enable_dyn_runtime] is listed in the
+//! [DynScriptingRuntimes] resource as a [DynScriptingRuntime] trait object, so that game systems
+//! and editor tooling that don't care about the language of a script can evaluate scripts, call
+//! their functions with [ScriptValue] arguments and register functions, for whichever runtimes
+//! are enabled:
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use bevy_scriptum::{DynScriptingRuntimes, ScriptValue};
+//!
+//! fn reset_all_scripts(world: &mut World) {
+//!     world.resource_scope(|world, runtimes: Mut<DynScriptingRuntimes>| {
+//!         for runtime in runtimes.iter() {
+//!             for entity in runtime.scripted_entities(world) {
+//!                 if let Err(e) = runtime.call_fn_if_exists(world, entity, "reset", vec![]) {
+//!                     error!("error resetting script of {}: {}", runtime.name(), e);
+//!                 }
+//!             }
+//!         }
+//!     });
+//! }
+//! ```
+
+use std::{any::TypeId, marker::PhantomData, sync::Arc};
+
+use bevy::{ecs::system::RunSystemOnce as _, prelude::*};
+
+use crate::{
+    callback::{FromRuntimeValueWithEngine, IntoRuntimeValueWithEngine},
+    FuncArgs, Runtime, Script, ScriptApiRegistry, ScriptFunctionInfo, ScriptInfo, ScriptMod,
+    ScriptValue, ScriptingError, ScriptingRuntimeBuilder,
+};
+
+/// A function registered with [DynScriptingRuntime::add_function], receiving arguments that
+/// a script passed to it.
+pub type DynFunction = Arc<dyn Fn(Vec<ScriptValue>) -> ScriptValue + Send + Sync>;
+
+/// Largest number of arguments of functions registered with [DynScriptingRuntime::add_function].
+pub const MAX_DYN_FUNCTION_ARITY: usize = 6;
+
+/// Operations of a scripting runtime that don't depend on its type, see the [module
+/// documentation](self). All of them take the world, as runtimes and data of scripts are stored
+/// in it, and fail with [ScriptingError::NoRuntimeResource] if the runtime has been removed.
+pub trait DynScriptingRuntime: Send + Sync {
+    /// Returns the type name of the runtime, e.g. for listing runtimes in editor tooling.
+    fn name(&self) -> &'static str;
+
+    /// Returns the type id of the runtime.
+    fn runtime_type_id(&self) -> TypeId;
+
+    /// Returns entities with scripts of the runtime that have been evaluated.
+    fn scripted_entities(&self, world: &mut World) -> Vec<Entity>;
+
+    /// Evaluates the script attached to provided entity, replacing its data if it has been
+    /// evaluated before, without waiting for the next frame.
+    fn eval(&self, world: &mut World, entity: Entity) -> Result<(), ScriptingError>;
+
+    /// Calls a function of the script attached to provided entity, see [Runtime::call_fn].
+    fn call_fn(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        name: &str,
+        args: Vec<ScriptValue>,
+    ) -> Result<ScriptValue, ScriptingError>;
+
+    /// Calls a function of the script attached to provided entity, returning `Ok(None)` if the
+    /// script does not define it, see [Runtime::call_fn_if_exists].
+    fn call_fn_if_exists(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        name: &str,
+        args: Vec<ScriptValue>,
+    ) -> Result<Option<ScriptValue>, ScriptingError> {
+        match self.call_fn(world, entity, name, args) {
+            Ok(value) => Ok(Some(value)),
+            Err(ScriptingError::FunctionNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Registers a function taking provided number of arguments, at most
+    /// [MAX_DYN_FUNCTION_ARITY], for calling from scripts, the same way as
+    /// [ScriptingRuntimeBuilder::add_function]. Returns an error if the arity is too large.
+    fn add_function(
+        &self,
+        world: &mut World,
+        name: String,
+        arity: usize,
+        f: DynFunction,
+    ) -> Result<(), ScriptingError>;
+
+    /// Returns descriptions of functions registered in the runtime, see [ScriptApiRegistry].
+    fn functions(&self, world: &mut World) -> Vec<ScriptFunctionInfo>;
+}
+
+/// A resource listing runtimes added with [ScriptingRuntimeBuilder::enable_dyn_runtime], in the
+/// order they were added.
+#[derive(Resource, Default, Clone)]
+pub struct DynScriptingRuntimes {
+    runtimes: Vec<Arc<dyn DynScriptingRuntime>>,
+}
+
+impl DynScriptingRuntimes {
+    /// Returns all listed runtimes.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DynScriptingRuntime> {
+        self.runtimes.iter().map(|runtime| &**runtime)
+    }
+
+    /// Returns runtime `R`, if it is listed.
+    pub fn get<R: Runtime>(&self) -> Option<&dyn DynScriptingRuntime> {
+        self.iter()
+            .find(|runtime| runtime.runtime_type_id() == TypeId::of::<R>())
+    }
+}
+
+/// Implements [DynScriptingRuntime] for runtime `R`.
+struct DynRuntime<R>(PhantomData<fn() -> R>);
+
+impl<R> DynScriptingRuntime for DynRuntime<R>
+where
+    R: Runtime,
+    ScriptValue: for<'a> FromRuntimeValueWithEngine<'a, R>
+        + for<'a> IntoRuntimeValueWithEngine<'a, ScriptValue, R>,
+    Vec<ScriptValue>: for<'a> FuncArgs<'a, R::Value, R>,
+{
+    fn name(&self) -> &'static str {
+        std::any::type_name::<R>()
+    }
+
+    fn runtime_type_id(&self) -> TypeId {
+        TypeId::of::<R>()
+    }
+
+    fn scripted_entities(&self, world: &mut World) -> Vec<Entity> {
+        world
+            .query_filtered::<Entity, With<R::ScriptData>>()
+            .iter(world)
+            .collect()
+    }
+
+    fn eval(&self, world: &mut World, entity: Entity) -> Result<(), ScriptingError> {
+        let script = world
+            .get::<Script<R::ScriptAsset>>(entity)
+            .ok_or(ScriptingError::NoScript(entity))?;
+        let asset_server = world.resource::<AssetServer>();
+        let assets = world.resource::<Assets<R::ScriptAsset>>();
+        let path = |handle: &Handle<R::ScriptAsset>| {
+            asset_server
+                .get_path(handle)
+                .map(|path| path.to_string())
+                .unwrap_or_default()
+        };
+        let asset = |handle: &Handle<R::ScriptAsset>| {
+            assets
+                .get(handle)
+                .ok_or_else(|| ScriptingError::ScriptNotLoaded(path(handle)))
+        };
+        let bases = script
+            .bases
+            .iter()
+            .map(|base| Ok((asset(base)?, path(base))))
+            .collect::<Result<Vec<_>, ScriptingError>>()?;
+        let info = ScriptInfo {
+            path: path(&script.script),
+            asset_id: script.script.id().to_string(),
+            mod_name: world
+                .get::<ScriptMod>(entity)
+                .map(|script_mod| script_mod.name.clone()),
+        };
+        let runtime = world
+            .get_resource::<R>()
+            .ok_or(ScriptingError::NoRuntimeResource)?;
+        let script_data = runtime.eval_with_bases(&bases, asset(&script.script)?, entity, &info)?;
+        world.entity_mut(entity).insert(script_data);
+        Ok(())
+    }
+
+    fn call_fn(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        name: &str,
+        args: Vec<ScriptValue>,
+    ) -> Result<ScriptValue, ScriptingError> {
+        if !world.contains_resource::<R>() {
+            return Err(ScriptingError::NoRuntimeResource);
+        }
+        world.resource_scope(|world, runtime: Mut<R>| {
+            let mut script_data = world
+                .get_mut::<R::ScriptData>(entity)
+                .ok_or(ScriptingError::NoScript(entity))?;
+            let value = runtime.call_fn(name, &mut script_data, entity, args)?;
+            Ok(runtime.to_script_value(&value, &script_data))
+        })
+    }
+
+    fn add_function(
+        &self,
+        world: &mut World,
+        name: String,
+        arity: usize,
+        f: DynFunction,
+    ) -> Result<(), ScriptingError> {
+        if !world.contains_resource::<R>() {
+            return Err(ScriptingError::NoRuntimeResource);
+        }
+        let builder = ScriptingRuntimeBuilder::<R>::new(world);
+        match arity {
+            0 => builder.add_function(name, move || f(vec![])),
+            1 => builder.add_function(name, move |In((a,)): In<(ScriptValue,)>| f(vec![a])),
+            2 => builder.add_function(name, move |In((a, b)): In<(ScriptValue, ScriptValue)>| {
+                f(vec![a, b])
+            }),
+            3 => builder.add_function(
+                name,
+                move |In((a, b, c)): In<(ScriptValue, ScriptValue, ScriptValue)>| f(vec![a, b, c]),
+            ),
+            4 => builder.add_function(
+                name,
+                move |In((a, b, c, d)): In<(
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                )>| { f(vec![a, b, c, d]) },
+            ),
+            5 => builder.add_function(
+                name,
+                move |In((a, b, c, d, e)): In<(
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                )>| f(vec![a, b, c, d, e]),
+            ),
+            6 => builder.add_function(
+                name,
+                move |In((a, b, c, d, e, g)): In<(
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                    ScriptValue,
+                )>| f(vec![a, b, c, d, e, g]),
+            ),
+            _ => {
+                return Err(ScriptingError::UnsupportedArity {
+                    function: name,
+                    arity,
+                    max: MAX_DYN_FUNCTION_ARITY,
+                })
+            }
+        };
+        Ok(())
+    }
+
+    fn functions(&self, world: &mut World) -> Vec<ScriptFunctionInfo> {
+        if !world.contains_resource::<R>() {
+            return Vec::new();
+        }
+        world.run_system_once(|registry: ScriptApiRegistry<R>| registry.iter().collect())
+    }
+}
+
+impl<R: Runtime> ScriptingRuntimeBuilder<'_, R>
+where
+    ScriptValue: for<'a> FromRuntimeValueWithEngine<'a, R>
+        + for<'a> IntoRuntimeValueWithEngine<'a, ScriptValue, R>,
+    Vec<ScriptValue>: for<'a> FuncArgs<'a, R::Value, R>,
+{
+    /// Lists the runtime in the [DynScriptingRuntimes] resource, so that it can be used
+    /// without knowing its type, see [DynScriptingRuntime].
+    pub fn enable_dyn_runtime(self) -> Self {
+        let mut runtimes = self
+            .world
+            .get_resource_or_insert_with(DynScriptingRuntimes::default);
+        if runtimes.get::<R>().is_none() {
+            runtimes
+                .runtimes
+                .push(Arc::new(DynRuntime::<R>(PhantomData)));
+        }
+
+        self
+    }
+}
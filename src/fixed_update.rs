@@ -0,0 +1,42 @@
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{activation::ScriptSuspended, FuncArgs, Runtime};
+
+/// Name of the function called on scripts every fixed timestep.
+pub(crate) const ON_FIXED_UPDATE_FN_NAME: &str = "on_fixed_update";
+
+/// A system set in `FixedUpdate` that scripts get their `on_fixed_update` function called in,
+/// once enabled with
+/// [ScriptingRuntimeBuilder::enable_fixed_update](crate::ScriptingRuntimeBuilder::enable_fixed_update).
+/// Calls that scripts make from the function get processed within the set as well, so systems
+/// ordered after it, e.g. ones applying forces to physics bodies, see their effects in the same
+/// fixed timestep.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScriptFixedUpdateSet;
+
+/// Calls `on_fixed_update` function of every scripted entity that is not suspended, passing it
+/// the fixed timestep in seconds.
+pub(crate) fn call_fixed_update<R: Runtime>(
+    mut scripted_entities: Query<(Entity, &mut R::ScriptData), Without<ScriptSuspended>>,
+    scripting_runtime: Res<R>,
+    time: Res<Time>,
+) where
+    (f64,): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    let dt = time.delta_seconds_f64();
+    for (entity, mut script_data) in &mut scripted_entities {
+        if let Err(e) = scripting_runtime.call_fn_if_exists(
+            ON_FIXED_UPDATE_FN_NAME,
+            &mut script_data,
+            entity,
+            (dt,),
+        ) {
+            tracing::error!(
+                "error calling '{}' on entity {:?}: {}",
+                ON_FIXED_UPDATE_FN_NAME,
+                entity,
+                e
+            );
+        }
+    }
+}
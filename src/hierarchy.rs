@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+use crate::{EntityArgs, ScriptValue};
+
+/// Namespace of functions that scripts use to navigate the hierarchy of entities.
+pub(crate) const HIERARCHY_NAMESPACE: &str = "hierarchy";
+
+/// Returns children of an entity, registered as `hierarchy.children`.
+pub(crate) fn children<R: EntityArgs>(
+    In((entity,)): In<(R::ScriptEntity,)>,
+    children: Query<&Children>,
+) -> ScriptValue {
+    ScriptValue::Array(
+        children
+            .get(R::script_entity(entity))
+            .map(|children| children.iter().copied().map(ScriptValue::Entity).collect())
+            .unwrap_or_default(),
+    )
+}
+
+/// Returns the parent of an entity, or nil if it has none, registered as `hierarchy.parent`.
+pub(crate) fn parent<R: EntityArgs>(
+    In((entity,)): In<(R::ScriptEntity,)>,
+    parents: Query<&Parent>,
+) -> ScriptValue {
+    parents
+        .get(R::script_entity(entity))
+        .map(|parent| ScriptValue::Entity(parent.get()))
+        .unwrap_or_default()
+}
+
+/// Returns the first descendant of an entity with provided [Name], searched breadth-first, or
+/// nil if there is none, registered as `hierarchy.find_child_by_name`.
+pub(crate) fn find_child_by_name<R: EntityArgs>(
+    In((entity, name)): In<(R::ScriptEntity, String)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) -> ScriptValue {
+    children
+        .iter_descendants(R::script_entity(entity))
+        .find(|descendant| {
+            names
+                .get(*descendant)
+                .is_ok_and(|found| found.as_str() == name)
+        })
+        .map(ScriptValue::Entity)
+        .unwrap_or_default()
+}
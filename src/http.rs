@@ -0,0 +1,412 @@
+//! HTTP requests made by scripts.
+//!
+//! [ScriptingRuntimeBuilder::enable_http](crate::ScriptingRuntimeBuilder::enable_http) exposes
+//! `http.get(url)` and `http.post(url, body)` functions to scripts, in Rhai called as
+//! `http::get` and `http::post`. Requests are sent on worker threads of [HttpClient] and the
+//! promises they return get resolved once responses arrive, with a table in Lua or an object map
+//! in Rhai holding the `status` code and the `body` of the response. Failed requests, and requests
+//! made while too many others are in flight, are logged and resolve the promise with `nil` in Lua
+//! and `()` in Rhai.
+//!
+//! Only hosts of domains listed in
+//! [ScriptingSettings::allowed_http_domains](crate::ScriptingSettings::allowed_http_domains),
+//! or their subdomains, can be requested. Requests are sent by [HttpClient] resource, which by
+//! default only supports plain `http://` URLs, so `https://` URLs are rejected like hosts that are
+//! not allowed. A client supporting TLS can be provided by implementing [HttpTransport] and
+//! inserting the resource before adding scripting:
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_scriptum::http::{HttpClient, HttpError, HttpRequest, HttpResponse, HttpTransport};
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//!
+//! struct TlsTransport;
+//!
+//! impl HttpTransport for TlsTransport {
+//!     fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+//!         todo!("send the request with a HTTP library of choice")
+//!     }
+//!
+//!     fn supports_https(&self) -> bool {
+//!         true
+//!     }
+//! }
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .insert_resource(HttpClient::new(TlsTransport))
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.enable_http(["leaderboard.example.com"]);
+//!     })
+//!     .run();
+//! ```
+
+use std::{
+    collections::HashMap,
+    io::{Read as _, Write as _},
+    net::TcpStream,
+    sync::{
+        mpsc::{self, Sender, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use bevy::{prelude::*, utils::tracing};
+use thiserror::Error;
+use url::Url;
+
+use crate::{promise::DeferredCall, Runtime, ScriptValue, ScriptingSettings};
+
+/// Namespace of functions that scripts use to make HTTP requests.
+pub(crate) const HTTP_NAMESPACE: &str = "http";
+
+#[derive(Error, Debug)]
+pub enum HttpError {
+    #[error("unsupported url {0}")]
+    UnsupportedUrl(String),
+    #[error("host {0} is not allowed")]
+    HostNotAllowed(String),
+    #[error("too many requests in flight, request to {0} was dropped")]
+    TooManyRequests(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("response is larger than {0} bytes")]
+    ResponseTooLarge(u64),
+    #[error("{0}")]
+    Other(String),
+}
+
+/// A HTTP request made by a script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    /// Method of the request, either `GET` or `POST`.
+    pub method: String,
+    pub url: String,
+    /// Body of the request, empty for `GET` requests.
+    pub body: String,
+}
+
+/// A response to a [HttpRequest].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl From<HttpResponse> for ScriptValue {
+    fn from(response: HttpResponse) -> Self {
+        ScriptValue::Map(HashMap::from([
+            (String::from("status"), i64::from(response.status).into()),
+            (String::from("body"), response.body.into()),
+        ]))
+    }
+}
+
+/// Sends HTTP requests made by scripts, blocking until responses arrive. It is called on worker
+/// threads of [HttpClient], so that requests don't block frames.
+pub trait HttpTransport: Send + Sync + 'static {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError>;
+
+    /// Whether the transport can send requests to `https://` URLs. Scripts can only request
+    /// such URLs if it does.
+    fn supports_https(&self) -> bool {
+        false
+    }
+}
+
+/// A request waiting for a worker of [HttpClient], along with the sender resolving its promise.
+struct QueuedRequest {
+    request: HttpRequest,
+    sender: Sender<ScriptValue>,
+}
+
+/// A resource that sends HTTP requests made by scripts using a [HttpTransport], by default
+/// [PlainHttpTransport].
+///
+/// Requests are sent by a fixed number of worker threads. Requests made while all workers are
+/// busy wait in a queue of limited capacity, and requests made while the queue is full are
+/// logged and resolve their promises with `nil` in Lua and `()` in Rhai.
+#[derive(Resource, Clone)]
+pub struct HttpClient {
+    requests: SyncSender<QueuedRequest>,
+    /// Whether the transport of the client supports `https://` URLs.
+    https: bool,
+}
+
+impl HttpClient {
+    /// Number of worker threads of clients created with [HttpClient::new].
+    pub const DEFAULT_WORKERS: usize = 4;
+    /// Capacity of the queue of requests of clients created with [HttpClient::new].
+    pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+    /// Creates a client sending requests with provided transport on
+    /// [HttpClient::DEFAULT_WORKERS] threads, queueing at most
+    /// [HttpClient::DEFAULT_QUEUE_CAPACITY] requests.
+    pub fn new(transport: impl HttpTransport) -> Self {
+        Self::with_limits(
+            transport,
+            Self::DEFAULT_WORKERS,
+            Self::DEFAULT_QUEUE_CAPACITY,
+        )
+    }
+
+    /// Creates a client sending requests with provided transport on provided number of worker
+    /// threads, queueing at most `queue_capacity` requests while all of them are busy. Workers
+    /// stop once the client and all of its clones are dropped.
+    pub fn with_limits(
+        transport: impl HttpTransport,
+        workers: usize,
+        queue_capacity: usize,
+    ) -> Self {
+        let https = transport.supports_https();
+        let transport: Arc<dyn HttpTransport> = Arc::new(transport);
+        let (requests, receiver) = mpsc::sync_channel::<QueuedRequest>(queue_capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..workers.max(1) {
+            let transport = transport.clone();
+            let receiver = receiver.clone();
+            std::thread::spawn(move || loop {
+                let next = receiver
+                    .lock()
+                    .expect("Failed to lock http request queue")
+                    .recv();
+                let Ok(QueuedRequest { request, sender }) = next else {
+                    return;
+                };
+                let url = request.url.clone();
+                let value = match transport.send(request) {
+                    Ok(response) => response.into(),
+                    Err(e) => {
+                        tracing::error!("error sending http request to {}: {}", url, e);
+                        ScriptValue::Nil
+                    }
+                };
+                let _ = sender.send(value);
+            });
+        }
+        Self { requests, https }
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(PlainHttpTransport::default())
+    }
+}
+
+/// A [HttpTransport] that sends HTTP/1.1 requests over plain TCP connections, so it only
+/// supports `http://` URLs. Responses larger than [PlainHttpTransport::MAX_RESPONSE_BYTES] fail
+/// with [HttpError::ResponseTooLarge].
+pub struct PlainHttpTransport {
+    /// Maximum time of connecting, and of every read and write after that.
+    pub timeout: Duration,
+}
+
+impl PlainHttpTransport {
+    /// Maximum size of a response, including its headers.
+    pub const MAX_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
+}
+
+impl Default for PlainHttpTransport {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HttpTransport for PlainHttpTransport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        let unsupported = || HttpError::UnsupportedUrl(request.url.clone());
+        let url = Url::parse(&request.url).map_err(|_| unsupported())?;
+        if url.scheme() != "http" {
+            return Err(unsupported());
+        }
+        let host = url.host_str().ok_or_else(unsupported)?;
+        let authority = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let socket_address = url
+            .socket_addrs(|| None)?
+            .into_iter()
+            .next()
+            .ok_or_else(unsupported)?;
+
+        let mut stream = TcpStream::connect_timeout(&socket_address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            request.method,
+            path,
+            authority,
+            request.body.len(),
+            request.body
+        )?;
+        let mut response = Vec::new();
+        // One byte past the limit is read to tell a response of exactly the maximum size apart
+        // from a larger one.
+        stream
+            .take(Self::MAX_RESPONSE_BYTES + 1)
+            .read_to_end(&mut response)?;
+        if response.len() as u64 > Self::MAX_RESPONSE_BYTES {
+            return Err(HttpError::ResponseTooLarge(Self::MAX_RESPONSE_BYTES));
+        }
+        parse_response(&response)
+    }
+}
+
+/// Parses provided response. Its head and chunks are split on raw bytes, as chunk sizes count
+/// bytes, and only the decoded body is converted into a string, replacing invalid UTF-8.
+fn parse_response(response: &[u8]) -> Result<HttpResponse, HttpError> {
+    let invalid = |reason: &str| HttpError::InvalidResponse(reason.to_string());
+    let (head, body) =
+        split_once(response, b"\r\n\r\n").ok_or_else(|| invalid("missing end of headers"))?;
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|status_line| status_line.split_whitespace().nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| invalid("missing status code"))?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+        })
+    });
+    let body = if chunked {
+        decode_chunked(body).ok_or_else(|| invalid("malformed chunked body"))?
+    } else {
+        body.to_vec()
+    };
+    Ok(HttpResponse {
+        status,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Splits provided bytes at the first occurrence of provided delimiter, which is left out.
+fn split_once<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    let index = bytes
+        .windows(delimiter.len())
+        .position(|window| window == delimiter)?;
+    Some((&bytes[..index], &bytes[index + delimiter.len()..]))
+}
+
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let (size, rest) = split_once(body, b"\r\n")?;
+        let size = std::str::from_utf8(size).ok()?.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(rest.get(..size)?);
+        body = rest.get(size..)?.strip_prefix(b"\r\n")?;
+    }
+}
+
+/// Returns `true` if provided URL is a `http://` URL, or a `https://` URL if `https` is set,
+/// pointing to a host that is one of provided domains or their subdomain. URLs with credentials
+/// or backslashes in their authority are never allowed, as parsers disagree on which host they
+/// point to.
+fn is_allowed(url: &str, allowed_domains: &[String], https: bool) -> bool {
+    let Some((_, address)) = url.split_once("://") else {
+        return false;
+    };
+    let authority = address.split(['/', '?', '#']).next().unwrap_or_default();
+    if authority.contains('\\') {
+        return false;
+    }
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let scheme_supported = match url.scheme() {
+        "http" => true,
+        "https" => https,
+        _ => false,
+    };
+    if !scheme_supported || !url.username().is_empty() || url.password().is_some() {
+        return false;
+    }
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    allowed_domains.iter().any(|domain| {
+        let domain = domain.to_ascii_lowercase();
+        host == domain || host.ends_with(&format!(".{domain}"))
+    })
+}
+
+/// Queues provided request to be sent by a worker of provided client unless its host is not
+/// allowed or the queue is full, in which case the promise of the call gets resolved with
+/// [ScriptValue::Nil] right away.
+fn send<R: Runtime>(
+    request: HttpRequest,
+    settings: &ScriptingSettings<R>,
+    client: &HttpClient,
+    deferred: &mut DeferredCall,
+) {
+    if !is_allowed(&request.url, &settings.allowed_http_domains, client.https) {
+        tracing::error!(
+            "error sending http request: {}",
+            HttpError::HostNotAllowed(request.url)
+        );
+        return;
+    }
+    let sender = deferred.defer();
+    match client.requests.try_send(QueuedRequest { request, sender }) {
+        Ok(()) => {}
+        Err(TrySendError::Full(QueuedRequest { request, sender })) => {
+            tracing::error!(
+                "error sending http request: {}",
+                HttpError::TooManyRequests(request.url)
+            );
+            let _ = sender.send(ScriptValue::Nil);
+        }
+        Err(TrySendError::Disconnected(QueuedRequest { sender, .. })) => {
+            let _ = sender.send(ScriptValue::Nil);
+        }
+    }
+}
+
+/// Sends a `GET` request, registered as `http.get` function.
+pub(crate) fn get<R: Runtime>(
+    In((url,)): In<(String,)>,
+    settings: Res<ScriptingSettings<R>>,
+    client: Res<HttpClient>,
+    mut deferred: ResMut<DeferredCall>,
+) {
+    let request = HttpRequest {
+        method: String::from("GET"),
+        url,
+        body: String::new(),
+    };
+    send(request, &settings, &client, &mut deferred);
+}
+
+/// Sends a `POST` request with provided body, registered as `http.post` function.
+pub(crate) fn post<R: Runtime>(
+    In((url, body)): In<(String, String)>,
+    settings: Res<ScriptingSettings<R>>,
+    client: Res<HttpClient>,
+    mut deferred: ResMut<DeferredCall>,
+) {
+    let request = HttpRequest {
+        method: String::from("POST"),
+        url,
+        body,
+    };
+    send(request, &settings, &client, &mut deferred);
+}
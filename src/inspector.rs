@@ -0,0 +1,139 @@
+//! Inspecting and editing variables of scripts, e.g. with `bevy-inspector-egui`.
+//!
+//! [ScriptingRuntimeBuilder::enable_inspector](crate::ScriptingRuntimeBuilder::enable_inspector)
+//! mirrors variables of every script in a reflected [ScriptVariables] component of its entity,
+//! which inspectors display along with other components. Values edited in the component are
+//! written back to the script on the next frame.
+//!
+//! Top-level variables of Rhai scripts and globals of Lua scripts are mirrored, with fields of
+//! maps and tables listed under dotted names, e.g. `state.speed`. Only booleans, integers,
+//! numbers and strings are mirrored. Lua scripts evaluated with shared isolation share their
+//! globals, so all of them list the same variables.
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_inspector_egui::quick::WorldInspectorPlugin;
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//!
+//! App::new()
+//!     .add_plugins((DefaultPlugins, WorldInspectorPlugin::new()))
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.enable_inspector();
+//!     })
+//!     .run();
+//! ```
+
+use bevy::{prelude::*, utils::tracing};
+
+use crate::Runtime;
+
+/// Fields of maps and tables nested deeper than this are not mirrored.
+pub(crate) const MAX_VARIABLE_DEPTH: usize = 4;
+
+/// Value of a script variable mirrored in [ScriptVariables].
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum ScriptVariableValue {
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+}
+
+/// A script variable mirrored in [ScriptVariables].
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct ScriptVariable {
+    /// Name of the variable, with names of maps or tables it is nested in separated by `.`.
+    pub name: String,
+    pub value: ScriptVariableValue,
+}
+
+impl ScriptVariable {
+    pub fn new(name: impl Into<String>, value: ScriptVariableValue) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+/// A component mirroring variables of the script of its entity. Changing values writes them
+/// back to the script, variables that are added or removed are ignored.
+#[derive(Component, Reflect, Debug, Default, Clone)]
+#[reflect(Component)]
+pub struct ScriptVariables {
+    pub variables: Vec<ScriptVariable>,
+    /// Variables as last read from the script, used to find edited values.
+    #[reflect(ignore)]
+    synced: Vec<ScriptVariable>,
+}
+
+impl ScriptVariables {
+    /// Returns the value of the variable with provided name.
+    pub fn get(&self, name: &str) -> Option<&ScriptVariableValue> {
+        self.variables
+            .iter()
+            .find(|variable| variable.name == name)
+            .map(|variable| &variable.value)
+    }
+
+    /// Sets the value of the variable with provided name, returning `false` if it does not exist.
+    pub fn set(&mut self, name: &str, value: ScriptVariableValue) -> bool {
+        match self
+            .variables
+            .iter_mut()
+            .find(|variable| variable.name == name)
+        {
+            Some(variable) => {
+                variable.value = value;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Writes values edited in [ScriptVariables] components to scripts, then mirrors current
+/// variables of scripts in them.
+pub(crate) fn sync_script_variables<R: Runtime>(
+    mut commands: Commands,
+    mut scripted_entities: Query<(Entity, &mut R::ScriptData, Option<&mut ScriptVariables>)>,
+    scripting_runtime: Res<R>,
+) {
+    for (entity, mut script_data, variables) in &mut scripted_entities {
+        let Some(mut variables) = variables else {
+            let current = scripting_runtime.script_variables(&script_data);
+            commands.entity(entity).insert(ScriptVariables {
+                variables: current.clone(),
+                synced: current,
+            });
+            continue;
+        };
+        for variable in &variables.variables {
+            let edited = variables
+                .synced
+                .iter()
+                .any(|synced| synced.name == variable.name && synced.value != variable.value);
+            if !edited {
+                continue;
+            }
+            if let Err(e) = scripting_runtime.set_script_variable(
+                &mut script_data,
+                &variable.name,
+                variable.value.clone(),
+            ) {
+                tracing::error!(
+                    "error setting variable '{}' on entity {:?}: {}",
+                    variable.name,
+                    entity,
+                    e
+                );
+            }
+        }
+        let current = scripting_runtime.script_variables(&script_data);
+        if variables.variables != current {
+            variables.variables.clone_from(&current);
+        }
+        variables.bypass_change_detection().synced = current;
+    }
+}
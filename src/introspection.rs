@@ -0,0 +1,47 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::Runtime;
+
+/// A system parameter listing functions defined by scripts of runtime `R`, e.g. for editor
+/// tooling showing which hooks a script implements.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+/// use bevy_scriptum::ScriptIntrospection;
+///
+/// fn list_hooks(introspection: ScriptIntrospection<LuaRuntime>) {
+///     for (entity, functions) in introspection.iter() {
+///         println!("{entity}: {}", functions.join(", "));
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct ScriptIntrospection<'w, 's, R: Runtime> {
+    runtime: Res<'w, R>,
+    scripts: Query<'w, 's, (Entity, &'static <R as Runtime>::ScriptData)>,
+}
+
+impl<R: Runtime> ScriptIntrospection<'_, '_, R> {
+    /// Returns names of functions defined by the script attached to provided entity, sorted,
+    /// or `None` if the entity has no evaluated script.
+    pub fn functions(&self, entity: Entity) -> Option<Vec<String>> {
+        let (_, script_data) = self.scripts.get(entity).ok()?;
+        Some(self.runtime.list_functions(script_data))
+    }
+
+    /// Returns `true` if the script attached to provided entity defines a function with provided
+    /// name.
+    pub fn defines(&self, entity: Entity, name: &str) -> bool {
+        self.functions(entity)
+            .is_some_and(|functions| functions.iter().any(|function| function == name))
+    }
+
+    /// Returns names of functions defined by every evaluated script, along with entities the
+    /// scripts are attached to.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Vec<String>)> + '_ {
+        self.scripts
+            .iter()
+            .map(|(entity, script_data)| (entity, self.runtime.list_functions(script_data)))
+    }
+}
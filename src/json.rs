@@ -0,0 +1,59 @@
+use std::ops::{Deref, DerefMut};
+
+/// A wrapper converting any serde-compatible type from and into script values, so that structs
+/// can be passed between Rust functions and scripts without extracting their fields manually.
+///
+/// Values get converted into Lua tables and Rhai object maps or arrays, according to how the
+/// wrapped type gets serialized. `None` and other null values get converted into `nil` in Lua
+/// and `()` in Rhai.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_scriptum::{prelude::*, Json};
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Stats {
+///     health: i64,
+///     name: String,
+/// }
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|runtime| {
+///         runtime.add_function(
+///             String::from("heal"),
+///             |In((Json(mut stats),)): In<(Json<Stats>,)>| {
+///                 stats.health += 10;
+///                 Json(stats)
+///             },
+///         );
+///     });
+/// ```
+///
+/// The wrapper deliberately does not implement [Clone], since runtimes convert any cloneable
+/// type into an opaque value.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
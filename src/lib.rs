@@ -84,7 +84,7 @@
 //! ```
 //! It is also possible to split the definition of your callback functions up over multiple plugins. This enables you to split up your code by subject and keep the main initialization light and clean.
 //! This can be accomplished by using `add_scripting_api`. Be careful though, `add_scripting` has to be called before adding plugins.
-//! ```rust 
+//! ```rust
 //! use bevy::prelude::*;
 //! use bevy_scriptum::prelude::*;
 //! use bevy_scriptum::runtimes::lua::prelude::*;
@@ -108,15 +108,15 @@
 //!     .add_plugins(MyPlugin)
 //!     .run();
 //! ```
-//! 
-//! 
+//!
+//!
 //! ## Usage
 //!
 //! Add the following to your `Cargo.toml`:
 //!
 //! ```toml
 //! [dependencies]
-//! bevy_scriptum = { version = "0.6", features = ["lua"] }
+//! bevy_scriptum = { version = "0.7", features = ["lua"] }
 //! ```
 //!
 //! or execute `cargo add bevy_scriptum --features lua` from your project directory.
@@ -189,12 +189,15 @@
 //!
 //! | bevy version | bevy_scriptum version |
 //! |--------------|-----------------------|
-//! | 0.14         | 0.6                   |
+//! | 0.14         | 0.6-0.7               |
 //! | 0.13         | 0.4-0.5               |
 //! | 0.12         | 0.3                   |
 //! | 0.11         | 0.2                   |
 //! | 0.10         | 0.1                   |
 //!
+//! Version 0.7 changes the `Runtime` trait in ways that require updating runtimes implemented
+//! outside of bevy_scriptum, and changes `LuaScript`, see [CHANGELOG.md](CHANGELOG.md) for upgrading.
+//!
 //! ## Promises - getting return values from scripts
 //!
 //! Every function called from script returns a promise that you can call `:and_then` with a callback function on. This callback function will be called when the promise is resolved, and will be passed the return value of the function called from script. For example:
@@ -228,7 +231,9 @@
 //! print("Current entity index: " .. entity.index)
 //! ```
 //!
-//! `entity` variable is currently not available within promise callbacks.
+//! `entity` variable is currently not available within promise callbacks, unless Lua scripts are
+//! evaluated with `ScriptVariables::Environment`. The variable can be renamed, or disabled, with
+//! `set_entity_variable`.
 //!
 //! ## Contributing
 //!
@@ -239,61 +244,220 @@
 //! bevy_scriptum is licensed under either of the following, at your option:
 //! Apache License, Version 2.0, (LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0) or MIT license (LICENSE-MIT or http://opensource.org/licenses/MIT)
 
+mod actions;
+pub mod activation;
+#[cfg(feature = "animation")]
+mod animation;
 mod assets;
+mod blackboard;
+mod boot;
+mod cache;
+pub mod call_queue;
 mod callback;
+mod capabilities;
+mod commands;
 mod components;
+mod conflicts;
+pub mod conformance;
+mod data;
+#[cfg(feature = "debugger")]
+pub mod debugger;
+mod declarations;
+pub mod decoders;
+mod dyn_runtime;
+mod fixed_update;
+mod hierarchy;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+mod introspection;
+mod json;
+pub mod localization;
+mod logging;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mods;
+mod named_systems;
+mod pack;
+#[cfg(feature = "physics")]
+pub mod physics;
 mod promise;
+mod registry;
+mod reload;
+mod required_functions;
+mod rng;
+pub mod runtime_support;
+pub mod scaffold;
+mod script_components;
+mod script_errors;
+mod script_function;
+mod script_systems;
+mod shutdown;
+#[cfg(feature = "states")]
+mod states;
 mod systems;
+pub mod testing;
+mod tracking;
+mod transform;
+#[cfg(feature = "ui")]
+mod ui;
+mod unregister;
+mod value;
 
 pub mod runtimes;
 
-pub use crate::components::Script;
+pub use crate::actions::ScriptActions;
+pub use crate::activation::{ScriptActivationRange, ScriptActivator, ScriptSuspended};
+pub use crate::assets::{
+    ResolveImports, ScriptExtensionPolicy, ScriptLoadError, ScriptLoaderSettings,
+};
+pub use crate::blackboard::{Blackboard, BlackboardChanged};
+pub use crate::cache::CompiledScriptCache;
+pub use crate::call_queue::{ScriptCallError, ScriptCallQueue, ScriptCallSender};
+pub use crate::capabilities::ScriptCapabilities;
+pub use crate::commands::ScriptEntityCommands;
+#[cfg(feature = "ui")]
+pub use crate::components::ScriptOnClick;
+#[cfg(feature = "animation")]
+pub use crate::components::{AnimationMarker, ScriptAnimationEvents};
+pub use crate::components::{Script, ScriptInfo, ScriptMod, ScriptTags};
+pub use crate::conflicts::{FunctionConflict, FunctionConflictPolicy};
+pub use crate::dyn_runtime::{
+    DynFunction, DynScriptingRuntime, DynScriptingRuntimes, MAX_DYN_FUNCTION_ARITY,
+};
+pub use crate::fixed_update::ScriptFixedUpdateSet;
+pub use crate::introspection::ScriptIntrospection;
+pub use crate::json::Json;
+pub use crate::logging::SCRIPT_LOG_TARGET;
+pub use crate::pack::{ResolvePackModules, ScriptPack};
+pub use crate::promise::PromiseResolved;
+pub use crate::registry::{ScriptApiRegistry, ScriptFunctionInfo};
+pub use crate::reload::ScriptReloaded;
+pub use crate::required_functions::ScriptMissingFunctions;
+pub use crate::rng::ScriptRng;
+pub use crate::script_components::{ScriptComponentDefinitions, ScriptComponents};
+pub use crate::script_errors::{ScriptDiagnostic, ScriptError};
+pub use crate::script_function::ScriptFunction;
+pub use crate::systems::{
+    apply_script_calls, resume_paused_calls, run_script_fn_on_all, run_script_fn_on_changed,
+    run_script_fn_on_tagged,
+};
+pub use crate::tracking::{CallFinished, TrackedCall};
+pub use crate::unregister::remove_script_function;
+pub use crate::value::ScriptValue;
 use assets::GetExtensions;
-use promise::Promise;
+use promise::{poll_pending_calls, Promise, PromiseQueue};
+use reload::{notify_reloaded_scripts, ReloadHooks};
 
 use std::{
     any::TypeId,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     hash::Hash,
     marker::PhantomData,
     sync::{Arc, Mutex},
 };
 
-use bevy::{app::MainScheduleOrder, ecs::schedule::ScheduleLabel, prelude::*};
-use callback::{Callback, IntoCallbackSystem};
-use systems::{init_callbacks, log_errors, process_calls};
+use bevy::{
+    app::MainScheduleOrder, asset::AssetPath, ecs::schedule::ScheduleLabel, prelude::*,
+    utils::tracing,
+};
+use boot::{eval_boot_scripts, BootScripts};
+use callback::{
+    Callback, FromRuntimeValueWithEngine, FunctionId, IntoCallbackSystem,
+    IntoRuntimeValueWithEngine, PendingFunctions,
+};
+use decoders::ScriptDecoder;
+use named_systems::{run_named_system, NamedSystems, RUN_SYSTEM_FN_NAME};
+use script_systems::{
+    register_script_system, run_script_systems, script_system_schedules, unregister_script_system,
+    ScriptSystems, REGISTER_SYSTEM_FN_NAME, UNREGISTER_SYSTEM_FN_NAME,
+};
+use systems::{has_queued_calls, init_callbacks, log_errors, process_calls};
 use thiserror::Error;
+use tracking::CallTracker;
 
 use self::{
-    assets::ScriptLoader,
+    assets::{ScriptDecoders, ScriptLoadFailures, ScriptLoader, ScriptPreprocessors},
+    capabilities::{sync_capabilities, CapabilityGrants},
+    pack::{pack_extensions, ScriptPackLoader},
+    script_errors::send_load_errors,
     systems::{process_new_scripts, reload_scripts},
 };
 
-const ENTITY_VAR_NAME: &str = "entity";
+/// Name of the constant under which the API version set with
+/// [ScriptingRuntimeBuilder::set_api_version] is available to scripts.
+pub const API_VERSION_VAR_NAME: &str = "API_VERSION";
 
 /// An error that can occur when internal [ScriptingPlugin] systems are being executed
 #[derive(Error, Debug)]
 pub enum ScriptingError {
     #[error("script runtime error: {0}")]
-    RuntimeError(Box<dyn std::error::Error>),
+    RuntimeError(Box<dyn std::error::Error + Send + Sync>),
     #[error("script compilation error: {0}")]
-    CompileError(Box<dyn std::error::Error>),
+    CompileError(ScriptDiagnostic),
+    #[error("script loading error: {0}")]
+    LoadError(ScriptLoadError),
+    #[error("function `{0}` is not defined in script")]
+    FunctionNotFound(String),
+    #[error("no overload of function `{0}` matches provided arguments")]
+    NoMatchingOverload(String),
+    #[error("type mismatch: expected `{expected}`, got `{got}`")]
+    TypeMismatch { expected: String, got: String },
+    #[error("argument {position} of function `{function}` has to be {expected}, got {got}")]
+    InvalidArgument {
+        function: String,
+        position: usize,
+        expected: String,
+        got: String,
+    },
+    #[error("script panicked: {0}")]
+    ScriptPanicked(String),
+    #[error("function `{function}` panicked: {message}")]
+    FunctionPanicked { function: String, message: String },
+    #[error("calling {function} requires {capability} capability")]
+    PermissionDenied {
+        function: String,
+        capability: String,
+    },
+    #[error("script has no paused call to resume")]
+    NoPausedCall,
     #[error("no runtime resource present")]
     NoRuntimeResource,
+    #[error("entity {0} has no evaluated script of the runtime")]
+    NoScript(Entity),
+    #[error("script {0} has not been loaded yet")]
+    ScriptNotLoaded(String),
+    #[error("function `{function}` takes {arity} arguments, at most {max} are supported")]
+    UnsupportedArity {
+        function: String,
+        arity: usize,
+        max: usize,
+    },
+    #[error("declared signature of function `{function}` does not match it: {reason}")]
+    SignatureMismatch { function: String, reason: String },
     #[error("no settings resource present")]
     NoSettingsResource,
+    #[error("{0} is not supported by the runtime")]
+    Unsupported(String),
 }
 
 /// Trait that represents a scripting runtime/engine. In practice it is
 /// implemented for a scripint language interpreter and the implementor provides
 /// function implementations for calling and registering functions within the interpreter.
-pub trait Runtime: Resource + Default {
+///
+/// Methods added after 0.6 have default implementations, which report
+/// [ScriptingError::Unsupported] or do nothing, so runtimes only have to override the ones
+/// for features they support.
+pub trait Runtime: Resource + FromWorld + Sized {
     type Schedule: ScheduleLabel + Debug + Clone + Eq + Hash + Default;
-    type ScriptAsset: Asset + From<String> + GetExtensions;
+    type ScriptAsset: Asset + ResolveImports + GetExtensions + ResolvePackModules;
     type ScriptData: Component;
     type CallContext: Send + Clone;
     type Value: Send + Clone;
     type RawEngine;
+    /// A captured state of a script, see [Runtime::snapshot_state].
+    type Snapshot: Send + Sync + Clone;
 
     /// Provides mutable reference to raw scripting engine instance.
     /// Can be used to directly interact with an interpreter to use interfaces
@@ -305,17 +469,50 @@ pub trait Runtime: Resource + Default {
     /// that bevy_scriptum does not provided adapters for.
     fn with_engine<T>(&self, f: impl FnOnce(&Self::RawEngine) -> T) -> T;
 
+    /// Provides immutable reference to the raw scripting engine instance that values passed
+    /// within provided call context belong to. Runtimes that use more than one engine instance
+    /// should override it, by default it is the same as [Runtime::with_engine].
+    fn with_context_engine<T>(
+        &self,
+        _context: &Self::CallContext,
+        f: impl FnOnce(&Self::RawEngine) -> T,
+    ) -> T {
+        self.with_engine(f)
+    }
+
+    /// Evaluates a script attached to provided entity. Provided script information is exposed
+    /// to the script as `script` global variable, during evaluation and subsequent calls.
     fn eval(
         &self,
         script: &Self::ScriptAsset,
         entity: Entity,
+        info: &ScriptInfo,
     ) -> Result<Self::ScriptData, ScriptingError>;
 
+    /// Evaluates provided base scripts, along with their paths, and then a script attached to
+    /// provided entity, all in the same environment of the entity. See [Script::with_base].
+    fn eval_with_bases(
+        &self,
+        bases: &[(&Self::ScriptAsset, String)],
+        script: &Self::ScriptAsset,
+        entity: Entity,
+        info: &ScriptInfo,
+    ) -> Result<Self::ScriptData, ScriptingError> {
+        if !bases.is_empty() {
+            return Err(ScriptingError::Unsupported(
+                "evaluating base scripts".to_string(),
+            ));
+        }
+        self.eval(script, entity, info)
+    }
+
     /// Registers a new function within the scripting engine. Provided callback
     /// function will be called when the function with provided name gets called
-    /// in script.
+    /// in script. A function registered within a namespace is accessed by scripts
+    /// as its member, e.g. `game.give_item` in Lua and `game::give_item` in Rhai.
     fn register_fn(
         &mut self,
+        namespace: Option<&str>,
         name: String,
         arg_types: Vec<TypeId>,
         f: impl Fn(
@@ -327,6 +524,41 @@ pub trait Runtime: Resource + Default {
             + 'static,
     ) -> Result<(), ScriptingError>;
 
+    /// Starts a batch of registrations, in which functions registered with [Runtime::register_fn]
+    /// don't have to be installed into the engine until [Runtime::finish_registering_fns] gets
+    /// called, so that all of them get installed at once. Registering functions one at a time is
+    /// still correct, runtimes that install functions cheaply don't need to override it.
+    fn start_registering_fns(&mut self) {}
+
+    /// Installs functions registered since [Runtime::start_registering_fns] got called.
+    fn finish_registering_fns(&mut self) -> Result<(), ScriptingError> {
+        Ok(())
+    }
+
+    /// Removes the function with provided name registered with [Runtime::register_fn] within
+    /// provided namespace, along with all of its overloads, so that scripts can no longer call it.
+    fn unregister_fn(
+        &mut self,
+        _namespace: Option<&str>,
+        _name: &str,
+    ) -> Result<(), ScriptingError> {
+        Err(ScriptingError::Unsupported(
+            "unregistering functions".to_string(),
+        ))
+    }
+
+    /// Registers a constant value that will be available under provided name
+    /// to every script evaluated by this runtime.
+    fn register_constant(
+        &mut self,
+        _name: String,
+        _value: Self::Value,
+    ) -> Result<(), ScriptingError> {
+        Err(ScriptingError::Unsupported(
+            "registering constants".to_string(),
+        ))
+    }
+
     /// Calls a function by name defined within the runtime in the context of the
     /// entity that haas been paassed. Can return a dynamically typed value
     /// that got returned from the function within a script.
@@ -338,6 +570,91 @@ pub trait Runtime: Resource + Default {
         args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
     ) -> Result<Self::Value, ScriptingError>;
 
+    /// Calls a function by name the same way as [Runtime::call_fn], but returns `Ok(None)`
+    /// instead of an error if the script does not define a function with provided name.
+    /// Useful for optional hooks like `on_update` that not every script defines.
+    fn call_fn_if_exists(
+        &self,
+        name: &str,
+        script_data: &mut Self::ScriptData,
+        entity: Entity,
+        args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+    ) -> Result<Option<Self::Value>, ScriptingError> {
+        match self.call_fn(name, script_data, entity, args) {
+            Ok(value) => Ok(Some(value)),
+            Err(ScriptingError::FunctionNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns `true` if provided script defines a function with provided name, which can be
+    /// called with [Runtime::call_fn].
+    fn has_fn(&self, _name: &str, _script_data: &Self::ScriptData) -> bool {
+        false
+    }
+
+    /// Returns names of functions defined by provided script, including ones defined by its
+    /// bases, sorted and without duplicates. Functions registered with
+    /// [ScriptingRuntimeBuilder::add_function] are not included.
+    fn list_functions(&self, _script_data: &Self::ScriptData) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Sets the name of the variable holding the entity that the running script is attached to,
+    /// [ENTITY_VAR_NAME](runtime_support::ENTITY_VAR_NAME) by default, or stops exposing the
+    /// entity to scripts if `None`. Only affects scripts evaluated afterwards. Runtimes that
+    /// don't override it always expose the entity under the default name.
+    fn set_entity_variable(&mut self, _name: Option<String>) {}
+
+    /// Calls a function by name the same way as [Runtime::call_fn], returning a handle that
+    /// tracks promises spawned by the call, including ones spawned by their callbacks, so that
+    /// Rust code can find out when the call completes as a whole, possibly several frames later.
+    fn call_fn_tracked(
+        &self,
+        name: &str,
+        script_data: &mut Self::ScriptData,
+        entity: Entity,
+        args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+    ) -> Result<TrackedCall<Self::Value>, ScriptingError> {
+        let tracker = CallTracker::default();
+        let value = {
+            let _tracker_guard = tracker.enter();
+            self.call_fn(name, script_data, entity, args)?
+        };
+        Ok(TrackedCall::new(value, tracker))
+    }
+
+    /// Calls a function by name the same way as [Runtime::call_fn], but pauses it once it
+    /// executes `budget` instructions, so that it can be resumed later with
+    /// [Runtime::resume_call], for example in the next frame. A script with a paused call
+    /// must not be called in any other way until the call finishes.
+    fn call_fn_resumable(
+        &self,
+        _name: &str,
+        _script_data: &mut Self::ScriptData,
+        _entity: Entity,
+        _args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+        _budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        Err(ScriptingError::Unsupported("resumable calls".to_string()))
+    }
+
+    /// Resumes a call paused by [Runtime::call_fn_resumable], allowing it to execute another
+    /// `budget` instructions.
+    fn resume_call(
+        &self,
+        _script_data: &mut Self::ScriptData,
+        _entity: Entity,
+        _budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        Err(ScriptingError::NoPausedCall)
+    }
+
+    /// Returns `true` if provided script has a call paused by [Runtime::call_fn_resumable].
+    fn has_paused_call(&self, _script_data: &Self::ScriptData) -> bool {
+        false
+    }
+
     /// Calls a function by value defined within the runtime in the context of the
     /// entity that haas been paassed. Can return a dynamically typed value
     /// that got returned from the function within a script.
@@ -347,12 +664,110 @@ pub trait Runtime: Resource + Default {
         context: &Self::CallContext,
         args: Vec<Self::Value>,
     ) -> Result<Self::Value, ScriptingError>;
+
+    /// Returns a human readable representation of provided value, used for example to record
+    /// calls in [testing::GoldenTest]. Runtimes that don't override it only name the type.
+    fn format_value(&self, _value: &Self::Value, _context: &Self::CallContext) -> String {
+        format!("<{}>", std::any::type_name::<Self::Value>())
+    }
+
+    /// Converts provided value, e.g. one returned by a function of a script with provided data,
+    /// into a [ScriptValue], so that host code can handle it regardless of the language of the
+    /// script. Values that [ScriptValue] can't hold, like functions, get converted into
+    /// [ScriptValue::Nil].
+    fn to_script_value(
+        &self,
+        _value: &Self::Value,
+        _script_data: &Self::ScriptData,
+    ) -> ScriptValue {
+        ScriptValue::Nil
+    }
+
+    /// Releases resources that the runtime holds outside of its engine, called by
+    /// [BuildScriptingRuntime::remove_scripting] right before the runtime, along with its
+    /// engine, gets dropped.
+    fn shutdown(&mut self) {}
+
+    /// Provides the cache that the runtime stores scripts it compiles in, called when the runtime
+    /// gets added. Runtimes that don't override it compile scripts every time they evaluate them.
+    fn set_compiled_script_cache(&mut self, _cache: CompiledScriptCache<Self>) {}
+
+    /// Makes random number functions of the language use provided generator and disables
+    /// functions reading wall-clock time, see [ScriptRng].
+    fn enable_deterministic_mode(&mut self, _rng: ScriptRng) -> Result<(), ScriptingError> {
+        Err(ScriptingError::Unsupported(
+            "deterministic mode".to_string(),
+        ))
+    }
+
+    /// Attaches provided debugger, making scripts stop at its breakpoints,
+    /// see [debugger::ScriptDebugger].
+    #[cfg(feature = "debugger")]
+    fn enable_debugger(
+        &mut self,
+        _debugger: debugger::ScriptDebugger,
+    ) -> Result<(), ScriptingError> {
+        Err(ScriptingError::Unsupported("debugging".to_string()))
+    }
+
+    /// Returns variables of provided script that can be inspected, see [inspector].
+    #[cfg(feature = "inspector")]
+    fn script_variables(&self, _script_data: &Self::ScriptData) -> Vec<inspector::ScriptVariable> {
+        Vec::new()
+    }
+
+    /// Sets the value of a variable of provided script, named as in [Runtime::script_variables].
+    #[cfg(feature = "inspector")]
+    fn set_script_variable(
+        &self,
+        _script_data: &mut Self::ScriptData,
+        _name: &str,
+        _value: inspector::ScriptVariableValue,
+    ) -> Result<(), ScriptingError> {
+        Err(ScriptingError::Unsupported(
+            "setting script variables".to_string(),
+        ))
+    }
+
+    /// Captures the state of a script, so it can be brought back later with
+    /// [Runtime::restore_state], for example when rolling back a simulation.
+    fn snapshot_state(
+        &self,
+        _script_data: &Self::ScriptData,
+    ) -> Result<Self::Snapshot, ScriptingError> {
+        Err(ScriptingError::Unsupported("snapshots".to_string()))
+    }
+
+    /// Brings the state of a script back to provided snapshot taken with [Runtime::snapshot_state].
+    fn restore_state(
+        &self,
+        _script_data: &mut Self::ScriptData,
+        _snapshot: &Self::Snapshot,
+    ) -> Result<(), ScriptingError> {
+        Err(ScriptingError::Unsupported("snapshots".to_string()))
+    }
 }
 
 pub trait FuncArgs<'a, V, R: Runtime> {
     fn parse(self, engine: &'a R::RawEngine) -> Vec<V>;
 }
 
+/// Implemented by runtimes that can pass an [Entity] to script functions, the same way as
+/// the `entity` variable is exposed to scripts.
+pub trait EntityArgs: Runtime {
+    /// Arguments consisting of a single entity.
+    type Args: for<'a> FuncArgs<'a, Self::Value, Self>;
+
+    /// Type that entities passed by scripts to registered functions are received as.
+    type ScriptEntity: Send + Sync + 'static;
+
+    /// Wraps provided entity into arguments that can be passed to a script function.
+    fn entity_args(entity: Entity) -> Self::Args;
+
+    /// Unwraps an entity passed by a script to a registered function.
+    fn script_entity(entity: Self::ScriptEntity) -> Entity;
+}
+
 /// An extension trait for [App] that allows to setup a scripting runtime `R`.
 pub trait BuildScriptingRuntime {
     /// Returns a "runtime" type than can be used to setup scripting runtime(
@@ -364,6 +779,33 @@ pub trait BuildScriptingRuntime {
         &mut self,
         f: impl Fn(ScriptingRuntimeBuilder<R>),
     ) -> &mut Self;
+
+    /// Returns a "runtime" type that registers every function and constant in both
+    /// runtimes `A` and `B`, so projects using two scripting languages can share the setup code.
+    fn add_mirrored_scripting_api<A: Runtime, B: Runtime>(
+        &mut self,
+        f: impl Fn(MirroredScriptingRuntimeBuilder<A, B>),
+    ) -> &mut Self;
+
+    /// Removes scripting runtime `R`, dropping its engine, functions registered in it and data
+    /// of all scripts it runs, so that scripting can be added again later with
+    /// [BuildScriptingRuntime::add_scripting], e.g. to reload all mods without restarting the
+    /// game. [Script] components are kept, so their scripts get evaluated again once scripting
+    /// is added back, while boot scripts are not, as they only run at startup.
+    /// [ScriptingSettings] are kept too.
+    fn remove_scripting<R: Runtime>(&mut self) -> &mut Self;
+
+    /// Evaluates the script with provided path whenever `state` is entered and despawns it when
+    /// the state is exited, calling its `on_exit` function first if it defines one. Calls that
+    /// the script makes to registered functions complete within the state transition.
+    #[cfg(feature = "states")]
+    fn add_state_script<R: Runtime, S: States>(
+        &mut self,
+        state: S,
+        path: impl Into<AssetPath<'static>>,
+    ) -> &mut Self
+    where
+        (): for<'a> FuncArgs<'a, R::Value, R>;
 }
 
 pub struct ScriptingRuntimeBuilder<'a, R: Runtime> {
@@ -388,16 +830,872 @@ impl<'a, R: Runtime> ScriptingRuntimeBuilder<'a, R> {
         name: String,
         fun: impl IntoCallbackSystem<R, In, Out, Marker>,
     ) -> Self {
+        self.try_add_function(name, fun).unwrap_or_else(|conflict| {
+            tracing::error!("error registering function: {}", conflict);
+            conflict.into_builder()
+        })
+    }
+
+    /// Registers a function like [ScriptingRuntimeBuilder::add_function], failing if it
+    /// conflicts with a function registered before and the [FunctionConflictPolicy] set with
+    /// [ScriptingRuntimeBuilder::set_function_conflict_policy] does not allow replacing it.
+    pub fn try_add_function<In, Out, Marker>(
+        self,
+        name: String,
+        fun: impl IntoCallbackSystem<R, In, Out, Marker>,
+    ) -> Result<Self, FunctionConflict<'a, R>> {
+        self.add_callback(None, name, fun)
+    }
+
+    /// Removes a function registered before, along with all of its overloads, see
+    /// [remove_script_function].
+    pub fn remove_function(self, name: &str) -> Self {
+        remove_script_function::<R>(self.world, None, name);
+
+        self
+    }
+
+    /// Sets what happens when a function registered in this runtime from now on conflicts with
+    /// a function registered before, see [FunctionConflictPolicy].
+    pub fn set_function_conflict_policy(self, policy: FunctionConflictPolicy) -> Self {
+        self.world.resource_mut::<Callbacks<R>>().conflict_policy = policy;
+
+        self
+    }
+
+    /// Sets the name of the plugin that functions registered in this runtime from now on are
+    /// listed as registered by in [ScriptApiRegistry], or clears it with `None`.
+    pub fn set_function_source(self, source: Option<impl Into<String>>) -> Self {
+        self.world.resource_mut::<Callbacks<R>>().source = source.map(Into::into);
+
+        self
+    }
+
+    /// Returns a builder registering functions within provided namespace, so that scripts
+    /// access them as its members, e.g. `game.give_item` in Lua and `game::give_item` in Rhai.
+    pub fn namespace(self, namespace: impl Into<String>) -> NamespaceBuilder<'a, R> {
+        NamespaceBuilder {
+            builder: self,
+            namespace: namespace.into(),
+        }
+    }
+
+    /// Lets registered functions resolve promises of their calls later with
+    /// [DeferredCall](promise::DeferredCall).
+    fn enable_deferred_calls(&mut self)
+    where
+        ScriptValue: for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+    {
+        self.world.init_resource::<promise::DeferredCall>();
+        if !self.world.contains_resource::<promise::PendingCalls<R>>() {
+            self.world
+                .insert_resource(promise::PendingCalls::<R>::new(|value, engine| {
+                    ScriptValue::try_into_runtime_value_with_engine(value, engine)
+                }));
+        }
+    }
+
+    /// Returns a run condition for systems added to schedules other than [Runtime::Schedule],
+    /// which stops them from running once scripting is removed with
+    /// [BuildScriptingRuntime::remove_scripting], as they can't be removed from schedules.
+    fn while_added(
+        &self,
+    ) -> impl FnMut(Option<Res<R>>, Option<Res<shutdown::ScriptingGeneration<R>>>) -> bool
+           + Send
+           + Sync
+           + 'static {
+        shutdown::in_generation::<R>(shutdown::current_generation::<R>(self.world))
+    }
+
+    fn add_callback<In, Out, Marker>(
+        self,
+        namespace: Option<String>,
+        name: String,
+        fun: impl IntoCallbackSystem<R, In, Out, Marker>,
+    ) -> Result<Self, FunctionConflict<'a, R>> {
         let system = fun.into_callback_system(self.world);
 
         let mut callbacks_resource = self.world.resource_mut::<Callbacks<R>>();
 
+        let namespace =
+            match callbacks_resource.resolve_conflict(namespace, &name, &system.arg_types) {
+                Ok(namespace) => namespace,
+                Err(name) => return Err(self.conflict(name)),
+            };
+        let id = callbacks_resource.next_id();
+        let source = callbacks_resource.source.clone();
         callbacks_resource.uninitialized_callbacks.push(Callback {
+            id,
             name,
+            namespace,
             system: Arc::new(Mutex::new(system)),
             calls: Arc::new(Mutex::new(vec![])),
+            capabilities: vec![],
+            source,
         });
 
+        Ok(self)
+    }
+
+    /// Registers a function like [ScriptingRuntimeBuilder::add_function] and returns
+    /// a [FunctionRegistration] that allows configuring it further.
+    pub fn add_function_with<In, Out, Marker>(
+        self,
+        name: String,
+        fun: impl IntoCallbackSystem<R, In, Out, Marker>,
+    ) -> FunctionRegistration<'a, R> {
+        match self.try_add_function(name, fun) {
+            Ok(builder) => FunctionRegistration {
+                builder,
+                registered: true,
+            },
+            Err(conflict) => {
+                tracing::error!("error registering function: {}", conflict);
+                FunctionRegistration {
+                    builder: conflict.into_builder(),
+                    registered: false,
+                }
+            }
+        }
+    }
+
+    /// Registers a function that transforms the source of every script before it is compiled,
+    /// e.g. to expand templates or inject prelude code. It receives the script source and its
+    /// asset path. Preprocessors run in the order they were added and only affect scripts
+    /// loaded afterwards.
+    pub fn add_script_preprocessor(
+        self,
+        preprocessor: impl Fn(String, &std::path::Path) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.world
+            .resource::<ScriptPreprocessors<R::ScriptAsset>>()
+            .preprocessors
+            .write()
+            .expect("Failed to lock preprocessors")
+            .push(Box::new(preprocessor));
+
+        self
+    }
+
+    /// Registers a decoder for script files whose name ends with provided suffix, for example
+    /// `"gz"` makes `script.lua.gz` files loadable by Lua runtime. The suffix is stripped and
+    /// the decoded bytes are loaded as a regular script. Decoders have to be added within
+    /// `add_scripting`, since file extensions of a loader can't be changed after it's registered.
+    pub fn add_script_decoder(self, suffix: &'static str, decoder: impl ScriptDecoder) -> Self {
+        self.world
+            .resource_mut::<ScriptDecoders<R::ScriptAsset>>()
+            .decoders
+            .push((suffix, Arc::new(decoder)));
+
+        self
+    }
+
+    /// Registers a one-shot system that scripts can run by name with `run_system` function,
+    /// e.g. `run_system("respawn_all")`. Like calls of other functions, the system runs in
+    /// the scripting schedule and the call returns a promise that resolves once it has run.
+    pub fn add_named_system<M>(
+        self,
+        name: impl Into<String>,
+        system: impl IntoSystem<(), (), M> + 'static,
+    ) -> Self
+    where
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+    {
+        let system = self.world.register_system(system);
+        let builder = if self.world.contains_resource::<NamedSystems<R>>() {
+            self
+        } else {
+            self.world.init_resource::<NamedSystems<R>>();
+            self.add_function(RUN_SYSTEM_FN_NAME.to_string(), run_named_system::<R>)
+        };
+        builder
+            .world
+            .resource_mut::<NamedSystems<R>>()
+            .systems
+            .insert(name.into(), system);
+
+        builder
+    }
+
+    /// Lets scripts register functions that get called every frame in a schedule with
+    /// `register_system` function, e.g. `register_system("Update", tick)`, which is passed the
+    /// time elapsed since the last frame in seconds. The call resolves to an id that can be passed
+    /// to `unregister_system` to stop calling the function. Supported schedules are `PreUpdate`,
+    /// `Update`, `PostUpdate` and `FixedUpdate`, the latter passing the fixed timestep.
+    pub fn add_script_systems(self) -> Self
+    where
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+        Option<i64>: for<'b> IntoRuntimeValueWithEngine<'b, Option<i64>, R>,
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        i64: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        (f64,): for<'b> FuncArgs<'b, R::Value, R>,
+    {
+        if self.world.contains_resource::<ScriptSystems<R>>() {
+            return self;
+        }
+        self.world.init_resource::<ScriptSystems<R>>();
+        for (name, schedule) in script_system_schedules() {
+            let condition = self.while_added();
+            self.world
+                .resource_mut::<Schedules>()
+                .add_systems(schedule, run_script_systems::<R>(name).run_if(condition));
+        }
+
+        self.add_function(
+            REGISTER_SYSTEM_FN_NAME.to_string(),
+            register_script_system::<R>,
+        )
+        .add_function(
+            UNREGISTER_SYSTEM_FN_NAME.to_string(),
+            unregister_script_system::<R>,
+        )
+    }
+
+    /// Forwards collision events `E` to scripts, calling `on_collision_start` and
+    /// `on_collision_stop` functions of both scripted entities involved in a collision with the
+    /// other entity, see [physics].
+    #[cfg(feature = "physics")]
+    pub fn forward_collision_events<E: physics::ScriptCollisionEvent>(self) -> Self
+    where
+        R: EntityArgs,
+    {
+        let condition = self.while_added();
+        self.world.resource_mut::<Schedules>().add_systems(
+            Update,
+            physics::forward_collision_events::<R, E>.run_if(condition),
+        );
+
+        self
+    }
+
+    /// Exposes `physics.raycast` and `physics.overlap_sphere` functions, answered by resource
+    /// `S` of a physics engine, to scripts of this runtime, see [physics].
+    #[cfg(feature = "physics")]
+    pub fn enable_spatial_queries<S: physics::ScriptSpatialQuery>(self) -> Self
+    where
+        f64: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> FromRuntimeValueWithEngine<'b, R>
+            + for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+    {
+        use physics::{overlap_sphere, raycast, PHYSICS_NAMESPACE};
+
+        self.namespace(PHYSICS_NAMESPACE)
+            .add_function("raycast", raycast::<S>)
+            .add_function("overlap_sphere", overlap_sphere::<S>)
+            .finish()
+    }
+
+    /// Suspends scripts of entities with [ScriptActivationRange] component while no entity with
+    /// [ScriptActivator] component is within their range, calling their `on_suspend` and
+    /// `on_resume` functions, see [activation].
+    pub fn enable_script_activation(self) -> Self
+    where
+        (): for<'b> FuncArgs<'b, R::Value, R>,
+    {
+        // Running right after new scripts get evaluated suspends them before any hook is called.
+        self.world.resource_mut::<Schedules>().add_systems(
+            R::Schedule::default(),
+            activation::update_script_activation::<R>
+                .after(process_new_scripts::<R>)
+                .before(process_calls::<R>),
+        );
+
+        self
+    }
+
+    /// Adds the [ScriptCallQueue] resource, through which threads other than the one running the
+    /// scripting schedule, like audio callbacks or network tasks, can queue calls of script
+    /// functions, see [call_queue]. The queue holds at most `capacity` calls that haven't run
+    /// yet, [DEFAULT_CALL_QUEUE_CAPACITY](call_queue::DEFAULT_CALL_QUEUE_CAPACITY) is enough
+    /// for calls queued about as often as frames are run.
+    pub fn enable_call_queue(self, capacity: usize) -> Self
+    where
+        Vec<ScriptValue>: for<'b> FuncArgs<'b, R::Value, R>,
+    {
+        if self.world.contains_resource::<ScriptCallQueue<R>>() {
+            return self;
+        }
+        self.world
+            .insert_resource(ScriptCallQueue::<R>::new(capacity));
+        // Promises of queued calls get processed in the same frame.
+        self.world.resource_mut::<Schedules>().add_systems(
+            R::Schedule::default(),
+            call_queue::run_queued_calls::<R>
+                .after(process_new_scripts::<R>)
+                .before(process_calls::<R>),
+        );
+
+        self
+    }
+
+    /// Calls `on_fixed_update(dt)` function of scripts that define it every fixed timestep, in
+    /// [ScriptFixedUpdateSet] of `FixedUpdate`, passing it the timestep in seconds. Calls made by
+    /// scripts from the function get processed before the set ends, so physics stepped in
+    /// `FixedPostUpdate` or `PostUpdate` sees their effects. Suspended entities are skipped.
+    pub fn enable_fixed_update(self) -> Self
+    where
+        (f64,): for<'b> FuncArgs<'b, R::Value, R>,
+    {
+        let condition = self.while_added();
+        self.world.resource_mut::<Schedules>().add_systems(
+            FixedUpdate,
+            (
+                fixed_update::call_fixed_update::<R>,
+                apply_script_calls::<R>,
+            )
+                .chain()
+                .in_set(ScriptFixedUpdateSet)
+                .run_if(condition),
+        );
+
+        self
+    }
+
+    /// Calls the function with provided name, without arguments, of scripts tagged with provided
+    /// tag in their [ScriptTags] whenever provided schedule runs, see [run_script_fn_on_tagged].
+    pub fn run_tagged_scripts_in(self, schedule: impl ScheduleLabel, tag: &str, name: &str) -> Self
+    where
+        (): for<'b> FuncArgs<'b, R::Value, R>,
+    {
+        let condition = self.while_added();
+        self.world.resource_mut::<Schedules>().add_systems(
+            schedule,
+            run_script_fn_on_tagged::<R, _>(tag, name, ()).run_if(condition),
+        );
+
+        self
+    }
+
+    /// Makes entities with [ScriptOnClick] component call their script function when pressed.
+    #[cfg(feature = "ui")]
+    pub fn enable_script_on_click(self) -> Self
+    where
+        R: EntityArgs,
+    {
+        let condition = self.while_added();
+        self.world
+            .resource_mut::<Schedules>()
+            .add_systems(Update, ui::call_script_on_click::<R>.run_if(condition));
+
+        self
+    }
+
+    /// Mirrors variables of scripts in [inspector::ScriptVariables] components, so that they can
+    /// be viewed and edited in an inspector.
+    #[cfg(feature = "inspector")]
+    pub fn enable_inspector(self) -> Self {
+        {
+            let mut registry = self.world.resource::<AppTypeRegistry>().write();
+            registry.register::<inspector::ScriptVariables>();
+            registry.register::<inspector::ScriptVariable>();
+            registry.register::<inspector::ScriptVariableValue>();
+        }
+        let condition = self.while_added();
+        self.world.resource_mut::<Schedules>().add_systems(
+            Update,
+            inspector::sync_script_variables::<R>.run_if(condition),
+        );
+
+        self
+    }
+
+    /// Makes entities with [ScriptAnimationEvents] component call `on_animation_event`
+    /// function of their script when their animations pass markers.
+    #[cfg(feature = "animation")]
+    pub fn enable_animation_events(self) -> Self
+    where
+        (String,): for<'b> FuncArgs<'b, R::Value, R>,
+    {
+        let condition = self.while_added();
+        self.world.resource_mut::<Schedules>().add_systems(
+            PostUpdate,
+            animation::trigger_animation_events::<R>
+                .after(bevy::animation::advance_animations)
+                .run_if(condition),
+        );
+
+        self
+    }
+
+    /// Exposes state `S` to scripts, which can get the name of its current variant with
+    /// `state.get("GameState")` and queue a transition with `state.set("GameState", "Paused")`,
+    /// using the short type name of the state. Only unit variants of enum states are supported.
+    /// In Rhai the functions are called as `state::get` and `state::set`.
+    #[cfg(feature = "states")]
+    pub fn expose_state<S: bevy::state::state::FreelyMutableState + FromReflect + TypePath>(
+        self,
+    ) -> Self
+    where
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+        Option<String>: for<'b> IntoRuntimeValueWithEngine<'b, Option<String>, R>,
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+    {
+        use states::{
+            get_exposed_state, get_state, set_exposed_state, set_state, ExposedStates,
+            STATE_NAMESPACE,
+        };
+
+        let builder = if self.world.contains_resource::<ExposedStates<R>>() {
+            self
+        } else {
+            self.world.init_resource::<ExposedStates<R>>();
+            self.namespace(STATE_NAMESPACE)
+                .add_function("get", get_exposed_state::<R>)
+                .add_function("set", set_exposed_state::<R>)
+                .finish()
+        };
+        builder
+            .world
+            .resource_mut::<ExposedStates<R>>()
+            .states
+            .insert(
+                S::short_type_path().to_string(),
+                (get_state::<S>, set_state::<S>),
+            );
+
+        builder
+    }
+
+    /// Registers a system that runs whenever a script has been evaluated again after its asset
+    /// has been modified, receiving the [ScriptReloaded] event, e.g. to re-bind cached
+    /// [ScriptFunction]s of the script. [ScriptReloaded] events are sent regardless of it.
+    pub fn on_script_reloaded<M>(
+        self,
+        system: impl IntoSystem<ScriptReloaded<R>, (), M> + 'static,
+    ) -> Self {
+        let system = self.world.register_system(system);
+        self.world
+            .resource_mut::<ReloadHooks<R>>()
+            .systems
+            .push(system);
+
+        self
+    }
+
+    /// Sends [PromiseResolved] events whenever functions registered in this runtime return.
+    pub fn enable_promise_events(self) -> Self {
+        if !self.world.contains_resource::<Events<PromiseResolved<R>>>() {
+            bevy::ecs::event::EventRegistry::register_event::<PromiseResolved<R>>(self.world);
+        }
+
+        self
+    }
+
+    /// Exposes the [Blackboard] resource, shared by scripts of all runtimes, to scripts of this
+    /// runtime with `blackboard.get` and `blackboard.set` functions, and sends
+    /// [BlackboardChanged] events when its values change.
+    pub fn enable_blackboard(self) -> Self
+    where
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> FromRuntimeValueWithEngine<'b, R>
+            + for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        use blackboard::{
+            get_blackboard_value, send_blackboard_events, set_blackboard_value,
+            BLACKBOARD_NAMESPACE,
+        };
+
+        if !self.world.contains_resource::<Blackboard>() {
+            self.world.init_resource::<Blackboard>();
+            bevy::ecs::event::EventRegistry::register_event::<BlackboardChanged>(self.world);
+            self.world
+                .resource_mut::<Schedules>()
+                .add_systems(PostUpdate, send_blackboard_events);
+        }
+        self.namespace(BLACKBOARD_NAMESPACE)
+            .add_function("get", get_blackboard_value)
+            .add_function("set", set_blackboard_value)
+            .finish()
+    }
+
+    /// Lets scripts of this runtime declare their own components with `define_component` and
+    /// add them to entities, stored in [ScriptComponents] component, which makes them queryable
+    /// from Rust and scripts of every runtime, see [ScriptComponents].
+    pub fn enable_script_components(self) -> Self
+    where
+        R: EntityArgs,
+        R::ScriptEntity: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> FromRuntimeValueWithEngine<'b, R>
+            + for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+        bool: for<'b> IntoRuntimeValueWithEngine<'b, bool, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        use script_components::*;
+
+        self.world.init_resource::<ScriptComponentDefinitions>();
+        self.add_function(
+            DEFINE_COMPONENT_FN_NAME.to_string(),
+            define_script_component,
+        )
+        .namespace(COMPONENTS_NAMESPACE)
+        .add_function("add", add_script_component::<R>)
+        .add_function("set", set_script_component::<R>)
+        .add_function("has", has_script_component::<R>)
+        .add_function("get", get_script_component::<R>)
+        .add_function("remove", remove_script_component::<R>)
+        .finish()
+    }
+
+    /// Exposes `hierarchy.children(entity)`, `hierarchy.parent(entity)` and
+    /// `hierarchy.find_child_by_name(entity, "Hand")` functions, backed by [Children], [Parent]
+    /// and [Name] components, to scripts of this runtime, in Rhai called as `hierarchy::children`
+    /// etc. They return promises of an array of entities, and of an entity or `nil` in Lua and
+    /// `()` in Rhai when there is no parent or no descendant with provided name respectively.
+    pub fn enable_hierarchy(self) -> Self
+    where
+        R: EntityArgs,
+        R::ScriptEntity: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+    {
+        use hierarchy::*;
+
+        self.namespace(HIERARCHY_NAMESPACE)
+            .add_function("children", children::<R>)
+            .add_function("parent", parent::<R>)
+            .add_function("find_child_by_name", find_child_by_name::<R>)
+            .finish()
+    }
+
+    /// Exposes `transform.position(entity)`, `transform.set_position(entity, position)`,
+    /// `transform.rotate_y(entity, angle)` and `transform.look_at(entity, target)` functions,
+    /// operating on [Transform] components, to scripts of this runtime, in Rhai called as
+    /// `transform::position` etc. Angles are in radians and targets of `look_at` can be either
+    /// positions or entities. `position` returns a promise of a [Vec3], or of `nil` in Lua and
+    /// `()` in Rhai when the entity has no [Transform].
+    pub fn enable_transform(self) -> Self
+    where
+        R: EntityArgs,
+        R::ScriptEntity: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        f64: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> FromRuntimeValueWithEngine<'b, R>
+            + for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        use transform::*;
+
+        self.namespace(TRANSFORM_NAMESPACE)
+            .add_function("position", position::<R>)
+            .add_function("set_position", set_position::<R>)
+            .add_function("rotate_y", rotate_y::<R>)
+            .add_function("look_at", look_at::<R>)
+            .finish()
+    }
+
+    /// Exposes `bind_action` function, which binds actions of [ScriptActions] resource to keys,
+    /// to scripts of this runtime, see [ScriptActions].
+    pub fn enable_action_bindings(self) -> Self
+    where
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        self.world.init_resource::<ScriptActions>();
+        self.add_function(
+            actions::BIND_ACTION_FN_NAME.to_string(),
+            actions::bind_action,
+        )
+    }
+
+    /// Makes every script of this runtime expected to define functions with provided names, e.g.
+    /// `on_update`. Whenever a script gets evaluated or reloaded without defining some of them,
+    /// a warning is logged and [ScriptMissingFunctions] event is sent in [PostUpdate]. Calling it
+    /// again adds to the functions required before.
+    pub fn require_functions(self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        use required_functions::*;
+
+        if !self.world.contains_resource::<RequiredFunctions<R>>() {
+            self.world.init_resource::<RequiredFunctions<R>>();
+            if !self
+                .world
+                .contains_resource::<Events<ScriptMissingFunctions<R>>>()
+            {
+                bevy::ecs::event::EventRegistry::register_event::<ScriptMissingFunctions<R>>(
+                    self.world,
+                );
+            }
+            let condition = self.while_added();
+            self.world
+                .resource_mut::<Schedules>()
+                .add_systems(PostUpdate, check_required_functions::<R>.run_if(condition));
+        }
+        self.world
+            .resource_mut::<RequiredFunctions<R>>()
+            .names
+            .extend(names.into_iter().map(Into::into));
+
+        self
+    }
+
+    /// Exposes `declare` function to scripts of this runtime, with which scripts declare
+    /// signatures of functions they expect the game to register, e.g.
+    /// `declare("give_item", {"string", "int"})` in Lua or `declare("give_item", ["string", "int"])`
+    /// in Rhai, so that mods written against another version of the game fail early rather than
+    /// when the function gets called. Argument types are `string`, `int`, `float`, `number`,
+    /// `bool`, `entity`, `vec3` and `any`, and arguments of functions taking other Rust types,
+    /// e.g. [ScriptValue], match every type. A signature that doesn't match the registered
+    /// function, or declares a function that is not registered, is logged and sent as
+    /// [ScriptError] with [ScriptingError::SignatureMismatch] in [PostUpdate] of the frame that
+    /// the script declared it in.
+    pub fn enable_signature_declarations(self) -> Self
+    where
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        use declarations::*;
+
+        if !self.world.contains_resource::<SignatureDeclarations<R>>() {
+            self.world.init_resource::<SignatureDeclarations<R>>();
+            let condition = self.while_added();
+            self.world.resource_mut::<Schedules>().add_systems(
+                PostUpdate,
+                check_signature_declarations::<R>.run_if(condition),
+            );
+        }
+        self.add_function(DECLARE_FN_NAME.to_string(), declare_signature::<R>)
+    }
+
+    /// Exposes `data.load` function to scripts of this runtime, which reads the data file with
+    /// provided asset path, e.g. `data.load("config/balance.toml")`, and returns a promise of
+    /// its contents as a table in Lua or an object map in Rhai, in Rhai called as `data::load`.
+    /// JSON files are supported, and TOML files with `toml` feature. The file is read directly
+    /// from its asset source every time the function is called, so changes to it are visible
+    /// without reloading scripts. It is read on the [IoTaskPool](bevy::tasks::IoTaskPool), and
+    /// the promise gets resolved in a later frame. Paths with `..`, absolute paths and paths of
+    /// other asset sources, e.g. `embedded://`, are rejected. Errors are logged and result in
+    /// `nil` in Lua and `()` in Rhai.
+    pub fn enable_data_loading(mut self) -> Self
+    where
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        self.enable_deferred_calls();
+        self.namespace(data::DATA_NAMESPACE)
+            .add_function("load", data::load_data)
+            .finish()
+    }
+
+    /// Exposes `t` function returning localized texts of [Localization](localization::Localization)
+    /// resource, and `localization.load` function loading them from data files, to scripts of
+    /// this runtime, see [localization].
+    pub fn enable_localization(self) -> Self
+    where
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>
+            + for<'b> IntoRuntimeValueWithEngine<'b, String, R>,
+        ScriptValue: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        use localization::*;
+
+        self.world.init_resource::<Localization>();
+        self.add_function(TRANSLATE_FN_NAME.to_string(), translate)
+            .namespace(LOCALIZATION_NAMESPACE)
+            .add_function("load", load_texts)
+            .finish()
+    }
+
+    /// Exposes `http.get` and `http.post` functions to scripts of this runtime, allowing them to
+    /// send requests to provided domains, which are added to
+    /// [ScriptingSettings::allowed_http_domains], see [http].
+    #[cfg(feature = "http")]
+    pub fn enable_http(
+        mut self,
+        allowed_domains: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self
+    where
+        String: for<'b> FromRuntimeValueWithEngine<'b, R>,
+        ScriptValue: for<'b> IntoRuntimeValueWithEngine<'b, ScriptValue, R>,
+        (): for<'b> IntoRuntimeValueWithEngine<'b, (), R>,
+    {
+        self.world
+            .resource_mut::<ScriptingSettings<R>>()
+            .allowed_http_domains
+            .extend(allowed_domains.into_iter().map(Into::into));
+        self.world.init_resource::<http::HttpClient>();
+        self.enable_deferred_calls();
+        self.namespace(http::HTTP_NAMESPACE)
+            .add_function("get", http::get::<R>)
+            .add_function("post", http::post::<R>)
+            .finish()
+    }
+
+    /// Adds a script that is read and evaluated during [Startup](bevy::app::Startup), blocking
+    /// until it is done, instead of being loaded as an asset in the background. Calls it makes to
+    /// registered functions also run during startup, so scripts that only register things, like
+    /// item definitions, are guaranteed to complete before the first [Update](bevy::app::Update).
+    /// Boot scripts run in the order they were added, each one on an entity of its own.
+    pub fn add_boot_script(self, path: impl Into<AssetPath<'static>>) -> Self {
+        self.world
+            .resource_mut::<BootScripts<R>>()
+            .paths
+            .push(path.into());
+
+        self
+    }
+
+    /// Registers a constant that will be available to all scripts under provided name.
+    /// Provided value needs to be convertible to runtime value type.
+    pub fn add_constant<T>(self, name: String, value: T) -> Self
+    where
+        T: for<'b> IntoRuntimeValueWithEngine<'b, T, R>,
+    {
+        let mut runtime = self.world.resource_mut::<R>();
+        let value =
+            runtime.with_engine_mut(|engine| T::try_into_runtime_value_with_engine(value, engine));
+        if let Err(e) = value.and_then(|value| runtime.register_constant(name, value)) {
+            tracing::error!("error registering constant: {:?}", e);
+        }
+
+        self
+    }
+
+    /// Renames the variable holding the entity that the running script is attached to, `entity`
+    /// by default, or stops exposing the entity to scripts if `None`, for games that pass it to
+    /// script functions as an argument instead.
+    pub fn set_entity_variable(self, name: Option<&str>) -> Self {
+        self.world
+            .resource_mut::<R>()
+            .set_entity_variable(name.map(ToString::to_string));
+
+        self
+    }
+
+    /// Stops caching scripts compiled by this runtime in [CompiledScriptCache], so that scripts
+    /// get compiled every time they are evaluated, e.g. to keep memory low when few entities
+    /// share a script.
+    pub fn disable_compiled_script_cache(self) -> Self {
+        self.world
+            .resource::<CompiledScriptCache<R>>()
+            .set_enabled(false);
+
+        self
+    }
+
+    /// Sets the version of the scripting API provided by this runtime. The version is available
+    /// to scripts as [API_VERSION_VAR_NAME] constant and mods requiring an incompatible
+    /// version are refused by [mods::ModManager].
+    pub fn set_api_version(self, version: semver::Version) -> Self
+    where
+        String: for<'b> IntoRuntimeValueWithEngine<'b, String, R>,
+    {
+        self.world
+            .get_resource_or_insert_with(ScriptingApiVersions::default)
+            .versions
+            .insert(TypeId::of::<R>(), version.clone());
+
+        self.add_constant(API_VERSION_VAR_NAME.to_string(), version.to_string())
+    }
+}
+
+/// A function registered with [ScriptingRuntimeBuilder::add_function_with].
+pub struct FunctionRegistration<'a, R: Runtime> {
+    builder: ScriptingRuntimeBuilder<'a, R>,
+    /// Unset when the function conflicted with a function registered before.
+    registered: bool,
+}
+
+impl<'a, R: Runtime> FunctionRegistration<'a, R> {
+    /// Allows calling the function only from scripts that have been granted provided capability
+    /// with [ScriptCapabilities] component. Other scripts get
+    /// [ScriptingError::PermissionDenied] error.
+    pub fn requires(self, capability: impl Into<String>) -> Self {
+        if !self.registered {
+            return self;
+        }
+        self.builder
+            .world
+            .resource_mut::<Callbacks<R>>()
+            .uninitialized_callbacks
+            .last_mut()
+            .expect("function has to be registered")
+            .capabilities
+            .push(capability.into());
+
+        self
+    }
+
+    /// Returns the builder, so more functions can be registered.
+    pub fn finish(self) -> ScriptingRuntimeBuilder<'a, R> {
+        self.builder
+    }
+}
+
+/// A builder returned by [ScriptingRuntimeBuilder::namespace].
+pub struct NamespaceBuilder<'a, R: Runtime> {
+    builder: ScriptingRuntimeBuilder<'a, R>,
+    namespace: String,
+}
+
+impl<'a, R: Runtime> NamespaceBuilder<'a, R> {
+    /// Registers a function within the namespace. See [ScriptingRuntimeBuilder::add_function].
+    pub fn add_function<In, Out, Marker>(
+        mut self,
+        name: impl Into<String>,
+        fun: impl IntoCallbackSystem<R, In, Out, Marker>,
+    ) -> Self {
+        self.builder = self
+            .builder
+            .add_callback(Some(self.namespace.clone()), name.into(), fun)
+            .unwrap_or_else(|conflict| {
+                tracing::error!("error registering function: {}", conflict);
+                conflict.into_builder()
+            });
+        self
+    }
+
+    /// Removes a function registered within the namespace before, see [remove_script_function].
+    pub fn remove_function(self, name: &str) -> Self {
+        remove_script_function::<R>(self.builder.world, Some(&self.namespace), name);
+        self
+    }
+
+    /// Returns the builder, so more functions can be registered outside of the namespace.
+    pub fn finish(self) -> ScriptingRuntimeBuilder<'a, R> {
+        self.builder
+    }
+}
+
+/// A builder that registers functions and constants in two runtimes at once.
+pub struct MirroredScriptingRuntimeBuilder<'a, A: Runtime, B: Runtime> {
+    _phantom_data: PhantomData<(A, B)>,
+    world: &'a mut World,
+}
+
+impl<'a, A: Runtime, B: Runtime> MirroredScriptingRuntimeBuilder<'a, A, B> {
+    fn new(world: &'a mut World) -> Self {
+        Self {
+            _phantom_data: PhantomData,
+            world,
+        }
+    }
+
+    /// Registers a function for calling from within scripts of both runtimes.
+    /// See [ScriptingRuntimeBuilder::add_function].
+    pub fn add_function<In, Out, Marker, F>(self, name: String, fun: F) -> Self
+    where
+        F: IntoCallbackSystem<A, In, Out, Marker> + IntoCallbackSystem<B, In, Out, Marker> + Clone,
+    {
+        ScriptingRuntimeBuilder::<A>::new(self.world).add_function(name.clone(), fun.clone());
+        ScriptingRuntimeBuilder::<B>::new(self.world).add_function(name, fun);
+
+        self
+    }
+
+    /// Registers a constant in both runtimes.
+    /// See [ScriptingRuntimeBuilder::add_constant].
+    pub fn add_constant<T>(self, name: String, value: T) -> Self
+    where
+        T: for<'b> IntoRuntimeValueWithEngine<'b, T, A>
+            + for<'b> IntoRuntimeValueWithEngine<'b, T, B>
+            + Clone,
+    {
+        ScriptingRuntimeBuilder::<A>::new(self.world).add_constant(name.clone(), value.clone());
+        ScriptingRuntimeBuilder::<B>::new(self.world).add_constant(name, value);
+
         self
     }
 }
@@ -410,30 +1708,97 @@ impl BuildScriptingRuntime for App {
             .resource_mut::<MainScheduleOrder>()
             .insert_after(Update, R::Schedule::default());
 
-        self.register_asset_loader(ScriptLoader::<R::ScriptAsset>::default())
-            .init_schedule(R::Schedule::default())
-            .init_asset::<R::ScriptAsset>()
+        if !self.world().contains_resource::<Assets<R::ScriptAsset>>() {
+            self.init_asset::<R::ScriptAsset>()
+                .init_asset::<ScriptPack<R::ScriptAsset>>();
+        }
+        self.init_schedule(R::Schedule::default())
+            .init_resource::<ScriptPreprocessors<R::ScriptAsset>>()
+            .init_resource::<ScriptDecoders<R::ScriptAsset>>()
+            .init_resource::<ScriptLoadFailures<R::ScriptAsset>>()
             .init_resource::<Callbacks<R>>()
-            .insert_resource(R::default())
+            .init_resource::<PromiseQueue<R>>()
+            .init_resource::<ReloadHooks<R>>()
+            .add_event::<ScriptReloaded<R>>()
+            .add_event::<ScriptError<R>>()
+            .init_resource::<CapabilityGrants>()
+            .init_resource::<ScriptingSettings<R>>()
+            .init_resource::<BootScripts<R>>()
+            .init_resource::<R>()
+            .add_systems(Startup, eval_boot_scripts::<R>.pipe(log_errors))
             .add_systems(
                 R::Schedule::default(),
                 (
                     reload_scripts::<R>,
                     process_calls::<R>
                         .pipe(log_errors)
+                        .run_if(has_queued_calls::<R>)
                         .after(process_new_scripts::<R>),
+                    poll_pending_calls::<R>.before(process_calls::<R>),
                     init_callbacks::<R>.pipe(log_errors),
                     process_new_scripts::<R>
                         .pipe(log_errors)
-                        .after(init_callbacks::<R>),
+                        .after(init_callbacks::<R>)
+                        .after(sync_capabilities),
+                    notify_reloaded_scripts::<R>.after(process_calls::<R>),
+                    send_load_errors::<R>,
+                    sync_capabilities,
                 ),
             );
 
+        let cache = CompiledScriptCache::<R>::default();
+        self.world_mut()
+            .resource_mut::<R>()
+            .set_compiled_script_cache(cache.clone());
+        self.insert_resource(cache);
+
+        if let Some(rng) = self.world().get_resource::<ScriptRng>().cloned() {
+            if let Err(e) = self
+                .world_mut()
+                .resource_mut::<R>()
+                .enable_deterministic_mode(rng)
+            {
+                tracing::error!("error enabling deterministic mode: {:?}", e);
+            }
+        }
+
+        #[cfg(feature = "debugger")]
+        if let Some(debugger) = self
+            .world()
+            .get_resource::<debugger::ScriptDebugger>()
+            .cloned()
+        {
+            if let Err(e) = self
+                .world_mut()
+                .resource_mut::<R>()
+                .enable_debugger(debugger)
+            {
+                tracing::error!("error enabling debugger: {:?}", e);
+            }
+        }
+
         let runtime = ScriptingRuntimeBuilder::<R>::new(self.world_mut());
 
         f(runtime);
 
-        self
+        let extensions = self
+            .world()
+            .get_resource::<ScriptExtensionPolicy>()
+            .cloned()
+            .unwrap_or_default()
+            .extensions_for::<R>();
+        let pack_loader = ScriptPackLoader::new(
+            ScriptLoader::<R::ScriptAsset>::with_extensions(pack_extensions(&extensions))
+                .with_preprocessors(self.world().resource())
+                .with_decoders(self.world().resource()),
+        );
+        let loader = ScriptLoader::<R::ScriptAsset>::with_extensions(extensions)
+            .with_preprocessors(self.world().resource())
+            .with_decoders(self.world().resource())
+            .with_failures(self.world().resource());
+
+        self.register_asset_loader(loader)
+            .register_asset_loader(pack_loader)
     }
 
     /// Adds a way to add additional accesspoints to the scripting runtime. For example from plugins to add
@@ -450,13 +1815,127 @@ impl BuildScriptingRuntime for App {
 
         self
     }
+
+    /// Adds a way to register the same functions and constants in two runtimes, for projects
+    /// that mix scripting languages.
+    ///
+    /// Make sure that `add_scripting` has been called for both runtimes before calling this function.
+    fn add_mirrored_scripting_api<A: Runtime, B: Runtime>(
+        &mut self,
+        f: impl Fn(MirroredScriptingRuntimeBuilder<A, B>),
+    ) -> &mut Self {
+        let runtime = MirroredScriptingRuntimeBuilder::<A, B>::new(self.world_mut());
+
+        f(runtime);
+
+        self
+    }
+
+    /// Make sure that `add_scripting` has been called for the runtime and that the state has
+    /// been initialized before calling this function.
+    #[cfg(feature = "states")]
+    fn add_state_script<R: Runtime, S: States>(
+        &mut self,
+        state: S,
+        path: impl Into<AssetPath<'static>>,
+    ) -> &mut Self
+    where
+        (): for<'a> FuncArgs<'a, R::Value, R>,
+    {
+        states::add_state_script::<R, S>(self, state, path.into());
+
+        self
+    }
+
+    fn remove_scripting<R: Runtime>(&mut self) -> &mut Self {
+        shutdown::remove_scripting::<R>(self.world_mut());
+
+        self
+    }
+}
+
+/// A resource that stores settings of scripting runtime `R`. It is initialized with default values by
+/// `add_scripting`, unless it has been inserted before.
+#[derive(Resource)]
+pub struct ScriptingSettings<R: Runtime> {
+    /// Evaluate newly loaded scripts in parallel on [bevy::tasks::ComputeTaskPool].
+    /// Scripts only run in parallel if the runtime can evaluate them concurrently, for example
+    /// Rhai or Lua with per-entity states.
+    pub parallel_eval: bool,
+    /// Maximum number of promise callbacks called each time calls of scripts are processed,
+    /// which happens once per frame in the runtime's schedule. Callbacks over the limit, e.g.
+    /// of a long chain of `and_then` callbacks, are called in following frames, so that they
+    /// can't stall a single frame. All callbacks are called at once if `None`.
+    pub max_promise_callbacks: Option<usize>,
+    /// Domains that scripts can send HTTP requests to, along with their subdomains, see [http].
+    #[cfg(feature = "http")]
+    pub allowed_http_domains: Vec<String>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for ScriptingSettings<R> {
+    fn default() -> Self {
+        Self {
+            parallel_eval: false,
+            max_promise_callbacks: None,
+            #[cfg(feature = "http")]
+            allowed_http_domains: Vec::new(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+/// Progress of a call started with [Runtime::call_fn_resumable].
+#[derive(Debug, Clone)]
+pub enum CallProgress<V> {
+    /// The call has finished, returning provided value.
+    Finished(V),
+    /// The call has used up its instruction budget and can be resumed with [Runtime::resume_call].
+    Paused,
+}
+
+/// A resource that stores API versions set with [ScriptingRuntimeBuilder::set_api_version],
+/// keyed by runtime type.
+#[derive(Resource, Default)]
+pub struct ScriptingApiVersions {
+    versions: HashMap<TypeId, semver::Version>,
+}
+
+impl ScriptingApiVersions {
+    /// Returns the API version of runtime `R`, if it has been set.
+    pub fn get<R: Runtime>(&self) -> Option<&semver::Version> {
+        self.get_by_type_id(TypeId::of::<R>())
+    }
+
+    pub(crate) fn get_by_type_id(&self, runtime: TypeId) -> Option<&semver::Version> {
+        self.versions.get(&runtime)
+    }
 }
 
 /// A resource that stores all the callbacks that were registered using [AddScriptFunctionAppExt::add_function].
 #[derive(Resource)]
-struct Callbacks<R: Runtime> {
+pub(crate) struct Callbacks<R: Runtime> {
     uninitialized_callbacks: Vec<Callback<R>>,
-    callbacks: Mutex<Vec<Callback<R>>>,
+    /// Initialized callbacks by their identifiers, so that calls are dispatched without scanning
+    /// all registered functions.
+    callbacks: Mutex<HashMap<FunctionId, Arc<Callback<R>>>>,
+    /// Functions that have calls waiting to be processed.
+    pending: PendingFunctions,
+    /// Identifier that the next registered function gets.
+    next_id: u32,
+    conflict_policy: FunctionConflictPolicy,
+    /// Source that functions registered from now on get, see [ScriptFunctionInfo::source].
+    source: Option<String>,
+    /// Signatures of all functions registered so far, used to detect conflicts.
+    signatures: HashSet<conflicts::FunctionSignature>,
+}
+
+impl<R: Runtime> Callbacks<R> {
+    fn next_id(&mut self) -> FunctionId {
+        let id = FunctionId(self.next_id);
+        self.next_id += 1;
+        id
+    }
 }
 
 impl<R: Runtime> Default for Callbacks<R> {
@@ -464,10 +1943,15 @@ impl<R: Runtime> Default for Callbacks<R> {
         Self {
             uninitialized_callbacks: Default::default(),
             callbacks: Default::default(),
+            pending: Default::default(),
+            next_id: 0,
+            conflict_policy: Default::default(),
+            source: None,
+            signatures: Default::default(),
         }
     }
 }
 
 pub mod prelude {
-    pub use crate::{BuildScriptingRuntime as _, Runtime as _, Script};
+    pub use crate::{BuildScriptingRuntime as _, Runtime as _, Script, ScriptEntityCommands as _};
 }
@@ -0,0 +1,167 @@
+//! Localized texts of scripts.
+//!
+//! [ScriptingRuntimeBuilder::enable_localization](crate::ScriptingRuntimeBuilder::enable_localization)
+//! exposes `t(key, args)` function to scripts, which returns a promise of the text with provided
+//! key from [Localization] resource, with `{name}` placeholders replaced by values of `args`, e.g.
+//! `t("hud.score", { points = 10 })`, or `t("hud.score", #{ points: 10 })` in Rhai. Texts
+//! without placeholders can be passed an empty table or `()` in Rhai. Texts that are missing
+//! resolve to their key.
+//!
+//! By default texts come from key-value data files, which both the host and scripts, e.g. ones
+//! of mods distributed in [ScriptPack](crate::ScriptPack)s, can load with
+//! `localization.load("mods/my_mod/en.json")`, in Rhai called as `localization::load`. Nested
+//! tables get their keys joined with `.`, so `{ "menu": { "start": "Start" } }` defines
+//! `menu.start`. Texts can be provided by other localization libraries by implementing
+//! [LocalizationBackend] and inserting the resource before adding scripting:
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//!
+//! use bevy::prelude::*;
+//! use bevy_scriptum::localization::{Localization, LocalizationBackend};
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//! use bevy_scriptum::ScriptValue;
+//!
+//! struct Fluent;
+//!
+//! impl LocalizationBackend for Fluent {
+//!     fn translate(&self, key: &str, args: &HashMap<String, ScriptValue>) -> Option<String> {
+//!         todo!("look the text up with a localization library of choice")
+//!     }
+//! }
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .insert_resource(Localization::with_backend(Fluent))
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.enable_localization();
+//!     })
+//!     .run();
+//! ```
+
+use std::collections::HashMap;
+
+use bevy::{asset::AssetPath, prelude::*, tasks::block_on, utils::tracing};
+
+use crate::{data::read_data, ScriptValue};
+
+/// Name of the function that scripts use to get localized texts.
+pub(crate) const TRANSLATE_FN_NAME: &str = "t";
+
+/// Namespace of functions that scripts use to load localized texts.
+pub(crate) const LOCALIZATION_NAMESPACE: &str = "localization";
+
+/// Provides localized texts, with placeholders replaced by provided arguments.
+pub trait LocalizationBackend: Send + Sync + 'static {
+    /// Returns the text with provided key, or `None` if there is none.
+    fn translate(&self, key: &str, args: &HashMap<String, ScriptValue>) -> Option<String>;
+}
+
+/// A resource holding localized texts of scripts, looked up by [LocalizationBackend] if one is
+/// set and by their keys in texts loaded from data files otherwise, see [localization](self).
+#[derive(Resource, Default)]
+pub struct Localization {
+    texts: HashMap<String, String>,
+    backend: Option<Box<dyn LocalizationBackend>>,
+}
+
+impl Localization {
+    /// Creates a localization looking texts up with provided backend before loaded texts.
+    pub fn with_backend(backend: impl LocalizationBackend) -> Self {
+        Self {
+            texts: HashMap::new(),
+            backend: Some(Box::new(backend)),
+        }
+    }
+
+    /// Sets the text with provided key, in which `{name}` placeholders get replaced by the
+    /// argument with the same name.
+    pub fn insert(&mut self, key: impl Into<String>, text: impl Into<String>) {
+        self.texts.insert(key.into(), text.into());
+    }
+
+    /// Sets texts held by provided value, a map whose nested maps get their keys joined with `.`,
+    /// as read from data files.
+    pub fn extend(&mut self, texts: ScriptValue) {
+        self.extend_prefixed(None, texts);
+    }
+
+    fn extend_prefixed(&mut self, prefix: Option<&str>, texts: ScriptValue) {
+        let ScriptValue::Map(texts) = texts else {
+            tracing::error!("localized texts must be a map, got {:?}", texts);
+            return;
+        };
+        for (key, text) in texts {
+            let key = match prefix {
+                Some(prefix) => format!("{prefix}.{key}"),
+                None => key,
+            };
+            match text {
+                ScriptValue::String(text) => self.insert(key, text),
+                ScriptValue::Map(_) => self.extend_prefixed(Some(&key), text),
+                text => tracing::error!("localized text {} must be a string, got {:?}", key, text),
+            }
+        }
+    }
+
+    /// Returns the text with provided key with placeholders replaced by `args`, or the key if
+    /// there is no such text.
+    pub fn translate(&self, key: &str, args: &HashMap<String, ScriptValue>) -> String {
+        if let Some(text) = self
+            .backend
+            .as_ref()
+            .and_then(|backend| backend.translate(key, args))
+        {
+            return text;
+        }
+        let Some(text) = self.texts.get(key) else {
+            return key.to_string();
+        };
+        args.iter().fold(text.clone(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &format_argument(value))
+        })
+    }
+}
+
+fn format_argument(value: &ScriptValue) -> String {
+    match value {
+        ScriptValue::Nil => String::new(),
+        ScriptValue::Bool(value) => value.to_string(),
+        ScriptValue::Int(value) => value.to_string(),
+        ScriptValue::Float(value) => value.to_string(),
+        ScriptValue::String(value) => value.clone(),
+        value => format!("{value:?}"),
+    }
+}
+
+/// Returns a localized text, registered as `t` function.
+pub(crate) fn translate(
+    In((key, args)): In<(String, ScriptValue)>,
+    localization: Res<Localization>,
+) -> String {
+    let args = match args {
+        ScriptValue::Map(args) => args,
+        // Empty Lua tables get converted into arrays.
+        ScriptValue::Nil => HashMap::new(),
+        ScriptValue::Array(args) if args.is_empty() => HashMap::new(),
+        args => {
+            tracing::error!("arguments of text {} must be a map, got {:?}", key, args);
+            HashMap::new()
+        }
+    };
+    localization.translate(&key, &args)
+}
+
+/// Loads localized texts from the data file with provided asset path, registered as
+/// `localization.load`. Errors are logged.
+pub(crate) fn load_texts(
+    In((path,)): In<(String,)>,
+    asset_server: Res<AssetServer>,
+    mut localization: ResMut<Localization>,
+) {
+    match block_on(read_data(&asset_server, &AssetPath::from(path.clone()))) {
+        Ok(texts) => localization.extend(texts),
+        Err(e) => tracing::error!("error loading localized texts {}: {}", path, e),
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::utils::tracing::{self, Level};
+
+use crate::capabilities::current_entity;
+
+/// Target of events logged by scripts, which can be used to filter them with Bevy's log settings.
+pub const SCRIPT_LOG_TARGET: &str = "bevy_scriptum::script";
+
+/// Names of logging functions available to scripts, with levels they log at.
+pub(crate) const LOG_FUNCTIONS: [(&str, Level); 4] = [
+    ("debug", Level::DEBUG),
+    ("info", Level::INFO),
+    ("warn", Level::WARN),
+    ("error", Level::ERROR),
+];
+
+/// Logs a message emitted by a script, with fields for the script path and the entity
+/// whose script is currently running.
+pub(crate) fn log_message(level: Level, script: Option<&str>, message: &str) {
+    let script = script.unwrap_or_default();
+    let entity = current_entity();
+    match level {
+        Level::ERROR => {
+            tracing::error!(target: SCRIPT_LOG_TARGET, script, ?entity, "{message}")
+        }
+        Level::WARN => {
+            tracing::warn!(target: SCRIPT_LOG_TARGET, script, ?entity, "{message}")
+        }
+        Level::INFO => {
+            tracing::info!(target: SCRIPT_LOG_TARGET, script, ?entity, "{message}")
+        }
+        Level::DEBUG => {
+            tracing::debug!(target: SCRIPT_LOG_TARGET, script, ?entity, "{message}")
+        }
+        _ => tracing::trace!(target: SCRIPT_LOG_TARGET, script, ?entity, "{message}"),
+    }
+}
@@ -0,0 +1,713 @@
+//! Discovery and management of script mods.
+//!
+//! A mod is a directory containing a `mod.json` manifest and scripts, for example:
+//!
+//! ```text
+//! assets/mods/my_mod/mod.json
+//! assets/mods/my_mod/main.lua
+//! ```
+//!
+//! ```json
+//! {
+//!     "name": "my_mod",
+//!     "version": "1.0.0",
+//!     "entry": "main.lua",
+//!     "dependencies": ["base"],
+//!     "requires_api": "^1.2"
+//! }
+//! ```
+//!
+//! [ModManagerPlugin] discovers all mods in a directory when it is added to the app, orders them
+//! so that every mod is loaded after its dependencies and spawns a [Script] entity for the entry
+//! script of every mod. Mods can be enabled, disabled and reloaded at runtime using [ModManager].
+//! With [ModManagerPlugin::watch] the directory keeps being scanned while the game runs, so that
+//! mods dropped into it get loaded without restarting.
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::mods::ModManagerPlugin;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_scripting::<LuaRuntime>(|_| {})
+//!     .add_plugins(ModManagerPlugin::new("mods").with_language::<LuaRuntime>("lua"))
+//!     .run();
+//! ```
+
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    asset::{
+        io::{AssetReaderError, AssetSourceId, ErasedAssetReader},
+        AsyncReadExt as _,
+    },
+    ecs::system::EntityCommands,
+    prelude::*,
+    tasks::{block_on, futures_lite::StreamExt as _},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{Runtime, Script, ScriptCapabilities, ScriptMod, ScriptingApiVersions};
+
+/// Name of the manifest file that has to be present in every mod directory.
+pub const MANIFEST_FILE_NAME: &str = "mod.json";
+
+#[derive(Error, Debug)]
+pub enum ModError {
+    #[error("failed to read manifest {0}: {1}")]
+    ManifestError(PathBuf, String),
+    #[error("mod {0} depends on {1}, which does not exist")]
+    MissingDependency(String, String),
+    #[error("mod {0} is part of a dependency cycle")]
+    DependencyCycle(String),
+    #[error("mod {0} does not exist")]
+    UnknownMod(String),
+    #[error("no runtime registered for language {0}")]
+    UnknownLanguage(String),
+    #[error("mod {name} requires API version {required}, but the runtime provides {}", provided.as_ref().map_or("none".to_string(), |v| v.to_string()))]
+    IncompatibleApi {
+        name: String,
+        required: semver::VersionReq,
+        provided: Option<semver::Version>,
+    },
+}
+
+/// Manifest describing a mod, read from [MANIFEST_FILE_NAME] file of a mod directory.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ModManifest {
+    pub name: String,
+    pub version: String,
+    /// Path of the entry script, relative to the mod directory.
+    pub entry: String,
+    /// Names of mods that have to be loaded before this mod.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Language of the entry script. If not provided, extension of the entry script is used.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Versions of the scripting API the mod works with, checked against the version set
+    /// with [ScriptingRuntimeBuilder::set_api_version](crate::ScriptingRuntimeBuilder::set_api_version).
+    #[serde(default)]
+    pub requires_api: Option<semver::VersionReq>,
+}
+
+impl ModManifest {
+    /// Returns the language of the entry script.
+    pub fn language(&self) -> &str {
+        self.language.as_deref().unwrap_or_else(|| {
+            Path::new(&self.entry)
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// A mod discovered by [ModManager].
+#[derive(Debug)]
+pub struct Mod {
+    pub manifest: ModManifest,
+    /// Asset path of the mod directory.
+    pub path: String,
+    enabled: bool,
+    entity: Option<Entity>,
+}
+
+impl Mod {
+    /// Returns asset path of the entry script of this mod.
+    pub fn entry_path(&self) -> String {
+        format!("{}/{}", self.path, self.manifest.entry)
+    }
+
+    /// Returns `true` if the mod is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the entity of the entry script, if it has been spawned.
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+}
+
+enum ModChange {
+    Enable(String),
+    Disable(String),
+    Reload(String),
+}
+
+type SpawnScript = fn(&mut Commands, &AssetServer, String) -> Entity;
+
+/// A function inserting components into the entity of the entry script of every mod that gets
+/// enabled, see [ModManagerPlugin::with_template].
+pub type ModTemplate = fn(&mut EntityCommands);
+
+/// A runtime that runs mods written in a given language.
+#[derive(Clone)]
+struct Language {
+    runtime: TypeId,
+    spawn: SpawnScript,
+}
+
+fn spawn_script<R: Runtime>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    path: String,
+) -> Entity {
+    commands
+        .spawn(Script::<R::ScriptAsset>::new(asset_server.load(path)))
+        .id()
+}
+
+/// A resource that stores all discovered mods in load order and allows enabling,
+/// disabling and reloading them. Changes are applied in [Update] schedule.
+#[derive(Resource, Default)]
+pub struct ModManager {
+    mods: Vec<Mod>,
+    languages: HashMap<String, Language>,
+    capabilities: HashMap<String, ScriptCapabilities>,
+    template: Option<ModTemplate>,
+    changes: Vec<ModChange>,
+}
+
+impl ModManager {
+    /// Returns all mods in load order.
+    pub fn mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    /// Returns mod with provided name.
+    pub fn get(&self, name: &str) -> Option<&Mod> {
+        self.mods.iter().find(|m| m.manifest.name == name)
+    }
+
+    /// Enables a mod, together with all mods it depends on.
+    pub fn enable(&mut self, name: &str) -> Result<(), ModError> {
+        self.queue(name, ModChange::Enable)
+    }
+
+    /// Disables a mod, together with all mods that depend on it, despawning their scripts.
+    pub fn disable(&mut self, name: &str) -> Result<(), ModError> {
+        self.queue(name, ModChange::Disable)
+    }
+
+    /// Grants provided capability to the script of a mod, starting with the next time it
+    /// gets enabled. Capabilities of an enabled mod can be changed using [ScriptCapabilities]
+    /// component of its [Mod::entity].
+    pub fn grant(&mut self, name: &str, capability: impl Into<String>) {
+        self.capabilities
+            .entry(name.to_string())
+            .or_default()
+            .grant(capability);
+    }
+
+    /// Reloads the entry script of a mod.
+    pub fn reload(&mut self, name: &str) -> Result<(), ModError> {
+        self.queue(name, ModChange::Reload)
+    }
+
+    fn queue(&mut self, name: &str, change: fn(String) -> ModChange) -> Result<(), ModError> {
+        if self.get(name).is_none() {
+            return Err(ModError::UnknownMod(name.to_string()));
+        }
+        self.changes.push(change(name.to_string()));
+        Ok(())
+    }
+
+    /// Reads manifests of all mods in provided directory and adds them in load order,
+    /// enabled. `asset_path` is the path of the same directory relative to the assets folder.
+    /// Mods that can't be loaded are skipped and returned as errors.
+    pub fn discover(&mut self, directory: &Path, asset_path: &str) -> Vec<ModError> {
+        let mut directories = match fs::read_dir(directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir())
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                return vec![ModError::ManifestError(
+                    directory.to_path_buf(),
+                    e.to_string(),
+                )]
+            }
+        };
+        directories.sort();
+
+        let mut errors = Vec::new();
+        let mut manifests = Vec::new();
+        for mod_directory in directories {
+            let manifest_path = mod_directory.join(MANIFEST_FILE_NAME);
+            let manifest = fs::read_to_string(&manifest_path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_manifest(&text));
+            match manifest {
+                Ok(manifest) => {
+                    let file_name = mod_directory.file_name().unwrap_or_default();
+                    let path = format!("{}/{}", asset_path, file_name.to_string_lossy());
+                    manifests.push((manifest, path));
+                }
+                Err(e) => errors.push(ModError::ManifestError(manifest_path, e)),
+            }
+        }
+        self.add_discovered(manifests, errors)
+    }
+
+    /// Like [ModManager::discover], but reads mods in provided directory of the default asset
+    /// source instead of the file system, so that it also works with assets packaged into
+    /// mobile apps. Some asset sources, e.g. the Android one, can't list directories, in which
+    /// case names of mod directories have to be provided as `mods`.
+    pub fn discover_assets(
+        &mut self,
+        asset_server: &AssetServer,
+        asset_path: &str,
+        mods: Option<&[String]>,
+    ) -> Vec<ModError> {
+        let reader = match asset_server.get_source(AssetSourceId::Default) {
+            Ok(source) => source.reader(),
+            Err(e) => return vec![ModError::ManifestError(asset_path.into(), e.to_string())],
+        };
+        let names = match mods {
+            Some(mods) => mods.to_vec(),
+            None => match block_on(list_directories(reader, Path::new(asset_path))) {
+                Ok(names) => names,
+                Err(e) => return vec![ModError::ManifestError(asset_path.into(), e.to_string())],
+            },
+        };
+        self.discover_asset_directories(reader, asset_path, names)
+    }
+
+    /// Like [ModManager::discover_assets], but only reads directories that are not paths of
+    /// already discovered mods, adding mods that have been created since, enabled.
+    pub fn discover_new_assets(
+        &mut self,
+        asset_server: &AssetServer,
+        asset_path: &str,
+    ) -> Vec<ModError> {
+        let reader = match asset_server.get_source(AssetSourceId::Default) {
+            Ok(source) => source.reader(),
+            Err(e) => return vec![ModError::ManifestError(asset_path.into(), e.to_string())],
+        };
+        let names = match block_on(list_directories(reader, Path::new(asset_path))) {
+            Ok(names) => names,
+            Err(e) => return vec![ModError::ManifestError(asset_path.into(), e.to_string())],
+        };
+        let names = names
+            .into_iter()
+            .filter(|name| {
+                let path = format!("{}/{}", asset_path, name);
+                !self.mods.iter().any(|m| m.path == path)
+            })
+            .collect();
+        self.discover_asset_directories(reader, asset_path, names)
+    }
+
+    fn discover_asset_directories(
+        &mut self,
+        reader: &dyn ErasedAssetReader,
+        asset_path: &str,
+        mut names: Vec<String>,
+    ) -> Vec<ModError> {
+        names.sort();
+
+        let mut errors = Vec::new();
+        let mut manifests = Vec::new();
+        for name in names {
+            let path = format!("{}/{}", asset_path, name);
+            let manifest_path = PathBuf::from(format!("{}/{}", path, MANIFEST_FILE_NAME));
+            let manifest =
+                block_on(read_text(reader, &manifest_path)).and_then(|text| parse_manifest(&text));
+            match manifest {
+                Ok(manifest) => manifests.push((manifest, path)),
+                Err(e) => errors.push(ModError::ManifestError(manifest_path, e)),
+            }
+        }
+        self.add_discovered(manifests, errors)
+    }
+
+    /// Adds discovered mods in load order, enabled, returning provided errors along with
+    /// errors of ordering them. Discovered mods may depend on mods that have been added before,
+    /// while those with names of mods that have been added before are skipped.
+    fn add_discovered(
+        &mut self,
+        manifests: Vec<(ModManifest, String)>,
+        mut errors: Vec<ModError>,
+    ) -> Vec<ModError> {
+        let (manifests, duplicates): (Vec<_>, Vec<_>) = manifests
+            .into_iter()
+            .partition(|(manifest, _)| self.get(&manifest.name).is_none());
+        for (manifest, path) in duplicates {
+            errors.push(ModError::ManifestError(
+                PathBuf::from(format!("{}/{}", path, MANIFEST_FILE_NAME)),
+                format!("mod {} already exists", manifest.name),
+            ));
+        }
+        let known = self.mods.iter().map(|m| m.manifest.clone());
+        let (ordered, order_errors) =
+            resolve_load_order(known.chain(manifests.iter().map(|(manifest, _)| manifest.clone())));
+        errors.extend(order_errors);
+        for manifest in ordered {
+            if self.get(&manifest.name).is_some() {
+                continue;
+            }
+            let (_, path) = manifests
+                .iter()
+                .find(|(m, _)| m.name == manifest.name)
+                .expect("ordered manifest has to be discovered");
+            self.changes.push(ModChange::Enable(manifest.name.clone()));
+            self.mods.push(Mod {
+                manifest,
+                path: path.clone(),
+                enabled: false,
+                entity: None,
+            });
+        }
+        errors
+    }
+
+    fn dependents(&self, name: &str) -> Vec<String> {
+        self.mods
+            .iter()
+            .filter(|m| m.manifest.dependencies.iter().any(|d| d == name))
+            .map(|m| m.manifest.name.clone())
+            .collect()
+    }
+
+    fn apply_enable(
+        &mut self,
+        name: &str,
+        commands: &mut Commands,
+        asset_server: &AssetServer,
+        api_versions: Option<&ScriptingApiVersions>,
+    ) -> Result<(), ModError> {
+        let Some(index) = self.mods.iter().position(|m| m.manifest.name == name) else {
+            return Err(ModError::UnknownMod(name.to_string()));
+        };
+        if self.mods[index].enabled {
+            return Ok(());
+        }
+        for dependency in self.mods[index].manifest.dependencies.clone() {
+            self.apply_enable(&dependency, commands, asset_server, api_versions)?;
+        }
+        let manifest = &self.mods[index].manifest;
+        let language = manifest.language().to_string();
+        let language = self
+            .languages
+            .get(&language)
+            .ok_or(ModError::UnknownLanguage(language))?;
+        if let Some(required) = &manifest.requires_api {
+            let provided =
+                api_versions.and_then(|versions| versions.get_by_type_id(language.runtime));
+            if !provided.is_some_and(|version| required.matches(version)) {
+                return Err(ModError::IncompatibleApi {
+                    name: manifest.name.clone(),
+                    required: required.clone(),
+                    provided: provided.cloned(),
+                });
+            }
+        }
+        let entity = (language.spawn)(commands, asset_server, self.mods[index].entry_path());
+        commands.entity(entity).insert(ScriptMod {
+            name: manifest.name.clone(),
+        });
+        if let Some(capabilities) = self.capabilities.get(name) {
+            commands.entity(entity).insert(capabilities.clone());
+        }
+        if let Some(template) = self.template {
+            template(&mut commands.entity(entity));
+        }
+        let m = &mut self.mods[index];
+        m.enabled = true;
+        m.entity = Some(entity);
+        Ok(())
+    }
+
+    fn apply_disable(&mut self, name: &str, commands: &mut Commands) {
+        for dependent in self.dependents(name) {
+            self.apply_disable(&dependent, commands);
+        }
+        if let Some(m) = self.mods.iter_mut().find(|m| m.manifest.name == name) {
+            m.enabled = false;
+            if let Some(entity) = m.entity.take() {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn parse_manifest(text: &str) -> Result<ModManifest, String> {
+    serde_json::from_str(text).map_err(|e| e.to_string())
+}
+
+/// Returns names of directories within provided directory of an asset source.
+async fn list_directories(
+    reader: &dyn ErasedAssetReader,
+    path: &Path,
+) -> Result<Vec<String>, AssetReaderError> {
+    let mut names = Vec::new();
+    let mut entries = reader.read_directory(path).await?;
+    while let Some(entry) = entries.next().await {
+        if reader.is_directory(&entry).await? {
+            if let Some(name) = entry.file_name() {
+                names.push(name.to_string_lossy().into_owned());
+            }
+        }
+    }
+    Ok(names)
+}
+
+async fn read_text(reader: &dyn ErasedAssetReader, path: &Path) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    reader
+        .read(path)
+        .await
+        .map_err(|e| e.to_string())?
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// Orders provided manifests so that every mod comes after its dependencies.
+/// Mods with missing dependencies or within dependency cycles are left out and returned as errors.
+pub fn resolve_load_order(
+    manifests: impl IntoIterator<Item = ModManifest>,
+) -> (Vec<ModManifest>, Vec<ModError>) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        InProgress,
+        Done,
+        Failed,
+    }
+
+    fn visit(
+        index: usize,
+        manifests: &[ModManifest],
+        visits: &mut [Option<Visit>],
+        ordered: &mut Vec<ModManifest>,
+        errors: &mut Vec<ModError>,
+    ) -> bool {
+        match visits[index] {
+            Some(Visit::Done) => return true,
+            Some(Visit::Failed) => return false,
+            Some(Visit::InProgress) => {
+                errors.push(ModError::DependencyCycle(manifests[index].name.clone()));
+                visits[index] = Some(Visit::Failed);
+                return false;
+            }
+            None => {}
+        }
+        visits[index] = Some(Visit::InProgress);
+        let manifest = &manifests[index];
+        let mut resolved = true;
+        for dependency in &manifest.dependencies {
+            match manifests.iter().position(|m| &m.name == dependency) {
+                Some(dependency) => {
+                    resolved &= visit(dependency, manifests, visits, ordered, errors);
+                }
+                None => {
+                    errors.push(ModError::MissingDependency(
+                        manifest.name.clone(),
+                        dependency.clone(),
+                    ));
+                    resolved = false;
+                }
+            }
+        }
+        if visits[index] == Some(Visit::Failed) {
+            return false;
+        }
+        if resolved {
+            visits[index] = Some(Visit::Done);
+            ordered.push(manifest.clone());
+        } else {
+            visits[index] = Some(Visit::Failed);
+        }
+        resolved
+    }
+
+    let manifests = manifests.into_iter().collect::<Vec<_>>();
+    let mut visits = vec![None; manifests.len()];
+    let mut ordered = Vec::new();
+    let mut errors = Vec::new();
+    for index in 0..manifests.len() {
+        visit(index, &manifests, &mut visits, &mut ordered, &mut errors);
+    }
+    (ordered, errors)
+}
+
+/// A plugin that discovers mods in provided directory of assets folder, reading them through the
+/// asset server, and manages them using [ModManager]. It has to be added after [AssetPlugin].
+pub struct ModManagerPlugin {
+    directory: String,
+    languages: HashMap<String, Language>,
+    capabilities: Vec<(String, String)>,
+    mods: Option<Vec<String>>,
+    template: Option<ModTemplate>,
+    watch_interval: Option<Duration>,
+}
+
+impl ModManagerPlugin {
+    /// Creates a plugin discovering mods in provided directory, relative to the assets folder.
+    pub fn new(directory: impl Into<String>) -> Self {
+        Self {
+            directory: directory.into(),
+            languages: Default::default(),
+            capabilities: Default::default(),
+            mods: None,
+            template: None,
+            watch_interval: None,
+        }
+    }
+
+    /// Only discovers mods in provided subdirectories of the mod directory, instead of listing
+    /// it, which is not supported by some asset sources, e.g. the Android one.
+    pub fn with_mods(mut self, mods: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.mods = Some(mods.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Grants provided capability to the script of provided mod, see [ModManager::grant].
+    pub fn grant(mut self, name: impl Into<String>, capability: impl Into<String>) -> Self {
+        self.capabilities.push((name.into(), capability.into()));
+        self
+    }
+
+    /// Calls provided function for the entity of the entry script of every mod that gets enabled,
+    /// e.g. to insert components that the game expects scripted entities to have.
+    pub fn with_template(mut self, template: ModTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Scans the mod directory for newly created mods every `interval` while the game runs,
+    /// adding and enabling them using [ModManager::discover_new_assets]. That requires the
+    /// asset source to support listing directories, in which case mods provided with
+    /// [ModManagerPlugin::with_mods] are only the ones discovered at startup. Requires [Time].
+    pub fn watch(mut self, interval: Duration) -> Self {
+        self.watch_interval = Some(interval);
+        self
+    }
+
+    /// Spawns entry scripts of mods written in provided language using runtime `R`.
+    pub fn with_language<R: Runtime>(mut self, language: impl Into<String>) -> Self {
+        self.languages.insert(
+            language.into(),
+            Language {
+                runtime: TypeId::of::<R>(),
+                spawn: spawn_script::<R>,
+            },
+        );
+        self
+    }
+}
+
+impl Plugin for ModManagerPlugin {
+    fn build(&self, app: &mut App) {
+        let mut manager = ModManager {
+            languages: self.languages.clone(),
+            template: self.template,
+            ..Default::default()
+        };
+        for (name, capability) in &self.capabilities {
+            manager.grant(name, capability.clone());
+        }
+        match app.world().get_resource::<AssetServer>() {
+            Some(asset_server) => {
+                for e in
+                    manager.discover_assets(asset_server, &self.directory, self.mods.as_deref())
+                {
+                    tracing::error!("{}", e);
+                }
+            }
+            None => tracing::error!("ModManagerPlugin has to be added after AssetPlugin"),
+        }
+
+        app.insert_resource(manager)
+            .add_systems(Update, apply_mod_changes);
+
+        if let Some(interval) = self.watch_interval {
+            app.insert_resource(ModWatcher {
+                directory: self.directory.clone(),
+                interval,
+                last_scan: None,
+                reported: Default::default(),
+            })
+            .add_systems(Update, discover_new_mods.before(apply_mod_changes));
+        }
+    }
+}
+
+/// A resource storing the state of scanning for new mods, see [ModManagerPlugin::watch].
+#[derive(Resource)]
+struct ModWatcher {
+    directory: String,
+    interval: Duration,
+    last_scan: Option<Duration>,
+    /// Errors of the last scan, so that e.g. a mod whose manifest is being written is not
+    /// reported on every scan.
+    reported: HashSet<String>,
+}
+
+/// Discovers mods created in the watched directory since the last scan.
+fn discover_new_mods(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut manager: ResMut<ModManager>,
+    mut watcher: ResMut<ModWatcher>,
+) {
+    let now = time.elapsed();
+    if watcher
+        .last_scan
+        .is_some_and(|last_scan| now - last_scan < watcher.interval)
+    {
+        return;
+    }
+    watcher.last_scan = Some(now);
+    let errors = manager
+        .discover_new_assets(&asset_server, &watcher.directory)
+        .into_iter()
+        .map(|e| e.to_string())
+        .collect::<HashSet<_>>();
+    for e in errors.difference(&watcher.reported) {
+        tracing::error!("{}", e);
+    }
+    watcher.reported = errors;
+}
+
+/// Applies changes requested through [ModManager].
+pub fn apply_mod_changes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut manager: ResMut<ModManager>,
+    api_versions: Option<Res<ScriptingApiVersions>>,
+) {
+    let changes = std::mem::take(&mut manager.changes);
+    for change in changes {
+        match change {
+            ModChange::Enable(name) => {
+                if let Err(e) = manager.apply_enable(
+                    &name,
+                    &mut commands,
+                    &asset_server,
+                    api_versions.as_deref(),
+                ) {
+                    tracing::error!("failed to enable mod {}: {}", name, e);
+                }
+            }
+            ModChange::Disable(name) => manager.apply_disable(&name, &mut commands),
+            ModChange::Reload(name) => {
+                if let Some(m) = manager.get(&name) {
+                    asset_server.reload(m.entry_path());
+                }
+            }
+        }
+    }
+}
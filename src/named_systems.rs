@@ -0,0 +1,41 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy::{ecs::system::SystemId, prelude::*, utils::tracing};
+
+use crate::Runtime;
+
+/// Name of the function that runs systems registered with
+/// [ScriptingRuntimeBuilder::add_named_system](crate::ScriptingRuntimeBuilder::add_named_system).
+pub(crate) const RUN_SYSTEM_FN_NAME: &str = "run_system";
+
+/// A resource that stores one-shot systems that scripts of runtime `R` can run by name.
+#[derive(Resource)]
+pub(crate) struct NamedSystems<R: Runtime> {
+    pub(crate) systems: HashMap<String, SystemId>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for NamedSystems<R> {
+    fn default() -> Self {
+        Self {
+            systems: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// Runs the named system with provided name, which is registered as `run_system` function.
+pub(crate) fn run_named_system<R: Runtime>(In((name,)): In<(String,)>, world: &mut World) {
+    let Some(system) = world
+        .resource::<NamedSystems<R>>()
+        .systems
+        .get(&name)
+        .copied()
+    else {
+        tracing::error!("system `{}` is not registered", name);
+        return;
+    };
+    if let Err(e) = world.run_system(system) {
+        tracing::error!("error running system `{}`: {}", name, e);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, LoadContext},
+    prelude::*,
+    utils::ConditionalSendFuture,
+};
+
+use crate::assets::{
+    intern_extension, GetExtensions, ResolveImports, ScriptLoader, ScriptLoaderSettings,
+};
+
+/// An asset bundling multiple scripts in a single file, which makes distributing mods easier.
+///
+/// Every script of a pack is registered as a labeled sub-asset, so a script named `main.lua`
+/// inside `mods/my_mod.lua.pack` can be loaded with `asset_server.load("mods/my_mod.lua.pack#main.lua")`.
+///
+/// A pack is a text file in which every script is preceded by a header line of the form
+/// `==> name <==`:
+///
+/// ```text
+/// ==> main.lua <==
+/// local util = require("lib.util")
+/// ==> lib/util.lua <==
+/// return { answer = 42 }
+/// ```
+///
+/// Scripts can use other scripts from the same pack when the runtime supports it,
+/// see [ResolvePackModules].
+#[derive(Asset, TypePath)]
+pub struct ScriptPack<A: Asset> {
+    /// Handles of all scripts within the pack, keyed by their name.
+    pub scripts: HashMap<String, Handle<A>>,
+}
+
+/// Allows scripts loaded from a [ScriptPack] to use other scripts from the same pack.
+pub trait ResolvePackModules: From<String> {
+    /// Creates a script asset from the source of a script and modules of the pack it was loaded
+    /// from. `modules` contains names and sources of all scripts in the pack.
+    /// By default modules are ignored.
+    fn resolve_pack_modules(source: String, _modules: &[(String, String)]) -> Self {
+        source.into()
+    }
+}
+
+/// A loader for script packs, registered next to the [ScriptLoader] of every runtime.
+pub(crate) struct ScriptPackLoader<A: Asset + From<String>> {
+    scripts: ScriptLoader<A>,
+}
+
+impl<A: Asset + From<String>> ScriptPackLoader<A> {
+    /// Create a pack loader that reads packs using provided loader, whose extensions
+    /// have to come from [pack_extensions].
+    pub(crate) fn new(scripts: ScriptLoader<A>) -> Self {
+        Self { scripts }
+    }
+}
+
+/// Returns extensions of packs containing scripts with provided extensions, e.g. `lua.pack`.
+pub(crate) fn pack_extensions(extensions: &[&'static str]) -> Vec<&'static str> {
+    extensions
+        .iter()
+        .map(|extension| intern_extension(format!("{extension}.pack")))
+        .collect()
+}
+
+/// Splits text of a pack into names and sources of contained scripts.
+pub(crate) fn parse_pack(text: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut scripts: Vec<(String, String)> = Vec::new();
+    for line in text.split_inclusive('\n') {
+        let header = line
+            .trim_end()
+            .strip_prefix("==> ")
+            .and_then(|line| line.strip_suffix(" <=="));
+        match (header, scripts.last_mut()) {
+            (Some(name), _) => {
+                if scripts.iter().any(|(existing, _)| existing == name) {
+                    anyhow::bail!("duplicate script in pack: {}", name);
+                }
+                scripts.push((name.to_string(), String::new()));
+            }
+            (None, Some((_, source))) => source.push_str(line),
+            (None, None) if line.trim().is_empty() => {}
+            (None, None) => anyhow::bail!("pack has to start with a `==> name <==` header"),
+        }
+    }
+    Ok(scripts)
+}
+
+impl<A: Asset + ResolveImports + GetExtensions + ResolvePackModules> AssetLoader
+    for ScriptPackLoader<A>
+{
+    type Asset = ScriptPack<A>;
+    type Settings = ScriptLoaderSettings;
+    type Error = anyhow::Error;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let pack_text = self
+                .scripts
+                .read_source(reader, load_context.path(), settings)
+                .await?;
+            let modules = parse_pack(&pack_text)?
+                .into_iter()
+                .map(|(name, source)| {
+                    let path = load_context.path().join(&name);
+                    (name, self.scripts.preprocess(source, &path))
+                })
+                .collect::<Vec<_>>();
+
+            let mut scripts = HashMap::new();
+            for (name, source) in &modules {
+                let script = A::resolve_pack_modules(source.clone(), &modules);
+                let handle = load_context.add_labeled_asset(name.clone(), script);
+                scripts.insert(name.clone(), handle);
+            }
+            Ok(ScriptPack { scripts })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.scripts.extensions()
+    }
+}
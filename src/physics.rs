@@ -0,0 +1,237 @@
+//! Forwarding of collision events and spatial queries of scripts to physics engines.
+//!
+//! Events implementing [ScriptCollisionEvent] can be forwarded to scripts with
+//! [ScriptingRuntimeBuilder::forward_collision_events](crate::ScriptingRuntimeBuilder::forward_collision_events).
+//! Both scripted entities involved in a collision get their `on_collision_start` or
+//! `on_collision_stop` function called with the other entity, if they define one.
+//!
+//! `rapier` feature implements the trait for `bevy_rapier3d::prelude::CollisionEvent` and
+//! `avian` feature for `avian3d::prelude::CollisionStarted` and `CollisionEnded`.
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use bevy_rapier3d::prelude::*;
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.forward_collision_events::<CollisionEvent>();
+//!     })
+//!     .run();
+//! ```
+//!
+//! Resources implementing [ScriptSpatialQuery] answer spatial queries of scripts enabled with
+//! [ScriptingRuntimeBuilder::enable_spatial_queries](crate::ScriptingRuntimeBuilder::enable_spatial_queries),
+//! `physics.raycast(origin, direction, max_distance)` returning a promise of a table with `entity`
+//! and `point` of the closest hit, or `nil` if nothing was hit, and
+//! `physics.overlap_sphere(center, radius)` returning a promise of a table of entities whose
+//! colliders intersect the sphere, in Rhai called as `physics::raycast` etc. and returning an
+//! object map, `()` and an array respectively.
+//!
+//! `rapier` feature implements the trait for `bevy_rapier3d::prelude::RapierContext` and `avian`
+//! feature for `avian3d::prelude::SpatialQueryPipeline`.
+
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{EntityArgs, ScriptValue};
+
+/// Namespace of functions that scripts use to query physics engines.
+pub(crate) const PHYSICS_NAMESPACE: &str = "physics";
+
+/// Name of the function called on scripted entities that started colliding with another entity.
+pub const ON_COLLISION_START_FN_NAME: &str = "on_collision_start";
+
+/// Name of the function called on scripted entities that stopped colliding with another entity.
+pub const ON_COLLISION_STOP_FN_NAME: &str = "on_collision_stop";
+
+/// Whether a collision has started or stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPhase {
+    Started,
+    Stopped,
+}
+
+/// An event reporting that two entities started or stopped colliding, which can be forwarded
+/// to scripts. Implement it for events of physics engines that are not supported out of the box.
+pub trait ScriptCollisionEvent: Event {
+    /// Returns the colliding entities and whether the collision started or stopped.
+    fn collision(&self) -> (Entity, Entity, CollisionPhase);
+}
+
+#[cfg(feature = "rapier")]
+impl ScriptCollisionEvent for bevy_rapier3d::prelude::CollisionEvent {
+    fn collision(&self) -> (Entity, Entity, CollisionPhase) {
+        match *self {
+            Self::Started(a, b, _) => (a, b, CollisionPhase::Started),
+            Self::Stopped(a, b, _) => (a, b, CollisionPhase::Stopped),
+        }
+    }
+}
+
+#[cfg(feature = "avian")]
+impl ScriptCollisionEvent for avian3d::prelude::CollisionStarted {
+    fn collision(&self) -> (Entity, Entity, CollisionPhase) {
+        (self.0, self.1, CollisionPhase::Started)
+    }
+}
+
+#[cfg(feature = "avian")]
+impl ScriptCollisionEvent for avian3d::prelude::CollisionEnded {
+    fn collision(&self) -> (Entity, Entity, CollisionPhase) {
+        (self.0, self.1, CollisionPhase::Stopped)
+    }
+}
+
+/// The closest entity hit by a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// Point at which the ray hit the entity.
+    pub point: Vec3,
+}
+
+/// A resource of a physics engine that answers spatial queries of scripts. Implement it for
+/// resources of physics engines that are not supported out of the box.
+pub trait ScriptSpatialQuery: Resource {
+    /// Returns the closest entity hit by a ray cast from `origin` in `direction`, which does not
+    /// have to be normalized, within `max_distance`.
+    fn cast_ray(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RayHit>;
+
+    /// Returns entities with colliders intersecting a sphere.
+    fn overlap_sphere(&self, center: Vec3, radius: f32) -> Vec<Entity>;
+}
+
+#[cfg(feature = "rapier")]
+impl ScriptSpatialQuery for bevy_rapier3d::prelude::RapierContext {
+    fn cast_ray(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RayHit> {
+        let direction = direction.try_normalize()?;
+        self.cast_ray(
+            origin,
+            direction,
+            max_distance,
+            true,
+            bevy_rapier3d::prelude::QueryFilter::default(),
+        )
+        .map(|(entity, distance)| RayHit {
+            entity,
+            point: origin + direction * distance,
+        })
+    }
+
+    fn overlap_sphere(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        self.intersections_with_shape(
+            center,
+            Quat::IDENTITY,
+            &bevy_rapier3d::prelude::Collider::ball(radius),
+            bevy_rapier3d::prelude::QueryFilter::default(),
+            |entity| {
+                entities.push(entity);
+                true
+            },
+        );
+        entities
+    }
+}
+
+#[cfg(feature = "avian")]
+impl ScriptSpatialQuery for avian3d::prelude::SpatialQueryPipeline {
+    fn cast_ray(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RayHit> {
+        let direction = Dir3::new(direction).ok()?;
+        self.cast_ray(
+            origin,
+            direction,
+            max_distance,
+            true,
+            avian3d::prelude::SpatialQueryFilter::default(),
+        )
+        .map(|hit| RayHit {
+            entity: hit.entity,
+            point: origin + direction * hit.time_of_impact,
+        })
+    }
+
+    fn overlap_sphere(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        self.shape_intersections(
+            &avian3d::prelude::Collider::sphere(radius),
+            center,
+            Quat::IDENTITY,
+            avian3d::prelude::SpatialQueryFilter::default(),
+        )
+    }
+}
+
+/// Casts a ray, registered as `physics.raycast`.
+pub(crate) fn raycast<S: ScriptSpatialQuery>(
+    In((origin, direction, max_distance)): In<(ScriptValue, ScriptValue, f64)>,
+    spatial_query: Res<S>,
+) -> ScriptValue {
+    let (Some(origin), Some(direction)) = (origin.as_vec3(), direction.as_vec3()) else {
+        tracing::error!(
+            "origin and direction must be Vec3, got {:?} and {:?}",
+            origin,
+            direction
+        );
+        return ScriptValue::Nil;
+    };
+    spatial_query
+        .cast_ray(origin, direction, max_distance as f32)
+        .map(|hit| {
+            ScriptValue::Map(
+                [
+                    ("entity".to_string(), ScriptValue::Entity(hit.entity)),
+                    ("point".to_string(), ScriptValue::Vec3(hit.point)),
+                ]
+                .into(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Finds entities intersecting a sphere, registered as `physics.overlap_sphere`.
+pub(crate) fn overlap_sphere<S: ScriptSpatialQuery>(
+    In((center, radius)): In<(ScriptValue, f64)>,
+    spatial_query: Res<S>,
+) -> ScriptValue {
+    let Some(center) = center.as_vec3() else {
+        tracing::error!("center must be a Vec3, got {:?}", center);
+        return ScriptValue::Array(Vec::new());
+    };
+    spatial_query
+        .overlap_sphere(center, radius as f32)
+        .into_iter()
+        .map(ScriptValue::Entity)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Calls collision hooks of scripted entities involved in collisions reported by events `E`.
+pub(crate) fn forward_collision_events<R: EntityArgs, E: ScriptCollisionEvent>(
+    mut events: EventReader<E>,
+    mut scripted_entities: Query<&mut R::ScriptData>,
+    scripting_runtime: Res<R>,
+) {
+    for event in events.read() {
+        let (a, b, phase) = event.collision();
+        let name = match phase {
+            CollisionPhase::Started => ON_COLLISION_START_FN_NAME,
+            CollisionPhase::Stopped => ON_COLLISION_STOP_FN_NAME,
+        };
+        for (entity, other) in [(a, b), (b, a)] {
+            let Ok(mut script_data) = scripted_entities.get_mut(entity) else {
+                continue;
+            };
+            if let Err(e) = scripting_runtime.call_fn_if_exists(
+                name,
+                &mut script_data,
+                entity,
+                R::entity_args(other),
+            ) {
+                tracing::error!("error calling '{}' on entity {:?}: {}", name, entity, e);
+            }
+        }
+    }
+}
@@ -1,6 +1,52 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use crate::{Runtime, ScriptingError};
+use bevy::{
+    prelude::*,
+    utils::{tracing, Instant},
+};
+
+use crate::{
+    capabilities::enter_entity,
+    tracking::{CallTracker, PendingPromise},
+    Runtime, ScriptValue, ScriptingError, ScriptingSettings,
+};
+
+/// An event sent whenever a function registered in runtime `R` returns, resolving the promise
+/// that the calling script got. Requires
+/// [ScriptingRuntimeBuilder::enable_promise_events](crate::ScriptingRuntimeBuilder::enable_promise_events).
+///
+/// Useful for analytics, debugging and tests asserting that asynchronous flows of scripts have
+/// completed.
+#[derive(Event)]
+pub struct PromiseResolved<R: Runtime> {
+    /// Entity whose script called the function.
+    pub entity: Option<Entity>,
+    /// Name of the function prefixed with its namespace, e.g. `game.give_item`.
+    pub function: String,
+    /// The returned value converted with [Runtime::to_script_value], or [ScriptValue::Nil] if
+    /// the calling entity has no script data of the runtime anymore.
+    pub value: ScriptValue,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> PromiseResolved<R> {
+    pub(crate) fn new(entity: Option<Entity>, function: String, value: ScriptValue) -> Self {
+        Self {
+            entity,
+            function,
+            value,
+            _phantom_data: PhantomData,
+        }
+    }
+}
 
 /// A struct that represents a function that will get called when the Promise is resolved.
 pub(crate) struct PromiseCallback<C: Send, V: Send> {
@@ -13,60 +59,177 @@ pub(crate) struct PromiseInner<C: Send, V: Send> {
     pub(crate) callbacks: Vec<PromiseCallback<C, V>>,
     #[allow(deprecated)]
     pub(crate) context: C,
+    /// Entity whose script created the promise.
+    pub(crate) entity: Option<Entity>,
+    /// Set when the promise has been cancelled by the script.
+    pub(crate) cancelled: bool,
+    /// Time after which the promise is treated as cancelled.
+    pub(crate) deadline: Option<Instant>,
+    /// Set when the promise has been spawned by a tracked call, until it settles.
+    pending: Option<PendingPromise>,
 }
 
-/// A struct that represents a Promise.
+/// A struct that represents a Promise of the value that a registered function returns, whose
+/// callbacks get called with the value once the function has run.
 #[derive(Clone)]
 pub struct Promise<C: Send, V: Send> {
     pub(crate) inner: Arc<Mutex<PromiseInner<C, V>>>,
 }
 
 impl<C: Send, V: Send + Clone> PromiseInner<C, V> {
-    /// Resolve the Promise. This will call all the callbacks that were added to the Promise.
-    fn resolve<R>(&mut self, runtime: &mut R, val: R::Value) -> Result<(), ScriptingError>
+    pub(crate) fn new(context: C, entity: Option<Entity>) -> Self {
+        Self {
+            callbacks: vec![],
+            context,
+            entity,
+            cancelled: false,
+            deadline: None,
+            pending: CallTracker::current().map(|tracker| tracker.track()),
+        }
+    }
+
+    /// Returns `true` if the promise has been cancelled or its deadline has passed.
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Resolve the Promise. This will call all the callbacks that were added to the Promise and
+    /// queue promises following them to be resolved with values they returned. Callbacks of
+    /// a cancelled promise are dropped instead. Returns the number of called callbacks.
+    fn resolve<R>(
+        &mut self,
+        runtime: &mut R,
+        val: R::Value,
+        queue: &mut VecDeque<ResolvedPromise<C, V>>,
+    ) -> Result<usize, ScriptingError>
     where
         R: Runtime<Value = V, CallContext = C>,
     {
-        for callback in &self.callbacks {
-            let next_val =
-                runtime.call_fn_from_value(&callback.callback, &self.context, vec![val.clone()])?;
+        let pending = self.pending.take();
+        if self.is_cancelled() {
+            self.cancel();
+            return Ok(0);
+        }
+        let _entity_guard = enter_entity(self.entity);
+        // Promises spawned by callbacks are tracked by the call that spawned this one.
+        let _tracker_guard = pending.as_ref().map(|pending| pending.tracker().enter());
+        let mut callbacks = std::mem::take(&mut self.callbacks).into_iter();
+        let mut called = 0;
+        while let Some(callback) = callbacks.next() {
+            called += 1;
+            match runtime.call_fn_from_value(&callback.callback, &self.context, vec![val.clone()]) {
+                Ok(next_val) => queue.push_back((
+                    Promise {
+                        inner: callback.following_promise,
+                    },
+                    next_val,
+                )),
+                Err(e) => {
+                    // Promises following failed callbacks never get resolved.
+                    for callback in std::iter::once(callback).chain(callbacks) {
+                        callback
+                            .following_promise
+                            .lock()
+                            .expect("Failed to lock promise mutex")
+                            .cancel();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(called)
+    }
 
+    fn cancel(&mut self) {
+        self.cancelled = true;
+        self.pending = None;
+        for callback in std::mem::take(&mut self.callbacks) {
             callback
                 .following_promise
                 .lock()
                 .expect("Failed to lock promise mutex")
-                .resolve(runtime, next_val)?;
+                .cancel();
         }
-        Ok(())
     }
 }
 
 impl<C: Clone + Send + 'static, V: Send + Clone> Promise<C, V> {
     /// Acquire [Mutex] for writing the promise and resolve it. Call will be forwarded to [PromiseInner::resolve].
-    pub(crate) fn resolve<R>(
+    fn resolve<R>(
         &mut self,
         runtime: &mut R,
         val: R::Value,
-    ) -> Result<(), ScriptingError>
+        queue: &mut VecDeque<ResolvedPromise<C, V>>,
+    ) -> Result<usize, ScriptingError>
     where
         R: Runtime<Value = V, CallContext = C>,
     {
-        if let Ok(mut inner) = self.inner.lock() {
-            inner.resolve(runtime, val)?;
+        match self.inner.lock() {
+            Ok(mut inner) => inner.resolve(runtime, val, queue),
+            Err(_) => Ok(0),
         }
-        Ok(())
+    }
+
+    /// Returns a copy of the context that the promise has been created in.
+    pub(crate) fn context(&self) -> C {
+        self.inner
+            .lock()
+            .expect("Failed to lock inner promise mutex")
+            .context
+            .clone()
+    }
+
+    /// Cancels the promise, dropping its callbacks. If the call that created the promise has not
+    /// been processed yet, it is dropped as well.
+    pub fn cancel(&mut self) {
+        self.inner
+            .lock()
+            .expect("Failed to lock inner promise mutex")
+            .cancel();
+    }
+
+    /// Makes the promise cancel itself if it does not get resolved within provided duration.
+    /// Promises created by registering callbacks on it inherit the deadline.
+    pub fn timeout(&mut self, duration: Duration) -> Self {
+        self.inner
+            .lock()
+            .expect("Failed to lock inner promise mutex")
+            .deadline = Some(Instant::now() + duration);
+        self.clone()
+    }
+
+    /// Returns `true` if the promise has been cancelled or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner
+            .lock()
+            .expect("Failed to lock inner promise mutex")
+            .is_cancelled()
+    }
+
+    /// Returns the entity whose script created the promise.
+    pub fn entity(&self) -> Option<Entity> {
+        self.inner
+            .lock()
+            .expect("Failed to lock inner promise mutex")
+            .entity
     }
 
     /// Register a callback that will be called when the [Promise] is resolved.
-    pub(crate) fn then(&mut self, callback: V) -> Self {
+    pub fn then(&mut self, callback: V) -> Self {
         let mut inner = self
             .inner
             .lock()
             .expect("Failed to lock inner promise mutex");
-        let following_inner = Arc::new(Mutex::new(PromiseInner {
-            callbacks: vec![],
-            context: inner.context.clone(),
-        }));
+        let mut following = PromiseInner::new(inner.context.clone(), inner.entity);
+        following.deadline = inner.deadline;
+        following.pending = inner
+            .pending
+            .as_ref()
+            .map(|pending| pending.tracker().track());
+        let following_inner = Arc::new(Mutex::new(following));
 
         inner.callbacks.push(PromiseCallback {
             following_promise: following_inner.clone(),
@@ -78,3 +241,185 @@ impl<C: Clone + Send + 'static, V: Send + Clone> Promise<C, V> {
         }
     }
 }
+
+/// A promise along with the value that it got resolved with.
+type ResolvedPromise<C, V> = (Promise<C, V>, V);
+
+/// A resource that stores promises of runtime `R` that got resolved with a value, until their
+/// callbacks get called by [resolve_queued_promises].
+#[derive(Resource)]
+pub(crate) struct PromiseQueue<R: Runtime> {
+    resolved: Mutex<VecDeque<ResolvedPromise<R::CallContext, R::Value>>>,
+}
+
+impl<R: Runtime> Default for PromiseQueue<R> {
+    fn default() -> Self {
+        Self {
+            resolved: Default::default(),
+        }
+    }
+}
+
+impl<R: Runtime> PromiseQueue<R> {
+    /// Queues provided promise to get resolved with provided value.
+    pub(crate) fn push(&self, promise: Promise<R::CallContext, R::Value>, value: R::Value) {
+        self.resolved
+            .lock()
+            .expect("Failed to lock promise queue")
+            .push_back((promise, value));
+    }
+
+    /// Returns whether there are no promises waiting for their callbacks to get called.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.resolved
+            .lock()
+            .expect("Failed to lock promise queue")
+            .is_empty()
+    }
+}
+
+/// Calls callbacks of queued promises, along with callbacks of promises following them, until
+/// the queue is empty or [ScriptingSettings::max_promise_callbacks] callbacks have been called.
+/// Remaining promises are resolved the next time this runs.
+pub(crate) fn resolve_queued_promises<R: Runtime>(world: &mut World) {
+    let max_callbacks = world
+        .get_resource::<ScriptingSettings<R>>()
+        .and_then(|settings| settings.max_promise_callbacks)
+        .unwrap_or(usize::MAX);
+    let Some(queue) = world.get_resource::<PromiseQueue<R>>() else {
+        return;
+    };
+    let mut resolved =
+        std::mem::take(&mut *queue.resolved.lock().expect("Failed to lock promise queue"));
+    let mut runtime = world.resource_mut::<R>();
+    let mut called = 0;
+    while called < max_callbacks {
+        let Some((mut promise, value)) = resolved.pop_front() else {
+            break;
+        };
+        match promise.resolve(runtime.as_mut(), value, &mut resolved) {
+            Ok(callbacks) => called += callbacks,
+            Err(e) => {
+                tracing::error!("error calling promise callback: {:?}", e);
+                called += 1;
+            }
+        }
+    }
+    if !resolved.is_empty() {
+        tracing::trace!(
+            "resolve_queued_promises: deferring {} promises to the next run",
+            resolved.len()
+        );
+        let queue = world.resource::<PromiseQueue<R>>();
+        let mut queued = queue.resolved.lock().expect("Failed to lock promise queue");
+        resolved.append(&mut queued);
+        *queued = resolved;
+    }
+}
+
+/// A resource that allows a registered function to resolve the promise of its call later, with
+/// a value sent from e.g. another thread, instead of with its return value, which is then
+/// ignored. Only runtimes that [PendingCalls] have been initialized for support it.
+#[derive(Resource, Default)]
+pub(crate) struct DeferredCall(Option<Mutex<Receiver<ScriptValue>>>);
+
+impl DeferredCall {
+    /// Defers resolving the promise of the function call being processed until a value is sent
+    /// through the returned sender. The promise is dropped if the sender is dropped first.
+    pub(crate) fn defer(&mut self) -> Sender<ScriptValue> {
+        let (sender, receiver) = mpsc::channel();
+        self.0 = Some(Mutex::new(receiver));
+        sender
+    }
+
+    pub(crate) fn take(&mut self) -> Option<Mutex<Receiver<ScriptValue>>> {
+        self.0.take()
+    }
+}
+
+/// A call of a registered function whose promise gets resolved with the value it receives.
+pub(crate) struct PendingCall<R: Runtime> {
+    pub(crate) promise: Promise<R::CallContext, R::Value>,
+    pub(crate) function: String,
+    pub(crate) receiver: Mutex<Receiver<ScriptValue>>,
+}
+
+/// Converts a [ScriptValue] into a value of runtime `R`.
+type IntoRuntimeValue<R> =
+    fn(ScriptValue, &<R as Runtime>::RawEngine) -> Result<<R as Runtime>::Value, ScriptingError>;
+
+/// A resource that stores calls of registered functions of runtime `R` that have been deferred
+/// with [DeferredCall], until they receive values.
+#[derive(Resource)]
+pub(crate) struct PendingCalls<R: Runtime> {
+    calls: Vec<PendingCall<R>>,
+    into_runtime_value: IntoRuntimeValue<R>,
+}
+
+impl<R: Runtime> PendingCalls<R> {
+    pub(crate) fn new(into_runtime_value: IntoRuntimeValue<R>) -> Self {
+        Self {
+            calls: Vec::new(),
+            into_runtime_value,
+        }
+    }
+
+    pub(crate) fn push(&mut self, call: PendingCall<R>) {
+        self.calls.push(call);
+    }
+}
+
+/// Queues promises of pending calls that have received values to get resolved with them. Calls
+/// whose promises have been cancelled, or whose entities have been despawned, are dropped.
+pub(crate) fn poll_pending_calls<R: Runtime>(world: &mut World) {
+    let Some(mut pending) = world.get_resource_mut::<PendingCalls<R>>() else {
+        return;
+    };
+    let into_runtime_value = pending.into_runtime_value;
+    let mut finished = Vec::new();
+    pending.calls.retain_mut(|call| {
+        if call.promise.is_cancelled() {
+            call.promise.cancel();
+            return false;
+        }
+        let receiver = call
+            .receiver
+            .get_mut()
+            .expect("Failed to lock pending call");
+        match receiver.try_recv() {
+            Ok(value) => {
+                finished.push((call.promise.clone(), call.function.clone(), value));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => {
+                call.promise.cancel();
+                false
+            }
+        }
+    });
+    for (mut promise, function, value) in finished {
+        let entity = promise.entity();
+        if entity.is_some_and(|entity| world.get_entity(entity).is_none()) {
+            continue;
+        }
+        if world.contains_resource::<Events<PromiseResolved<R>>>() {
+            world.send_event(PromiseResolved::<R>::new(
+                entity,
+                function.clone(),
+                value.clone(),
+            ));
+        }
+        let runtime = world.resource::<R>();
+        let value = runtime.with_context_engine(&promise.context(), |engine| {
+            into_runtime_value(value, engine)
+        });
+        match value {
+            Ok(value) => world.resource::<PromiseQueue<R>>().push(promise, value),
+            Err(e) => {
+                tracing::error!("error resolving promise of {}: {}", function, e);
+                promise.cancel();
+            }
+        }
+    }
+}
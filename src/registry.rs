@@ -0,0 +1,94 @@
+use std::any::TypeId;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{callback::Callback, Callbacks, Runtime};
+
+/// Description of a function registered in runtime `R`, as listed by [ScriptApiRegistry].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptFunctionInfo {
+    pub name: String,
+    /// Namespace that scripts access the function through, if any.
+    pub namespace: Option<String>,
+    /// Types that the runtime uses to decide which values can be passed as the arguments.
+    pub arg_types: Vec<TypeId>,
+    /// Names of Rust types of the arguments, e.g. `alloc::string::String`.
+    pub arg_type_names: Vec<&'static str>,
+    /// Name of the Rust type of the value the function resolves its promise with.
+    pub return_type_name: &'static str,
+    /// Capabilities a script needs to be granted to call the function.
+    pub capabilities: Vec<String>,
+    /// Name of the plugin that registered the function, set with
+    /// [ScriptingRuntimeBuilder::set_function_source](crate::ScriptingRuntimeBuilder::set_function_source).
+    pub source: Option<String>,
+}
+
+impl ScriptFunctionInfo {
+    /// Returns the name of the function prefixed with its namespace, e.g. `game.give_item`.
+    pub fn qualified_name(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{namespace}.{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl<R: Runtime> From<&Callback<R>> for ScriptFunctionInfo {
+    fn from(callback: &Callback<R>) -> Self {
+        let system = callback
+            .system
+            .lock()
+            .expect("Failed to lock callback system mutex");
+        Self {
+            name: callback.name.clone(),
+            namespace: callback.namespace.clone(),
+            arg_types: system.arg_types.clone(),
+            arg_type_names: system.arg_type_names.clone(),
+            return_type_name: system.return_type_name,
+            capabilities: callback.capabilities.clone(),
+            source: callback.source.clone(),
+        }
+    }
+}
+
+/// A system parameter listing functions registered in runtime `R`, e.g. for editor tooling
+/// describing the API exposed to scripts.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+/// use bevy_scriptum::ScriptApiRegistry;
+///
+/// fn list_functions(registry: ScriptApiRegistry<LuaRuntime>) {
+///     for function in registry.iter() {
+///         println!("{}({})", function.qualified_name(), function.arg_type_names.join(", "));
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct ScriptApiRegistry<'w, R: Runtime> {
+    callbacks: Res<'w, Callbacks<R>>,
+}
+
+impl<R: Runtime> ScriptApiRegistry<'_, R> {
+    /// Returns descriptions of all registered functions, including ones that have not been
+    /// installed into the runtime yet, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = ScriptFunctionInfo> {
+        let mut callbacks = self
+            .callbacks
+            .callbacks
+            .lock()
+            .expect("Failed to lock callbacks mutex")
+            .values()
+            .map(|callback| (callback.id, ScriptFunctionInfo::from(&**callback)))
+            .collect::<Vec<_>>();
+        callbacks.extend(
+            self.callbacks
+                .uninitialized_callbacks
+                .iter()
+                .map(|callback| (callback.id, ScriptFunctionInfo::from(callback))),
+        );
+        callbacks.sort_by_key(|(id, _)| id.0);
+        callbacks.into_iter().map(|(_, function)| function)
+    }
+}
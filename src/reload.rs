@@ -0,0 +1,97 @@
+use std::marker::PhantomData;
+
+use bevy::{ecs::system::SystemId, prelude::*, utils::tracing};
+
+use crate::{Runtime, Script};
+
+/// An event sent when a script of runtime `R` has been evaluated again after its asset, or an
+/// asset of one of its bases, has been modified, e.g. when a mod is edited while the game runs.
+#[derive(Event)]
+pub struct ScriptReloaded<R: Runtime> {
+    /// Entity whose script has been reloaded.
+    pub entity: Entity,
+    /// Handle of the script asset of the entity.
+    pub script: Handle<R::ScriptAsset>,
+    /// Number of times the script of the entity had been reloaded before.
+    pub old_version: u32,
+    /// Number of times the script of the entity has been reloaded, including this time.
+    pub new_version: u32,
+}
+
+impl<R: Runtime> Clone for ScriptReloaded<R> {
+    fn clone(&self) -> Self {
+        Self {
+            entity: self.entity,
+            script: self.script.clone(),
+            old_version: self.old_version,
+            new_version: self.new_version,
+        }
+    }
+}
+
+/// A component tracking reloads of the script of runtime `R` of its entity.
+#[derive(Component)]
+pub(crate) struct ScriptRevision<R: Runtime> {
+    pub(crate) version: u32,
+    /// Set when the script data has been removed, until the script gets evaluated again.
+    pub(crate) reloading: bool,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> ScriptRevision<R> {
+    pub(crate) fn reloading() -> Self {
+        Self {
+            version: 0,
+            reloading: true,
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+/// A resource that stores systems registered with
+/// [ScriptingRuntimeBuilder::on_script_reloaded](crate::ScriptingRuntimeBuilder::on_script_reloaded).
+#[derive(Resource)]
+pub(crate) struct ReloadHooks<R: Runtime> {
+    pub(crate) systems: Vec<SystemId<ScriptReloaded<R>>>,
+}
+
+impl<R: Runtime> Default for ReloadHooks<R> {
+    fn default() -> Self {
+        Self {
+            systems: Default::default(),
+        }
+    }
+}
+
+/// Sends [ScriptReloaded] events for scripts that have been evaluated again since they were
+/// reloaded, and runs reload hooks for each of them.
+pub(crate) fn notify_reloaded_scripts<R: Runtime>(world: &mut World) {
+    let mut reloaded = Vec::new();
+    let mut revisions = world.query_filtered::<(
+        Entity,
+        &Script<R::ScriptAsset>,
+        &mut ScriptRevision<R>,
+    ), With<R::ScriptData>>();
+    for (entity, script, mut revision) in revisions.iter_mut(world) {
+        if !revision.reloading {
+            continue;
+        }
+        revision.reloading = false;
+        revision.version += 1;
+        reloaded.push(ScriptReloaded::<R> {
+            entity,
+            script: script.script.clone(),
+            old_version: revision.version - 1,
+            new_version: revision.version,
+        });
+    }
+    let hooks = world.resource::<ReloadHooks<R>>().systems.clone();
+    for event in reloaded {
+        for hook in &hooks {
+            if let Err(e) = world.run_system_with_input(*hook, event.clone()) {
+                tracing::error!("error running reload hook: {}", e);
+            }
+        }
+        world.send_event(event);
+    }
+}
@@ -0,0 +1,78 @@
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{Runtime, Script};
+
+/// Names of functions that every script of runtime `R` is expected to define, set with
+/// [ScriptingRuntimeBuilder::require_functions](crate::ScriptingRuntimeBuilder::require_functions).
+#[derive(Resource)]
+pub(crate) struct RequiredFunctions<R: Runtime> {
+    pub(crate) names: Vec<String>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for RequiredFunctions<R> {
+    fn default() -> Self {
+        Self {
+            names: Vec::new(),
+            _phantom_data: PhantomData,
+        }
+    }
+}
+
+/// An event sent when a script of runtime `R` gets evaluated, or reloaded, without defining some
+/// of the functions required with
+/// [ScriptingRuntimeBuilder::require_functions](crate::ScriptingRuntimeBuilder::require_functions),
+/// e.g. because of a typo like `on_updat`, which would otherwise make the script silently do
+/// nothing.
+#[derive(Event)]
+pub struct ScriptMissingFunctions<R: Runtime> {
+    /// Entity that the script is attached to.
+    pub entity: Entity,
+    pub script: Handle<R::ScriptAsset>,
+    /// Path of the script asset.
+    pub path: String,
+    /// Names of required functions that the script does not define, in the order they were
+    /// required.
+    pub missing: Vec<String>,
+}
+
+/// Checks that scripts evaluated since it last ran define all required functions, logging
+/// a warning and sending [ScriptMissingFunctions] for ones that don't.
+#[allow(clippy::type_complexity)]
+pub(crate) fn check_required_functions<R: Runtime>(
+    runtime: Res<R>,
+    required: Res<RequiredFunctions<R>>,
+    scripts: Query<(Entity, &Script<R::ScriptAsset>, &R::ScriptData), Added<R::ScriptData>>,
+    asset_server: Res<AssetServer>,
+    mut events: EventWriter<ScriptMissingFunctions<R>>,
+) {
+    for (entity, script, script_data) in &scripts {
+        let defined = runtime.list_functions(script_data);
+        let missing = required
+            .names
+            .iter()
+            .filter(|name| !defined.contains(name))
+            .cloned()
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            continue;
+        }
+        let path = asset_server
+            .get_path(&script.script)
+            .map(|path| path.to_string())
+            .unwrap_or_default();
+        tracing::warn!(
+            "script {} does not define required functions: {}",
+            path,
+            missing.join(", ")
+        );
+        events.send(ScriptMissingFunctions {
+            entity,
+            script: script.script.clone(),
+            path,
+            missing,
+        });
+    }
+}
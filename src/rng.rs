@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+
+/// A seeded random number generator shared by scripts and the host.
+///
+/// Inserting this resource before calling `add_scripting` enables deterministic mode for the
+/// runtime being added: random number functions of the language (e.g. `math.random` in Lua)
+/// draw from this generator instead of their default source and functions reading wall-clock
+/// time are disabled, so every peer running the same scripts with the same seed gets the same
+/// results. The state can be saved and restored together with script snapshots for rollback.
+#[derive(Resource, Clone, Debug)]
+pub struct ScriptRng {
+    state: Arc<Mutex<u64>>,
+}
+
+impl ScriptRng {
+    /// Creates a generator seeded with provided value.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(seed)),
+        }
+    }
+
+    /// Resets the generator to provided seed.
+    pub fn reseed(&self, seed: u64) {
+        *self.lock() = seed;
+    }
+
+    /// Returns current state of the generator, which can be brought back with [ScriptRng::reseed].
+    pub fn state(&self) -> u64 {
+        *self.lock()
+    }
+
+    /// Returns next pseudo-random number, using SplitMix64 algorithm.
+    pub fn next_u64(&self) -> u64 {
+        let mut state = self.lock();
+        *state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns next pseudo-random number in `[0, 1)` range.
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns next pseudo-random integer in `[min, max]` range. `min` must not be greater
+    /// than `max`.
+    pub fn next_in_range(&self, min: i64, max: i64) -> i64 {
+        let span = max.wrapping_sub(min) as u64;
+        if span == u64::MAX {
+            return self.next_u64() as i64;
+        }
+        min.wrapping_add((self.next_u64() % (span + 1)) as i64)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, u64> {
+        self.state.lock().expect("Failed to lock script rng")
+    }
+}
@@ -0,0 +1,134 @@
+//! Building blocks of the built-in runtimes, for implementing [Runtime](crate::Runtime) for other
+//! languages.
+//!
+//! Values passed between scripts and Rust are converted with [IntoRuntimeValueWithEngine] and
+//! [FromRuntimeValueWithEngine], arguments of script functions with [FuncArgs](crate::FuncArgs),
+//! which can be implemented for tuples of every size with
+//! [impl_func_args_for_tuples](crate::impl_func_args_for_tuples).
+//! Registered functions return a [Promise], which runtimes expose to scripts as a type with
+//! methods calling [Promise::then], [Promise::cancel] and [Promise::timeout].
+//!
+//! Whenever a runtime evaluates a script or calls a function of it, it should expose the entity
+//! that the script is attached to as a variable named [ENTITY_VAR_NAME], information about the
+//! script returned by [script_info_value] as a variable named [SCRIPT_VAR_NAME], and mark the
+//! entity as the current one with [enter_entity], so that capabilities and log messages of
+//! the script are attributed to it:
+//!
+//! ```ignore
+//! fn call_fn(
+//!     &self,
+//!     name: &str,
+//!     script_data: &mut Self::ScriptData,
+//!     entity: Entity,
+//!     args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+//! ) -> Result<Self::Value, ScriptingError> {
+//!     script_data.set_global(ENTITY_VAR_NAME, entity);
+//!     let _entity_guard = enter_entity(Some(entity));
+//!     let result = script_data.call(name, args.parse(&self.engine));
+//!     script_data.clear_global(ENTITY_VAR_NAME);
+//!     result
+//! }
+//! ```
+//!
+//! Runtimes can be checked against the [conformance](crate::conformance) suite.
+
+use std::collections::HashMap;
+
+use crate::{ScriptInfo, ScriptValue};
+
+pub use crate::assets::GetExtensions;
+pub use crate::callback::{FromRuntimeValueWithEngine, IntoRuntimeValueWithEngine};
+pub use crate::capabilities::{current_entity, enter_entity, EntityGuard};
+pub use crate::promise::Promise;
+
+/// Name of the variable holding the entity that the running script is attached to.
+pub const ENTITY_VAR_NAME: &str = "entity";
+
+/// Name of the variable holding information about the running script, see
+/// [script_info_value].
+pub const SCRIPT_VAR_NAME: &str = "script";
+
+/// Returns the value exposed to scripts as the [SCRIPT_VAR_NAME] variable, a map with `path`,
+/// `asset_id` and `mod_name` of the script.
+pub fn script_info_value(info: &ScriptInfo) -> ScriptValue {
+    ScriptValue::Map(HashMap::from([
+        (String::from("path"), ScriptValue::from(info.path.as_str())),
+        (
+            String::from("asset_id"),
+            ScriptValue::from(info.asset_id.as_str()),
+        ),
+        (
+            String::from("mod_name"),
+            ScriptValue::from(info.mod_name.clone()),
+        ),
+    ]))
+}
+
+/// Implements [FuncArgs](crate::FuncArgs) for tuples of up to 26 elements, converting every
+/// element with provided closure-like expression, which gets the engine of the runtime and the
+/// element. Elements have to satisfy bounds given in square brackets, which can refer to the
+/// lifetime of the engine:
+///
+/// ```ignore
+/// bevy_scriptum::impl_func_args_for_tuples!(
+///     <'a> MyRuntime, MyValue, [IntoMyValue<'a>],
+///     |engine, value| value.into_my_value(engine)
+/// );
+/// ```
+#[macro_export]
+macro_rules! impl_func_args_for_tuples {
+    (
+        <$lt:lifetime> $runtime:ty, $value:ty, [$($bound:tt)+],
+        |$engine:ident, $arg:ident| $convert:expr
+    ) => {
+        $crate::impl_func_args_for_tuples!(
+            @tuples <$lt> $runtime, $value, [$($bound)+], |$engine, $arg| $convert;
+            A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z
+        );
+    };
+    (
+        @tuples <$lt:lifetime> $runtime:ty, $value:ty, $bounds:tt,
+        |$engine:ident, $arg:ident| $convert:expr; $t:ident $(, $rest:ident)*
+    ) => {
+        $crate::impl_func_args_for_tuples!(
+            @bounds <$lt> $runtime, $value, $bounds, |$engine, $arg| $convert;
+            []; [$t $(, $rest)*]; $t $(, $rest)*
+        );
+        $crate::impl_func_args_for_tuples!(
+            @tuples <$lt> $runtime, $value, $bounds, |$engine, $arg| $convert; $($rest),*
+        );
+    };
+    (
+        @tuples <$lt:lifetime> $runtime:ty, $value:ty, $bounds:tt,
+        |$engine:ident, $arg:ident| $convert:expr;
+    ) => {};
+    (
+        @bounds <$lt:lifetime> $runtime:ty, $value:ty, [$($bound:tt)+],
+        |$engine:ident, $arg:ident| $convert:expr;
+        [$($generics:tt)*]; $types:tt; $t:ident $(, $rest:ident)*
+    ) => {
+        $crate::impl_func_args_for_tuples!(
+            @bounds <$lt> $runtime, $value, [$($bound)+], |$engine, $arg| $convert;
+            [$($generics)* $t: $($bound)+,]; $types; $($rest),*
+        );
+    };
+    (
+        @bounds <$lt:lifetime> $runtime:ty, $value:ty, $bounds:tt,
+        |$engine:ident, $arg:ident| $convert:expr;
+        [$($generics:tt)*]; [$($t:ident),+];
+    ) => {
+        impl<$lt, $($generics)*> $crate::FuncArgs<$lt, $value, $runtime> for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn parse(
+                self,
+                $engine: &$lt <$runtime as $crate::Runtime>::RawEngine,
+            ) -> Vec<$value> {
+                let ($($t,)+) = self;
+                vec![$({
+                    let $arg = $t;
+                    $convert
+                }),+]
+            }
+        }
+    };
+}
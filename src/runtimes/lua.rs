@@ -1,25 +1,68 @@
 use bevy::{
-    asset::Asset,
-    ecs::{component::Component, entity::Entity, schedule::ScheduleLabel, system::Resource},
+    asset::{Asset, AssetPath},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        schedule::ScheduleLabel,
+        system::Resource,
+        world::{FromWorld, World},
+    },
     math::Vec3,
     reflect::TypePath,
 };
 use mlua::{
-    FromLua, Function, IntoLua, IntoLuaMulti, Lua, RegistryKey, UserData, UserDataFields,
-    UserDataMethods, Variadic,
+    FromLua, FromLuaMulti, Function, IntoLua, IntoLuaMulti, Lua, LuaSerdeExt, MultiValue,
+    RegistryKey, Table, Thread, UserData, UserDataFields, UserDataMethods, Variadic,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
 };
-use serde::Deserialize;
-use std::sync::{Arc, Mutex};
 
 use crate::{
-    assets::GetExtensions,
-    callback::{FromRuntimeValueWithEngine, IntoRuntimeValueWithEngine},
-    promise::Promise,
-    FuncArgs, Runtime, ScriptingError, ENTITY_VAR_NAME,
+    assets::ResolveImports,
+    cache::SourceHasher,
+    impl_func_args_for_tuples,
+    logging::{log_message, LOG_FUNCTIONS},
+    pack::ResolvePackModules,
+    runtime_support::{
+        enter_entity, script_info_value, FromRuntimeValueWithEngine, GetExtensions,
+        IntoRuntimeValueWithEngine, Promise, ENTITY_VAR_NAME, SCRIPT_VAR_NAME,
+    },
+    CallProgress, CompiledScriptCache, EntityArgs, FuncArgs, Json, Runtime, ScriptDiagnostic,
+    ScriptInfo, ScriptRng, ScriptValue, ScriptingError,
 };
 
+mod annotations;
+mod backend;
+#[cfg(all(feature = "debugger", not(feature = "luau")))]
+mod debugger;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod modules;
+mod overloads;
+mod resumable;
+mod snapshot;
+mod value;
+
+use overloads::{HostFunction, Overload};
+
+pub use annotations::lua_annotations;
+pub use snapshot::LuaSnapshot;
+
+/// The version of mlua used by this runtime, built for the Lua implementation selected with a
+/// feature of this crate. Depending on mlua directly requires enabling the same implementation.
+pub use mlua;
+
 type LuaEngine = Arc<Mutex<Lua>>;
 
+type LuaHostFunction = dyn Fn(LuaCallContext, Vec<LuaValue>) -> Result<Promise<LuaCallContext, LuaValue>, ScriptingError>
+    + Send
+    + Sync;
+
 #[derive(Clone)]
 pub struct LuaValue(Arc<RegistryKey>);
 
@@ -31,11 +74,172 @@ impl LuaValue {
                 .expect("Error creating a registry key for value"),
         ))
     }
+
+    /// Converts the value into provided type, using the Lua state that it has been created in.
+    /// Multiple values returned by a function can be converted into a tuple.
+    pub fn get<'a, T: FromLuaMulti<'a>>(&self, engine: &'a Lua) -> mlua::Result<T> {
+        T::from_lua_multi(self.unpack(engine)?, engine)
+    }
+
+    /// Creates a value from values returned by a function. Multiple values get packed into
+    /// a table marked with a metatable, which gets unpacked by [LuaValue::unpack].
+    fn from_multi<'a>(engine: &'a Lua, values: MultiValue<'a>) -> mlua::Result<Self> {
+        if values.len() <= 1 {
+            return Ok(Self::new(engine, values.into_iter().next()));
+        }
+        let table = engine.create_table()?;
+        table.raw_set("n", values.len())?;
+        for (i, value) in values.into_iter().enumerate() {
+            table.raw_set(i + 1, value)?;
+        }
+        table.set_metatable(Some(multi_value_metatable(engine)?));
+        Ok(Self::new(engine, table))
+    }
+
+    /// Returns values held by this value, which are multiple if it has been created
+    /// from multiple values returned by a function.
+    fn unpack<'a>(&self, engine: &'a Lua) -> mlua::Result<MultiValue<'a>> {
+        let value = engine.registry_value::<mlua::Value>(&self.0)?;
+        if let mlua::Value::Table(table) = &value {
+            if table.get_metatable() == Some(multi_value_metatable(engine)?) {
+                let n = table.raw_get::<_, usize>("n")?;
+                return (1..=n).map(|i| table.raw_get(i)).collect();
+            }
+        }
+        Ok(MultiValue::from_vec(vec![value]))
+    }
+}
+
+/// Name of the registry value holding the metatable of tables packing multiple values.
+const MULTI_VALUE_METATABLE: &str = "bevy_scriptum.multi_value";
+
+fn multi_value_metatable(engine: &Lua) -> mlua::Result<Table<'_>> {
+    if let Some(metatable) = engine.named_registry_value::<Option<Table>>(MULTI_VALUE_METATABLE)? {
+        return Ok(metatable);
+    }
+    let metatable = engine.create_table()?;
+    engine.set_named_registry_value(MULTI_VALUE_METATABLE, metatable.clone())?;
+    Ok(metatable)
+}
+
+/// Converts provided values into arguments of a Lua function, unpacking multiple values.
+fn unpack_args<'a>(engine: &'a Lua, args: Vec<LuaValue>) -> mlua::Result<MultiValue<'a>> {
+    let mut unpacked = Vec::with_capacity(args.len());
+    for arg in args {
+        unpacked.extend(arg.unpack(engine)?);
+    }
+    Ok(MultiValue::from_vec(unpacked))
+}
+
+/// Decides whether scripted entities share a single Lua state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IsolationMode {
+    /// All scripts are evaluated within one Lua state and share globals, unless they are
+    /// evaluated in environments of their own with [ScriptVariables::Environment].
+    #[default]
+    Shared,
+    /// Every scripted entity gets its own Lua state with registered functions copied
+    /// into it. Scripts cannot see each other's globals and can be evaluated in parallel.
+    PerEntity,
+}
+
+/// Decides how the `entity` and `script` variables get exposed to Lua scripts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScriptVariables {
+    /// Every script is evaluated in its own environment, a table whose lookups of the variables
+    /// fall back to a table holding them, and lookups of other globals fall back to globals of
+    /// the Lua state. Functions and global variables defined by the script are stored in its
+    /// environment, so scripts sharing a Lua state don't replace each other's functions, and
+    /// every function of a script sees the variables of its own script, including promise
+    /// callbacks called after the call that created them has returned.
+    Environment,
+    /// The variables are set as globals before every call of a script function and cleared
+    /// once it returns, so functions called at other times, like promise callbacks, don't see
+    /// them. Scripts sharing a Lua state share their globals.
+    #[default]
+    Global,
+}
+
+/// Decides which Lua standard libraries get loaded into Lua states.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LuaStdLib {
+    /// All libraries that mlua considers safe, which includes `io` and `os`.
+    #[default]
+    All,
+    /// Libraries that give no access to the file system and the operating system, for
+    /// sandboxed mods. `io` and `os` libraries are not loaded, `dofile`, `loadfile` and
+    /// `string.dump` functions are removed, and `load` only accepts source code, not bytecode.
+    /// `require` still loads modules among script assets.
+    Safe,
+    /// Provided set of libraries. It can't include libraries that mlua considers unsafe, such
+    /// as `debug`, and has to include `package` for `require` to work.
+    Custom(mlua::StdLib),
+}
+
+impl LuaStdLib {
+    fn libraries(self) -> mlua::StdLib {
+        match self {
+            LuaStdLib::All => mlua::StdLib::ALL_SAFE,
+            #[cfg(not(feature = "luau"))]
+            LuaStdLib::Safe => mlua::StdLib::ALL_SAFE ^ mlua::StdLib::IO ^ mlua::StdLib::OS,
+            #[cfg(feature = "luau")]
+            LuaStdLib::Safe => mlua::StdLib::ALL_SAFE ^ mlua::StdLib::OS,
+            LuaStdLib::Custom(libraries) => libraries,
+        }
+    }
+}
+
+/// Settings used to create [LuaRuntime]. Insert it as a resource before calling `add_scripting`.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LuaRuntimeSettings {
+    pub isolation: IsolationMode,
+    /// How the `entity` and `script` variables get exposed to scripts.
+    pub variables: ScriptVariables,
+    /// Standard libraries loaded into Lua states.
+    pub stdlib: LuaStdLib,
+    /// Turns LuaJIT's JIT compiler off, running all scripts in its interpreter.
+    #[cfg(feature = "lua")]
+    pub disable_jit: bool,
+    /// Enables Luau sandboxing, which makes built-in libraries and globals set up by the
+    /// runtime read-only, so that scripts can only add globals of their own.
+    #[cfg(feature = "luau")]
+    pub sandbox: bool,
 }
 
+/// A runtime running Lua scripts.
+///
+/// Lua states are kept behind mutexes, which the runtime locks for the duration of every
+/// evaluation and call. These happen in systems, on threads that run the runtime's schedule or,
+/// with [ScriptingSettings::parallel_eval](crate::ScriptingSettings::parallel_eval), on
+/// [ComputeTaskPool](bevy::tasks::ComputeTaskPool) threads. Holding a state locked from another
+/// thread stalls them, and Lua values like [LuaValue] can only be used with the state they have
+/// been created in, so threads that need to call scripts, like audio callbacks or network tasks,
+/// should queue calls with a [ScriptCallSender](crate::ScriptCallSender) instead, see
+/// [call_queue](crate::call_queue).
 #[derive(Resource)]
 pub struct LuaRuntime {
     engine: LuaEngine,
+    settings: LuaRuntimeSettings,
+    /// Bytecode of evaluated scripts, loaded instead of compiling their sources again.
+    cache: Option<CompiledScriptCache<Self>>,
+    functions: Vec<HostFunction>,
+    constants: Vec<(String, LuaValue)>,
+    entity_engines: Mutex<Vec<Weak<Mutex<Lua>>>>,
+    /// Namespaces and names of functions registered since [Runtime::start_registering_fns] got
+    /// called, which get installed once [Runtime::finish_registering_fns] gets called.
+    batch: Option<HashSet<(Option<String>, String)>>,
+    rng: Option<ScriptRng>,
+    /// Name of the variable holding the entity that the running script is attached to, if it
+    /// is exposed to scripts.
+    entity_variable: Option<String>,
+    #[cfg(all(feature = "debugger", not(feature = "luau")))]
+    debugger: Option<crate::debugger::ScriptDebugger>,
+}
+
+/// Identifies the Lua state a function has been called from.
+#[derive(Clone)]
+pub struct LuaCallContext {
+    engine: Weak<Mutex<Lua>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -72,51 +276,593 @@ impl FromLua<'_> for BevyVec3 {
     }
 }
 
-impl Default for LuaRuntime {
-    fn default() -> Self {
-        let engine = LuaEngine::default();
+impl From<mlua::Error> for ScriptingError {
+    fn from(error: mlua::Error) -> Self {
+        match error {
+            mlua::Error::FromLuaConversionError { from, to, .. } => ScriptingError::TypeMismatch {
+                expected: to.to_string(),
+                got: from.to_string(),
+            },
+            mlua::Error::ToLuaConversionError { from, to, .. } => ScriptingError::TypeMismatch {
+                expected: to.to_string(),
+                got: from.to_string(),
+            },
+            mlua::Error::PreviouslyResumedPanic => {
+                ScriptingError::ScriptPanicked(error.to_string())
+            }
+            _ => ScriptingError::RuntimeError(Box::new(error)),
+        }
+    }
+}
 
-        {
-            let engine = engine.lock().expect("Failed to lock engine");
-            engine
-                .register_userdata_type::<BevyEntity>(|typ| {
-                    typ.add_field_method_get("index", |_, entity| Ok(entity.0.index()));
-                })
-                .expect("Failed to register BevyEntity userdata type");
+/// Returns a diagnostic of a Lua syntax error with provided message, which has the form of
+/// `<chunk name>:<line>: <message>`, in provided script.
+fn syntax_error_diagnostic(message: &str, path: &str, source: &str) -> ScriptDiagnostic {
+    // Chunk names can be truncated or contain colons, so the line is looked for as the first
+    // number delimited by colons.
+    let location = message.match_indices(':').find_map(|(start, _)| {
+        let rest = &message[start + 1..];
+        let end = rest.find(':')?;
+        let line = rest[..end].parse::<usize>().ok()?;
+        Some((line, rest[end + 1..].trim_start()))
+    });
+    match location {
+        Some((line, message)) => ScriptDiagnostic::new(path, Some(line), None, message, source),
+        None => ScriptDiagnostic::new(path, None, None, message, source),
+    }
+}
 
+/// Creates a Lua state with standard libraries selected in provided settings.
+fn new_engine(settings: &LuaRuntimeSettings) -> LuaEngine {
+    let engine = Lua::new_with(settings.stdlib.libraries(), mlua::LuaOptions::default())
+        .expect("Failed to create Lua state, unsafe standard libraries can't be loaded");
+    if settings.stdlib == LuaStdLib::Safe {
+        for name in ["dofile", "loadfile"] {
             engine
-                .register_userdata_type::<Promise<(), LuaValue>>(|typ| {
-                    typ.add_method_mut("and_then", |engine, promise, callback: Function| {
-                        Ok(Promise::then(promise, LuaValue::new(engine, callback)))
-                    });
-                })
-                .expect("Failed to register Promise userdata type");
+                .globals()
+                .set(name, mlua::Value::Nil)
+                .expect("Failed to remove file loading function");
+        }
+    }
+    Arc::new(Mutex::new(engine))
+}
 
-            engine
-                .register_userdata_type::<BevyVec3>(|typ| {
-                    typ.add_field_method_get("x", |_engine, vec| Ok(vec.0.x));
-                    typ.add_field_method_get("y", |_engine, vec| Ok(vec.0.y));
-                    typ.add_field_method_get("z", |_engine, vec| Ok(vec.0.z));
+fn init_engine(engine: &Lua, settings: &LuaRuntimeSettings) {
+    engine
+        .register_userdata_type::<BevyEntity>(|typ| {
+            typ.add_field_method_get("index", |_, entity| Ok(entity.0.index()));
+        })
+        .expect("Failed to register BevyEntity userdata type");
+
+    engine
+        .register_userdata_type::<Promise<LuaCallContext, LuaValue>>(|typ| {
+            typ.add_method_mut("and_then", |engine, promise, callback: Function| {
+                Ok(Promise::then(promise, LuaValue::new(engine, callback)))
+            });
+            typ.add_method_mut("cancel", |_, promise, ()| {
+                promise.cancel();
+                Ok(())
+            });
+            typ.add_method_mut("timeout", |_, promise, seconds: f64| {
+                Duration::try_from_secs_f64(seconds)
+                    .map(|duration| promise.timeout(duration))
+                    .map_err(mlua::Error::external)
+            });
+        })
+        .expect("Failed to register Promise userdata type");
+
+    engine
+        .register_userdata_type::<BevyVec3>(|typ| {
+            typ.add_field_method_get("x", |_engine, vec| Ok(vec.0.x));
+            typ.add_field_method_get("y", |_engine, vec| Ok(vec.0.y));
+            typ.add_field_method_get("z", |_engine, vec| Ok(vec.0.z));
+        })
+        .expect("Failed to register BevyVec3 userdata type");
+    let vec3_constructor = engine
+        .create_function(|_, (x, y, z)| Ok(BevyVec3(Vec3::new(x, y, z))))
+        .expect("Failed to create Vec3 constructor");
+    engine
+        .globals()
+        .set("Vec3", vec3_constructor)
+        .expect("Failed to set Vec3 global");
+
+    let log = create_log_table(engine, None).expect("Failed to create log table");
+    engine
+        .globals()
+        .set("log", log)
+        .expect("Failed to set log global");
+
+    backend::configure(engine, settings).expect("Failed to configure Lua state");
+}
+
+/// Creates the table of logging functions, which attribute messages to the script with provided
+/// path, or to the script described by the `script` global variable if `None`.
+fn create_log_table(engine: &Lua, path: Option<String>) -> mlua::Result<Table<'_>> {
+    let log = engine.create_table()?;
+    for (name, level) in LOG_FUNCTIONS {
+        let path = path.clone();
+        let function = engine.create_function(move |engine, message: String| {
+            let global_path;
+            let path = match &path {
+                Some(path) => Some(path.as_str()),
+                None => {
+                    global_path = engine
+                        .globals()
+                        .get::<_, Option<Table>>(SCRIPT_VAR_NAME)?
+                        .map(|script| script.get::<_, Option<String>>("path"))
+                        .transpose()?
+                        .flatten();
+                    global_path.as_deref()
+                }
+            };
+            log_message(level, path, &message);
+            Ok(())
+        })?;
+        log.raw_set(name, function)?;
+    }
+    Ok(log)
+}
+
+/// Registers host functions as globals within provided Lua state, or as fields of global tables
+/// named after their namespaces, locking the state once for all of them.
+fn install_functions<'a>(
+    engine: &LuaEngine,
+    functions: impl IntoIterator<Item = &'a HostFunction>,
+) -> Result<(), ScriptingError> {
+    let context = LuaCallContext {
+        engine: Arc::downgrade(engine),
+    };
+    let engine = engine.lock().expect("Failed to lock engine");
+    for function in functions {
+        install_function(&engine, &context, function)?;
+    }
+    Ok(())
+}
+
+/// Registers a host function as a global within provided Lua state, or as a field of a global
+/// table named after its namespace.
+fn install_function(
+    engine: &Lua,
+    context: &LuaCallContext,
+    function: &HostFunction,
+) -> Result<(), ScriptingError> {
+    let context = context.clone();
+    let name = function.name.clone();
+    let overloads = function.overloads.clone();
+    let func = engine.create_function(move |engine, args: Variadic<mlua::Value>| {
+        let overload =
+            overloads::select(&name, &overloads, &args).map_err(mlua::Error::external)?;
+        // Arguments passed to functions without parameters are ignored, so they are not stored.
+        let args = if overload.arg_types.is_empty() {
+            Vec::new()
+        } else {
+            args.into_iter().map(|x| LuaValue::new(engine, x)).collect()
+        };
+        (overload.f)(context.clone(), args).map_err(mlua::Error::external)
+    })?;
+    let globals = engine.globals();
+    let table = match function.namespace.as_deref() {
+        Some(namespace) => match globals.get::<_, Option<Table>>(namespace)? {
+            Some(table) => table,
+            None => {
+                let table = engine.create_table()?;
+                globals.set(namespace, table.clone())?;
+                table
+            }
+        },
+        None => globals,
+    };
+    table.set(function.name.as_str(), func)?;
+    Ok(())
+}
+
+/// Removes a host function installed with [install_functions] from provided Lua state.
+fn uninstall_function(
+    engine: &LuaEngine,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<(), ScriptingError> {
+    let engine = engine.lock().expect("Failed to lock engine");
+    let globals = engine.globals();
+    let table = match namespace {
+        Some(namespace) => match globals.get::<_, Option<Table>>(namespace)? {
+            Some(table) => table,
+            None => return Ok(()),
+        },
+        None => globals,
+    };
+    table.set(name, mlua::Value::Nil)?;
+    Ok(())
+}
+
+/// Replaces random number functions of provided Lua state with ones drawing from provided
+/// generator and disables functions reading wall-clock time.
+fn install_deterministic(engine: &Lua, rng: &ScriptRng) -> mlua::Result<()> {
+    let math = engine.globals().get::<_, mlua::Table>("math")?;
+    let random_rng = rng.clone();
+    let random = engine.create_function(move |engine, (m, n): (Option<i64>, Option<i64>)| {
+        let (min, max) = match (m, n) {
+            (None, _) => return Ok(mlua::Value::Number(random_rng.next_f64())),
+            (Some(max), None) => (1, max),
+            (Some(min), Some(max)) => (min, max),
+        };
+        if min > max {
+            return Err(mlua::Error::RuntimeError(String::from(
+                "bad argument to 'random' (interval is empty)",
+            )));
+        }
+        random_rng.next_in_range(min, max).into_lua(engine)
+    })?;
+    math.set("random", random)?;
+    let seed_rng = rng.clone();
+    let randomseed = engine.create_function(move |_, seed: mlua::Integer| {
+        seed_rng.reseed(seed as u64);
+        Ok(())
+    })?;
+    math.set("randomseed", randomseed)?;
+
+    if let Ok(os) = engine.globals().get::<_, mlua::Table>("os") {
+        for name in ["time", "clock", "date"] {
+            let disabled = engine.create_function(move |_, ()| -> mlua::Result<()> {
+                Err(mlua::Error::RuntimeError(format!(
+                    "os.{name} is disabled in deterministic mode"
+                )))
+            })?;
+            os.set(name, disabled)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies a value from one Lua state to another. Only plain data, tables and builtin
+/// userdata types can be copied.
+fn copy_value<'to>(value: mlua::Value, to: &'to Lua) -> mlua::Result<mlua::Value<'to>> {
+    Ok(match value {
+        mlua::Value::Nil => mlua::Value::Nil,
+        mlua::Value::Boolean(value) => mlua::Value::Boolean(value),
+        mlua::Value::Integer(value) => mlua::Value::Integer(value),
+        mlua::Value::Number(value) => mlua::Value::Number(value),
+        mlua::Value::String(value) => mlua::Value::String(to.create_string(value.as_bytes())?),
+        mlua::Value::Table(table) => {
+            let copy = to.create_table()?;
+            for pair in table.pairs::<mlua::Value, mlua::Value>() {
+                let (key, value) = pair?;
+                copy.raw_set(copy_value(key, to)?, copy_value(value, to)?)?;
+            }
+            mlua::Value::Table(copy)
+        }
+        mlua::Value::UserData(ud) if ud.is::<BevyEntity>() => {
+            BevyEntity(ud.borrow::<BevyEntity>()?.0).into_lua(to)?
+        }
+        mlua::Value::UserData(ud) if ud.is::<BevyVec3>() => {
+            BevyVec3(ud.borrow::<BevyVec3>()?.0).into_lua(to)?
+        }
+        value => {
+            return Err(mlua::Error::RuntimeError(format!(
+                "value of type {} cannot be copied between Lua states",
+                value.type_name()
+            )))
+        }
+    })
+}
+
+/// Formats a value in a stable, human readable form. Array parts of tables are listed first,
+/// followed by remaining entries sorted by their formatted keys.
+fn format_lua_value(value: &mlua::Value) -> String {
+    match value {
+        mlua::Value::Nil => "nil".to_string(),
+        mlua::Value::Boolean(value) => value.to_string(),
+        mlua::Value::Integer(value) => value.to_string(),
+        mlua::Value::Number(value) if value.fract() == 0.0 && value.is_finite() => {
+            format!("{}", *value as i64)
+        }
+        mlua::Value::Number(value) => value.to_string(),
+        mlua::Value::String(value) => format!("{:?}", value.to_string_lossy()),
+        mlua::Value::Table(table) => {
+            let length = table.raw_len();
+            let mut entries = (1..=length)
+                .map(|i| {
+                    table
+                        .raw_get::<_, mlua::Value>(i)
+                        .map(|value| format_lua_value(&value))
+                        .unwrap_or_default()
                 })
-                .expect("Failed to register BevyVec3 userdata type");
-            let vec3_constructor = engine
-                .create_function(|_, (x, y, z)| Ok(BevyVec3(Vec3::new(x, y, z))))
-                .expect("Failed to create Vec3 constructor");
-            engine
-                .globals()
-                .set("Vec3", vec3_constructor)
-                .expect("Failed to set Vec3 global");
+                .collect::<Vec<_>>();
+            let mut other = table
+                .clone()
+                .pairs::<mlua::Value, mlua::Value>()
+                .filter_map(Result::ok)
+                .filter(|(key, _)| !matches!(key, mlua::Value::Integer(i) if (1..=length as mlua::Integer).contains(i)))
+                .map(|(key, value)| {
+                    let key = match key {
+                        mlua::Value::String(key) => key.to_string_lossy().to_string(),
+                        key => format!("[{}]", format_lua_value(&key)),
+                    };
+                    format!("{key} = {}", format_lua_value(&value))
+                })
+                .collect::<Vec<_>>();
+            other.sort();
+            entries.append(&mut other);
+            format!("{{{}}}", entries.join(", "))
         }
+        mlua::Value::UserData(ud) if ud.is::<BevyEntity>() => ud
+            .borrow::<BevyEntity>()
+            .map(|entity| format!("Entity({})", entity.0))
+            .unwrap_or_default(),
+        mlua::Value::UserData(ud) if ud.is::<BevyVec3>() => ud
+            .borrow::<BevyVec3>()
+            .map(|vec| format!("Vec3({}, {}, {})", vec.0.x, vec.0.y, vec.0.z))
+            .unwrap_or_default(),
+        value => value.type_name().to_string(),
+    }
+}
+
+/// Sets global variables describing the script that is about to run.
+fn set_script_globals(
+    engine: &Lua,
+    entity_variable: Option<&str>,
+    entity: Entity,
+    info: &ScriptInfo,
+) -> mlua::Result<()> {
+    if let Some(name) = entity_variable {
+        engine.globals().set(name, BevyEntity(entity))?;
+    }
+    engine
+        .globals()
+        .set(SCRIPT_VAR_NAME, script_info_value(info))
+}
 
-        Self { engine }
+/// Clears global variables set with [set_script_globals].
+fn clear_script_globals(engine: &Lua, entity_variable: Option<&str>) -> mlua::Result<()> {
+    if let Some(name) = entity_variable {
+        engine.globals().set(name, mlua::Value::Nil)?;
+    }
+    engine.globals().set(SCRIPT_VAR_NAME, mlua::Value::Nil)
+}
+
+/// Returns a table whose lookups fall back to provided table.
+fn fallback_metatable<'lua>(engine: &'lua Lua, fallback: Table<'lua>) -> mlua::Result<Table<'lua>> {
+    let metatable = engine.create_table()?;
+    metatable.raw_set("__index", fallback)?;
+    Ok(metatable)
+}
+
+/// Returns the table holding global variables of provided script, which is its environment
+/// with [ScriptVariables::Environment].
+fn script_globals<'lua>(
+    engine: &'lua Lua,
+    script_data: &LuaScriptData,
+) -> mlua::Result<Table<'lua>> {
+    match &script_data.env {
+        Some(env) => engine.registry_value(&env.0),
+        None => Ok(engine.globals()),
+    }
+}
+
+/// Returns a function defined as a global variable with provided name.
+fn global_function<'lua>(
+    globals: &Table<'lua>,
+    name: &str,
+) -> Result<Function<'lua>, ScriptingError> {
+    match globals
+        .get::<_, mlua::Value>(name)
+        .map_err(ScriptingError::from)?
+    {
+        mlua::Value::Function(func) => Ok(func),
+        mlua::Value::Nil => Err(ScriptingError::FunctionNotFound(name.to_string())),
+        value => Err(ScriptingError::TypeMismatch {
+            expected: String::from("function"),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+impl LuaRuntime {
+    /// Creates a new runtime using provided settings.
+    pub fn with_settings(settings: LuaRuntimeSettings) -> Self {
+        let engine = new_engine(&settings);
+        init_engine(&engine.lock().expect("Failed to lock engine"), &settings);
+
+        Self {
+            engine,
+            settings,
+            cache: None,
+            functions: Vec::new(),
+            constants: Vec::new(),
+            entity_engines: Mutex::new(Vec::new()),
+            batch: None,
+            rng: None,
+            entity_variable: Some(ENTITY_VAR_NAME.to_string()),
+            #[cfg(all(feature = "debugger", not(feature = "luau")))]
+            debugger: None,
+        }
+    }
+
+    /// Provides reference to the Lua state that provided script has been evaluated in.
+    /// With [IsolationMode::Shared] this is the same state as [Runtime::with_engine] provides.
+    pub fn with_script_engine<T>(
+        &self,
+        script_data: &LuaScriptData,
+        f: impl FnOnce(&Lua) -> T,
+    ) -> T {
+        match &script_data.engine {
+            Some(engine) => f(&engine.lock().expect("Failed to lock engine")),
+            None => self.with_engine(f),
+        }
+    }
+
+    /// Creates the environment that a script gets evaluated in with
+    /// [ScriptVariables::Environment]. The variables are held by a separate table that the
+    /// environment falls back to, so that they are not listed among variables of the script,
+    /// along with logging functions attributing messages to the script, as the `script` global
+    /// variable is not set.
+    fn create_script_env<'lua>(
+        &self,
+        engine: &'lua Lua,
+        entity: Entity,
+        info: &ScriptInfo,
+    ) -> mlua::Result<Table<'lua>> {
+        let variables = engine.create_table()?;
+        if let Some(name) = &self.entity_variable {
+            variables.raw_set(name.as_str(), BevyEntity(entity))?;
+        }
+        variables.raw_set(SCRIPT_VAR_NAME, script_info_value(info))?;
+        variables.raw_set("log", create_log_table(engine, Some(info.path.clone()))?)?;
+        variables.set_metatable(Some(fallback_metatable(engine, engine.globals())?));
+        let env = engine.create_table()?;
+        env.set_metatable(Some(fallback_metatable(engine, variables)?));
+        Ok(env)
+    }
+
+    /// Calls provided function with the variables of provided script set as globals, unless the
+    /// script has its own environment holding them, and with `require` finding modules of the
+    /// script.
+    fn with_script_variables<T>(
+        &self,
+        engine: &Lua,
+        entity: Entity,
+        script_data: &LuaScriptData,
+        f: impl FnOnce() -> T,
+    ) -> mlua::Result<T> {
+        let previous = modules::enter_script(engine, script_data);
+        let result = if script_data.env.is_some() {
+            Ok(f())
+        } else {
+            let entity_variable = self.entity_variable.as_deref();
+            set_script_globals(engine, entity_variable, entity, &script_data.info).and_then(|()| {
+                let result = f();
+                clear_script_globals(engine, entity_variable)?;
+                Ok(result)
+            })
+        };
+        modules::exit_script(engine, previous)?;
+        result
+    }
+
+    /// Provides reference to the Lua state that provided script has been evaluated in, along
+    /// with the table holding global variables of the script. With
+    /// [ScriptVariables::Environment] this is the environment of the script, whose lookups fall
+    /// back to globals of the Lua state, otherwise it is the table of globals of the Lua state.
+    pub fn with_script_globals<T>(
+        &self,
+        script_data: &LuaScriptData,
+        f: impl FnOnce(&Lua, Table) -> T,
+    ) -> T {
+        self.with_script_engine(script_data, |engine| {
+            let globals =
+                script_globals(engine, script_data).expect("Error getting script globals");
+            f(engine, globals)
+        })
+    }
+
+    /// Runs provided script source in provided Lua state, loading its bytecode from the
+    /// [CompiledScriptCache] if it has been compiled for the script with provided asset id before.
+    /// The script runs in provided environment, or in the global one if `None`.
+    fn exec_script(
+        &self,
+        engine: &Lua,
+        env: Option<&Table>,
+        source: &str,
+        path: &str,
+        asset_id: &str,
+    ) -> mlua::Result<()> {
+        let name = format!("@{path}");
+        let exec = |chunk: mlua::Chunk| match env {
+            Some(env) => chunk.set_environment(env.clone()).exec(),
+            None => chunk.exec(),
+        };
+        let Some(cache) = &self.cache else {
+            return exec(engine.load(source).set_name(name));
+        };
+        let hash = SourceHasher::default()
+            .update(path)
+            .update(source)
+            .finalize();
+        let bytecode = cache.get_or_compile(asset_id, hash, || {
+            #[cfg(not(feature = "luau"))]
+            let bytecode = engine
+                .load(source)
+                .set_name(name.as_str())
+                .into_function()?
+                .dump(false);
+            // Luau reports syntax errors once the bytecode gets loaded.
+            #[cfg(feature = "luau")]
+            let bytecode = mlua::Compiler::new().compile(source);
+            Ok::<_, mlua::Error>(Arc::<[u8]>::from(bytecode))
+        })?;
+        exec(engine.load(&*bytecode).set_name(name))
+    }
+
+    /// Installs provided host functions in the shared Lua state and in Lua states of all
+    /// scripted entities.
+    fn install_in_all_engines(&self, functions: &[&HostFunction]) -> Result<(), ScriptingError> {
+        install_functions(&self.engine, functions.iter().copied())?;
+        let entity_engines = self
+            .entity_engines
+            .lock()
+            .expect("Failed to lock entity engines")
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        for engine in entity_engines {
+            install_functions(&engine, functions.iter().copied())?;
+        }
+        Ok(())
+    }
+
+    /// Creates a new Lua state for a scripted entity, with all registered functions
+    /// and constants copied into it.
+    fn create_entity_engine(&self) -> Result<LuaEngine, ScriptingError> {
+        let engine = new_engine(&self.settings);
+        init_engine(
+            &engine.lock().expect("Failed to lock engine"),
+            &self.settings,
+        );
+        install_functions(&engine, &self.functions)?;
+        if let Some(rng) = &self.rng {
+            install_deterministic(&engine.lock().expect("Failed to lock engine"), rng)?;
+        }
+        #[cfg(all(feature = "debugger", not(feature = "luau")))]
+        if let Some(debugger) = &self.debugger {
+            debugger::install_debugger(&engine.lock().expect("Failed to lock engine"), debugger);
+        }
+        self.with_engine(|shared| {
+            let entity_engine = engine.lock().expect("Failed to lock engine");
+            for (name, value) in &self.constants {
+                let value = shared.registry_value::<mlua::Value>(&value.0)?;
+                entity_engine
+                    .globals()
+                    .set(name.as_str(), copy_value(value, &entity_engine)?)?;
+            }
+            Ok::<_, mlua::Error>(())
+        })?;
+        let mut entity_engines = self
+            .entity_engines
+            .lock()
+            .expect("Failed to lock entity engines");
+        entity_engines.retain(|engine| engine.strong_count() > 0);
+        entity_engines.push(Arc::downgrade(&engine));
+        Ok(engine)
+    }
+}
+
+impl FromWorld for LuaRuntime {
+    fn from_world(world: &mut World) -> Self {
+        let settings = world
+            .get_resource::<LuaRuntimeSettings>()
+            .cloned()
+            .unwrap_or_default();
+        Self::with_settings(settings)
     }
 }
 
 #[derive(ScheduleLabel, Clone, PartialEq, Eq, Debug, Hash, Default)]
 pub struct LuaSchedule;
 
+/// A Lua script asset. Besides the source of the script it holds names and sources of modules
+/// that the script can `require`.
 #[derive(Asset, Debug, Deserialize, TypePath)]
-pub struct LuaScript(pub String);
+pub struct LuaScript(pub String, #[serde(skip)] pub(crate) Vec<(String, String)>);
 
 impl GetExtensions for LuaScript {
     fn extensions() -> &'static [&'static str] {
@@ -124,14 +870,55 @@ impl GetExtensions for LuaScript {
     }
 }
 
+/// Makes modules required by a script with a constant name, e.g. `require("mod.utils")`,
+/// available to it. Modules are looked up in the directory of the script first, then in each of
+/// its parent directories up to the asset root, so scripts of a mod can require modules relative
+/// to the mod directory.
+impl ResolveImports for LuaScript {
+    fn imports(source: &str, path: &Path) -> Vec<(String, Vec<AssetPath<'static>>)> {
+        modules::imports(source, path)
+    }
+
+    fn with_imports(source: String, modules: Vec<(String, String)>) -> Self {
+        Self(source, modules)
+    }
+}
+
+/// Makes every script of a pack available through `require`, also with names computed at
+/// runtime, using the script name without `.lua` extension and with `/` replaced by `.` as
+/// module name, so `lib/util.lua` can be required as `lib.util`.
+impl ResolvePackModules for LuaScript {
+    fn resolve_pack_modules(source: String, modules: &[(String, String)]) -> Self {
+        let modules = modules
+            .iter()
+            .map(|(name, module)| (modules::pack_module_name(name), module.clone()))
+            .collect();
+        Self(source, modules)
+    }
+}
+
 impl From<String> for LuaScript {
     fn from(value: String) -> Self {
-        Self(value)
+        Self(value, Vec::new())
     }
 }
 
+/// A component that represents the data of a script. With [IsolationMode::PerEntity] it owns
+/// the Lua state that the script has been evaluated in.
 #[derive(Component)]
-pub struct LuaScriptData;
+pub struct LuaScriptData {
+    engine: Option<LuaEngine>,
+    info: ScriptInfo,
+    /// Environment the script has been evaluated in with [ScriptVariables::Environment].
+    env: Option<LuaValue>,
+    /// Modules that the script and its bases can `require`.
+    modules: Option<modules::ScriptModules>,
+    /// Chunk names of the script and its bases, which functions defined by them report as
+    /// their source.
+    chunks: Vec<String>,
+    /// Coroutine of a call paused by [Runtime::call_fn_resumable].
+    paused: Option<LuaValue>,
+}
 
 impl Runtime for LuaRuntime {
     type Schedule = LuaSchedule;
@@ -140,7 +927,8 @@ impl Runtime for LuaRuntime {
 
     type ScriptData = LuaScriptData;
 
-    type CallContext = ();
+    type CallContext = LuaCallContext;
+    type Snapshot = LuaSnapshot;
 
     type Value = LuaValue;
 
@@ -150,27 +938,80 @@ impl Runtime for LuaRuntime {
         &self,
         script: &Self::ScriptAsset,
         entity: bevy::prelude::Entity,
+        info: &ScriptInfo,
     ) -> Result<Self::ScriptData, crate::ScriptingError> {
-        self.with_engine(|engine| {
-            engine
-                .globals()
-                .set(ENTITY_VAR_NAME, BevyEntity(entity))
-                .expect("Error setting entity global variable");
-            let result = engine.load(&script.0).exec();
-            engine
-                .globals()
-                .set(ENTITY_VAR_NAME, mlua::Value::Nil)
-                .expect("Error clearing entity global variable");
-            result
-        })
-        .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
-        Ok(LuaScriptData)
+        self.eval_with_bases(&[], script, entity, info)
+    }
+
+    fn eval_with_bases(
+        &self,
+        bases: &[(&Self::ScriptAsset, String)],
+        script: &Self::ScriptAsset,
+        entity: bevy::prelude::Entity,
+        info: &ScriptInfo,
+    ) -> Result<Self::ScriptData, crate::ScriptingError> {
+        let mut script_data = LuaScriptData {
+            engine: match self.settings.isolation {
+                IsolationMode::Shared => None,
+                IsolationMode::PerEntity => Some(self.create_entity_engine()?),
+            },
+            info: info.clone(),
+            chunks: bases
+                .iter()
+                .map(|(_, path)| path.as_str())
+                .chain([info.path.as_str()])
+                .map(|path| format!("@{path}"))
+                .collect(),
+            env: None,
+            modules: None,
+            paused: None,
+        };
+        let sources = [script]
+            .into_iter()
+            .chain(bases.iter().map(|(base, _)| *base))
+            .flat_map(|script| script.1.iter().cloned())
+            .collect();
+        script_data.modules = self.with_script_engine(&script_data, |engine| {
+            modules::ScriptModules::new(engine, sources)
+        })?;
+        if self.settings.variables == ScriptVariables::Environment {
+            script_data.env = Some(self.with_script_engine(&script_data, |engine| {
+                let env = self.create_script_env(engine, entity, info)?;
+                Ok::<_, mlua::Error>(LuaValue::new(engine, env))
+            })?);
+        }
+        let _entity_guard = enter_entity(Some(entity));
+        self.with_script_engine(&script_data, |engine| {
+            let env = match &script_data.env {
+                Some(env) => Some(engine.registry_value::<Table>(&env.0)?),
+                None => None,
+            };
+            self.with_script_variables(engine, entity, &script_data, || {
+                bases
+                    .iter()
+                    .map(|(base, path)| (*base, path.as_str()))
+                    .chain([(script, info.path.as_str())])
+                    .try_for_each(|(script, path)| {
+                        self.exec_script(engine, env.as_ref(), &script.0, path, &info.asset_id)
+                            .map_err(|e| match e {
+                                mlua::Error::SyntaxError { message, .. } => {
+                                    ScriptingError::CompileError(syntax_error_diagnostic(
+                                        &message, path, &script.0,
+                                    ))
+                                }
+                                e => ScriptingError::RuntimeError(Box::new(e)),
+                            })
+                    })
+            })?
+        })?;
+        Ok(script_data)
     }
 
     fn register_fn(
         &mut self,
+        namespace: Option<&str>,
         name: String,
-        _arg_types: Vec<std::any::TypeId>,
+        arg_types: Vec<std::any::TypeId>,
         f: impl Fn(
                 Self::CallContext,
                 Vec<Self::Value>,
@@ -181,70 +1022,366 @@ impl Runtime for LuaRuntime {
             + Sync
             + 'static,
     ) -> Result<(), crate::ScriptingError> {
+        let overload = Overload {
+            arg_types,
+            f: Arc::new(f),
+        };
+        let namespace = namespace.map(ToString::to_string);
+        let index = match self
+            .functions
+            .iter()
+            .position(|function| function.namespace == namespace && function.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.functions.push(HostFunction {
+                    namespace,
+                    name,
+                    overloads: Arc::default(),
+                });
+                self.functions.len() - 1
+            }
+        };
+        let overloads = Arc::make_mut(&mut self.functions[index].overloads);
+        // Registering a function with the same parameter types replaces it.
+        match overloads
+            .iter_mut()
+            .find(|existing| existing.arg_types == overload.arg_types)
+        {
+            Some(existing) => *existing = overload,
+            None => overloads.push(overload),
+        }
+        if let Some(batch) = &mut self.batch {
+            batch.insert((
+                self.functions[index].namespace.clone(),
+                self.functions[index].name.clone(),
+            ));
+            return Ok(());
+        }
+        self.install_in_all_engines(&[&self.functions[index]])
+    }
+
+    fn start_registering_fns(&mut self) {
+        self.batch.get_or_insert_with(HashSet::new);
+    }
+
+    fn finish_registering_fns(&mut self) -> Result<(), ScriptingError> {
+        let Some(batch) = self.batch.take() else {
+            return Ok(());
+        };
+        let functions = self
+            .functions
+            .iter()
+            .filter(|function| batch.contains(&(function.namespace.clone(), function.name.clone())))
+            .collect::<Vec<_>>();
+        self.install_in_all_engines(&functions)
+    }
+
+    fn unregister_fn(
+        &mut self,
+        namespace: Option<&str>,
+        name: &str,
+    ) -> Result<(), crate::ScriptingError> {
+        if let Some(batch) = &mut self.batch {
+            batch.retain(|(batched_namespace, batched_name)| {
+                batched_namespace.as_deref() != namespace || batched_name != name
+            });
+        }
+        self.functions
+            .retain(|function| function.namespace.as_deref() != namespace || function.name != name);
+        uninstall_function(&self.engine, namespace, name)?;
+        let entity_engines = self
+            .entity_engines
+            .lock()
+            .expect("Failed to lock entity engines")
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        for engine in entity_engines {
+            uninstall_function(&engine, namespace, name)?;
+        }
+        Ok(())
+    }
+
+    fn register_constant(
+        &mut self,
+        name: String,
+        value: Self::Value,
+    ) -> Result<(), ScriptingError> {
         self.with_engine(|engine| {
-            let func = engine
-                .create_function(move |engine, args: Variadic<mlua::Value>| {
-                    let args = { args.into_iter().map(|x| LuaValue::new(engine, x)).collect() };
-                    let result = f((), args).unwrap();
-                    Ok(result)
-                })
-                .unwrap();
+            let value = engine
+                .registry_value::<mlua::Value>(&value.0)
+                .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
             engine
                 .globals()
-                .set(name, func)
-                .expect("Error registering function in global lua scope");
-        });
+                .set(name.as_str(), value)
+                .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))
+        })?;
+        self.constants.push((name, value));
         Ok(())
     }
 
     fn call_fn(
         &self,
         name: &str,
-        _script_data: &mut Self::ScriptData,
+        script_data: &mut Self::ScriptData,
         entity: bevy::prelude::Entity,
         args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
     ) -> Result<Self::Value, crate::ScriptingError> {
-        self.with_engine(|engine| {
-            engine
-                .globals()
-                .set(ENTITY_VAR_NAME, BevyEntity(entity))
-                .expect("Error setting entity global variable");
-            let func = engine
-                .globals()
-                .get::<_, Function>(name)
-                .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
-            let args = args
-                .parse(engine)
-                .into_iter()
-                .map(|a| engine.registry_value::<mlua::Value>(&a.0).unwrap());
-            let result = func
-                .call::<_, mlua::Value>(Variadic::from_iter(args))
-                .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
-            engine
-                .globals()
-                .set(ENTITY_VAR_NAME, mlua::Value::Nil)
-                .expect("Error clearing entity global variable");
-            Ok(LuaValue::new(engine, result))
+        let _entity_guard = enter_entity(Some(entity));
+        self.with_script_engine(script_data, |engine| {
+            let func = global_function(&script_globals(engine, script_data)?, name)?;
+            let args = unpack_args(engine, args.parse(engine))?;
+            let result = self
+                .with_script_variables(engine, entity, script_data, || {
+                    func.call::<_, MultiValue>(args)
+                })?
+                .map_err(ScriptingError::from)?;
+            Ok(LuaValue::from_multi(engine, result)?)
+        })
+    }
+
+    fn has_fn(&self, name: &str, script_data: &Self::ScriptData) -> bool {
+        self.with_script_engine(script_data, |engine| {
+            script_globals(engine, script_data)
+                .is_ok_and(|globals| global_function(&globals, name).is_ok())
+        })
+    }
+
+    fn set_entity_variable(&mut self, name: Option<String>) {
+        self.entity_variable = name;
+    }
+
+    /// Scans global variables of the script for Lua functions created by chunks of the script
+    /// or its bases, which leaves out host functions and, with [ScriptVariables::Global] and
+    /// [IsolationMode::Shared], functions of other scripts.
+    fn list_functions(&self, script_data: &Self::ScriptData) -> Vec<String> {
+        self.with_script_engine(script_data, |engine| {
+            let Ok(globals) = script_globals(engine, script_data) else {
+                return Vec::new();
+            };
+            let mut names = globals
+                .pairs::<String, mlua::Value>()
+                .filter_map(Result::ok)
+                .filter_map(|(name, value)| match value {
+                    mlua::Value::Function(func) => {
+                        let source = func.info().source?;
+                        script_data.chunks.contains(&source).then_some(name)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        })
+    }
+
+    /// Runs the function in a coroutine yielding from a count hook once the budget is used up.
+    /// Count hooks are not called from JIT-compiled code, so JIT compilation of the function
+    /// and functions nested in it gets disabled, while loops in other functions it calls may
+    /// run without pausing. Since hooks are global in LuaJIT, a paused call does not stop at
+    /// debugger breakpoints.
+    fn call_fn_resumable(
+        &self,
+        name: &str,
+        script_data: &mut Self::ScriptData,
+        entity: bevy::prelude::Entity,
+        args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+        budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        let _entity_guard = enter_entity(Some(entity));
+        let (thread, progress) = self.with_script_engine(script_data, |engine| {
+            let func = global_function(&script_globals(engine, script_data)?, name)?;
+            let thread = resumable::create_resumable(engine, func)?;
+            let args = unpack_args(engine, args.parse(engine))?;
+            let result = self.with_script_variables(engine, entity, script_data, || {
+                resumable::resume_with_budget(engine, &thread, args, budget)
+            })?;
+            Ok::<_, ScriptingError>((
+                LuaValue::new(engine, thread),
+                result?
+                    .map(|values| LuaValue::from_multi(engine, values))
+                    .transpose()?,
+            ))
+        })?;
+        Ok(match progress {
+            Some(value) => CallProgress::Finished(value),
+            None => {
+                script_data.paused = Some(thread);
+                CallProgress::Paused
+            }
         })
     }
 
+    fn resume_call(
+        &self,
+        script_data: &mut Self::ScriptData,
+        entity: bevy::prelude::Entity,
+        budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        let thread = script_data
+            .paused
+            .take()
+            .ok_or(ScriptingError::NoPausedCall)?;
+        let _entity_guard = enter_entity(Some(entity));
+        let progress = self.with_script_engine(script_data, |engine| {
+            let result = self.with_script_variables(engine, entity, script_data, || {
+                engine
+                    .registry_value::<Thread>(&thread.0)
+                    .and_then(|coroutine| {
+                        resumable::resume_with_budget(engine, &coroutine, (), budget)
+                    })
+            })?;
+            Ok::<_, ScriptingError>(
+                result?
+                    .map(|values| LuaValue::from_multi(engine, values))
+                    .transpose()?,
+            )
+        })?;
+        Ok(match progress {
+            Some(value) => CallProgress::Finished(value),
+            None => {
+                script_data.paused = Some(thread);
+                CallProgress::Paused
+            }
+        })
+    }
+
+    fn has_paused_call(&self, script_data: &Self::ScriptData) -> bool {
+        script_data.paused.is_some()
+    }
+
     fn call_fn_from_value(
         &self,
         value: &Self::Value,
-        _context: &Self::CallContext,
+        context: &Self::CallContext,
         args: Vec<Self::Value>,
     ) -> Result<Self::Value, crate::ScriptingError> {
-        self.with_engine(|engine| {
+        self.with_context_engine(context, |engine| {
             let val = engine
                 .registry_value::<Function>(&value.0)
                 .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
-            let args = args
-                .into_iter()
-                .map(|a| engine.registry_value::<mlua::Value>(&a.0).unwrap());
+            let args = unpack_args(engine, args)?;
             let result = val
-                .call::<_, mlua::Value>(Variadic::from_iter(args))
-                .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
-            Ok(LuaValue::new(engine, result))
+                .call::<_, MultiValue>(args)
+                .map_err(ScriptingError::from)?;
+            Ok(LuaValue::from_multi(engine, result)?)
+        })
+    }
+
+    fn set_compiled_script_cache(&mut self, cache: CompiledScriptCache<Self>) {
+        self.cache = Some(cache);
+    }
+
+    fn enable_deterministic_mode(&mut self, rng: ScriptRng) -> Result<(), ScriptingError> {
+        self.with_engine(|engine| install_deterministic(engine, &rng))?;
+        for engine in self
+            .entity_engines
+            .lock()
+            .expect("Failed to lock entity engines")
+            .iter()
+            .filter_map(Weak::upgrade)
+        {
+            install_deterministic(&engine.lock().expect("Failed to lock engine"), &rng)?;
+        }
+        self.rng = Some(rng);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "debugger", feature = "luau"))]
+    fn enable_debugger(
+        &mut self,
+        _debugger: crate::debugger::ScriptDebugger,
+    ) -> Result<(), ScriptingError> {
+        Err(ScriptingError::RuntimeError(
+            "Luau does not support debug hooks required by the debugger".into(),
+        ))
+    }
+
+    #[cfg(all(feature = "debugger", not(feature = "luau")))]
+    fn enable_debugger(
+        &mut self,
+        debugger: crate::debugger::ScriptDebugger,
+    ) -> Result<(), ScriptingError> {
+        self.with_engine(|engine| debugger::install_debugger(engine, &debugger));
+        for engine in self
+            .entity_engines
+            .lock()
+            .expect("Failed to lock entity engines")
+            .iter()
+            .filter_map(Weak::upgrade)
+        {
+            debugger::install_debugger(&engine.lock().expect("Failed to lock engine"), &debugger);
+        }
+        self.debugger = Some(debugger);
+        Ok(())
+    }
+
+    #[cfg(feature = "inspector")]
+    fn script_variables(
+        &self,
+        script_data: &Self::ScriptData,
+    ) -> Vec<crate::inspector::ScriptVariable> {
+        self.with_script_engine(script_data, |engine| {
+            script_globals(engine, script_data)
+                .and_then(inspector::variables)
+                .unwrap_or_default()
+        })
+    }
+
+    #[cfg(feature = "inspector")]
+    fn set_script_variable(
+        &self,
+        script_data: &mut Self::ScriptData,
+        name: &str,
+        value: crate::inspector::ScriptVariableValue,
+    ) -> Result<(), ScriptingError> {
+        self.with_script_engine(script_data, |engine| {
+            inspector::set_variable(script_globals(engine, script_data)?, name, value)
+                .map_err(ScriptingError::from)
+        })
+    }
+
+    /// Captures global variables of a script. With [ScriptVariables::Global] these are globals
+    /// of its Lua state, which with [IsolationMode::Shared] is shared by all scripts, so the
+    /// snapshot covers all of them.
+    fn snapshot_state(
+        &self,
+        script_data: &Self::ScriptData,
+    ) -> Result<Self::Snapshot, ScriptingError> {
+        self.with_script_engine(script_data, |engine| {
+            let globals = script_globals(engine, script_data)?;
+            snapshot::snapshot_globals(&globals).map_err(ScriptingError::from)
+        })
+    }
+
+    fn restore_state(
+        &self,
+        script_data: &mut Self::ScriptData,
+        snapshot: &Self::Snapshot,
+    ) -> Result<(), ScriptingError> {
+        self.with_script_engine(script_data, |engine| {
+            let globals = script_globals(engine, script_data)?;
+            snapshot::restore_globals(engine, &globals, snapshot).map_err(ScriptingError::from)
+        })
+    }
+
+    fn to_script_value(&self, value: &Self::Value, script_data: &Self::ScriptData) -> ScriptValue {
+        self.with_script_engine(script_data, |engine| value.get(engine).unwrap_or_default())
+    }
+
+    fn format_value(&self, value: &Self::Value, context: &Self::CallContext) -> String {
+        self.with_context_engine(context, |engine| {
+            value
+                .unpack(engine)
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(format_lua_value)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_else(|_| "?".to_string())
         })
     }
 
@@ -257,21 +1394,74 @@ impl Runtime for LuaRuntime {
         let engine = self.engine.lock().unwrap();
         f(&engine)
     }
+
+    fn with_context_engine<T>(
+        &self,
+        context: &Self::CallContext,
+        f: impl FnOnce(&Self::RawEngine) -> T,
+    ) -> T {
+        match context.engine.upgrade() {
+            Some(engine) => f(&engine.lock().expect("Failed to lock engine")),
+            None => self.with_engine(f),
+        }
+    }
 }
 
 impl<'a, T: IntoLuaMulti<'a>> IntoRuntimeValueWithEngine<'a, T, LuaRuntime> for T {
     fn into_runtime_value_with_engine(value: T, engine: &'a Lua) -> LuaValue {
-        let mut iter = value.into_lua_multi(engine).unwrap().into_iter();
-        if iter.len() > 1 {
-            unimplemented!("Returning multiple values from function");
-        }
-        LuaValue(Arc::new(engine.create_registry_value(iter.next()).unwrap()))
+        Self::try_into_runtime_value_with_engine(value, engine).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_into_runtime_value_with_engine(
+        value: T,
+        engine: &'a Lua,
+    ) -> Result<LuaValue, ScriptingError> {
+        // Conversion errors are kept whole, as [ScriptingError::TypeMismatch] would drop the
+        // reason, e.g. an integer being out of range.
+        let values = value
+            .into_lua_multi(engine)
+            .and_then(|values| value::check_integers(&values).map(|()| values))
+            .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
+        Ok(LuaValue::from_multi(engine, values)?)
     }
 }
 
-impl<'a, T: FromLua<'a>> FromRuntimeValueWithEngine<'a, LuaRuntime> for T {
-    fn from_runtime_value_with_engine(value: LuaValue, engine: &'a Lua) -> Self {
-        engine.registry_value(&value.0).unwrap()
+impl<'a, T: FromLuaMulti<'a>> FromRuntimeValueWithEngine<'a, LuaRuntime> for T {
+    fn from_runtime_value_with_engine(
+        value: LuaValue,
+        engine: &'a Lua,
+        _context: &LuaCallContext,
+    ) -> Self {
+        value.get(engine).unwrap()
+    }
+}
+
+impl<'lua, T: Serialize> IntoLua<'lua> for Json<T> {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<mlua::Value<'lua>> {
+        // Null values become nil rather than `null` light userdata, which is truthy.
+        let options = mlua::SerializeOptions::new()
+            .serialize_none_to_null(false)
+            .serialize_unit_to_null(false);
+        lua.to_value_with(&self.0, options)
+    }
+}
+
+impl<'lua, T: DeserializeOwned> FromLua<'lua> for Json<T> {
+    fn from_lua(value: mlua::Value<'lua>, lua: &'lua Lua) -> mlua::Result<Self> {
+        lua.from_value(value).map(Json)
+    }
+}
+
+impl EntityArgs for LuaRuntime {
+    type Args = (BevyEntity,);
+    type ScriptEntity = BevyEntity;
+
+    fn entity_args(entity: Entity) -> Self::Args {
+        (BevyEntity(entity),)
+    }
+
+    fn script_entity(entity: Self::ScriptEntity) -> Entity {
+        entity.0
     }
 }
 
@@ -287,49 +1477,16 @@ impl<'a, T: IntoLua<'a>> FuncArgs<'a, LuaValue, LuaRuntime> for Vec<T> {
     }
 }
 
-impl UserData for Promise<(), LuaValue> {}
+impl UserData for Promise<LuaCallContext, LuaValue> {}
 
 pub mod prelude {
-    pub use super::{BevyEntity, BevyVec3, LuaRuntime, LuaScript, LuaScriptData};
-}
-
-macro_rules! impl_tuple {
-    ($($idx:tt $t:tt),+) => {
-        impl<'a, $($t: IntoLua<'a>,)+> FuncArgs<'a, LuaValue, LuaRuntime>
-            for ($($t,)+)
-        {
-            fn parse(self, engine: &'a Lua) -> Vec<LuaValue> {
-                vec![
-                    $(LuaValue::new(engine, self.$idx), )+
-                ]
-            }
-        }
+    pub use super::{
+        BevyEntity, BevyVec3, IsolationMode, LuaRuntime, LuaRuntimeSettings, LuaScript,
+        LuaScriptData, LuaStdLib, ScriptVariables,
     };
 }
 
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W, 23 X, 24 Y, 25 Z);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W, 23 X, 24 Y);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W, 23 X);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E);
-impl_tuple!(0 A, 1 B, 2 C, 3 D);
-impl_tuple!(0 A, 1 B, 2 C);
-impl_tuple!(0 A, 1 B);
-impl_tuple!(0 A);
+impl_func_args_for_tuples!(
+    <'a> LuaRuntime, LuaValue, [IntoLua<'a>],
+    |engine, value| LuaValue::new(engine, value)
+);
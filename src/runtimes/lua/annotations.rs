@@ -0,0 +1,165 @@
+use std::{collections::BTreeSet, fmt::Write as _, path::PathBuf};
+
+use bevy::{prelude::*, utils::tracing};
+
+use super::LuaRuntime;
+use crate::{
+    logging::LOG_FUNCTIONS,
+    runtime_support::{ENTITY_VAR_NAME, SCRIPT_VAR_NAME},
+    ScriptApiRegistry, ScriptFunctionInfo, ScriptingRuntimeBuilder,
+};
+
+/// Definitions of types and globals that every Lua script has access to.
+const BUILTIN_DEFINITIONS: &str = r#"---@class BevyEntity
+---@field index integer
+
+---@class Vec3
+---@field x number
+---@field y number
+---@field z number
+
+---@param x number
+---@param y number
+---@param z number
+---@return Vec3
+function Vec3(x, y, z) end
+
+---@class Promise
+local Promise = {}
+
+---Calls provided function with the value the promise resolves with.
+---@param callback fun(value: any): any
+---@return Promise
+function Promise:and_then(callback) end
+
+---Stops the promise from resolving and calling its callbacks.
+function Promise:cancel() end
+
+---Cancels the promise if it does not resolve within provided number of seconds.
+---@param seconds number
+---@return Promise
+function Promise:timeout(seconds) end
+"#;
+
+/// Returns the Lua type of values converted into or from provided Rust type.
+fn lua_type(rust_type: &str) -> String {
+    let (outer, inner) = match rust_type.find('<') {
+        Some(start) if rust_type.ends_with('>') => (
+            &rust_type[..start],
+            &rust_type[start + 1..rust_type.len() - 1],
+        ),
+        _ => (rust_type, ""),
+    };
+    match outer.rsplit("::").next().unwrap_or(outer) {
+        "bool" => "boolean".to_string(),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            "integer".to_string()
+        }
+        "f32" | "f64" => "number".to_string(),
+        "String" | "str" | "&str" => "string".to_string(),
+        "()" => "nil".to_string(),
+        "BevyEntity" => "BevyEntity".to_string(),
+        "BevyVec3" => "Vec3".to_string(),
+        "ScriptFunction" | "Function" => "function".to_string(),
+        "Vec" => format!("{}[]", lua_type(inner)),
+        "Option" => format!("{}?", lua_type(inner)),
+        "HashMap" | "BTreeMap" | "Table" => "table".to_string(),
+        _ => "any".to_string(),
+    }
+}
+
+/// Returns definitions of provided registered functions and of builtin types and globals, in
+/// the format of [Lua Language Server](https://luals.github.io/wiki/annotations/) annotations,
+/// so that editors can complete them in scripts. Arguments are named `arg1`, `arg2` etc., as
+/// names of Rust parameters are not known. Every function returns a `Promise`, resolving with
+/// a value of the type the Rust function returns.
+pub fn lua_annotations<'a>(functions: impl IntoIterator<Item = &'a ScriptFunctionInfo>) -> String {
+    let mut definitions = String::from("---@meta\n-- Generated by bevy_scriptum.\n\n");
+    definitions.push_str(BUILTIN_DEFINITIONS);
+
+    let _ = write!(
+        definitions,
+        "\n---@type BevyEntity\n{ENTITY_VAR_NAME} = nil\n"
+    );
+    let _ = write!(
+        definitions,
+        "\n---@type {{ path: string, asset_id: string, mod_name: string? }}\n{SCRIPT_VAR_NAME} = nil\n"
+    );
+    definitions.push_str("\nlog = {}\n");
+    for (name, _) in LOG_FUNCTIONS {
+        let _ = write!(
+            definitions,
+            "\n---@param message string\nfunction log.{name}(message) end\n"
+        );
+    }
+
+    let functions = functions.into_iter().collect::<Vec<_>>();
+    let namespaces = functions
+        .iter()
+        .filter_map(|function| function.namespace.as_deref())
+        .collect::<BTreeSet<_>>();
+    for namespace in namespaces {
+        let _ = write!(definitions, "\n{namespace} = {{}}\n");
+    }
+    for function in functions {
+        definitions.push('\n');
+        if let Some(source) = &function.source {
+            let _ = writeln!(definitions, "---Registered by {source}.");
+        }
+        let mut args = Vec::new();
+        for (index, arg_type) in function.arg_type_names.iter().enumerate() {
+            let arg = format!("arg{}", index + 1);
+            let _ = writeln!(definitions, "---@param {arg} {}", lua_type(arg_type));
+            args.push(arg);
+        }
+        let _ = writeln!(
+            definitions,
+            "---@return Promise # resolves with {}",
+            lua_type(function.return_type_name)
+        );
+        let _ = writeln!(
+            definitions,
+            "function {}({}) end",
+            function.qualified_name(),
+            args.join(", ")
+        );
+    }
+    definitions
+}
+
+/// Writes definitions of functions registered in the Lua runtime to provided path whenever
+/// they change.
+fn write_lua_annotations(
+    path: PathBuf,
+) -> impl FnMut(ScriptApiRegistry<LuaRuntime>, Local<Option<String>>) {
+    move |registry, mut written| {
+        let functions = registry.iter().collect::<Vec<_>>();
+        let definitions = lua_annotations(&functions);
+        if written.as_ref() == Some(&definitions) {
+            return;
+        }
+        match std::fs::write(&path, &definitions) {
+            Ok(()) => tracing::debug!("wrote Lua annotations to {}", path.display()),
+            Err(e) => tracing::error!("error writing Lua annotations to {}: {}", path.display(), e),
+        }
+        *written = Some(definitions);
+    }
+}
+
+impl ScriptingRuntimeBuilder<'_, LuaRuntime> {
+    /// Writes definitions of functions registered in the runtime and of builtin types, see
+    /// [lua_annotations], to provided path, e.g. `scripts/bevy_scriptum.d.lua`, whenever
+    /// registered functions change, so that editors using Lua Language Server can complete them.
+    /// Definitions are only written in debug builds.
+    pub fn write_lua_annotations(self, path: impl Into<PathBuf>) -> Self {
+        if cfg!(debug_assertions) {
+            let condition = self.while_added();
+            self.world.resource_mut::<Schedules>().add_systems(
+                PostUpdate,
+                write_lua_annotations(path.into()).run_if(condition),
+            );
+        }
+
+        self
+    }
+}
@@ -0,0 +1,141 @@
+//! Setup specific to the Lua implementation selected with cargo features.
+
+#[cfg(feature = "lua")]
+use std::ffi::c_int;
+
+#[cfg(feature = "lua")]
+use mlua::ffi;
+use mlua::Lua;
+
+use super::{LuaRuntimeSettings, LuaStdLib};
+
+#[cfg(feature = "lua")]
+const LUAJIT_MODE_ENGINE: c_int = 0;
+#[cfg(feature = "lua")]
+pub(super) const LUAJIT_MODE_ALLFUNC: c_int = 3;
+#[cfg(feature = "lua")]
+pub(super) const LUAJIT_MODE_OFF: c_int = 0x0000;
+
+#[cfg(feature = "lua")]
+extern "C-unwind" {
+    pub(super) fn luaJIT_setmode(state: *mut ffi::lua_State, idx: c_int, mode: c_int) -> c_int;
+}
+
+/// Turns the JIT compiler off for the whole Lua state.
+#[cfg(feature = "lua")]
+unsafe extern "C-unwind" fn disable_engine_jit(state: *mut ffi::lua_State) -> c_int {
+    luaJIT_setmode(state, 0, LUAJIT_MODE_ENGINE | LUAJIT_MODE_OFF);
+    0
+}
+
+/// Applies settings of the enabled Lua implementation to a newly created Lua state.
+pub(super) fn configure(engine: &Lua, settings: &LuaRuntimeSettings) -> mlua::Result<()> {
+    #[cfg(feature = "lua")]
+    if settings.disable_jit {
+        // SAFETY: the function only changes JIT mode of the state.
+        unsafe { engine.create_c_function(disable_engine_jit)? }.call::<_, ()>(())?;
+    }
+    let safe = settings.stdlib == LuaStdLib::Safe;
+    #[cfg(feature = "luau")]
+    add_luau_package_loading(engine, safe)?;
+    restrict_package_loading(engine)?;
+    if safe {
+        restrict_bytecode_loading(engine)?;
+    }
+    #[cfg(feature = "luau")]
+    if settings.sandbox {
+        engine.sandbox(true)?;
+    }
+    #[cfg(not(any(feature = "lua", feature = "luau")))]
+    let _ = settings;
+    Ok(())
+}
+
+/// Makes `require` only find modules in `package.preload` and modules read through the asset
+/// server along with the running script, instead of searching the file system for Lua files and
+/// C libraries. This keeps `require` working the same in shipped builds and prevents scripts from
+/// loading native code.
+fn restrict_package_loading(engine: &Lua) -> mlua::Result<()> {
+    let Some(package) = engine.globals().get::<_, Option<mlua::Table>>("package")? else {
+        return Ok(());
+    };
+    let searchers = match package.get::<_, Option<mlua::Table>>("searchers")? {
+        Some(searchers) => searchers,
+        None => package.get::<_, mlua::Table>("loaders")?,
+    };
+    let preload_searcher = searchers.raw_get::<_, mlua::Function>(1)?;
+    for index in (1..=searchers.raw_len()).rev() {
+        searchers.raw_remove(index)?;
+    }
+    searchers.raw_push(preload_searcher)?;
+    searchers.raw_push(super::modules::create_searcher(engine)?)?;
+    package.set("path", "")?;
+    package.set("cpath", "")?;
+    package.set("loadlib", mlua::Value::Nil)
+}
+
+/// Makes `load` and `loadstring` only accept source code and removes `string.dump`, as loading
+/// crafted bytecode, which Lua doesn't verify, lets scripts escape the sandbox.
+fn restrict_bytecode_loading(engine: &Lua) -> mlua::Result<()> {
+    let globals = engine.globals();
+    if let Some(string) = globals.get::<_, Option<mlua::Table>>("string")? {
+        string.set("dump", mlua::Value::Nil)?;
+    }
+    // The Luau `load` only accepts source code already, see [add_luau_package_loading].
+    #[cfg(not(feature = "luau"))]
+    if let Some(load) = globals.get::<_, Option<mlua::Function>>("load")? {
+        // Remaining arguments are passed on as they are, as Lua tells an omitted environment
+        // apart from `nil`.
+        let text_load = engine
+            .load(
+                r#"
+                local load = ...
+                return function(chunk, name, _, ...)
+                    return load(chunk, name, "t", ...)
+                end
+                "#,
+            )
+            .set_name("=load")
+            .call::<_, mlua::Function>(load)?;
+        if globals.contains_key("loadstring")? {
+            globals.set("loadstring", text_load.clone())?;
+        }
+        globals.set("load", text_load)?;
+    }
+    Ok(())
+}
+
+/// Luau lacks `load` and `package.preload`, so they are added along with a `require` loader
+/// looking modules up in `package.preload`. With `text_only` the added `load` rejects bytecode.
+#[cfg(feature = "luau")]
+fn add_luau_package_loading(engine: &Lua, text_only: bool) -> mlua::Result<()> {
+    let load = engine.create_function(
+        move |engine, (source, name): (mlua::String, Option<String>)| {
+            let chunk = engine.load(source.as_bytes());
+            let chunk = match name {
+                Some(name) => chunk.set_name(name),
+                None => chunk,
+            };
+            if text_only {
+                chunk.set_mode(mlua::ChunkMode::Text).into_function()
+            } else {
+                chunk.into_function()
+            }
+        },
+    )?;
+    engine.globals().set("load", load)?;
+    let Some(package) = engine.globals().get::<_, Option<mlua::Table>>("package")? else {
+        return Ok(());
+    };
+    package.set("preload", engine.create_table()?)?;
+    let preload_loader = engine.create_function(|engine, name: String| {
+        engine
+            .globals()
+            .get::<_, mlua::Table>("package")?
+            .get::<_, mlua::Table>("preload")?
+            .get::<_, mlua::Value>(name)
+    })?;
+    package
+        .get::<_, mlua::Table>("loaders")?
+        .raw_insert(1, preload_loader)
+}
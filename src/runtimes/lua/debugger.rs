@@ -0,0 +1,81 @@
+use std::ffi::{c_int, CStr};
+
+use mlua::{ffi, HookTriggers, Lua, MultiValue};
+
+use crate::debugger::ScriptDebugger;
+
+use super::format_lua_value;
+
+/// Collects local variables of the function at stack level provided as the first argument,
+/// returning alternating names and values. Temporaries, whose names start with `(`, are skipped.
+unsafe extern "C-unwind" fn collect_locals(state: *mut ffi::lua_State) -> c_int {
+    let level = ffi::luaL_checkinteger(state, 1) as c_int;
+    let mut ar: ffi::lua_Debug = std::mem::zeroed();
+    if ffi::lua_getstack(state, level, &mut ar) == 0 {
+        return 0;
+    }
+    let mut count = 0;
+    let mut n = 1;
+    loop {
+        if ffi::lua_checkstack(state, 2) == 0 {
+            break;
+        }
+        let name = ffi::lua_getlocal(state, &ar, n);
+        if name.is_null() {
+            break;
+        }
+        if CStr::from_ptr(name).to_bytes().starts_with(b"(") {
+            ffi::lua_pop(state, 1);
+        } else {
+            ffi::lua_pushstring(state, name);
+            ffi::lua_insert(state, -2);
+            count += 1;
+        }
+        n += 1;
+    }
+    count * 2
+}
+
+/// Returns local variables of the function that is currently running, formatted for the debugger.
+fn locals(lua: &Lua) -> mlua::Result<Vec<(String, String)>> {
+    // SAFETY: `collect_locals` only reads the stack and pushes values it returns.
+    let collect = unsafe { lua.create_c_function(collect_locals)? };
+    // Level 0 is `collect_locals` itself, the hooked function is right below it.
+    let values = collect.call::<_, MultiValue>(1)?.into_vec();
+    Ok(values
+        .chunks(2)
+        .map(|pair| {
+            let name = match &pair[0] {
+                mlua::Value::String(name) => name.to_string_lossy().to_string(),
+                name => format_lua_value(name),
+            };
+            let value = pair.get(1).map(format_lua_value).unwrap_or_default();
+            (name, value)
+        })
+        .collect())
+}
+
+/// Installs a line hook stopping scripts running in provided Lua state at breakpoints
+/// of provided debugger.
+pub(super) fn install_debugger(engine: &Lua, debugger: &ScriptDebugger) {
+    let debugger = debugger.clone();
+    engine.set_hook(HookTriggers::EVERY_LINE, move |lua, debug| {
+        let Ok(line) = u32::try_from(debug.curr_line()) else {
+            return Ok(());
+        };
+        let source = debug.source();
+        let Some(path) = source
+            .source
+            .as_deref()
+            .and_then(|source| source.strip_prefix('@'))
+        else {
+            return Ok(());
+        };
+        if debugger.should_stop(path, line) {
+            debugger.stop(path, line, || {
+                locals(lua).unwrap_or_else(|e| vec![("error".to_string(), e.to_string())])
+            });
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,94 @@
+use mlua::Table;
+
+use super::value::integer_into_lua;
+use crate::inspector::{ScriptVariable, ScriptVariableValue, MAX_VARIABLE_DEPTH};
+
+/// Globals set up by Lua implementations rather than by scripts.
+const LIBRARY_GLOBALS: [&str; 17] = [
+    "_G",
+    "_VERSION",
+    "bit",
+    "bit32",
+    "buffer",
+    "coroutine",
+    "debug",
+    "ffi",
+    "io",
+    "jit",
+    "math",
+    "os",
+    "package",
+    "string",
+    "table",
+    "utf8",
+    "vector",
+];
+
+/// Returns provided global variables holding primitive values, along with primitive fields of
+/// global tables.
+pub(super) fn variables(globals: Table) -> mlua::Result<Vec<ScriptVariable>> {
+    let mut variables = Vec::new();
+    collect(globals, "", 0, &mut variables)?;
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(variables)
+}
+
+fn collect(
+    table: Table,
+    prefix: &str,
+    depth: usize,
+    variables: &mut Vec<ScriptVariable>,
+) -> mlua::Result<()> {
+    for pair in table.pairs::<mlua::Value, mlua::Value>() {
+        let (key, value) = pair?;
+        let mlua::Value::String(key) = key else {
+            continue;
+        };
+        let Ok(key) = key.to_str() else {
+            continue;
+        };
+        if depth == 0 && LIBRARY_GLOBALS.contains(&key) {
+            continue;
+        }
+        let name = format!("{prefix}{key}");
+        let value = match value {
+            mlua::Value::Boolean(value) => ScriptVariableValue::Bool(value),
+            // Integers of Luau are 32 bit.
+            #[allow(clippy::useless_conversion)]
+            mlua::Value::Integer(value) => ScriptVariableValue::Integer(value.into()),
+            mlua::Value::Number(value) => ScriptVariableValue::Number(value),
+            mlua::Value::String(value) => {
+                ScriptVariableValue::String(value.to_string_lossy().into_owned())
+            }
+            mlua::Value::Table(table) if depth + 1 < MAX_VARIABLE_DEPTH => {
+                collect(table, &format!("{name}."), depth + 1, variables)?;
+                continue;
+            }
+            _ => continue,
+        };
+        variables.push(ScriptVariable::new(name, value));
+    }
+    Ok(())
+}
+
+/// Sets the global variable, or field of a global table, with provided dotted name.
+pub(super) fn set_variable(
+    globals: Table,
+    name: &str,
+    value: ScriptVariableValue,
+) -> mlua::Result<()> {
+    let mut table = globals;
+    let mut keys = name.split('.').peekable();
+    while let Some(key) = keys.next() {
+        if keys.peek().is_none() {
+            return match value {
+                ScriptVariableValue::Bool(value) => table.set(key, value),
+                ScriptVariableValue::Integer(value) => table.set(key, integer_into_lua(value)?),
+                ScriptVariableValue::Number(value) => table.set(key, value),
+                ScriptVariableValue::String(value) => table.set(key, value),
+            };
+        }
+        table = table.get::<_, Table>(key)?;
+    }
+    Ok(())
+}
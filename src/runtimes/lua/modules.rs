@@ -0,0 +1,245 @@
+//! `require` of Lua modules read along with scripts, instead of from the file system.
+
+use std::{path::Path, sync::Arc};
+
+use bevy::asset::AssetPath;
+use mlua::{Function, IntoLuaMulti as _, Lua, MultiValue, Table};
+
+use super::{LuaScriptData, LuaValue};
+
+/// Returns names of modules required by provided source with a constant string name, e.g.
+/// `require("mod.utils")` or `require "mod.utils"`, along with asset paths of the modules. Module
+/// names have `.` replaced by `/` and `.lua` appended, and are looked up in the directory of the
+/// script first, then in each of its parent directories up to the asset root.
+pub(super) fn imports(source: &str, path: &Path) -> Vec<(String, Vec<AssetPath<'static>>)> {
+    required_modules(source)
+        .into_iter()
+        .map(|name| {
+            let file = format!("{}.lua", name.replace('.', "/"));
+            let candidates = path
+                .ancestors()
+                .skip(1)
+                .map(|dir| AssetPath::from(dir.join(&file)))
+                .collect();
+            (name, candidates)
+        })
+        .collect()
+}
+
+/// Returns the name that a script of a pack is required with, which is its name without `.lua`
+/// extension and with `/` replaced by `.`, so `lib/util.lua` is required as `lib.util`.
+pub(super) fn pack_module_name(script_name: &str) -> String {
+    script_name
+        .strip_suffix(".lua")
+        .unwrap_or(script_name)
+        .replace('/', ".")
+}
+
+/// A token of Lua source that matters for finding `require` calls.
+#[derive(PartialEq)]
+enum Token<'a> {
+    Name(&'a str),
+    String(&'a str),
+    Symbol(char),
+}
+
+/// Returns names of modules required by provided source with a constant string name. Comments
+/// and contents of string literals are skipped.
+fn required_modules(source: &str) -> Vec<String> {
+    let tokens = tokenize(source);
+    let mut modules: Vec<String> = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if *token != Token::Name("require") {
+            continue;
+        }
+        let is_field = index
+            .checked_sub(1)
+            .is_some_and(|previous| matches!(tokens[previous], Token::Symbol('.' | ':')));
+        if is_field {
+            continue;
+        }
+        let argument = match tokens.get(index + 1) {
+            Some(Token::Symbol('(')) => tokens.get(index + 2),
+            argument => argument,
+        };
+        let Some(Token::String(name)) = argument else {
+            continue;
+        };
+        // Names with escape sequences are left for the runtime to report.
+        if !name.is_empty() && !name.contains('\\') && !modules.iter().any(|m| m == name) {
+            modules.push(name.to_string());
+        }
+    }
+    modules
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if let Some(comment) = rest.strip_prefix("--") {
+            rest = match long_bracket(comment) {
+                Some((_, after)) => after,
+                None => comment.find('\n').map_or("", |end| &comment[end..]),
+            };
+        } else if let Some((contents, after)) = long_bracket(rest) {
+            tokens.push(Token::String(contents));
+            rest = after;
+        } else if c == '"' || c == '\'' {
+            let mut end = rest.len();
+            let mut escaped = false;
+            for (index, next) in rest.char_indices().skip(1) {
+                match next {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '\n' => {
+                        end = index;
+                        break;
+                    }
+                    next if next == c => {
+                        end = index;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            tokens.push(Token::String(&rest[1..end]));
+            rest = rest.get(end + 1..).unwrap_or_default();
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(&rest[..end]));
+            rest = &rest[end..];
+        } else {
+            tokens.push(Token::Symbol(c));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    tokens
+}
+
+/// Splits off a long bracket, e.g. `[==[ text ]==]`, from the start of provided source,
+/// returning its contents and the source after it. Unclosed brackets run to the end of source.
+fn long_bracket(source: &str) -> Option<(&str, &str)> {
+    let level = source.strip_prefix('[')?.find(|c| c != '=')?;
+    let contents = source[level + 1..].strip_prefix('[')?;
+    let close = format!("]{}]", "=".repeat(level));
+    Some(match contents.find(&close) {
+        Some(end) => (&contents[..end], &contents[end + close.len()..]),
+        None => (contents, ""),
+    })
+}
+
+/// Modules that a script can require, along with modules it has already loaded.
+#[derive(Clone)]
+pub(super) struct ScriptModules {
+    /// Names and sources of the modules.
+    sources: Arc<[(String, String)]>,
+    /// Table of modules loaded by the script, keyed by name.
+    loaded: LuaValue,
+}
+
+impl ScriptModules {
+    /// Creates modules of a script with provided module names and sources, or `None` if there
+    /// are no modules.
+    pub(super) fn new(engine: &Lua, sources: Vec<(String, String)>) -> mlua::Result<Option<Self>> {
+        if sources.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            sources: sources.into(),
+            loaded: LuaValue::new(engine, engine.create_table()?),
+        }))
+    }
+}
+
+/// The script that is being evaluated or called in a Lua state, kept in its app data for the
+/// module searcher.
+pub(super) struct RunningScript {
+    modules: Option<ScriptModules>,
+    env: Option<LuaValue>,
+    /// Names of modules of the script that `require` has put in `package.loaded`.
+    required: Vec<String>,
+}
+
+/// Marks provided script as the one whose modules `require` finds, returning the script that
+/// was running before, which has to be passed to [exit_script] once the script stops running.
+pub(super) fn enter_script(engine: &Lua, script_data: &LuaScriptData) -> Option<RunningScript> {
+    engine.set_app_data(RunningScript {
+        modules: script_data.modules.clone(),
+        env: script_data.env.clone(),
+        required: Vec::new(),
+    })
+}
+
+/// Makes `require` find modules of provided script that was running before, moving modules
+/// loaded by the script that stops running out of `package.loaded`, so that scripts sharing a
+/// Lua state get their own instances of modules with the same name.
+pub(super) fn exit_script(engine: &Lua, previous: Option<RunningScript>) -> mlua::Result<()> {
+    let running = match previous {
+        Some(previous) => engine.set_app_data(previous),
+        None => engine.remove_app_data::<RunningScript>(),
+    };
+    let Some(RunningScript {
+        modules: Some(modules),
+        required,
+        ..
+    }) = running
+    else {
+        return Ok(());
+    };
+    if required.is_empty() {
+        return Ok(());
+    }
+    let package_loaded = engine.named_registry_value::<Table>("_LOADED")?;
+    let loaded = engine.registry_value::<Table>(&modules.loaded.0)?;
+    for name in required {
+        let module = package_loaded.raw_get::<_, mlua::Value>(name.as_str())?;
+        // Lua 5.1 leaves a light userdata marker behind when loading a module fails.
+        if !matches!(module, mlua::Value::Nil | mlua::Value::LightUserData(_)) {
+            loaded.raw_set(name.as_str(), module)?;
+        }
+        package_loaded.raw_set(name, mlua::Value::Nil)?;
+    }
+    Ok(())
+}
+
+/// Creates a `require` searcher that finds modules of the running script, loading every module
+/// once per script in the environment of the script.
+pub(super) fn create_searcher(engine: &Lua) -> mlua::Result<Function<'_>> {
+    engine.create_function(|engine, name: String| {
+        let not_found = || format!("\n\tno module '{name}' among script assets");
+        let Some((modules, env)) = engine
+            .app_data_ref::<RunningScript>()
+            .and_then(|running| Some((running.modules.clone()?, running.env.clone())))
+        else {
+            return not_found().into_lua_multi(engine);
+        };
+        let loaded = engine.registry_value::<Table>(&modules.loaded.0)?;
+        let module = loaded.raw_get::<_, mlua::Value>(name.as_str())?;
+        let loader = if !module.is_nil() {
+            let module = LuaValue::new(engine, module);
+            engine.create_function(move |engine, _: MultiValue| {
+                engine.registry_value::<mlua::Value>(&module.0)
+            })?
+        } else {
+            let Some((_, source)) = modules.sources.iter().find(|(module, _)| *module == name)
+            else {
+                return not_found().into_lua_multi(engine);
+            };
+            let chunk = engine.load(source.as_str()).set_name(format!("={name}"));
+            match env {
+                Some(env) => chunk.set_environment(engine.registry_value::<Table>(&env.0)?),
+                None => chunk,
+            }
+            .into_function()?
+        };
+        if let Some(mut running) = engine.app_data_mut::<RunningScript>() {
+            running.required.push(name.clone());
+        }
+        (loader, name).into_lua_multi(engine)
+    })
+}
@@ -0,0 +1,149 @@
+use std::{any::TypeId, sync::Arc};
+
+use mlua::Value;
+
+use crate::{ScriptFunction, ScriptingError};
+
+use super::{BevyEntity, BevyVec3, LuaHostFunction, LuaRuntime};
+
+/// A host function registered under a name shared with other functions, which gets called
+/// when arguments passed from script match its parameter types.
+#[derive(Clone)]
+pub(super) struct Overload {
+    pub(super) arg_types: Vec<TypeId>,
+    pub(super) f: Arc<LuaHostFunction>,
+}
+
+/// Host functions registered under the same name.
+pub(super) struct HostFunction {
+    pub(super) namespace: Option<String>,
+    pub(super) name: String,
+    pub(super) overloads: Arc<Vec<Overload>>,
+}
+
+/// Selects the overload to call with provided arguments, picking the first one that takes
+/// the same number of arguments of matching types. A function without overloads is selected
+/// as long as its parameters of known types match, otherwise an error naming the mismatched
+/// argument is returned.
+pub(super) fn select<'a>(
+    name: &str,
+    overloads: &'a [Overload],
+    args: &[Value],
+) -> Result<&'a Overload, ScriptingError> {
+    if let [overload] = overloads {
+        return match mismatch(overload, args) {
+            Some((index, expected, got)) => Err(ScriptingError::InvalidArgument {
+                function: name.to_string(),
+                position: index + 1,
+                expected: expected.to_string(),
+                got,
+            }),
+            None => Ok(overload),
+        };
+    }
+    overloads
+        .iter()
+        .find(|overload| {
+            overload.arg_types.len() == args.len() && mismatch(overload, args).is_none()
+        })
+        .ok_or_else(|| ScriptingError::NoMatchingOverload(name.to_string()))
+}
+
+/// Returns the index of the first argument that doesn't match the type of its parameter, along
+/// with the name of the expected type and a description of the argument. Missing arguments are
+/// `nil`.
+fn mismatch(overload: &Overload, args: &[Value]) -> Option<(usize, &'static str, String)> {
+    overload
+        .arg_types
+        .iter()
+        .enumerate()
+        .find_map(|(index, arg_type)| {
+            let arg = args.get(index).unwrap_or(&Value::Nil);
+            let (expected, matches) = expected_type(*arg_type)?;
+            (!matches(arg)).then(|| (index, expected, describe(arg)))
+        })
+}
+
+/// Returns the type name of provided value, followed by the value itself for numbers, which
+/// may have the expected type but be out of range of the parameter.
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Integer(integer) => format!("integer {integer}"),
+        Value::Number(number) => format!("number {number}"),
+        value => value.type_name().to_string(),
+    }
+}
+
+/// Checks whether a Lua value can be converted into a parameter of a certain type.
+type TypeCheck = fn(&Value) -> bool;
+
+/// Checks whether provided value is an integer, or a number without a fractional part, within
+/// the range of `T`, so that it is neither truncated nor overflows once converted.
+fn is_integer_of<T: TryFrom<i64>>(value: &Value) -> bool {
+    match value {
+        // Integers of Luau are 32 bit.
+        #[allow(clippy::useless_conversion)]
+        Value::Integer(integer) => T::try_from(i64::from(*integer)).is_ok(),
+        Value::Number(number) => {
+            number.fract() == 0.0
+                && (i64::MIN as f64..i64::MAX as f64).contains(number)
+                && T::try_from(*number as i64).is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// Returns the name of the Lua type that values passed as a parameter of provided type need
+/// to have, along with a check whether a value has it. Types that are not known here accept
+/// any value.
+fn expected_type(arg_type: TypeId) -> Option<(&'static str, TypeCheck)> {
+    macro_rules! is_any_of {
+        ($($t:ty),*) => {
+            [$(TypeId::of::<$t>()),*].contains(&arg_type)
+        };
+    }
+    macro_rules! integer_types {
+        ($($t:ty),*) => {
+            $(
+                if arg_type == TypeId::of::<$t>() {
+                    return Some((concat!("integer in range of ", stringify!($t)), is_integer_of::<$t>));
+                }
+            )*
+        };
+    }
+    integer_types!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+    if is_any_of!(f32) {
+        return Some(("number in range of f32", |value| match value {
+            Value::Integer(_) => true,
+            Value::Number(number) => !number.is_finite() || number.abs() <= f32::MAX as f64,
+            _ => false,
+        }));
+    }
+    if is_any_of!(f64) {
+        return Some(("number", |value| {
+            matches!(value, Value::Integer(_) | Value::Number(_))
+        }));
+    }
+    if is_any_of!(bool) {
+        return Some(("boolean", |value| matches!(value, Value::Boolean(_))));
+    }
+    if is_any_of!(String) {
+        return Some(("string", |value| matches!(value, Value::String(_))));
+    }
+    if is_any_of!(BevyVec3) {
+        return Some((
+            "Vec3",
+            |value| matches!(value, Value::UserData(data) if data.is::<BevyVec3>()),
+        ));
+    }
+    if is_any_of!(BevyEntity) {
+        return Some((
+            "Entity",
+            |value| matches!(value, Value::UserData(data) if data.is::<BevyEntity>()),
+        ));
+    }
+    if is_any_of!(ScriptFunction<LuaRuntime>) {
+        return Some(("function", |value| matches!(value, Value::Function(_))));
+    }
+    None
+}
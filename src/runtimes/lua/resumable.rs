@@ -0,0 +1,125 @@
+#[cfg(not(feature = "luau"))]
+use std::ffi::c_int;
+
+#[cfg(not(feature = "luau"))]
+use mlua::ffi;
+use mlua::{Function, IntoLuaMulti, Lua, MultiValue, Thread};
+
+#[cfg(feature = "lua")]
+use super::backend::{luaJIT_setmode, LUAJIT_MODE_ALLFUNC, LUAJIT_MODE_OFF};
+
+/// Disables JIT compilation of the function provided as the first argument and functions
+/// nested in it. Count hooks are not called from JIT-compiled code, so it would never yield.
+#[cfg(feature = "lua")]
+unsafe extern "C-unwind" fn disable_jit(state: *mut ffi::lua_State) -> c_int {
+    luaJIT_setmode(state, 1, LUAJIT_MODE_ALLFUNC | LUAJIT_MODE_OFF);
+    0
+}
+
+#[cfg(not(feature = "luau"))]
+unsafe extern "C-unwind" fn yield_hook(state: *mut ffi::lua_State, _ar: *mut ffi::lua_Debug) {
+    ffi::lua_yield(state, 0);
+}
+
+/// Resumes the coroutine provided as the first argument with the remaining arguments, making it
+/// yield every number of instructions provided as the second argument. Returns `true` followed
+/// by values returned by the coroutine once it finishes, or `false` if it has been paused.
+///
+/// The coroutine is resumed directly instead of with [Thread::resume], which clears the stack of
+/// the coroutine and so would drop locals of a function paused by a hook. Hooks are global in
+/// LuaJIT, so the previous hook gets brought back once the coroutine stops.
+#[cfg(not(feature = "luau"))]
+unsafe extern "C-unwind" fn resume_budgeted(state: *mut ffi::lua_State) -> c_int {
+    let thread = ffi::lua_tothread(state, 1);
+    let budget = ffi::luaL_checkinteger(state, 2) as c_int;
+    let nargs = ffi::lua_gettop(state) - 2;
+    ffi::lua_xmove(state, thread, nargs);
+    let hook = ffi::lua_gethook(thread);
+    let mask = ffi::lua_gethookmask(thread);
+    let count = ffi::lua_gethookcount(thread);
+    ffi::lua_sethook(thread, Some(yield_hook), ffi::LUA_MASKCOUNT, budget);
+    let mut nresults = 0;
+    let status = ffi::lua_resume(thread, state, nargs, &mut nresults);
+    ffi::lua_sethook(thread, hook, mask, count);
+    match status {
+        ffi::LUA_OK => {
+            ffi::luaL_checkstack(state, nresults + 1, std::ptr::null());
+            ffi::lua_pushboolean(state, 1);
+            ffi::lua_xmove(thread, state, nresults);
+            nresults + 1
+        }
+        ffi::LUA_YIELD => {
+            ffi::lua_pop(thread, nresults);
+            ffi::lua_pushboolean(state, 0);
+            1
+        }
+        _ => {
+            ffi::lua_xmove(thread, state, 1);
+            ffi::lua_error(state)
+        }
+    }
+}
+
+/// Creates a coroutine running provided function, that can be resumed with [resume_with_budget].
+pub(super) fn create_resumable<'lua>(
+    lua: &'lua Lua,
+    function: Function<'lua>,
+) -> mlua::Result<Thread<'lua>> {
+    #[cfg(feature = "lua")]
+    {
+        // SAFETY: the function only changes JIT mode of its argument.
+        let disable_jit = unsafe { lua.create_c_function(disable_jit)? };
+        disable_jit.call::<_, ()>(function.clone())?;
+    }
+    lua.create_thread(function)
+}
+
+/// Resumes provided coroutine, making it yield once it executes `budget` instructions.
+/// Returns values returned by the coroutine, or `None` if it has been paused.
+#[cfg(not(feature = "luau"))]
+pub(super) fn resume_with_budget<'lua>(
+    lua: &'lua Lua,
+    thread: &Thread<'lua>,
+    args: impl IntoLuaMulti<'lua>,
+    budget: u64,
+) -> mlua::Result<Option<MultiValue<'lua>>> {
+    // SAFETY: the function only moves values between the stacks and manipulates hooks.
+    let resume = unsafe { lua.create_c_function(resume_budgeted)? };
+    let budget = budget.clamp(1, c_int::MAX as u64) as ffi::lua_Integer;
+    let mut values = args.into_lua_multi(lua)?;
+    values.push_front(mlua::Value::Integer(budget));
+    values.push_front(mlua::Value::Thread(thread.clone()));
+    let mut results = resume.call::<_, MultiValue>(values)?;
+    Ok(match results.pop_front() {
+        Some(mlua::Value::Boolean(true)) => Some(results),
+        _ => None,
+    })
+}
+
+/// Resumes provided coroutine, making it yield once it gets interrupted `budget` times.
+/// Luau has no instruction hooks, instead its interrupts run on function calls and loop
+/// iterations. Returns values returned by the coroutine, or `None` if it has been paused.
+#[cfg(feature = "luau")]
+pub(super) fn resume_with_budget<'lua>(
+    lua: &'lua Lua,
+    thread: &Thread<'lua>,
+    args: impl IntoLuaMulti<'lua>,
+    budget: u64,
+) -> mlua::Result<Option<MultiValue<'lua>>> {
+    let interrupts = std::sync::atomic::AtomicU64::new(0);
+    let budget = budget.max(1);
+    lua.set_interrupt(move |_| {
+        if interrupts.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1 >= budget {
+            Ok(mlua::VmState::Yield)
+        } else {
+            Ok(mlua::VmState::Continue)
+        }
+    });
+    let result = thread.resume::<_, MultiValue>(args);
+    lua.remove_interrupt();
+    let values = result?;
+    Ok(match thread.status() {
+        mlua::ThreadStatus::Resumable => None,
+        _ => Some(values),
+    })
+}
@@ -0,0 +1,161 @@
+use std::{collections::HashSet, ffi::c_void};
+
+use bevy::{ecs::entity::Entity, math::Vec3};
+use mlua::{IntoLua, Lua, Table};
+
+use super::{BevyEntity, BevyVec3};
+
+/// A captured state of a Lua script, holding copies of plain data stored in global variables.
+/// Functions and userdata other than builtin types are not captured and are left untouched
+/// when the state is restored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LuaSnapshot(Vec<(SnapshotValue, SnapshotValue)>);
+
+#[derive(Clone, Debug, PartialEq)]
+enum SnapshotValue {
+    Boolean(bool),
+    Integer(mlua::Integer),
+    Number(mlua::Number),
+    String(Vec<u8>),
+    Entity(Entity),
+    Vec3(Vec3),
+    Table(Vec<(SnapshotValue, SnapshotValue)>),
+    /// A table that appears elsewhere in the snapshot. It is left as it is on restore.
+    Visited,
+}
+
+impl SnapshotValue {
+    fn capture(
+        value: &mlua::Value,
+        visited: &mut HashSet<*const c_void>,
+    ) -> mlua::Result<Option<Self>> {
+        Ok(Some(match value {
+            mlua::Value::Boolean(value) => Self::Boolean(*value),
+            mlua::Value::Integer(value) => Self::Integer(*value),
+            mlua::Value::Number(value) => Self::Number(*value),
+            mlua::Value::String(value) => Self::String(value.as_bytes().to_vec()),
+            mlua::Value::UserData(ud) if ud.is::<BevyEntity>() => {
+                Self::Entity(ud.borrow::<BevyEntity>()?.0)
+            }
+            mlua::Value::UserData(ud) if ud.is::<BevyVec3>() => {
+                Self::Vec3(ud.borrow::<BevyVec3>()?.0)
+            }
+            mlua::Value::Table(table) => {
+                if !visited.insert(table.to_pointer()) {
+                    Self::Visited
+                } else {
+                    Self::Table(capture_table(table, visited)?)
+                }
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Captures a table key. Only primitive keys are captured, entries with other keys
+    /// are left untouched on restore.
+    fn capture_key(key: &mlua::Value) -> Option<Self> {
+        Some(match key {
+            mlua::Value::Boolean(value) => Self::Boolean(*value),
+            mlua::Value::Integer(value) => Self::Integer(*value),
+            mlua::Value::Number(value) => Self::Number(*value),
+            mlua::Value::String(value) => Self::String(value.as_bytes().to_vec()),
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if provided value would be captured.
+    fn is_captured(value: &mlua::Value) -> bool {
+        match value {
+            mlua::Value::Boolean(_)
+            | mlua::Value::Integer(_)
+            | mlua::Value::Number(_)
+            | mlua::Value::String(_)
+            | mlua::Value::Table(_) => true,
+            mlua::Value::UserData(ud) => ud.is::<BevyEntity>() || ud.is::<BevyVec3>(),
+            _ => false,
+        }
+    }
+
+    fn build<'lua>(&self, lua: &'lua Lua) -> mlua::Result<mlua::Value<'lua>> {
+        Ok(match self {
+            Self::Boolean(value) => mlua::Value::Boolean(*value),
+            Self::Integer(value) => mlua::Value::Integer(*value),
+            Self::Number(value) => mlua::Value::Number(*value),
+            Self::String(value) => mlua::Value::String(lua.create_string(value)?),
+            Self::Entity(value) => BevyEntity(*value).into_lua(lua)?,
+            Self::Vec3(value) => BevyVec3(*value).into_lua(lua)?,
+            Self::Table(entries) => {
+                let table = lua.create_table()?;
+                restore_table(lua, &table, entries)?;
+                mlua::Value::Table(table)
+            }
+            Self::Visited => mlua::Value::Nil,
+        })
+    }
+}
+
+fn capture_table(
+    table: &Table,
+    visited: &mut HashSet<*const c_void>,
+) -> mlua::Result<Vec<(SnapshotValue, SnapshotValue)>> {
+    let mut entries = Vec::new();
+    for pair in table.clone().pairs::<mlua::Value, mlua::Value>() {
+        let (key, value) = pair?;
+        let Some(key) = SnapshotValue::capture_key(&key) else {
+            continue;
+        };
+        if let Some(value) = SnapshotValue::capture(&value, visited)? {
+            entries.push((key, value));
+        }
+    }
+    Ok(entries)
+}
+
+/// Restores data of a table in place, so references to it stay valid.
+fn restore_table(
+    lua: &Lua,
+    table: &Table,
+    entries: &[(SnapshotValue, SnapshotValue)],
+) -> mlua::Result<()> {
+    let mut stale = Vec::new();
+    for pair in table.clone().pairs::<mlua::Value, mlua::Value>() {
+        let (key, value) = pair?;
+        let Some(captured_key) = SnapshotValue::capture_key(&key) else {
+            continue;
+        };
+        if SnapshotValue::is_captured(&value) && !entries.iter().any(|(k, _)| *k == captured_key) {
+            stale.push(key);
+        }
+    }
+    for key in stale {
+        table.raw_set(key, mlua::Value::Nil)?;
+    }
+
+    for (key, value) in entries {
+        let key = key.build(lua)?;
+        match value {
+            SnapshotValue::Visited => {}
+            SnapshotValue::Table(entries) => match table.raw_get::<_, mlua::Value>(key.clone())? {
+                mlua::Value::Table(existing) => restore_table(lua, &existing, entries)?,
+                _ => table.raw_set(key, value.build(lua)?)?,
+            },
+            value => table.raw_set(key, value.build(lua)?)?,
+        }
+    }
+    Ok(())
+}
+
+/// Captures provided table of global variables.
+pub(super) fn snapshot_globals(globals: &Table) -> mlua::Result<LuaSnapshot> {
+    let mut visited = HashSet::from([globals.to_pointer()]);
+    Ok(LuaSnapshot(capture_table(globals, &mut visited)?))
+}
+
+/// Restores provided table of global variables.
+pub(super) fn restore_globals(
+    lua: &Lua,
+    globals: &Table,
+    snapshot: &LuaSnapshot,
+) -> mlua::Result<()> {
+    restore_table(lua, globals, &snapshot.0)
+}
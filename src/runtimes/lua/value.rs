@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use mlua::{FromLua, IntoLua, Lua, MultiValue, Table};
+
+use crate::ScriptValue;
+
+use super::{BevyEntity, BevyVec3};
+
+/// Largest magnitude of integers that Lua numbers hold exactly. LuaJIT stores every number as
+/// a double, as does Luau, whose integers are only 32 bit, while integers of Lua 5.4 are 64 bit.
+#[cfg(any(feature = "lua", feature = "luau"))]
+const MAX_EXACT_INTEGER: u64 = 1 << f64::MANTISSA_DIGITS;
+#[cfg(not(any(feature = "lua", feature = "luau")))]
+const MAX_EXACT_INTEGER: u64 = i64::MIN.unsigned_abs();
+
+fn out_of_range(value: i64) -> mlua::Error {
+    mlua::Error::ToLuaConversionError {
+        from: "i64",
+        to: "integer",
+        message: Some(format!(
+            "{value} is out of range of integers that Lua numbers hold exactly"
+        )),
+    }
+}
+
+/// Converts provided integer into a Lua integer, or into a number if Lua integers are too
+/// narrow to hold it, failing instead of rounding it if Lua numbers can't hold it exactly.
+pub(super) fn integer_into_lua<'lua>(value: i64) -> mlua::Result<mlua::Value<'lua>> {
+    if value.unsigned_abs() > MAX_EXACT_INTEGER {
+        return Err(out_of_range(value));
+    }
+    Ok(match mlua::Integer::try_from(value) {
+        Ok(value) => mlua::Value::Integer(value),
+        Err(_) => mlua::Value::Number(value as f64),
+    })
+}
+
+/// Checks that integers among provided values, converted from Rust values, are held exactly by
+/// Lua numbers, so that they aren't rounded silently once they get into Lua.
+pub(super) fn check_integers(values: &MultiValue) -> mlua::Result<()> {
+    for value in values {
+        if let mlua::Value::Integer(integer) = value {
+            // Integers of Luau are 32 bit.
+            #[allow(clippy::useless_conversion)]
+            integer_into_lua(i64::from(*integer))?;
+        }
+    }
+    Ok(())
+}
+
+impl<'lua> IntoLua<'lua> for ScriptValue {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<mlua::Value<'lua>> {
+        match self {
+            ScriptValue::Nil => Ok(mlua::Value::Nil),
+            ScriptValue::Bool(value) => value.into_lua(lua),
+            ScriptValue::Int(value) => integer_into_lua(value),
+            ScriptValue::Float(value) => value.into_lua(lua),
+            ScriptValue::String(value) => value.into_lua(lua),
+            ScriptValue::Array(values) => lua.create_sequence_from(values)?.into_lua(lua),
+            ScriptValue::Map(values) => lua.create_table_from(values)?.into_lua(lua),
+            ScriptValue::Entity(value) => BevyEntity(value).into_lua(lua),
+            ScriptValue::Vec3(value) => BevyVec3(value).into_lua(lua),
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for ScriptValue {
+    fn from_lua(value: mlua::Value<'lua>, lua: &'lua Lua) -> mlua::Result<Self> {
+        match value {
+            mlua::Value::Nil => Ok(ScriptValue::Nil),
+            mlua::Value::Boolean(value) => Ok(ScriptValue::Bool(value)),
+            // Integers of Luau are 32 bit.
+            #[allow(clippy::useless_conversion)]
+            mlua::Value::Integer(value) => Ok(ScriptValue::Int(value.into())),
+            mlua::Value::Number(value) => Ok(ScriptValue::Float(value)),
+            mlua::Value::String(value) => Ok(ScriptValue::String(value.to_str()?.to_string())),
+            mlua::Value::Table(table) => from_table(table, lua),
+            mlua::Value::UserData(ud) if ud.is::<BevyEntity>() => {
+                Ok(ScriptValue::Entity(ud.borrow::<BevyEntity>()?.0))
+            }
+            mlua::Value::UserData(ud) if ud.is::<BevyVec3>() => {
+                Ok(ScriptValue::Vec3(ud.borrow::<BevyVec3>()?.0))
+            }
+            value => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "ScriptValue",
+                message: None,
+            }),
+        }
+    }
+}
+
+/// Converts a table into an array if its keys are consecutive integers starting at 1, or into a
+/// map otherwise.
+fn from_table<'lua>(table: Table<'lua>, lua: &'lua Lua) -> mlua::Result<ScriptValue> {
+    let len = table.raw_len();
+    if len > 0 && table.clone().pairs::<mlua::Value, mlua::Value>().count() == len {
+        return table
+            .sequence_values::<ScriptValue>()
+            .collect::<mlua::Result<_>>()
+            .map(ScriptValue::Array);
+    }
+    let mut values = HashMap::new();
+    for pair in table.pairs::<mlua::Value, mlua::Value>() {
+        let (key, value) = pair?;
+        let key = match key {
+            mlua::Value::String(key) => key.to_str()?.to_string(),
+            mlua::Value::Integer(key) => key.to_string(),
+            key => {
+                return Err(mlua::Error::FromLuaConversionError {
+                    from: key.type_name(),
+                    to: "ScriptValue map key",
+                    message: None,
+                })
+            }
+        };
+        values.insert(key, ScriptValue::from_lua(value, lua)?);
+    }
+    Ok(ScriptValue::Map(values))
+}
@@ -1,4 +1,4 @@
-#[cfg(feature = "lua")]
+#[cfg(any(feature = "lua", feature = "lua54", feature = "luau"))]
 pub mod lua;
 #[cfg(feature = "rhai")]
 pub mod rhai;
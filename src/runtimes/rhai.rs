@@ -1,23 +1,55 @@
-use std::fmt::Debug;
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Debug,
+    path::Path,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
+};
 
 use bevy::{
-    asset::Asset,
+    asset::{Asset, AssetPath},
     ecs::{component::Component, entity::Entity, schedule::ScheduleLabel, system::Resource},
     math::Vec3,
     reflect::TypePath,
+    utils::tracing::Level,
+};
+use rhai::{
+    CallFnOptions, Dynamic, Engine, EvalAltResult, FnPtr, FuncRegistration, ImmutableString,
+    Module, NativeCallContext, RhaiFunc, Scope, Shared, Variant,
 };
-use rhai::{CallFnOptions, Dynamic, Engine, FnPtr, Scope, Variant};
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
-    assets::GetExtensions,
-    callback::{FromRuntimeValueWithEngine, IntoRuntimeValueWithEngine},
-    promise::Promise,
-    FuncArgs, Runtime, ScriptingError, ENTITY_VAR_NAME,
+    assets::ResolveImports,
+    cache::SourceHasher,
+    impl_func_args_for_tuples,
+    logging::{log_message, LOG_FUNCTIONS},
+    pack::ResolvePackModules,
+    runtime_support::{
+        enter_entity, script_info_value, FromRuntimeValueWithEngine, GetExtensions,
+        IntoRuntimeValueWithEngine, Promise, ENTITY_VAR_NAME, SCRIPT_VAR_NAME,
+    },
+    CallProgress, CompiledScriptCache, EntityArgs, FuncArgs, Json, Runtime, ScriptDiagnostic,
+    ScriptFunction, ScriptInfo, ScriptRng, ScriptValue, ScriptingError, ScriptingRuntimeBuilder,
 };
 
+#[cfg(feature = "inspector")]
+mod inspector;
+mod modules;
+mod number;
+mod value;
+
+use modules::{AssetModuleResolver, ModuleSources};
+
+/// A Rhai script asset. Besides the source of the script it holds sources of modules that the
+/// script imports, keyed by the path used in `import` statements.
 #[derive(Asset, Debug, Deserialize, TypePath)]
-pub struct RhaiScript(pub String);
+pub struct RhaiScript(pub String, #[serde(skip)] pub(crate) Vec<(String, String)>);
 
 impl GetExtensions for RhaiScript {
     fn extensions() -> &'static [&'static str] {
@@ -25,15 +57,39 @@ impl GetExtensions for RhaiScript {
     }
 }
 
+impl ResolvePackModules for RhaiScript {}
+
+impl ResolveImports for RhaiScript {
+    fn imports(source: &str, _path: &Path) -> Vec<(String, Vec<AssetPath<'static>>)> {
+        modules::imports(source)
+    }
+
+    fn with_imports(source: String, modules: Vec<(String, String)>) -> Self {
+        Self(source, modules)
+    }
+}
+
 impl From<String> for RhaiScript {
     fn from(value: String) -> Self {
-        Self(value)
+        Self(value, Vec::new())
     }
 }
 
 #[derive(Resource)]
 pub struct RhaiRuntime {
-    engine: rhai::Engine,
+    engine: Arc<rhai::Engine>,
+    /// Sources of modules imported by evaluated scripts, shared with the module resolver.
+    modules: ModuleSources,
+    constants: Vec<(String, Dynamic)>,
+    /// Modules holding functions registered within namespaces.
+    namespaces: HashMap<String, Module>,
+    /// Namespaces, names and argument types of registered functions.
+    functions: Vec<(Option<String>, String, Vec<TypeId>)>,
+    /// ASTs of evaluated scripts, shared by all entities running the same script.
+    cache: Option<CompiledScriptCache<Self>>,
+    /// Name of the variable holding the entity that the running script is attached to, if it
+    /// is exposed to scripts, shared with the engine, which prevents scripts from defining it.
+    entity_variable: Arc<RwLock<Option<String>>>,
 }
 
 #[derive(ScheduleLabel, Clone, PartialEq, Eq, Debug, Hash, Default)]
@@ -44,18 +100,296 @@ pub struct RhaiSchedule;
 #[derive(Component)]
 pub struct RhaiScriptData {
     pub scope: rhai::Scope<'static>,
-    pub(crate) ast: rhai::AST,
+    pub(crate) ast: Shared<rhai::AST>,
+    pub(crate) paused: Option<PausedCall>,
+}
+
+/// A call paused by [Runtime::call_fn_resumable]. The call runs on its own thread, which blocks
+/// in [on_progress] while paused. The scope of the script is moved to that thread until the call
+/// finishes.
+pub(crate) struct PausedCall {
+    resume: Sender<u64>,
+    messages: Mutex<Receiver<CallMessage>>,
+}
+
+// Calls can not pause on WebAssembly, where they run to completion on the current thread.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+enum CallMessage {
+    Paused,
+    Finished(Result<Dynamic, ScriptingError>, Scope<'static>),
+}
+
+/// Instruction budget of a resumable call running on the current thread.
+struct CallBudget {
+    limit: u64,
+    messages: Sender<CallMessage>,
+    resume: Receiver<u64>,
+}
+
+thread_local! {
+    static CALL_BUDGET: RefCell<Option<CallBudget>> = const { RefCell::new(None) };
+}
+
+/// Pauses a resumable call once it uses up its budget, until it gets resumed. Terminates the call
+/// if it is dropped instead.
+fn on_progress(operations: u64) -> Option<Dynamic> {
+    CALL_BUDGET.with_borrow_mut(|budget| {
+        let budget = budget.as_mut()?;
+        if operations < budget.limit {
+            return None;
+        }
+        if budget.messages.send(CallMessage::Paused).is_err() {
+            return Some(Dynamic::UNIT);
+        }
+        match budget.resume.recv() {
+            Ok(more) => {
+                budget.limit = operations.saturating_add(more);
+                None
+            }
+            Err(_) => Some(Dynamic::UNIT),
+        }
+    })
 }
 
 #[derive(Debug, Clone)]
 pub struct RhaiValue(rhai::Dynamic);
 
+impl RhaiValue {
+    /// Converts the value into provided type, returning `None` if it holds a different type.
+    /// Rhai functions return multiple values as an array, which can be obtained as [rhai::Array].
+    pub fn get<T: Clone + 'static>(&self) -> Option<T> {
+        from_dynamic(&self.0)
+    }
+}
+
+/// Returns `true` if provided error has been caused by calling provided function pointer
+/// with a wrong number of arguments.
+fn is_arity_mismatch(error: &EvalAltResult, f: &FnPtr) -> bool {
+    matches!(
+        error,
+        EvalAltResult::ErrorFunctionNotFound(signature, _) if signature.starts_with(f.fn_name())
+    )
+}
+
+/// Returns a compile error describing provided error of compiling provided script, which is
+/// not necessarily a parse error, as modules imported by the script are resolved while compiling.
+fn compile_error(mut error: EvalAltResult, script: &RhaiScript, path: &str) -> ScriptingError {
+    let position = error.position();
+    error.clear_position();
+    ScriptingError::CompileError(ScriptDiagnostic::new(
+        path,
+        position.line(),
+        position.position(),
+        error.to_string(),
+        &script.0,
+    ))
+}
+
+/// Invokes provided macro with types whose vectors, hash maps and options get converted
+/// into Rhai values, see [into_dynamic].
+macro_rules! with_collection_element_types {
+    ($macro:ident) => {
+        $macro!(
+            rhai::INT,
+            rhai::FLOAT,
+            i32,
+            f32,
+            bool,
+            char,
+            String,
+            ImmutableString,
+            Dynamic
+        )
+    };
+}
+
+/// Maps a type of a Rust function parameter to the type of Rhai values that get passed to it.
+fn script_type_id(arg_type: TypeId) -> TypeId {
+    if let Some(script_type) = number::script_type_id(arg_type) {
+        return script_type;
+    }
+    // Rhai does not map the first parameter of raw functions to its string type.
+    if arg_type == TypeId::of::<String>() {
+        return TypeId::of::<ImmutableString>();
+    }
+    if arg_type == TypeId::of::<ScriptFunction<RhaiRuntime>>() {
+        return TypeId::of::<FnPtr>();
+    }
+    if arg_type == TypeId::of::<ScriptValue>() {
+        return TypeId::of::<Dynamic>();
+    }
+    macro_rules! convert {
+        ($($t:ty),*) => {
+            $(
+                if arg_type == TypeId::of::<Vec<$t>>() {
+                    return TypeId::of::<rhai::Array>();
+                }
+                if arg_type == TypeId::of::<HashMap<String, $t>>() {
+                    return TypeId::of::<rhai::Map>();
+                }
+                if arg_type == TypeId::of::<Option<$t>>() {
+                    return TypeId::of::<Dynamic>();
+                }
+            )*
+        };
+    }
+    with_collection_element_types!(convert);
+    arg_type
+}
+
+/// Returns an error naming the first of provided arguments that doesn't have the type of its
+/// parameter, if the name of the type is known.
+fn invalid_argument(
+    function: &str,
+    arg_types: &[TypeId],
+    args: &[&mut Dynamic],
+    engine: &Engine,
+) -> Option<ScriptingError> {
+    arg_types
+        .iter()
+        .zip(args)
+        .enumerate()
+        .find(|(_, (arg_type, arg))| Dynamic::type_id(arg) != **arg_type)
+        .and_then(|(index, (arg_type, arg))| {
+            Some(ScriptingError::InvalidArgument {
+                function: function.to_string(),
+                position: index + 1,
+                expected: type_name(*arg_type)?.to_string(),
+                got: engine.map_type_name(Dynamic::type_name(arg)).to_string(),
+            })
+        })
+}
+
+/// Returns the name of the Rhai type with provided id, for types that values passed to
+/// registered functions commonly have.
+fn type_name(type_id: TypeId) -> Option<&'static str> {
+    [
+        (TypeId::of::<i64>(), "i64"),
+        (TypeId::of::<f64>(), "f64"),
+        (TypeId::of::<bool>(), "bool"),
+        (TypeId::of::<char>(), "char"),
+        (TypeId::of::<ImmutableString>(), "string"),
+        (TypeId::of::<rhai::Array>(), "array"),
+        (TypeId::of::<rhai::Map>(), "map"),
+        (TypeId::of::<FnPtr>(), "Fn"),
+        (TypeId::of::<Entity>(), "Entity"),
+        (TypeId::of::<Vec3>(), "Vec3"),
+    ]
+    .into_iter()
+    .find(|(id, _)| *id == type_id)
+    .map(|(_, name)| name)
+}
+
+/// Converts provided value into a [Dynamic], see [try_into_dynamic]. Panics if the value is
+/// a number out of range of Rhai numbers.
+fn into_dynamic<T: Variant + Clone>(value: T) -> Dynamic {
+    try_into_dynamic(value).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Converts provided value into a [Dynamic], turning `Vec<T>` into an array,
+/// `HashMap<String, T>` into an object map and `Option<T>` into either the value or
+/// `()` when `T` is one of common element types. [ScriptValue] gets converted into the
+/// value it holds. Returns an error if the value is, or contains, a number out of range
+/// of Rhai numbers.
+fn try_into_dynamic<T: Variant + Clone>(value: T) -> Result<Dynamic, number::OutOfRange> {
+    if let Some(number) = number::into_dynamic(&value) {
+        return number;
+    }
+    let mut value = Some(value);
+    let any = &mut value as &mut dyn Any;
+    if let Some(value) = any.downcast_mut::<Option<ScriptValue>>() {
+        return Ok(value::into_dynamic(
+            value.take().expect("Value has already been taken"),
+        ));
+    }
+    macro_rules! convert {
+        ($($t:ty),*) => {
+            $(
+                if let Some(vec) = any.downcast_mut::<Option<Vec<$t>>>() {
+                    let vec = vec.take().expect("Value has already been taken");
+                    return vec
+                        .into_iter()
+                        .map(try_into_dynamic)
+                        .collect::<Result<_, _>>()
+                        .map(Dynamic::from_array);
+                }
+                if let Some(map) = any.downcast_mut::<Option<HashMap<String, $t>>>() {
+                    let map = map.take().expect("Value has already been taken");
+                    return map
+                        .into_iter()
+                        .map(|(key, value)| Ok((key.into(), try_into_dynamic(value)?)))
+                        .collect::<Result<_, _>>()
+                        .map(Dynamic::from_map);
+                }
+                if let Some(option) = any.downcast_mut::<Option<Option<$t>>>() {
+                    let option = option.take().expect("Value has already been taken");
+                    return option.map_or(Ok(Dynamic::UNIT), try_into_dynamic);
+                }
+            )*
+        };
+    }
+    with_collection_element_types!(convert);
+    Ok(Dynamic::from(value.expect("Value has already been taken")))
+}
+
+/// Converts provided [Dynamic] into provided type, reversing conversions of [into_dynamic].
+/// Returns `None` if the value holds a different type.
+fn from_dynamic<T: Clone + 'static>(value: &Dynamic) -> Option<T> {
+    if value.is::<T>() {
+        return value.clone().try_cast();
+    }
+    if let Some(number) = number::from_dynamic::<T>(value) {
+        return number.ok();
+    }
+    let mut result = None::<T>;
+    let any = &mut result as &mut dyn Any;
+    if let Some(result_value) = any.downcast_mut::<Option<ScriptValue>>() {
+        *result_value = value::from_dynamic(value);
+        return result;
+    }
+    macro_rules! convert {
+        ($($t:ty),*) => {
+            $(
+                if let Some(vec) = any.downcast_mut::<Option<Vec<$t>>>() {
+                    *vec = value
+                        .clone()
+                        .try_cast::<rhai::Array>()?
+                        .into_iter()
+                        .map(|element| from_dynamic::<$t>(&element))
+                        .collect();
+                    return result;
+                }
+                if let Some(map) = any.downcast_mut::<Option<HashMap<String, $t>>>() {
+                    *map = value
+                        .clone()
+                        .try_cast::<rhai::Map>()?
+                        .into_iter()
+                        .map(|(key, value)| Some((key.to_string(), from_dynamic::<$t>(&value)?)))
+                        .collect();
+                    return result;
+                }
+                if let Some(option) = any.downcast_mut::<Option<Option<$t>>>() {
+                    *option = if value.is_unit() {
+                        Some(None)
+                    } else {
+                        Some(Some(from_dynamic::<$t>(value)?))
+                    };
+                    return result;
+                }
+            )*
+        };
+    }
+    with_collection_element_types!(convert);
+    None
+}
+
 impl Runtime for RhaiRuntime {
     type Schedule = RhaiSchedule;
     type ScriptAsset = RhaiScript;
     type ScriptData = RhaiScriptData;
     #[allow(deprecated)]
     type CallContext = rhai::NativeCallContextStore;
+    type Snapshot = Scope<'static>;
     type Value = RhaiValue;
     type RawEngine = rhai::Engine;
 
@@ -63,29 +397,96 @@ impl Runtime for RhaiRuntime {
         &self,
         script: &Self::ScriptAsset,
         entity: Entity,
+        info: &ScriptInfo,
+    ) -> Result<Self::ScriptData, ScriptingError> {
+        self.eval_with_bases(&[], script, entity, info)
+    }
+
+    fn eval_with_bases(
+        &self,
+        bases: &[(&Self::ScriptAsset, String)],
+        script: &Self::ScriptAsset,
+        entity: Entity,
+        info: &ScriptInfo,
     ) -> Result<Self::ScriptData, ScriptingError> {
         let mut scope = Scope::new();
-        scope.push(ENTITY_VAR_NAME, entity);
+        for (name, value) in &self.constants {
+            scope.push_constant_dynamic(name.clone(), value.clone());
+        }
+        scope.push_constant_dynamic(
+            SCRIPT_VAR_NAME,
+            value::into_dynamic(script_info_value(info)),
+        );
+        let entity_variable = self.entity_variable();
+        if let Some(name) = &entity_variable {
+            scope.push(name.clone(), entity);
+        }
 
         let engine = &self.engine;
+        let _entity_guard = enter_entity(Some(entity));
 
-        let ast = engine
-            .compile_with_scope(&scope, script.0.as_str())
-            .map_err(|e| ScriptingError::CompileError(Box::new(e)))?;
+        let mut modules = self.modules.write().expect("Failed to lock module sources");
+        for (script, _) in bases {
+            modules.extend(script.1.iter().cloned());
+        }
+        modules.extend(script.1.iter().cloned());
+        drop(modules);
+        // Imported modules are embedded in the AST, so that functions called later can import
+        // them without resolving them again.
+        let compile_script = |script: &RhaiScript, path: &str| {
+            let mut ast = engine
+                .compile_into_self_contained(&scope, script.0.as_str())
+                .map_err(|e| compile_error(*e, script, path))?;
+            ast.set_source(path);
+            Ok::<_, ScriptingError>(ast)
+        };
+        // Statements of bases run before those of the script, while functions of the script
+        // replace functions of bases with the same name and number of parameters.
+        let compile = || {
+            let mut ast = rhai::AST::empty();
+            for (base, path) in bases {
+                ast += compile_script(base, path)?;
+            }
+            ast += compile_script(script, &info.path)?;
+            ast.set_source(info.path.as_str());
+            Ok::<_, ScriptingError>(Shared::new(ast))
+        };
+        let ast = match &self.cache {
+            Some(cache) => {
+                let mut hasher = SourceHasher::default();
+                let scripts = bases.iter().map(|(base, path)| (*base, path));
+                for (script, path) in scripts.chain([(script, &info.path)]) {
+                    hasher.update(path).update(&script.0);
+                    for (name, source) in &script.1 {
+                        hasher.update(name).update(source);
+                    }
+                }
+                hasher.update(info.mod_name.as_deref().unwrap_or_default());
+                cache.get_or_compile(&info.asset_id, hasher.finalize(), compile)?
+            }
+            None => compile()?,
+        };
 
         engine
             .run_ast_with_scope(&mut scope, &ast)
             .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
 
-        scope.remove::<Entity>(ENTITY_VAR_NAME).unwrap();
+        if let Some(name) = &entity_variable {
+            let _ = scope.remove::<Entity>(name);
+        }
 
-        Ok(Self::ScriptData { ast, scope })
+        Ok(Self::ScriptData {
+            ast,
+            scope,
+            paused: None,
+        })
     }
 
     fn register_fn(
         &mut self,
+        namespace: Option<&str>,
         name: String,
-        arg_types: Vec<std::any::TypeId>,
+        arg_types: Vec<TypeId>,
         f: impl Fn(
                 Self::CallContext,
                 Vec<Self::Value>,
@@ -94,13 +495,86 @@ impl Runtime for RhaiRuntime {
             + Sync
             + 'static,
     ) -> Result<(), ScriptingError> {
-        self.engine
-            .register_raw_fn(name, arg_types, move |context, args| {
+        let range_checks = arg_types
+            .iter()
+            .map(|arg_type| number::range_check(*arg_type))
+            .collect::<Vec<_>>();
+        let arg_types = arg_types
+            .into_iter()
+            .map(script_type_id)
+            .collect::<Vec<_>>();
+        let signature = (
+            namespace.map(ToString::to_string),
+            name.clone(),
+            arg_types.clone(),
+        );
+        if !self.functions.contains(&signature) {
+            self.functions.push(signature);
+        }
+        if !arg_types.is_empty() && !arg_types.contains(&TypeId::of::<Dynamic>()) {
+            self.install_argument_check(namespace, &name, arg_types.len());
+        }
+        let function = name.clone();
+        self.install_fn(
+            namespace,
+            name,
+            arg_types,
+            move |context: NativeCallContext, args: &mut [&mut Dynamic]| {
+                if let Some(error) =
+                    number::out_of_range_argument(&function, &range_checks, args, context.engine())
+                {
+                    return Err(Box::new(EvalAltResult::ErrorRuntime(
+                        error.to_string().into(),
+                        context.call_position(),
+                    )));
+                }
                 let args = args.iter_mut().map(|arg| RhaiValue(arg.clone())).collect();
                 #[allow(deprecated)]
-                let promise = f(context.store_data(), args).unwrap();
-                Ok(promise)
+                f(context.store_data(), args)
+                    .map(Dynamic::from)
+                    .map_err(|e| {
+                        Box::new(EvalAltResult::ErrorRuntime(
+                            e.to_string().into(),
+                            context.position(),
+                        ))
+                    })
+            },
+        );
+        Ok(())
+    }
+
+    fn unregister_fn(&mut self, namespace: Option<&str>, name: &str) -> Result<(), ScriptingError> {
+        // Rhai can't remove registered functions, so every overload gets replaced with one that
+        // fails the same way as calling an undefined function.
+        let (removed, functions) = std::mem::take(&mut self.functions)
+            .into_iter()
+            .partition::<Vec<_>, _>(|function| {
+                function.0.as_deref() == namespace && function.1 == name
             });
+        self.functions = functions;
+        for (_, name, arg_types) in removed {
+            let signature = name.clone();
+            self.install_fn(
+                namespace,
+                name,
+                arg_types,
+                move |context: NativeCallContext, _args: &mut [&mut Dynamic]| {
+                    Err(Box::new(EvalAltResult::ErrorFunctionNotFound(
+                        signature.clone(),
+                        context.call_position(),
+                    )))
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn register_constant(
+        &mut self,
+        name: String,
+        value: Self::Value,
+    ) -> Result<(), ScriptingError> {
+        self.constants.push((name, value.0));
         Ok(())
     }
 
@@ -112,8 +586,15 @@ impl Runtime for RhaiRuntime {
         args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
     ) -> Result<RhaiValue, ScriptingError> {
         let ast = script_data.ast.clone();
+        if !ast.iter_functions().any(|f| f.name == name) {
+            return Err(ScriptingError::FunctionNotFound(name.to_string()));
+        }
+        let _entity_guard = enter_entity(Some(entity));
         let scope = &mut script_data.scope;
-        scope.push(ENTITY_VAR_NAME, entity);
+        let entity_variable = self.entity_variable();
+        if let Some(name) = &entity_variable {
+            scope.push(name.clone(), entity);
+        }
         let options = CallFnOptions::new().eval_ast(false);
         let args = args
             .parse(&self.engine)
@@ -123,11 +604,131 @@ impl Runtime for RhaiRuntime {
         let result = self
             .engine
             .call_fn_with_options::<Dynamic>(options, scope, &ast, name, args);
-        scope.remove::<Entity>(ENTITY_VAR_NAME).unwrap();
+        if let Some(name) = &entity_variable {
+            let _ = scope.remove::<Entity>(name);
+        }
         match result {
             Ok(val) => Ok(RhaiValue(val)),
-            Err(e) => Err(ScriptingError::RuntimeError(Box::new(e))),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn has_fn(&self, name: &str, script_data: &Self::ScriptData) -> bool {
+        script_data.ast.iter_functions().any(|f| f.name == name)
+    }
+
+    fn set_entity_variable(&mut self, name: Option<String>) {
+        *self
+            .entity_variable
+            .write()
+            .expect("Failed to lock entity variable") = name;
+    }
+
+    fn list_functions(&self, script_data: &Self::ScriptData) -> Vec<String> {
+        let mut names = script_data
+            .ast
+            .iter_functions()
+            .map(|f| f.name.to_string())
+            .collect::<Vec<_>>();
+        // Overloads with different numbers of parameters share the name.
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Runs the function on a separate thread, which gets blocked in [Engine::on_progress] once
+    /// the budget of operations is used up. Overriding `on_progress` through
+    /// [Runtime::with_engine_mut] makes calls run without pausing.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn call_fn_resumable(
+        &self,
+        name: &str,
+        script_data: &mut Self::ScriptData,
+        entity: Entity,
+        args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+        budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        let ast = script_data.ast.clone();
+        if !ast.iter_functions().any(|f| f.name == name) {
+            return Err(ScriptingError::FunctionNotFound(name.to_string()));
+        }
+        let args = args
+            .parse(&self.engine)
+            .into_iter()
+            .map(|a| a.0)
+            .collect::<Vec<Dynamic>>();
+        let mut scope = std::mem::take(&mut script_data.scope);
+        let engine = self.engine.clone();
+        let name = name.to_string();
+        let entity_variable = self.entity_variable();
+        let (resume, resume_receiver) = std::sync::mpsc::channel();
+        let (messages_sender, messages) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .name(String::from("rhai resumable call"))
+            .spawn(move || {
+                let _entity_guard = enter_entity(Some(entity));
+                if let Some(variable) = &entity_variable {
+                    scope.push(variable.clone(), entity);
+                }
+                CALL_BUDGET.set(Some(CallBudget {
+                    limit: budget,
+                    messages: messages_sender.clone(),
+                    resume: resume_receiver,
+                }));
+                let options = CallFnOptions::new().eval_ast(false);
+                let result = engine
+                    .call_fn_with_options::<Dynamic>(options, &mut scope, &ast, &name, args)
+                    .map_err(ScriptingError::from);
+                CALL_BUDGET.set(None);
+                if let Some(variable) = &entity_variable {
+                    let _ = scope.remove::<Entity>(variable);
+                }
+                let _ = messages_sender.send(CallMessage::Finished(result, scope));
+            })
+            .map_err(|e| ScriptingError::RuntimeError(Box::new(e)))?;
+        Self::wait_for_call(
+            script_data,
+            PausedCall {
+                resume,
+                messages: Mutex::new(messages),
+            },
+        )
+    }
+
+    /// Threads are not available on WebAssembly, so calls run to completion without pausing.
+    #[cfg(target_arch = "wasm32")]
+    fn call_fn_resumable(
+        &self,
+        name: &str,
+        script_data: &mut Self::ScriptData,
+        entity: Entity,
+        args: impl for<'a> FuncArgs<'a, Self::Value, Self>,
+        _budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        self.call_fn(name, script_data, entity, args)
+            .map(CallProgress::Finished)
+    }
+
+    fn resume_call(
+        &self,
+        script_data: &mut Self::ScriptData,
+        _entity: Entity,
+        budget: u64,
+    ) -> Result<CallProgress<Self::Value>, ScriptingError> {
+        let call = script_data
+            .paused
+            .take()
+            .ok_or(ScriptingError::NoPausedCall)?;
+        if call.resume.send(budget).is_err() {
+            return Err(ScriptingError::ScriptPanicked(String::from(
+                "resumable call thread stopped unexpectedly",
+            )));
         }
+        Self::wait_for_call(script_data, call)
+    }
+
+    fn has_paused_call(&self, script_data: &Self::ScriptData) -> bool {
+        script_data.paused.is_some()
     }
 
     fn call_fn_from_value(
@@ -142,19 +743,128 @@ impl Runtime for RhaiRuntime {
         let ctx = &context.create_context(&self.engine);
 
         let result = if args.len() == 1 && args.first().unwrap().0.is_unit() {
-            f.call_raw(ctx, None, [])
-                .map_err(|e| ScriptingError::RuntimeError(e))?
+            // Callbacks for functions returning nothing take no parameters, while ones
+            // for functions returning `None` take `()`.
+            match f.call_raw(ctx, None, []) {
+                Err(e) if is_arity_mismatch(&e, &f) => f.call_raw(ctx, None, [Dynamic::UNIT]),
+                result => result,
+            }
+            .map_err(ScriptingError::from)?
         } else {
             let args = args.into_iter().map(|a| a.0).collect::<Vec<Dynamic>>();
-            f.call_raw(ctx, None, args)
-                .map_err(|e| ScriptingError::RuntimeError(e))?
+            f.call_raw(ctx, None, args).map_err(ScriptingError::from)?
         };
 
         Ok(RhaiValue(result))
     }
 
+    fn set_compiled_script_cache(&mut self, cache: CompiledScriptCache<Self>) {
+        self.cache = Some(cache);
+    }
+
+    /// Registers `rand()`, `rand(min, max)` and `rand_float()` functions drawing from provided
+    /// generator and disables `timestamp()`.
+    fn enable_deterministic_mode(&mut self, rng: ScriptRng) -> Result<(), ScriptingError> {
+        let int_rng = rng.clone();
+        self.engine_mut()
+            .register_fn("rand", move || int_rng.next_u64() as rhai::INT);
+        let range_rng = rng.clone();
+        self.engine_mut().register_fn(
+            "rand",
+            move |min: rhai::INT, max: rhai::INT| -> Result<rhai::INT, Box<EvalAltResult>> {
+                if min > max {
+                    return Err("rand: interval is empty".into());
+                }
+                Ok(range_rng.next_in_range(min, max))
+            },
+        );
+        self.engine_mut()
+            .register_fn("rand_float", move || rng.next_f64() as rhai::FLOAT);
+        self.engine_mut()
+            .register_fn("timestamp", || -> Result<Dynamic, Box<EvalAltResult>> {
+                Err("timestamp is disabled in deterministic mode".into())
+            });
+        Ok(())
+    }
+
+    /// Stops at statements on lines with breakpoints. Variables of the scope of the script
+    /// are listed when stopped.
+    #[cfg(feature = "debugger")]
+    fn enable_debugger(
+        &mut self,
+        debugger: crate::debugger::ScriptDebugger,
+    ) -> Result<(), ScriptingError> {
+        #[allow(deprecated)]
+        self.engine_mut().register_debugger(
+            |_, rhai_debugger| rhai_debugger,
+            move |context, _, node, source, position| {
+                if let (rhai::ASTNode::Stmt(_), Some(path), Some(line)) =
+                    (node, source, position.line())
+                {
+                    let line = line as u32;
+                    if debugger.should_stop(path, line) {
+                        debugger.stop(path, line, || {
+                            context
+                                .scope()
+                                .iter_raw()
+                                .map(|(name, _, value)| (name.to_string(), format!("{value:?}")))
+                                .collect()
+                        });
+                    }
+                }
+                Ok(rhai::debugger::DebuggerCommand::StepInto)
+            },
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "inspector")]
+    fn script_variables(
+        &self,
+        script_data: &Self::ScriptData,
+    ) -> Vec<crate::inspector::ScriptVariable> {
+        inspector::variables(&script_data.scope)
+    }
+
+    #[cfg(feature = "inspector")]
+    fn set_script_variable(
+        &self,
+        script_data: &mut Self::ScriptData,
+        name: &str,
+        value: crate::inspector::ScriptVariableValue,
+    ) -> Result<(), ScriptingError> {
+        inspector::set_variable(&mut script_data.scope, name, value)
+            .map_err(|e| ScriptingError::RuntimeError(e.into()))
+    }
+
+    /// Clones the scope of a script. Variables captured by closures are shared
+    /// with the snapshot rather than copied.
+    fn snapshot_state(
+        &self,
+        script_data: &Self::ScriptData,
+    ) -> Result<Self::Snapshot, ScriptingError> {
+        Ok(script_data.scope.clone())
+    }
+
+    fn restore_state(
+        &self,
+        script_data: &mut Self::ScriptData,
+        snapshot: &Self::Snapshot,
+    ) -> Result<(), ScriptingError> {
+        script_data.scope = snapshot.clone();
+        Ok(())
+    }
+
+    fn to_script_value(&self, value: &Self::Value, _script_data: &Self::ScriptData) -> ScriptValue {
+        value.clone().into()
+    }
+
+    fn format_value(&self, value: &Self::Value, _context: &Self::CallContext) -> String {
+        format!("{:?}", value.0)
+    }
+
     fn with_engine_mut<T>(&mut self, f: impl FnOnce(&mut Self::RawEngine) -> T) -> T {
-        f(&mut self.engine)
+        f(self.engine_mut())
     }
 
     fn with_engine<T>(&self, f: impl FnOnce(&Self::RawEngine) -> T) -> T {
@@ -162,6 +872,164 @@ impl Runtime for RhaiRuntime {
     }
 }
 
+impl From<Box<EvalAltResult>> for ScriptingError {
+    fn from(error: Box<EvalAltResult>) -> Self {
+        match *error {
+            EvalAltResult::ErrorMismatchDataType(ref expected, ref got, _)
+            | EvalAltResult::ErrorMismatchOutputType(ref expected, ref got, _) => {
+                ScriptingError::TypeMismatch {
+                    expected: expected.clone(),
+                    got: got.clone(),
+                }
+            }
+            EvalAltResult::ErrorTerminated(..) => ScriptingError::ScriptPanicked(error.to_string()),
+            _ => ScriptingError::RuntimeError(error),
+        }
+    }
+}
+
+impl RhaiRuntime {
+    /// Returns the name of the variable holding the entity that the running script is attached
+    /// to, if it is exposed to scripts.
+    fn entity_variable(&self) -> Option<String> {
+        self.entity_variable
+            .read()
+            .expect("Failed to lock entity variable")
+            .clone()
+    }
+
+    /// Registers a native Rhai module, e.g. one of a Rhai package, whose functions scripts call
+    /// as `name::function`. Functions registered within a namespace of the same name with
+    /// [ScriptingRuntimeBuilder::namespace] are kept alongside the functions of the module.
+    pub fn register_module(&mut self, name: &str, module: Module) {
+        let namespace = self.namespaces.entry(name.to_string()).or_default();
+        namespace.combine(module);
+        let namespace = namespace.clone();
+        self.engine_mut()
+            .register_static_module(name, namespace.into());
+    }
+
+    /// Registers a native Rhai module whose functions scripts call without a namespace, e.g.
+    /// a Rhai package obtained with `Package::as_shared_module`.
+    pub fn register_global_module(&mut self, module: Shared<Module>) {
+        self.engine_mut().register_global_module(module);
+    }
+
+    /// Registers a native function in the engine, or within the module of provided namespace.
+    fn install_fn(
+        &mut self,
+        namespace: Option<&str>,
+        name: String,
+        arg_types: Vec<TypeId>,
+        func: impl Fn(NativeCallContext, &mut [&mut Dynamic]) -> Result<Dynamic, Box<EvalAltResult>>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        match namespace {
+            Some(namespace) => {
+                let module = self.namespaces.entry(namespace.to_string()).or_default();
+                // Modules have no counterpart of `Engine::register_raw_fn`, so the function
+                // is registered the same way it does.
+                FuncRegistration::new(name).set_into_module_raw(
+                    module,
+                    arg_types,
+                    RhaiFunc::Method {
+                        func: Shared::new(
+                            move |context: Option<NativeCallContext>, args: &mut [&mut Dynamic]| {
+                                func(context.expect("Missing call context"), args)
+                            },
+                        ),
+                        has_context: true,
+                        is_pure: true,
+                        is_volatile: true,
+                    },
+                );
+                let module = module.clone();
+                self.engine_mut()
+                    .register_static_module(namespace, module.into());
+            }
+            None => {
+                self.engine_mut().register_raw_fn(name, arg_types, func);
+            }
+        }
+    }
+
+    /// Registers a function taking provided number of arguments of any type, which Rhai calls
+    /// when arguments passed to the function with provided name match none of its overloads.
+    /// It fails with an error naming the mismatched argument, or with
+    /// [ScriptingError::NoMatchingOverload] if the function has more overloads taking that many
+    /// arguments.
+    fn install_argument_check(&mut self, namespace: Option<&str>, name: &str, arity: usize) {
+        let any_types = vec![TypeId::of::<Dynamic>(); arity];
+        let overloads = self
+            .functions
+            .iter()
+            .filter(|(function_namespace, function_name, arg_types)| {
+                function_namespace.as_deref() == namespace
+                    && function_name == name
+                    && arg_types.len() == arity
+                    && *arg_types != any_types
+            })
+            .map(|(_, _, arg_types)| arg_types.clone())
+            .collect::<Vec<_>>();
+        let signature = (
+            namespace.map(ToString::to_string),
+            name.to_string(),
+            any_types.clone(),
+        );
+        if !self.functions.contains(&signature) {
+            self.functions.push(signature);
+        }
+        let function = name.to_string();
+        self.install_fn(
+            namespace,
+            name.to_string(),
+            any_types,
+            move |context: NativeCallContext, args: &mut [&mut Dynamic]| {
+                let error = match overloads.as_slice() {
+                    [arg_types] => invalid_argument(&function, arg_types, args, context.engine()),
+                    _ => None,
+                }
+                .unwrap_or_else(|| ScriptingError::NoMatchingOverload(function.clone()));
+                Err(Box::new(EvalAltResult::ErrorRuntime(
+                    error.to_string().into(),
+                    context.call_position(),
+                )))
+            },
+        );
+    }
+
+    fn engine_mut(&mut self) -> &mut Engine {
+        Arc::get_mut(&mut self.engine).expect("Rhai engine is used by a paused call")
+    }
+
+    /// Waits until a resumable call pauses again or finishes.
+    fn wait_for_call(
+        script_data: &mut RhaiScriptData,
+        call: PausedCall,
+    ) -> Result<CallProgress<RhaiValue>, ScriptingError> {
+        let message = call
+            .messages
+            .lock()
+            .expect("Failed to lock call messages")
+            .recv();
+        match message {
+            Ok(CallMessage::Paused) => {
+                script_data.paused = Some(call);
+                Ok(CallProgress::Paused)
+            }
+            Ok(CallMessage::Finished(result, scope)) => {
+                script_data.scope = scope;
+                result.map(|value| CallProgress::Finished(RhaiValue(value)))
+            }
+            Err(_) => Err(ScriptingError::ScriptPanicked(String::from(
+                "resumable call thread stopped unexpectedly",
+            ))),
+        }
+    }
+}
+
 impl Default for RhaiRuntime {
     fn default() -> Self {
         let mut engine = Engine::new();
@@ -178,6 +1046,31 @@ impl Default for RhaiRuntime {
                  callback: rhai::Dynamic| {
                     Promise::then(promise, RhaiValue(callback));
                 },
+            )
+            .register_fn(
+                "cancel",
+                |promise: &mut Promise<rhai::NativeCallContextStore, RhaiValue>| promise.cancel(),
+            )
+            .register_fn(
+                "timeout",
+                |promise: &mut Promise<rhai::NativeCallContextStore, RhaiValue>,
+                 seconds: f64|
+                 -> Result<_, Box<EvalAltResult>> {
+                    let duration =
+                        Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())?;
+                    Ok(promise.timeout(duration))
+                },
+            )
+            .register_fn(
+                "timeout",
+                |promise: &mut Promise<rhai::NativeCallContextStore, RhaiValue>,
+                 seconds: rhai::INT|
+                 -> Result<_, Box<EvalAltResult>> {
+                    let duration = u64::try_from(seconds)
+                        .map(Duration::from_secs)
+                        .map_err(|e| e.to_string())?;
+                    Ok(promise.timeout(duration))
+                },
             );
 
         engine
@@ -188,16 +1081,93 @@ impl Default for RhaiRuntime {
             .register_get("x", |vec: &mut Vec3| vec.x as f64)
             .register_get("y", |vec: &mut Vec3| vec.y as f64)
             .register_get("z", |vec: &mut Vec3| vec.z as f64);
+        // `debug` is a reserved keyword in Rhai, so the builtin `debug` function logs at
+        // debug level instead of `log::debug`.
+        let mut log = Module::new();
+        for (name, level) in LOG_FUNCTIONS {
+            if level == Level::DEBUG {
+                engine.on_debug(move |message, source, _| log_message(level, source, message));
+                continue;
+            }
+            log.set_native_fn(name, move |context: NativeCallContext, message: &str| {
+                log_message(level, context.call_source(), message);
+                Ok(())
+            });
+        }
+        engine.register_static_module("log", log.into());
+
+        let entity_variable = Arc::new(RwLock::new(Some(ENTITY_VAR_NAME.to_string())));
+        let defined_entity_variable = entity_variable.clone();
         #[allow(deprecated)]
-        engine.on_def_var(|_, info, _| Ok(info.name != "entity"));
+        engine.on_def_var(move |_, info, _| {
+            let entity_variable = defined_entity_variable
+                .read()
+                .expect("Failed to lock entity variable");
+            Ok(entity_variable.as_deref() != Some(info.name))
+        });
+
+        engine.on_progress(on_progress);
+
+        let modules = ModuleSources::default();
+        engine.set_module_resolver(AssetModuleResolver {
+            sources: modules.clone(),
+        });
 
-        RhaiRuntime { engine }
+        RhaiRuntime {
+            engine: Arc::new(engine),
+            modules,
+            constants: Vec::new(),
+            namespaces: HashMap::new(),
+            functions: Vec::new(),
+            cache: None,
+            entity_variable,
+        }
     }
 }
 
 impl<'a, T: Clone + Variant> IntoRuntimeValueWithEngine<'a, T, RhaiRuntime> for T {
     fn into_runtime_value_with_engine(value: T, _engine: &'a rhai::Engine) -> RhaiValue {
-        RhaiValue(Dynamic::from(value))
+        RhaiValue(into_dynamic(value))
+    }
+
+    fn try_into_runtime_value_with_engine(
+        value: T,
+        _engine: &'a rhai::Engine,
+    ) -> Result<RhaiValue, ScriptingError> {
+        Ok(RhaiValue(try_into_dynamic(value)?))
+    }
+}
+
+impl<'a, T: Serialize> IntoRuntimeValueWithEngine<'a, Json<T>, RhaiRuntime> for Json<T> {
+    fn into_runtime_value_with_engine(value: Json<T>, _engine: &'a rhai::Engine) -> RhaiValue {
+        RhaiValue(rhai::serde::to_dynamic(&value.0).expect("Failed to serialize value"))
+    }
+}
+
+impl<T: DeserializeOwned> FromRuntimeValueWithEngine<'_, RhaiRuntime> for Json<T> {
+    fn from_runtime_value_with_engine(
+        value: RhaiValue,
+        _engine: &rhai::Engine,
+        _context: &<RhaiRuntime as Runtime>::CallContext,
+    ) -> Self {
+        Json(rhai::serde::from_dynamic(&value.0).expect("Failed to deserialize value"))
+    }
+
+    fn arg_type_id() -> TypeId {
+        TypeId::of::<Dynamic>()
+    }
+}
+
+impl EntityArgs for RhaiRuntime {
+    type Args = (Entity,);
+    type ScriptEntity = Entity;
+
+    fn entity_args(entity: Entity) -> Self::Args {
+        (entity,)
+    }
+
+    fn script_entity(entity: Self::ScriptEntity) -> Entity {
+        entity
     }
 }
 
@@ -209,58 +1179,59 @@ impl FuncArgs<'_, RhaiValue, RhaiRuntime> for () {
 impl<T: Clone + Send + Sync + 'static> FuncArgs<'_, RhaiValue, RhaiRuntime> for Vec<T> {
     fn parse(self, _engine: &rhai::Engine) -> Vec<RhaiValue> {
         self.into_iter()
-            .map(|v| RhaiValue(Dynamic::from(v)))
+            .map(|v| RhaiValue(into_dynamic(v)))
             .collect()
     }
 }
 
 impl<T: Clone + 'static> FromRuntimeValueWithEngine<'_, RhaiRuntime> for T {
-    fn from_runtime_value_with_engine(value: RhaiValue, _engine: &rhai::Engine) -> Self {
-        value.0.clone_cast()
+    fn from_runtime_value_with_engine(
+        value: RhaiValue,
+        engine: &rhai::Engine,
+        context: &<RhaiRuntime as Runtime>::CallContext,
+    ) -> Self {
+        Self::try_from_runtime_value_with_engine(value, engine, context)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_from_runtime_value_with_engine(
+        value: RhaiValue,
+        _engine: &rhai::Engine,
+        _context: &<RhaiRuntime as Runtime>::CallContext,
+    ) -> Result<Self, ScriptingError> {
+        if let Some(number) = number::from_dynamic(&value.0) {
+            return Ok(number?);
+        }
+        Ok(from_dynamic(&value.0).unwrap_or_else(|| value.0.clone_cast()))
     }
 }
 
-pub mod prelude {
-    pub use super::{RhaiRuntime, RhaiScript, RhaiScriptData};
+impl ScriptingRuntimeBuilder<'_, RhaiRuntime> {
+    /// Registers a native Rhai module in the runtime, see [RhaiRuntime::register_module].
+    pub fn register_module(self, name: &str, module: Module) -> Self {
+        self.world
+            .resource_mut::<RhaiRuntime>()
+            .register_module(name, module);
+
+        self
+    }
+
+    /// Registers a native Rhai module in the runtime without a namespace, see
+    /// [RhaiRuntime::register_global_module].
+    pub fn register_global_module(self, module: Shared<Module>) -> Self {
+        self.world
+            .resource_mut::<RhaiRuntime>()
+            .register_global_module(module);
+
+        self
+    }
 }
 
-macro_rules! impl_tuple {
-    ($($idx:tt $t:tt),+) => {
-        impl<$($t: Clone +Variant,)+> FuncArgs<'_, RhaiValue, RhaiRuntime>
-            for ($($t,)+)
-        {
-            fn parse(self, _engine: &rhai::Engine) -> Vec<RhaiValue> {
-                vec![
-                    $(RhaiValue(Dynamic::from(self.$idx)), )+
-                ]
-            }
-        }
-    };
+pub mod prelude {
+    pub use super::{RhaiRuntime, RhaiScript, RhaiScriptData};
 }
 
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W, 23 X, 24 Y, 25 Z);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W, 23 X, 24 Y);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W, 23 X);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V, 22 W);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U, 21 V);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T, 20 U);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S, 19 T);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R, 18 S);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q, 17 R);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P, 16 Q);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O, 15 P);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N, 14 O);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M, 13 N);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L, 12 M);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
-impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E);
-impl_tuple!(0 A, 1 B, 2 C, 3 D);
-impl_tuple!(0 A, 1 B, 2 C);
-impl_tuple!(0 A, 1 B);
-impl_tuple!(0 A);
+impl_func_args_for_tuples!(
+    <'a> RhaiRuntime, RhaiValue, [Clone + Variant],
+    |_engine, value| RhaiValue(into_dynamic(value))
+);
@@ -0,0 +1,85 @@
+use rhai::{Dynamic, Map, Scope};
+
+use crate::inspector::{ScriptVariable, ScriptVariableValue, MAX_VARIABLE_DEPTH};
+
+/// Returns variables of provided scope holding primitive values, along with primitive fields of
+/// maps they hold. Variables shadowed by later ones with the same name are left out.
+pub(super) fn variables(scope: &Scope) -> Vec<ScriptVariable> {
+    let mut names = Vec::new();
+    let mut variables = Vec::new();
+    let scope_variables = scope.iter_raw().collect::<Vec<_>>();
+    for (name, _, value) in scope_variables.into_iter().rev() {
+        if names.contains(&name) {
+            continue;
+        }
+        names.push(name);
+        collect(name, value, 0, &mut variables);
+    }
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    variables
+}
+
+fn collect(name: &str, value: &Dynamic, depth: usize, variables: &mut Vec<ScriptVariable>) {
+    let value = value.flatten_clone();
+    let value = if let Some(value) = value.clone().try_cast::<bool>() {
+        ScriptVariableValue::Bool(value)
+    } else if let Some(value) = value.clone().try_cast::<rhai::INT>() {
+        ScriptVariableValue::Integer(value)
+    } else if let Some(value) = value.clone().try_cast::<rhai::FLOAT>() {
+        ScriptVariableValue::Number(value)
+    } else if value.is_string() {
+        ScriptVariableValue::String(value.to_string())
+    } else if value.is_map() && depth + 1 < MAX_VARIABLE_DEPTH {
+        let map = value.read_lock::<Map>().expect("Dynamic has to hold a map");
+        for (field, value) in map.iter() {
+            collect(&format!("{name}.{field}"), value, depth + 1, variables);
+        }
+        return;
+    } else {
+        return;
+    };
+    variables.push(ScriptVariable::new(name, value));
+}
+
+/// Sets the variable, or field of a map held by a variable, with provided dotted name.
+pub(super) fn set_variable(
+    scope: &mut Scope,
+    name: &str,
+    value: ScriptVariableValue,
+) -> Result<(), String> {
+    let value = match value {
+        ScriptVariableValue::Bool(value) => Dynamic::from(value),
+        ScriptVariableValue::Integer(value) => Dynamic::from(value),
+        ScriptVariableValue::Number(value) => Dynamic::from(value),
+        ScriptVariableValue::String(value) => Dynamic::from(value),
+    };
+    let mut keys = name.split('.');
+    let variable = keys.next().unwrap_or_default();
+    if scope.is_constant(variable).unwrap_or(false) {
+        return Err(format!("`{variable}` is a constant"));
+    }
+    let mut target = scope
+        .get_mut(variable)
+        .ok_or_else(|| format!("variable `{variable}` does not exist"))?
+        .write_lock::<Dynamic>()
+        .ok_or_else(|| format!("variable `{variable}` is locked"))?;
+    set_field(&mut target, keys, value)
+}
+
+fn set_field<'a>(
+    target: &mut Dynamic,
+    mut keys: impl Iterator<Item = &'a str>,
+    value: Dynamic,
+) -> Result<(), String> {
+    let Some(key) = keys.next() else {
+        *target = value;
+        return Ok(());
+    };
+    let mut map = target
+        .write_lock::<Map>()
+        .ok_or_else(|| format!("`{key}` is not a field of a map"))?;
+    let field = map
+        .get_mut(key)
+        .ok_or_else(|| format!("field `{key}` does not exist"))?;
+    set_field(field, keys, value)
+}
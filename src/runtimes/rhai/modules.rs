@@ -0,0 +1,72 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use bevy::asset::AssetPath;
+use rhai::{
+    ASTNode, Engine, EvalAltResult, Expr, Module, ModuleResolver, Position, Scope, Shared, Stmt,
+};
+
+/// Sources of modules imported by scripts, keyed by the path used in `import` statements.
+pub(super) type ModuleSources = Arc<RwLock<HashMap<String, String>>>;
+
+/// Returns paths used by `import` statements of provided script, along with asset paths of the
+/// modules. Paths are relative to the asset directory and `.rhai` extension can be omitted.
+/// Only imports with a constant string path can be resolved.
+pub(super) fn imports(source: &str) -> Vec<(String, Vec<AssetPath<'static>>)> {
+    let Ok(ast) = Engine::new_raw().compile(source) else {
+        return Vec::new();
+    };
+    let mut imports = Vec::new();
+    ast.walk(&mut |path| {
+        if let Some(ASTNode::Stmt(Stmt::Import(import, ..))) = path.last() {
+            if let Expr::StringConstant(name, ..) = &import.0 {
+                imports.push((name.to_string(), vec![module_asset_path(name)]));
+            }
+        }
+        true
+    });
+    imports
+}
+
+fn module_asset_path(name: &str) -> AssetPath<'static> {
+    if Path::new(name).extension().is_some() {
+        AssetPath::from(name.to_string())
+    } else {
+        AssetPath::from(format!("{name}.rhai"))
+    }
+}
+
+/// A module resolver looking imported modules up in sources read by the asset loader, instead
+/// of the file system, so that imports work with any asset source.
+pub(super) struct AssetModuleResolver {
+    pub(super) sources: ModuleSources,
+}
+
+impl ModuleResolver for AssetModuleResolver {
+    fn resolve(
+        &self,
+        engine: &Engine,
+        _source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        let source = self
+            .sources
+            .read()
+            .expect("Failed to lock module sources")
+            .get(path)
+            .cloned()
+            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))?;
+        let in_module = |error: Box<EvalAltResult>| {
+            Box::new(EvalAltResult::ErrorInModule(path.to_string(), error, pos))
+        };
+        let mut ast = engine.compile(source).map_err(|e| in_module(e.into()))?;
+        ast.set_source(path);
+        Module::eval_ast_as_new(Scope::new(), &ast, engine)
+            .map(Into::into)
+            .map_err(in_module)
+    }
+}
@@ -0,0 +1,169 @@
+use std::{any::Any, any::TypeId, fmt};
+
+use rhai::{Dynamic, Engine, FLOAT, INT};
+
+use crate::ScriptingError;
+
+/// Describes a number that doesn't fit the numeric type it is converted into.
+#[derive(Debug)]
+pub(super) struct OutOfRange {
+    value: String,
+    target: &'static str,
+}
+
+impl OutOfRange {
+    fn new(value: impl ToString, target: &'static str) -> Self {
+        Self {
+            value: value.to_string(),
+            target,
+        }
+    }
+}
+
+impl fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is out of range of {}", self.value, self.target)
+    }
+}
+
+impl std::error::Error for OutOfRange {}
+
+impl From<OutOfRange> for ScriptingError {
+    fn from(error: OutOfRange) -> Self {
+        ScriptingError::RuntimeError(Box::new(error))
+    }
+}
+
+/// Invokes provided macro with integer types other than [INT], which Rhai scripts see as
+/// integers.
+macro_rules! with_integer_types {
+    ($macro:ident) => {
+        $macro!(i8, i16, i32, i128, isize, u8, u16, u32, u64, u128, usize)
+    };
+}
+
+fn f32_from_float(value: FLOAT) -> Result<f32, OutOfRange> {
+    if value.is_finite() && value.abs() > f32::MAX as FLOAT {
+        return Err(OutOfRange::new(value, "f32"));
+    }
+    Ok(value as f32)
+}
+
+/// Converts provided value into a Rhai integer or float if it is a number of another width,
+/// as scripts can't do arithmetic on numbers of other types. Returns `None` if the value is not
+/// such a number.
+pub(super) fn into_dynamic(value: &dyn Any) -> Option<Result<Dynamic, OutOfRange>> {
+    macro_rules! convert {
+        ($($t:ty),*) => {
+            $(
+                if let Some(value) = value.downcast_ref::<$t>() {
+                    return Some(
+                        INT::try_from(*value)
+                            .map(Dynamic::from_int)
+                            .map_err(|_| OutOfRange::new(value, "Rhai integers")),
+                    );
+                }
+            )*
+        };
+    }
+    with_integer_types!(convert);
+    value
+        .downcast_ref::<f32>()
+        .map(|value| Ok(Dynamic::from_float(FLOAT::from(*value))))
+}
+
+/// Converts provided Rhai integer or float into `T` if it is a number type of another width.
+/// Returns `None` if `T` is not such a type or the value is not a number, and an error if the
+/// value is out of range of `T`.
+pub(super) fn from_dynamic<T: 'static>(value: &Dynamic) -> Option<Result<T, OutOfRange>> {
+    let mut result = None::<Result<T, OutOfRange>>;
+    let any = &mut result as &mut dyn Any;
+    macro_rules! convert {
+        ($($t:ty),*) => {
+            $(
+                if let Some(slot) = any.downcast_mut::<Option<Result<$t, OutOfRange>>>() {
+                    let value = value.as_int().ok()?;
+                    *slot = Some(
+                        <$t>::try_from(value).map_err(|_| OutOfRange::new(value, stringify!($t))),
+                    );
+                    return result;
+                }
+            )*
+        };
+    }
+    with_integer_types!(convert);
+    if let Some(slot) = any.downcast_mut::<Option<Result<f32, OutOfRange>>>() {
+        *slot = Some(f32_from_float(value.as_float().ok()?));
+        return result;
+    }
+    None
+}
+
+/// Returns the type of Rhai values passed as parameters of provided numeric type, if it is
+/// a number type of another width than Rhai numbers.
+pub(super) fn script_type_id(arg_type: TypeId) -> Option<TypeId> {
+    macro_rules! convert {
+        ($($t:ty),*) => {
+            $(
+                if arg_type == TypeId::of::<$t>() {
+                    return Some(TypeId::of::<INT>());
+                }
+            )*
+        };
+    }
+    with_integer_types!(convert);
+    (arg_type == TypeId::of::<f32>()).then(TypeId::of::<FLOAT>)
+}
+
+/// Checks whether a Rhai number fits a parameter of a certain numeric type.
+pub(super) type RangeCheck = fn(&Dynamic) -> Option<OutOfRange>;
+
+/// Returns the name of provided numeric parameter type, along with a check whether Rhai numbers
+/// passed as the parameter fit it, if it is a number type of another width than Rhai numbers.
+pub(super) fn range_check(arg_type: TypeId) -> Option<(&'static str, RangeCheck)> {
+    macro_rules! check {
+        ($($t:ty),*) => {
+            $(
+                if arg_type == TypeId::of::<$t>() {
+                    return Some((
+                        concat!("integer in range of ", stringify!($t)),
+                        |value| from_dynamic::<$t>(value)?.err(),
+                    ));
+                }
+            )*
+        };
+    }
+    with_integer_types!(check);
+    (arg_type == TypeId::of::<f32>()).then_some((
+        "float in range of f32",
+        (|value| from_dynamic::<f32>(value)?.err()) as RangeCheck,
+    ))
+}
+
+/// Returns an error naming the first of provided arguments that is out of range of its numeric
+/// parameter, given checks returned by [range_check] for every parameter.
+pub(super) fn out_of_range_argument(
+    function: &str,
+    range_checks: &[Option<(&'static str, RangeCheck)>],
+    args: &[&mut Dynamic],
+    engine: &Engine,
+) -> Option<ScriptingError> {
+    range_checks
+        .iter()
+        .zip(args)
+        .enumerate()
+        .find_map(|(index, (range_check, arg))| {
+            let (expected, range_check) = (*range_check)?;
+            let error = range_check(arg)?;
+            Some(ScriptingError::InvalidArgument {
+                function: function.to_string(),
+                position: index + 1,
+                expected: expected.to_string(),
+                got: format!(
+                    "{} {}",
+                    engine.map_type_name(Dynamic::type_name(arg)),
+                    error.value
+                ),
+            })
+        })
+}
@@ -0,0 +1,76 @@
+use bevy::{ecs::entity::Entity, math::Vec3};
+use rhai::{Dynamic, ImmutableString};
+
+use crate::ScriptValue;
+
+use super::RhaiValue;
+
+/// Converts provided value into a [Dynamic], turning arrays and maps into Rhai arrays and
+/// object maps.
+pub(super) fn into_dynamic(value: ScriptValue) -> Dynamic {
+    match value {
+        ScriptValue::Nil => Dynamic::UNIT,
+        ScriptValue::Bool(value) => value.into(),
+        ScriptValue::Int(value) => value.into(),
+        ScriptValue::Float(value) => value.into(),
+        ScriptValue::String(value) => value.into(),
+        ScriptValue::Array(values) => {
+            Dynamic::from_array(values.into_iter().map(into_dynamic).collect())
+        }
+        ScriptValue::Map(values) => Dynamic::from_map(
+            values
+                .into_iter()
+                .map(|(key, value)| (key.into(), into_dynamic(value)))
+                .collect(),
+        ),
+        ScriptValue::Entity(value) => Dynamic::from(value),
+        ScriptValue::Vec3(value) => Dynamic::from(value),
+    }
+}
+
+/// Converts provided [Dynamic] into a [ScriptValue], returning `None` if it holds a value of
+/// another type, e.g. a function pointer.
+pub(super) fn from_dynamic(value: &Dynamic) -> Option<ScriptValue> {
+    let value = value.flatten_clone();
+    if value.is_unit() {
+        Some(ScriptValue::Nil)
+    } else if let Some(value) = value.clone().try_cast::<bool>() {
+        Some(ScriptValue::Bool(value))
+    } else if let Some(value) = value.clone().try_cast::<rhai::INT>() {
+        Some(ScriptValue::Int(value))
+    } else if let Some(value) = value.clone().try_cast::<rhai::FLOAT>() {
+        Some(ScriptValue::Float(value))
+    } else if let Some(value) = value.clone().try_cast::<ImmutableString>() {
+        Some(ScriptValue::String(value.to_string()))
+    } else if let Some(values) = value.clone().try_cast::<rhai::Array>() {
+        values
+            .iter()
+            .map(from_dynamic)
+            .collect::<Option<_>>()
+            .map(ScriptValue::Array)
+    } else if let Some(values) = value.clone().try_cast::<rhai::Map>() {
+        values
+            .iter()
+            .map(|(key, value)| Some((key.to_string(), from_dynamic(value)?)))
+            .collect::<Option<_>>()
+            .map(ScriptValue::Map)
+    } else if let Some(value) = value.clone().try_cast::<Entity>() {
+        Some(ScriptValue::Entity(value))
+    } else {
+        value.try_cast::<Vec3>().map(ScriptValue::Vec3)
+    }
+}
+
+impl From<ScriptValue> for RhaiValue {
+    fn from(value: ScriptValue) -> Self {
+        RhaiValue(into_dynamic(value))
+    }
+}
+
+/// Values of types that [ScriptValue] can't hold, e.g. function pointers, get converted into
+/// [ScriptValue::Nil].
+impl From<RhaiValue> for ScriptValue {
+    fn from(value: RhaiValue) -> Self {
+        from_dynamic(&value.0).unwrap_or_default()
+    }
+}
@@ -0,0 +1,210 @@
+//! Starter scripts for modders.
+//!
+//! [starter_script] returns a script defining empty stubs of every hook that the library calls
+//! on scripts, e.g. `on_fixed_update`, preceded by a comment listing functions registered by
+//! the game, so that modders can start from a script showing everything available to them.
+//! It can be written from a dev command of the game, or from a build script, with functions
+//! listed by [ScriptApiRegistry](crate::ScriptApiRegistry):
+//!
+//! ```no_run
+//! use bevy::{ecs::system::RunSystemOnce as _, prelude::*};
+//! use bevy_scriptum::prelude::*;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//! use bevy_scriptum::{scaffold, ScriptApiRegistry};
+//!
+//! let mut app = App::new();
+//! app.add_plugins(DefaultPlugins)
+//!     .add_scripting::<LuaRuntime>(|runtime| {
+//!         runtime.add_function(String::from("hello_bevy"), || println!("hello bevy"));
+//!     });
+//! let functions = app
+//!     .world_mut()
+//!     .run_system_once(|registry: ScriptApiRegistry<LuaRuntime>| registry.iter().collect::<Vec<_>>());
+//! scaffold::write_starter_script("mods/template/main.lua", &functions).unwrap();
+//! ```
+
+use std::{fmt::Write as _, io, path::Path};
+
+use crate::ScriptFunctionInfo;
+
+/// Language of a starter script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaffoldLanguage {
+    Lua,
+    Rhai,
+}
+
+impl ScaffoldLanguage {
+    /// Returns the language of scripts with provided extension, e.g. `lua`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "lua" => Some(Self::Lua),
+            "rhai" => Some(Self::Rhai),
+            _ => None,
+        }
+    }
+
+    fn comment(self) -> &'static str {
+        match self {
+            Self::Lua => "--",
+            Self::Rhai => "//",
+        }
+    }
+
+    fn namespace_separator(self) -> &'static str {
+        match self {
+            Self::Lua => ".",
+            Self::Rhai => "::",
+        }
+    }
+}
+
+/// Hooks that the library calls on scripts, along with their parameters and descriptions.
+const HOOKS: [(&str, &[&str], &str); 8] = [
+    (
+        "on_fixed_update",
+        &["dt"],
+        "Called every fixed timestep with its length in seconds, once enabled with `enable_fixed_update`.",
+    ),
+    (
+        "on_suspend",
+        &[],
+        "Called when no activator is within the activation range of the entity.",
+    ),
+    (
+        "on_resume",
+        &[],
+        "Called when an activator gets within the activation range of the entity again.",
+    ),
+    (
+        "on_collision_start",
+        &["other"],
+        "Called when the entity starts colliding with another entity.",
+    ),
+    (
+        "on_collision_stop",
+        &["other"],
+        "Called when the entity stops colliding with another entity.",
+    ),
+    (
+        "on_animation_event",
+        &["event"],
+        "Called when an animation of the entity passes a marker.",
+    ),
+    (
+        "on_exit",
+        &[],
+        "Called when the state that the script has been run for is exited.",
+    ),
+    (
+        "on_detach",
+        &[],
+        "Called when the script is detached from its entity.",
+    ),
+];
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Returns provided Rust type name with module paths stripped, e.g. `Option<String>`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    let mut start = 0;
+    for (index, character) in name.char_indices() {
+        if character.is_alphanumeric() || character == '_' || character == ':' {
+            continue;
+        }
+        short.push_str(last_segment(&name[start..index]));
+        short.push(character);
+        start = index + character.len_utf8();
+    }
+    short.push_str(last_segment(&name[start..]));
+    short
+}
+
+/// Returns a starter script in provided language, with stubs of all hooks and a comment
+/// describing provided registered functions.
+pub fn starter_script<'a>(
+    language: ScaffoldLanguage,
+    functions: impl IntoIterator<Item = &'a ScriptFunctionInfo>,
+) -> String {
+    let comment = language.comment();
+    let mut script = format!("{comment} Starter script generated by bevy_scriptum.\n");
+    let _ = writeln!(
+        script,
+        "{comment}\n{comment} `entity` holds the entity this script is attached to."
+    );
+
+    let mut functions = functions.into_iter().peekable();
+    if functions.peek().is_some() {
+        let _ = writeln!(
+            script,
+            "{comment}\n{comment} Functions registered by the game, each returning a promise:"
+        );
+    }
+    for function in functions {
+        let name = match &function.namespace {
+            Some(namespace) => {
+                format!(
+                    "{namespace}{}{}",
+                    language.namespace_separator(),
+                    function.name
+                )
+            }
+            None => function.name.clone(),
+        };
+        let args = function
+            .arg_type_names
+            .iter()
+            .map(|arg| short_type_name(arg))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(
+            script,
+            "{comment}   {name}({args}) -> {}",
+            short_type_name(function.return_type_name)
+        );
+        if let Some(source) = &function.source {
+            let _ = write!(script, ", registered by {source}");
+        }
+        script.push('\n');
+    }
+
+    for (name, params, description) in HOOKS {
+        let params = params.join(", ");
+        script.push('\n');
+        match language {
+            ScaffoldLanguage::Lua => {
+                let _ = writeln!(script, "--- {description}\nfunction {name}({params})\nend");
+            }
+            ScaffoldLanguage::Rhai => {
+                let _ = writeln!(script, "/// {description}\nfn {name}({params}) {{\n}}");
+            }
+        }
+    }
+    script
+}
+
+/// Writes a starter script, see [starter_script], to provided path, in the language matching
+/// its extension.
+pub fn write_starter_script<'a>(
+    path: impl AsRef<Path>,
+    functions: impl IntoIterator<Item = &'a ScriptFunctionInfo>,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let language = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(ScaffoldLanguage::from_extension)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported script extension of {}", path.display()),
+            )
+        })?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, starter_script(language, functions))
+}
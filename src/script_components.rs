@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{EntityArgs, ScriptValue};
+
+/// Name of the function that scripts use to declare components.
+pub(crate) const DEFINE_COMPONENT_FN_NAME: &str = "define_component";
+
+/// Namespace of functions that scripts use to add, query and remove components of entities.
+pub(crate) const COMPONENTS_NAMESPACE: &str = "components";
+
+/// A resource storing components declared by scripts with `define_component`, e.g.
+/// `define_component("Burning", { duration = 3 })`, along with the values that entities get
+/// when a component is added to them. Components can be defined from Rust as well.
+#[derive(Resource, Default, Debug)]
+pub struct ScriptComponentDefinitions {
+    defaults: HashMap<String, ScriptValue>,
+}
+
+impl ScriptComponentDefinitions {
+    /// Defines a component with provided name, replacing its default value if it is already
+    /// defined. Components without fields, used as markers, have a [ScriptValue::Nil] default.
+    pub fn define(&mut self, name: impl Into<String>, default: impl Into<ScriptValue>) {
+        self.defaults.insert(name.into(), default.into());
+    }
+
+    /// Returns the default value of the component with provided name, or `None` if it is not
+    /// defined.
+    pub fn get(&self, name: &str) -> Option<&ScriptValue> {
+        self.defaults.get(name)
+    }
+
+    /// Returns whether a component with provided name is defined.
+    pub fn contains(&self, name: &str) -> bool {
+        self.defaults.contains_key(name)
+    }
+}
+
+/// A component storing components that scripts have added to its entity, by their names, so
+/// that gameplay state defined only in scripts can be queried from Rust, e.g. with
+/// `Query<&ScriptComponents>` filtered by [ScriptComponents::has], and from other scripts.
+///
+/// Scripts add components with `components.add(entity, "Burning")`, check them with
+/// `components.has(entity, "Burning")`, get and set their values with `components.get` and
+/// `components.set`, and remove them with `components.remove`, all of which return promises, in
+/// Rhai called as `components::add` etc. Requires
+/// [ScriptingRuntimeBuilder::enable_script_components](crate::ScriptingRuntimeBuilder::enable_script_components).
+#[derive(Component, Default, Debug, Clone, PartialEq)]
+pub struct ScriptComponents(HashMap<String, ScriptValue>);
+
+impl ScriptComponents {
+    /// Returns whether the entity has the component with provided name.
+    pub fn has(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Returns the value of the component with provided name, or `None` if the entity does
+    /// not have it.
+    pub fn get(&self, name: &str) -> Option<&ScriptValue> {
+        self.0.get(name)
+    }
+
+    /// Adds the component with provided name, or replaces its value if the entity has it.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<ScriptValue>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    /// Removes the component with provided name, returning its value.
+    pub fn remove(&mut self, name: &str) -> Option<ScriptValue> {
+        self.0.remove(name)
+    }
+
+    /// Returns an iterator over names and values of the components.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ScriptValue)> {
+        self.0.iter()
+    }
+}
+
+/// Defines a component, registered as `define_component` function.
+pub(crate) fn define_script_component(
+    In((name, default)): In<(String, ScriptValue)>,
+    mut definitions: ResMut<ScriptComponentDefinitions>,
+) {
+    definitions.define(name, default);
+}
+
+/// Adds a component with provided value to provided entity, inserting [ScriptComponents] into
+/// it if it has none yet.
+fn insert_script_component(
+    commands: &mut Commands,
+    entity: Entity,
+    name: String,
+    value: ScriptValue,
+) {
+    let Some(mut entity_commands) = commands.get_entity(entity) else {
+        tracing::error!(
+            "can not add component `{}` to missing entity {:?}",
+            name,
+            entity
+        );
+        return;
+    };
+    entity_commands.add(move |entity: Entity, world: &mut World| {
+        match world.get_mut::<ScriptComponents>(entity) {
+            Some(mut components) => components.insert(name, value),
+            None => {
+                let mut components = ScriptComponents::default();
+                components.insert(name, value);
+                world.entity_mut(entity).insert(components);
+            }
+        }
+    });
+}
+
+/// Adds a component with its default value to an entity, registered as `components.add`.
+pub(crate) fn add_script_component<R: EntityArgs>(
+    In((entity, name)): In<(R::ScriptEntity, String)>,
+    definitions: Res<ScriptComponentDefinitions>,
+    mut commands: Commands,
+) {
+    let Some(default) = definitions.get(&name) else {
+        tracing::error!("component `{}` is not defined", name);
+        return;
+    };
+    insert_script_component(
+        &mut commands,
+        R::script_entity(entity),
+        name,
+        default.clone(),
+    );
+}
+
+/// Sets the value of a component of an entity, adding it if the entity does not have it,
+/// registered as `components.set`.
+pub(crate) fn set_script_component<R: EntityArgs>(
+    In((entity, name, value)): In<(R::ScriptEntity, String, ScriptValue)>,
+    definitions: Res<ScriptComponentDefinitions>,
+    mut commands: Commands,
+) {
+    if !definitions.contains(&name) {
+        tracing::error!("component `{}` is not defined", name);
+        return;
+    }
+    insert_script_component(&mut commands, R::script_entity(entity), name, value);
+}
+
+/// Returns whether an entity has a component, registered as `components.has`.
+pub(crate) fn has_script_component<R: EntityArgs>(
+    In((entity, name)): In<(R::ScriptEntity, String)>,
+    components: Query<&ScriptComponents>,
+) -> bool {
+    components
+        .get(R::script_entity(entity))
+        .is_ok_and(|components| components.has(&name))
+}
+
+/// Returns the value of a component of an entity, registered as `components.get`.
+pub(crate) fn get_script_component<R: EntityArgs>(
+    In((entity, name)): In<(R::ScriptEntity, String)>,
+    components: Query<&ScriptComponents>,
+) -> ScriptValue {
+    components
+        .get(R::script_entity(entity))
+        .ok()
+        .and_then(|components| components.get(&name).cloned())
+        .unwrap_or_default()
+}
+
+/// Removes a component from an entity, registered as `components.remove`.
+pub(crate) fn remove_script_component<R: EntityArgs>(
+    In((entity, name)): In<(R::ScriptEntity, String)>,
+    mut components: Query<&mut ScriptComponents>,
+) {
+    if let Ok(mut components) = components.get_mut(R::script_entity(entity)) {
+        components.remove(&name);
+    }
+}
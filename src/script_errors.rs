@@ -0,0 +1,110 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{assets::ScriptLoadFailures, Runtime, Script, ScriptingError};
+
+/// An event sent when a script of runtime `R` fails to load, e.g. because it is not valid UTF-8,
+/// or to compile, so that the game can show the error to modders rather than the script silently
+/// not running. It is sent for every entity that the script, or a base of it, is attached to.
+#[derive(Event)]
+pub struct ScriptError<R: Runtime> {
+    /// Entity that the script is attached to, or `None` if it is not attached to any entity.
+    pub entity: Option<Entity>,
+    /// Handle of the script asset that failed, which is a base of the script attached to the
+    /// entity if the base failed to compile.
+    pub script: Handle<R::ScriptAsset>,
+    /// Path of the script asset that failed.
+    pub path: String,
+    pub error: ScriptingError,
+}
+
+/// Sends [ScriptError] events for scripts of runtime `R` that have failed to load since it last
+/// ran. Failures of scripts that nothing holds a handle to anymore are dropped.
+pub(crate) fn send_load_errors<R: Runtime>(
+    failures: Res<ScriptLoadFailures<R::ScriptAsset>>,
+    asset_server: Res<AssetServer>,
+    scripts: Query<(Entity, &Script<R::ScriptAsset>)>,
+    mut errors: EventWriter<ScriptError<R>>,
+) {
+    let failures = std::mem::take(
+        &mut *failures
+            .failures
+            .lock()
+            .expect("Failed to lock script load failures"),
+    );
+    for (path, error) in failures {
+        let Some(handle) = asset_server.get_handle::<R::ScriptAsset>(path.clone()) else {
+            continue;
+        };
+        let mut entities = scripts
+            .iter()
+            .filter(|(_, script)| script.script == handle || script.bases.contains(&handle))
+            .map(|(entity, _)| Some(entity))
+            .collect::<Vec<_>>();
+        if entities.is_empty() {
+            entities.push(None);
+        }
+        for entity in entities {
+            errors.send(ScriptError {
+                entity,
+                script: handle.clone(),
+                path: path.to_string(),
+                error: ScriptingError::LoadError(error.clone()),
+            });
+        }
+    }
+}
+
+/// Location and description of an error in a script that failed to compile, so that e.g. an
+/// in-game editor can underline the offending line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptDiagnostic {
+    /// Path of the script that failed to compile.
+    pub file: String,
+    /// Line of the error, starting at 1, if the runtime reports it.
+    pub line: Option<usize>,
+    /// Column of the error, starting at 1, if the runtime reports it.
+    pub column: Option<usize>,
+    pub message: String,
+    /// Source of the line of the error, if known.
+    pub snippet: Option<String>,
+}
+
+impl ScriptDiagnostic {
+    /// Creates a diagnostic of an error at provided position of provided script source, taking
+    /// the snippet from the source.
+    pub(crate) fn new(
+        file: impl Into<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: impl Into<String>,
+        source: &str,
+    ) -> Self {
+        let snippet = line
+            .and_then(|line| source.lines().nth(line.checked_sub(1)?))
+            .map(ToString::to_string);
+        Self {
+            file: file.into(),
+            line,
+            column,
+            message: message.into(),
+            snippet,
+        }
+    }
+}
+
+impl fmt::Display for ScriptDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ScriptDiagnostic {}
@@ -0,0 +1,66 @@
+use crate::{callback::FromRuntimeValueWithEngine, FuncArgs, Runtime, ScriptingError};
+
+/// A function passed from a script to a Rust function, which can be stored and called later,
+/// for example to notify scripts about events they have subscribed to.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_scriptum::{prelude::*, ScriptFunction};
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// #[derive(Resource, Default)]
+/// struct CollisionListeners(Vec<ScriptFunction<LuaRuntime>>);
+///
+/// fn notify_listeners(listeners: Res<CollisionListeners>, runtime: Res<LuaRuntime>) {
+///     for listener in &listeners.0 {
+///         listener.call(&*runtime, (1,)).unwrap();
+///     }
+/// }
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .init_resource::<CollisionListeners>()
+///     .add_scripting::<LuaRuntime>(|runtime| {
+///         runtime.add_function(
+///             String::from("on_collision"),
+///             |In((listener,)): In<(ScriptFunction<LuaRuntime>,)>,
+///              mut listeners: ResMut<CollisionListeners>| {
+///                 listeners.0.push(listener);
+///             },
+///         );
+///     })
+///     .add_systems(Update, notify_listeners)
+///     .run();
+/// ```
+///
+/// Like [Json](crate::Json) it does not implement [Clone], since runtimes convert any
+/// cloneable type into an opaque value.
+pub struct ScriptFunction<R: Runtime> {
+    function: R::Value,
+    context: R::CallContext,
+}
+
+impl<R: Runtime> ScriptFunction<R> {
+    /// Calls the function with provided arguments, returning the value it returns.
+    pub fn call(
+        &self,
+        runtime: &R,
+        args: impl for<'a> FuncArgs<'a, R::Value, R>,
+    ) -> Result<R::Value, ScriptingError> {
+        let args = runtime.with_context_engine(&self.context, |engine| args.parse(engine));
+        runtime.call_fn_from_value(&self.function, &self.context, args)
+    }
+}
+
+impl<'a, R: Runtime> FromRuntimeValueWithEngine<'a, R> for ScriptFunction<R> {
+    fn from_runtime_value_with_engine(
+        value: R::Value,
+        _engine: &'a R::RawEngine,
+        context: &R::CallContext,
+    ) -> Self {
+        Self {
+            function: value,
+            context: context.clone(),
+        }
+    }
+}
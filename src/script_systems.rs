@@ -0,0 +1,117 @@
+use std::{marker::PhantomData, sync::Mutex};
+
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+    utils::tracing,
+};
+
+use crate::{FuncArgs, Runtime, ScriptFunction};
+
+/// Name of the function that scripts use to register a function running every frame in a schedule.
+pub(crate) const REGISTER_SYSTEM_FN_NAME: &str = "register_system";
+
+/// Name of the function that scripts use to remove a function registered with `register_system`.
+pub(crate) const UNREGISTER_SYSTEM_FN_NAME: &str = "unregister_system";
+
+/// Returns schedules that scripts can register functions into, along with their names.
+pub(crate) fn script_system_schedules() -> [(&'static str, InternedScheduleLabel); 4] {
+    [
+        ("PreUpdate", PreUpdate.intern()),
+        ("Update", Update.intern()),
+        ("PostUpdate", PostUpdate.intern()),
+        ("FixedUpdate", FixedUpdate.intern()),
+    ]
+}
+
+struct ScriptSystem<R: Runtime> {
+    id: i64,
+    schedule: String,
+    function: ScriptFunction<R>,
+}
+
+/// A resource that stores functions that scripts of runtime `R` have registered into schedules.
+#[derive(Resource)]
+pub(crate) struct ScriptSystems<R: Runtime> {
+    next_id: i64,
+    systems: Mutex<Vec<ScriptSystem<R>>>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for ScriptSystems<R> {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            systems: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// Registers provided function into the schedule with provided name, registered as
+/// `register_system` function. Returns an id that can be passed to `unregister_system`.
+pub(crate) fn register_script_system<R: Runtime>(
+    In((schedule, function)): In<(String, ScriptFunction<R>)>,
+    mut script_systems: ResMut<ScriptSystems<R>>,
+) -> Option<i64> {
+    if !script_system_schedules()
+        .iter()
+        .any(|(name, _)| *name == schedule)
+    {
+        tracing::error!(
+            "functions can not be registered into schedule `{}`",
+            schedule
+        );
+        return None;
+    }
+    let id = script_systems.next_id;
+    script_systems.next_id += 1;
+    script_systems
+        .systems
+        .get_mut()
+        .expect("Failed to lock script systems")
+        .push(ScriptSystem {
+            id,
+            schedule,
+            function,
+        });
+    Some(id)
+}
+
+/// Removes the function with provided id, registered as `unregister_system` function.
+pub(crate) fn unregister_script_system<R: Runtime>(
+    In((id,)): In<(i64,)>,
+    mut script_systems: ResMut<ScriptSystems<R>>,
+) {
+    script_systems
+        .systems
+        .get_mut()
+        .expect("Failed to lock script systems")
+        .retain(|system| system.id != id);
+}
+
+/// Returns a system calling functions registered into the schedule with provided name, passing
+/// them the time elapsed since the last run of the schedule in seconds.
+pub(crate) fn run_script_systems<R: Runtime>(
+    schedule: &'static str,
+) -> impl Fn(Res<ScriptSystems<R>>, Res<R>, Res<Time>)
+where
+    (f64,): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    move |script_systems, runtime, time| {
+        let systems = script_systems
+            .systems
+            .lock()
+            .expect("Failed to lock script systems");
+        for system in systems.iter().filter(|system| system.schedule == schedule) {
+            if let Err(e) = system.function.call(&*runtime, (time.delta_seconds_f64(),)) {
+                tracing::error!(
+                    "error running script system {} in {}: {}",
+                    system.id,
+                    schedule,
+                    e
+                );
+            }
+        }
+    }
+}
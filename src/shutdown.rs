@@ -0,0 +1,94 @@
+use std::marker::PhantomData;
+
+use bevy::{app::MainScheduleOrder, ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::{
+    assets::{ScriptDecoders, ScriptPreprocessors},
+    boot::BootScripts,
+    declarations::SignatureDeclarations,
+    named_systems::NamedSystems,
+    promise::{PendingCalls, PromiseQueue},
+    reload::{ReloadHooks, ScriptRevision},
+    required_functions::RequiredFunctions,
+    script_systems::ScriptSystems,
+    Callbacks, CompiledScriptCache, Runtime, ScriptCallQueue,
+};
+
+/// A resource counting how many times runtime `R` has been removed with
+/// [BuildScriptingRuntime::remove_scripting](crate::BuildScriptingRuntime::remove_scripting).
+/// Systems that scripting adds to schedules other than [Runtime::Schedule] can't be removed, so
+/// they only run while the generation they have been added in is the current one.
+#[derive(Resource)]
+pub(crate) struct ScriptingGeneration<R: Runtime> {
+    generation: u32,
+    _phantom_data: PhantomData<R>,
+}
+
+/// Returns the current generation of runtime `R`.
+pub(crate) fn current_generation<R: Runtime>(world: &World) -> u32 {
+    world
+        .get_resource::<ScriptingGeneration<R>>()
+        .map_or(0, |generation| generation.generation)
+}
+
+/// Returns a run condition that is met while runtime `R` exists and has not been removed since
+/// provided generation.
+pub(crate) fn in_generation<R: Runtime>(
+    generation: u32,
+) -> impl FnMut(Option<Res<R>>, Option<Res<ScriptingGeneration<R>>>) -> bool + Send + Sync + 'static
+{
+    move |runtime, current| {
+        runtime.is_some() && current.map_or(0, |current| current.generation) == generation
+    }
+}
+
+/// Removes runtime `R` along with callbacks registered in it, its schedule and data of all
+/// scripts it runs, keeping their [Script](crate::Script) components, so that they get evaluated
+/// again if scripting is added back.
+pub(crate) fn remove_scripting<R: Runtime>(world: &mut World) {
+    let Some(mut runtime) = world.remove_resource::<R>() else {
+        return;
+    };
+    runtime.shutdown();
+    drop(runtime);
+
+    let entities = world
+        .query_filtered::<Entity, With<R::ScriptData>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for entity in entities {
+        let mut entity = world.entity_mut(entity);
+        entity.remove::<(R::ScriptData, ScriptRevision<R>)>();
+        #[cfg(feature = "inspector")]
+        entity.remove::<crate::inspector::ScriptVariables>();
+    }
+
+    world.remove_resource::<Callbacks<R>>();
+    world.remove_resource::<CompiledScriptCache<R>>();
+    world.remove_resource::<PromiseQueue<R>>();
+    world.remove_resource::<PendingCalls<R>>();
+    world.remove_resource::<ReloadHooks<R>>();
+    world.remove_resource::<BootScripts<R>>();
+    world.remove_resource::<NamedSystems<R>>();
+    world.remove_resource::<ScriptSystems<R>>();
+    world.remove_resource::<RequiredFunctions<R>>();
+    world.remove_resource::<SignatureDeclarations<R>>();
+    world.remove_resource::<ScriptCallQueue<R>>();
+    #[cfg(feature = "states")]
+    world.remove_resource::<crate::states::ExposedStates<R>>();
+    world.remove_resource::<ScriptPreprocessors<R::ScriptAsset>>();
+    world.remove_resource::<ScriptDecoders<R::ScriptAsset>>();
+
+    let schedule = R::Schedule::default().intern();
+    world.resource_mut::<Schedules>().remove(schedule);
+    world
+        .resource_mut::<MainScheduleOrder>()
+        .labels
+        .retain(|label| *label != schedule);
+
+    let generation = current_generation::<R>(world) + 1;
+    world.insert_resource(ScriptingGeneration::<R> {
+        generation,
+        _phantom_data: PhantomData,
+    });
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    asset::AssetPath,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, ReflectRef},
+    state::state::FreelyMutableState,
+    utils::tracing,
+};
+
+use crate::{
+    boot::eval_script_blocking,
+    systems::{init_callbacks, process_calls},
+    FuncArgs, Runtime,
+};
+
+/// Name of the function that a state script can define to run when its state is exited.
+pub(crate) const ON_EXIT_FN_NAME: &str = "on_exit";
+
+/// Adds systems evaluating the script with provided path when `state` is entered, and calling
+/// its `on_exit` function and despawning it when the state is exited. Both run within the
+/// state transition and so do calls the script makes to registered functions, so that their
+/// effects are complete once the transition is done.
+pub(crate) fn add_state_script<R: Runtime, S: States>(
+    app: &mut App,
+    state: S,
+    path: AssetPath<'static>,
+) where
+    (): for<'a> FuncArgs<'a, R::Value, R>,
+{
+    let entity = Arc::new(Mutex::new(None));
+    let entered = entity.clone();
+    app.add_systems(OnEnter(state.clone()), move |world: &mut World| {
+        if !world.contains_resource::<R>() {
+            return;
+        }
+        if let Err(e) = init_callbacks::<R>(world) {
+            tracing::error!("{}", e);
+        }
+        let mut entity = entered.lock().expect("Failed to lock state script entity");
+        *entity = eval_script_blocking::<R>(world, &path);
+        if let Err(e) = process_calls::<R>(world) {
+            tracing::error!("{}", e);
+        }
+    });
+    app.add_systems(OnExit(state), move |world: &mut World| {
+        let Some(entity) = entity
+            .lock()
+            .expect("Failed to lock state script entity")
+            .take()
+        else {
+            return;
+        };
+        if !world.contains_resource::<R>() {
+            world.despawn(entity);
+            return;
+        }
+        world.resource_scope(|world, runtime: Mut<R>| {
+            let Some(mut script_data) = world.get_mut::<R::ScriptData>(entity) else {
+                return;
+            };
+            if let Err(e) = runtime.call_fn_if_exists(ON_EXIT_FN_NAME, &mut script_data, entity, ())
+            {
+                tracing::error!(
+                    "error calling '{}' on entity {:?}: {}",
+                    ON_EXIT_FN_NAME,
+                    entity,
+                    e
+                );
+            }
+        });
+        if let Err(e) = process_calls::<R>(world) {
+            tracing::error!("{}", e);
+        }
+        world.despawn(entity);
+    });
+}
+
+/// Namespace of functions that scripts use to get and set states exposed with
+/// [ScriptingRuntimeBuilder::expose_state](crate::ScriptingRuntimeBuilder::expose_state).
+pub(crate) const STATE_NAMESPACE: &str = "state";
+
+type GetState = fn(&World) -> Option<String>;
+
+type SetState = fn(&mut World, &str) -> Result<(), String>;
+
+/// A resource that stores states exposed to scripts of runtime `R`, keyed by type name.
+#[derive(Resource)]
+pub(crate) struct ExposedStates<R: Runtime> {
+    pub(crate) states: HashMap<String, (GetState, SetState)>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> Default for ExposedStates<R> {
+    fn default() -> Self {
+        Self {
+            states: Default::default(),
+            _phantom_data: Default::default(),
+        }
+    }
+}
+
+/// Returns the name of the current variant of state `S`, or `None` if it does not exist.
+pub(crate) fn get_state<S: States + Reflect>(world: &World) -> Option<String> {
+    let state = world.get_resource::<State<S>>()?;
+    match state.get().reflect_ref() {
+        ReflectRef::Enum(state) => Some(state.variant_name().to_string()),
+        _ => None,
+    }
+}
+
+/// Queues a transition of state `S` to its unit variant with provided name.
+pub(crate) fn set_state<S: FreelyMutableState + FromReflect + TypePath>(
+    world: &mut World,
+    variant: &str,
+) -> Result<(), String> {
+    let state = S::from_reflect(&DynamicEnum::new(variant, DynamicVariant::Unit))
+        .ok_or_else(|| format!("`{variant}` is not a variant of `{}`", S::short_type_path()))?;
+    world
+        .get_resource_mut::<NextState<S>>()
+        .ok_or_else(|| format!("state `{}` is not initialized", S::short_type_path()))?
+        .set(state);
+    Ok(())
+}
+
+/// Returns the current variant of the exposed state with provided name, registered as
+/// `state.get` function.
+pub(crate) fn get_exposed_state<R: Runtime>(
+    In((name,)): In<(String,)>,
+    world: &mut World,
+) -> Option<String> {
+    let Some((get, _)) = world
+        .resource::<ExposedStates<R>>()
+        .states
+        .get(&name)
+        .copied()
+    else {
+        tracing::error!("state `{}` is not exposed to scripts", name);
+        return None;
+    };
+    get(world)
+}
+
+/// Queues a transition of the exposed state with provided name, registered as `state.set`
+/// function.
+pub(crate) fn set_exposed_state<R: Runtime>(
+    In((name, variant)): In<(String, String)>,
+    world: &mut World,
+) {
+    let Some((_, set)) = world
+        .resource::<ExposedStates<R>>()
+        .states
+        .get(&name)
+        .copied()
+    else {
+        tracing::error!("state `{}` is not exposed to scripts", name);
+        return;
+    };
+    if let Err(e) = set(world, &variant) {
+        tracing::error!("error setting state `{}`: {}", name, e);
+    }
+}
@@ -1,28 +1,72 @@
-use bevy::{prelude::*, utils::tracing};
+use bevy::{
+    prelude::*,
+    tasks::{ComputeTaskPool, TaskPool},
+    utils::tracing,
+};
 use std::{
+    collections::HashMap,
     fmt::Display,
+    panic::{catch_unwind, AssertUnwindSafe},
     sync::{Arc, Mutex},
 };
 
 use crate::{
-    callback::FunctionCallEvent,
-    promise::{Promise, PromiseInner},
-    Callback, Callbacks, Runtime, ScriptingError,
+    activation::ScriptSuspended,
+    callback::{panicked, FunctionCallEvent},
+    capabilities::{current_entity, enter_entity, CapabilityGrants},
+    promise::{
+        resolve_queued_promises, DeferredCall, PendingCall, PendingCalls, Promise, PromiseInner,
+        PromiseQueue, PromiseResolved,
+    },
+    reload::ScriptRevision,
+    testing::CallRecorder,
+    Callback, Callbacks, CompiledScriptCache, FuncArgs, Runtime, ScriptDiagnostic, ScriptError,
+    ScriptingError, ScriptingSettings,
 };
 
-use super::components::Script;
+use super::components::{Script, ScriptInfo, ScriptMod, ScriptTags};
 
-/// Reloads scripts when they are modified.
+/// Reloads scripts when they are modified, and removes compiled scripts from
+/// [CompiledScriptCache] once their assets are modified, removed or no longer used.
+#[allow(clippy::type_complexity)]
 pub(crate) fn reload_scripts<R: Runtime>(
     mut commands: Commands,
     mut ev_asset: EventReader<AssetEvent<R::ScriptAsset>>,
-    mut scripts: Query<(Entity, &mut Script<R::ScriptAsset>)>,
+    mut scripts: Query<(
+        Entity,
+        &mut Script<R::ScriptAsset>,
+        Option<&mut ScriptRevision<R>>,
+    )>,
+    cache: Option<Res<CompiledScriptCache<R>>>,
 ) {
     for ev in ev_asset.read() {
+        if let (AssetEvent::Removed { id } | AssetEvent::Unused { id }, Some(cache)) = (ev, &cache)
+        {
+            cache.invalidate(*id);
+            for (_, script, _) in &scripts {
+                if script.bases.iter().any(|base| base.id() == *id) {
+                    cache.invalidate(script.script.id());
+                }
+            }
+        }
         if let AssetEvent::Modified { id } = ev {
-            for (entity, script) in &mut scripts {
-                if script.script.id() == *id {
+            if let Some(cache) = &cache {
+                cache.invalidate(*id);
+            }
+            for (entity, script, revision) in &mut scripts {
+                if script.script.id() == *id || script.bases.iter().any(|base| base.id() == *id) {
+                    if let Some(cache) = &cache {
+                        cache.invalidate(script.script.id());
+                    }
                     commands.entity(entity).remove::<R::ScriptData>();
+                    match revision {
+                        Some(mut revision) => revision.reloading = true,
+                        None => {
+                            commands
+                                .entity(entity)
+                                .insert(ScriptRevision::<R>::reloading());
+                        }
+                    }
                 }
             }
         }
@@ -30,30 +74,100 @@ pub(crate) fn reload_scripts<R: Runtime>(
 }
 
 /// Processes new scripts. Evaluates them and stores the script data in the entity.
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub(crate) fn process_new_scripts<R: Runtime>(
     mut commands: Commands,
     mut added_scripted_entities: Query<
-        (Entity, &mut Script<R::ScriptAsset>),
+        (Entity, &mut Script<R::ScriptAsset>, Option<&ScriptMod>),
         Without<R::ScriptData>,
     >,
     scripting_runtime: ResMut<R>,
     scripts: Res<Assets<R::ScriptAsset>>,
     asset_server: Res<AssetServer>,
+    settings: Res<ScriptingSettings<R>>,
+    mut errors: EventWriter<ScriptError<R>>,
+    mut reported: Local<HashMap<Entity, ScriptDiagnostic>>,
 ) -> Result<(), ScriptingError> {
-    for (entity, script_component) in &mut added_scripted_entities {
-        tracing::trace!("evaulating a new script");
-        if let Some(script) = scripts.get(&script_component.script) {
-            match scripting_runtime.eval(script, entity) {
-                Ok(script_data) => {
-                    commands.entity(entity).insert(script_data);
-                }
-                Err(e) => {
-                    let path = asset_server
-                        .get_path(&script_component.script)
-                        .unwrap_or_default();
-                    tracing::error!("error running script {} {:?}", path, e);
+    let loaded_scripts = added_scripted_entities
+        .iter_mut()
+        .filter_map(|(entity, script_component, script_mod)| {
+            let path = |handle: &Handle<R::ScriptAsset>| {
+                asset_server
+                    .get_path(handle)
+                    .map(|path| path.to_string())
+                    .unwrap_or_default()
+            };
+            let script = scripts.get(&script_component.script)?;
+            let bases = script_component
+                .bases
+                .iter()
+                .map(|base| scripts.get(base).map(|asset| (asset, path(base))))
+                .collect::<Option<Vec<_>>>()?;
+            let info = ScriptInfo {
+                path: path(&script_component.script),
+                asset_id: script_component.script.id().to_string(),
+                mod_name: script_mod.map(|script_mod| script_mod.name.clone()),
+            };
+            let handles = script_component
+                .bases
+                .iter()
+                .chain([&script_component.script])
+                .map(|handle| (handle.clone(), path(handle)))
+                .collect::<Vec<_>>();
+            Some((entity, info, bases, script, handles))
+        })
+        .collect::<Vec<_>>();
+
+    let runtime = scripting_runtime.as_ref();
+    let results = if settings.parallel_eval {
+        ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+            for (index, (entity, info, bases, script, _)) in loaded_scripts.iter().enumerate() {
+                scope.spawn(async move {
+                    tracing::trace!("evaulating a new script");
+                    (index, runtime.eval_with_bases(bases, script, *entity, info))
+                });
+            }
+        })
+    } else {
+        loaded_scripts
+            .iter()
+            .enumerate()
+            .map(|(index, (entity, info, bases, script, _))| {
+                tracing::trace!("evaulating a new script");
+                (index, runtime.eval_with_bases(bases, script, *entity, info))
+            })
+            .collect()
+    };
+
+    for (index, result) in results {
+        let (entity, info, _, _, handles) = &loaded_scripts[index];
+        match result {
+            Ok(script_data) => {
+                reported.remove(entity);
+                commands.entity(*entity).insert(script_data);
+            }
+            Err(ScriptingError::CompileError(diagnostic)) => {
+                // Scripts that failed are evaluated again every frame, so errors are only
+                // reported once, until the script fails differently.
+                if reported.get(entity) == Some(&diagnostic) {
+                    continue;
                 }
+                reported.insert(*entity, diagnostic.clone());
+                tracing::error!("error compiling script {}", diagnostic);
+                // The script that failed to compile is either the script or one of its bases.
+                let (script, path) = handles
+                    .iter()
+                    .find(|(_, path)| *path == diagnostic.file)
+                    .unwrap_or_else(|| handles.last().expect("Script handle is missing"));
+                errors.send(ScriptError {
+                    entity: Some(*entity),
+                    script: script.clone(),
+                    path: path.clone(),
+                    error: ScriptingError::CompileError(diagnostic),
+                });
+            }
+            Err(e) => {
+                tracing::error!("error running script {} {:?}", info.path, e);
             }
         }
     }
@@ -66,44 +180,81 @@ pub(crate) fn init_callbacks<R: Runtime>(world: &mut World) -> Result<(), Script
         .get_resource_mut::<Callbacks<R>>()
         .ok_or(ScriptingError::NoSettingsResource)?;
 
-    let mut callbacks = callbacks_resource
+    let callbacks = callbacks_resource
         .uninitialized_callbacks
         .drain(..)
-        .collect::<Vec<Callback<R>>>();
+        .map(Arc::new)
+        .collect::<Vec<Arc<Callback<R>>>>();
+    let pending = callbacks_resource.pending.clone();
+
+    let grants = world.resource::<CapabilityGrants>().clone();
+    let recorder = world
+        .get_resource::<CallRecorder<R>>()
+        .map(|recorder| recorder.calls.clone());
 
-    for callback in callbacks.iter_mut() {
+    for callback in callbacks.iter() {
         if let Ok(mut system) = callback.system.lock() {
             system.system.initialize(world);
+        }
+    }
 
-            let mut scripting_runtime = world
-                .get_resource_mut::<R>()
-                .ok_or(ScriptingError::NoRuntimeResource)?;
-
-            tracing::trace!("init_callbacks: registering callback: '{}'", callback.name);
+    let mut scripting_runtime = world
+        .get_resource_mut::<R>()
+        .ok_or(ScriptingError::NoRuntimeResource)?;
+    // All functions registered in a frame get installed into the engine at once.
+    scripting_runtime.start_registering_fns();
+    for callback in callbacks.iter() {
+        if let Ok(system) = callback.system.lock() {
+            let name = callback.qualified_name().into_owned();
+            tracing::trace!("init_callbacks: registering callback: '{}'", name);
 
-            let callback = callback.clone();
+            let function = callback.clone();
+            let grants = grants.clone();
+            let recorder = recorder.clone();
+            let pending = pending.clone();
 
             let result = scripting_runtime.register_fn(
-                callback.name,
+                callback.namespace.as_deref(),
+                callback.name.clone(),
                 system.arg_types.clone(),
                 move |context, params| {
-                    let promise = Promise {
-                        inner: Arc::new(Mutex::new(PromiseInner {
-                            callbacks: vec![],
-                            context,
-                        })),
-                    };
-
-                    let mut calls = callback
-                        .calls
-                        .lock()
-                        .expect("Failed to lock callback calls mutex");
-
-                    calls.push(FunctionCallEvent {
-                        promise: promise.clone(),
-                        params,
-                    });
-                    Ok(promise)
+                    catch_unwind(AssertUnwindSafe(|| {
+                        let entity = current_entity();
+                        if let Some(capability) = grants.missing(entity, &function.capabilities) {
+                            return Err(ScriptingError::PermissionDenied {
+                                function: name.clone(),
+                                capability: capability.to_string(),
+                            });
+                        }
+                        if let Some(recorder) = &recorder {
+                            recorder
+                                .lock()
+                                .expect("Failed to lock call recorder")
+                                .push((name.clone(), params.clone(), context.clone()));
+                        }
+
+                        let promise = Promise {
+                            inner: Arc::new(Mutex::new(PromiseInner::new(context, entity))),
+                        };
+
+                        let mut calls = function
+                            .calls
+                            .lock()
+                            .expect("Failed to lock callback calls mutex");
+
+                        if calls.is_empty() {
+                            pending
+                                .lock()
+                                .expect("Failed to lock pending functions mutex")
+                                .push(function.id);
+                        }
+                        calls.push(FunctionCallEvent {
+                            promise: promise.clone(),
+                            params,
+                        });
+                        Ok(promise)
+                    }))
+                    .unwrap_or_else(|panic| Err(panicked(&name, panic)))
                 },
             );
             if let Err(e) = result {
@@ -111,6 +262,9 @@ pub(crate) fn init_callbacks<R: Runtime>(world: &mut World) -> Result<(), Script
             }
         }
     }
+    if let Err(e) = scripting_runtime.finish_registering_fns() {
+        tracing::error!("error registering functions: {:?}", e);
+    }
 
     let callbacks_resource = world
         .get_resource_mut::<Callbacks<R>>()
@@ -119,53 +273,327 @@ pub(crate) fn init_callbacks<R: Runtime>(world: &mut World) -> Result<(), Script
         .callbacks
         .lock()
         .expect("Failed to lock callbacks mutex")
-        .append(&mut callbacks.clone());
+        .extend(
+            callbacks
+                .into_iter()
+                .map(|callback| (callback.id, callback)),
+        );
 
     Ok(())
 }
 
+/// A run condition that is met when scripts have made calls that have not been processed yet or
+/// resolved promises are waiting for their callbacks, so that [process_calls], which needs
+/// exclusive access to the world, doesn't run in frames in which scripts call nothing.
+pub(crate) fn has_queued_calls<R: Runtime>(
+    callbacks: Option<Res<Callbacks<R>>>,
+    queue: Option<Res<PromiseQueue<R>>>,
+) -> bool {
+    let calls = callbacks.is_some_and(|callbacks| {
+        !callbacks
+            .pending
+            .lock()
+            .expect("Failed to lock pending functions mutex")
+            .is_empty()
+    });
+    calls || queue.is_some_and(|queue| !queue.is_empty())
+}
+
 /// Processes calls. Calls the user-defined callback systems
 pub(crate) fn process_calls<R: Runtime>(world: &mut World) -> Result<(), ScriptingError> {
     let callbacks_resource = world
         .get_resource::<Callbacks<R>>()
         .ok_or(ScriptingError::NoSettingsResource)?;
 
-    let callbacks = callbacks_resource
-        .callbacks
-        .lock()
-        .expect("Failed to lock callbacks mutex")
-        .clone();
+    // Only functions that have been called are looked up, so frames in which scripts call nothing
+    // don't allocate, however many functions are registered.
+    let pending = std::mem::take(
+        &mut *callbacks_resource
+            .pending
+            .lock()
+            .expect("Failed to lock pending functions mutex"),
+    );
+    let callbacks = {
+        let callbacks = callbacks_resource
+            .callbacks
+            .lock()
+            .expect("Failed to lock callbacks mutex");
+        pending
+            .iter()
+            .filter_map(|id| callbacks.get(id).cloned())
+            .collect::<Vec<_>>()
+    };
 
+    // Calls of every callback are moved into the same buffer, which keeps the capacity of queues
+    // of callbacks for the next frame.
+    let mut calls = Vec::<FunctionCallEvent<R::CallContext, R::Value>>::new();
     for callback in callbacks.into_iter() {
-        let calls = callback
-            .calls
-            .lock()
-            .expect("Failed to lock callback calls mutex")
-            .drain(..)
-            .collect::<Vec<FunctionCallEvent<R::CallContext, R::Value>>>();
-        for mut call in calls {
+        calls.append(
+            &mut callback
+                .calls
+                .lock()
+                .expect("Failed to lock callback calls mutex"),
+        );
+        let name = callback.qualified_name();
+        for mut call in calls.drain(..) {
+            if call.promise.is_cancelled() {
+                tracing::trace!("process_calls: dropping cancelled call '{}'", callback.name);
+                call.promise.cancel();
+                continue;
+            }
+            if let Some(entity) = call.promise.entity() {
+                if world.get_entity(entity).is_none() {
+                    tracing::trace!(
+                        "process_calls: dropping call '{}' of despawned entity",
+                        callback.name
+                    );
+                    call.promise.cancel();
+                    continue;
+                }
+            }
             tracing::trace!("process_calls: calling '{}'", callback.name);
             let mut system = callback
                 .system
                 .lock()
                 .expect("Failed to lock callback system mutex");
-            let val = system.call(&call, world);
-            let mut runtime = world
-                .get_resource_mut::<R>()
-                .ok_or(ScriptingError::NoRuntimeResource)?;
-
-            let result = call.promise.resolve(runtime.as_mut(), val);
-            match result {
-                Ok(_) => {}
+            // Functions can tell which entity's script called them with `current_entity`.
+            let entity_guard = enter_entity(call.promise.entity());
+            let result = system.call(&name, &mut call, world);
+            drop(entity_guard);
+            let val = match result {
+                Ok(val) => val,
                 Err(e) => {
-                    tracing::error!("error resolving call: {} {:?}", callback.name, e);
+                    tracing::error!("error processing call: {}", e);
+                    call.promise.cancel();
+                    continue;
                 }
+            };
+            let deferred = world
+                .get_resource_mut::<DeferredCall>()
+                .and_then(|mut deferred| deferred.take());
+            if let Some(receiver) = deferred {
+                if let Some(mut pending) = world.get_resource_mut::<PendingCalls<R>>() {
+                    pending.push(PendingCall {
+                        promise: call.promise,
+                        function: name.to_string(),
+                        receiver,
+                    });
+                    continue;
+                }
+                tracing::error!(
+                    "function '{}' has deferred its call, which the runtime does not support",
+                    callback.name
+                );
+            }
+            if world.contains_resource::<Events<PromiseResolved<R>>>() {
+                let entity = call.promise.entity();
+                let value = entity
+                    .and_then(|entity| world.get::<R::ScriptData>(entity))
+                    .map(|script_data| world.resource::<R>().to_script_value(&val, script_data))
+                    .unwrap_or_default();
+                world.send_event(PromiseResolved::<R>::new(entity, name.to_string(), value));
             }
+            world.resource::<PromiseQueue<R>>().push(call.promise, val);
         }
     }
+    resolve_queued_promises::<R>(world);
     Ok(())
 }
 
+/// A system that runs calls that scripts of runtime `R` have made to registered functions so far,
+/// instead of waiting for the runtime's schedule, which runs right after [Update]. Commands issued
+/// by a function, like spawning entities, are applied as soon as it returns, so once this system
+/// has run they are visible to systems ordered after it. Calls made by promise callbacks of
+/// these calls are run on the next sync point.
+///
+/// Without it, effects of functions called from scripts during [Update] are visible from
+/// [PostUpdate] of the same frame.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::apply_script_calls;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// fn call_scripts() {}
+///
+/// fn use_spawned_entities() {}
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|_| {})
+///     .add_systems(
+///         Update,
+///         (call_scripts, apply_script_calls::<LuaRuntime>, use_spawned_entities).chain(),
+///     );
+/// ```
+pub fn apply_script_calls<R: Runtime>(world: &mut World) {
+    if let Err(e) = process_calls::<R>(world) {
+        tracing::error!("error applying script calls: {}", e);
+    }
+}
+
+/// Creates a system that calls the script function with provided name on every scripted entity
+/// of runtime `R`, passing a clone of `args` to each call. Entities whose script does not define the
+/// function are skipped and errors are logged per entity, so one failing script does not prevent
+/// others from running. Entities suspended by [activation](crate::activation) are skipped too.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::run_script_fn_on_all;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|_| {})
+///     .add_systems(Update, run_script_fn_on_all::<LuaRuntime, _>("on_update", ()));
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn run_script_fn_on_all<R: Runtime, A>(
+    name: &str,
+    args: A,
+) -> impl FnMut(Query<(Entity, &mut R::ScriptData), Without<ScriptSuspended>>, Res<R>)
+where
+    A: for<'a> FuncArgs<'a, R::Value, R> + Clone + Send + Sync + 'static,
+{
+    let name = name.to_owned();
+    move |mut scripted_entities, scripting_runtime| {
+        for (entity, mut script_data) in &mut scripted_entities {
+            if let Err(e) =
+                scripting_runtime.call_fn_if_exists(&name, &mut script_data, entity, args.clone())
+            {
+                tracing::error!("error calling '{}' on entity {:?}: {}", name, entity, e);
+            }
+        }
+    }
+}
+
+/// Creates a system like [run_script_fn_on_all], that only calls the function on scripted
+/// entities whose component `C` has been added or changed since the system last ran, using Bevy
+/// change detection, and on entities whose script has just been evaluated. Like
+/// [run_script_fn_on_all], it skips suspended entities. Scenes with many
+/// mostly idle scripted entities then only pay for scripts that have something to react to.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::run_script_fn_on_changed;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|_| {})
+///     .add_systems(
+///         Update,
+///         run_script_fn_on_changed::<LuaRuntime, Transform, _>("on_moved", ()),
+///     );
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn run_script_fn_on_changed<R: Runtime, C: Component, A>(
+    name: &str,
+    args: A,
+) -> impl FnMut(
+    Query<
+        (Entity, &mut R::ScriptData),
+        (
+            Or<(Changed<C>, Added<R::ScriptData>)>,
+            Without<ScriptSuspended>,
+        ),
+    >,
+    Res<R>,
+)
+where
+    A: for<'a> FuncArgs<'a, R::Value, R> + Clone + Send + Sync + 'static,
+{
+    let name = name.to_owned();
+    move |mut scripted_entities, scripting_runtime| {
+        for (entity, mut script_data) in &mut scripted_entities {
+            if let Err(e) =
+                scripting_runtime.call_fn_if_exists(&name, &mut script_data, entity, args.clone())
+            {
+                tracing::error!("error calling '{}' on entity {:?}: {}", name, entity, e);
+            }
+        }
+    }
+}
+
+/// Creates a system like [run_script_fn_on_all], that only calls the function on scripted
+/// entities with provided tag in their [ScriptTags]. Systems created for different tags can be
+/// added to different schedules, e.g. to run `"ui"` scripts in `PostUpdate` and `"sim"` scripts
+/// in `FixedUpdate` within the same runtime. Like [run_script_fn_on_all], it skips suspended
+/// entities.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::run_script_fn_on_tagged;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|_| {})
+///     .add_systems(
+///         FixedUpdate,
+///         run_script_fn_on_tagged::<LuaRuntime, _>("sim", "on_tick", ()),
+///     );
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn run_script_fn_on_tagged<R: Runtime, A>(
+    tag: &str,
+    name: &str,
+    args: A,
+) -> impl FnMut(Query<(Entity, &mut R::ScriptData, &ScriptTags), Without<ScriptSuspended>>, Res<R>)
+where
+    A: for<'a> FuncArgs<'a, R::Value, R> + Clone + Send + Sync + 'static,
+{
+    let tag = tag.to_owned();
+    let name = name.to_owned();
+    move |mut scripted_entities, scripting_runtime| {
+        for (entity, mut script_data, tags) in &mut scripted_entities {
+            if !tags.contains(&tag) {
+                continue;
+            }
+            if let Err(e) =
+                scripting_runtime.call_fn_if_exists(&name, &mut script_data, entity, args.clone())
+            {
+                tracing::error!("error calling '{}' on entity {:?}: {}", name, entity, e);
+            }
+        }
+    }
+}
+
+/// Creates a system that resumes calls paused by [Runtime::call_fn_resumable] on every scripted
+/// entity of runtime `R`, allowing each of them to execute another `budget` instructions. Values
+/// returned by calls that finish are discarded and errors are logged per entity.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::resume_paused_calls;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|_| {})
+///     .add_systems(Update, resume_paused_calls::<LuaRuntime>(10_000));
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn resume_paused_calls<R: Runtime>(
+    budget: u64,
+) -> impl FnMut(Query<(Entity, &mut R::ScriptData)>, Res<R>) {
+    move |mut scripted_entities, scripting_runtime| {
+        for (entity, mut script_data) in &mut scripted_entities {
+            if !scripting_runtime.has_paused_call(&script_data) {
+                continue;
+            }
+            if let Err(e) = scripting_runtime.resume_call(&mut script_data, entity, budget) {
+                tracing::error!("error resuming call on entity {:?}: {}", entity, e);
+            }
+        }
+    }
+}
+
 /// Error logging system
 pub fn log_errors<E: Display>(In(res): In<Result<(), E>>) {
     if let Err(error) = res {
@@ -0,0 +1,178 @@
+//! Golden-file testing of scripts.
+//!
+//! [GoldenTest] runs a script, records every call of a registered Rust function together with
+//! its arguments and compares the recording with a file stored next to tests. This allows
+//! catching changes in behaviour of mod scripts across library or game updates.
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_scriptum::testing::GoldenTest;
+//! use bevy_scriptum::runtimes::lua::prelude::*;
+//!
+//! GoldenTest::<LuaRuntime>::new(|runtime| {
+//!     runtime.add_function(String::from("spawn_enemy"), |In((_x,)): In<(i64,)>| {});
+//! })
+//! .run_script("mods/enemies/main.lua")
+//! .call_fn("on_wave", ())
+//! .assert_golden("tests/golden/enemies.txt");
+//! ```
+//!
+//! Golden files are created or updated by running tests with `UPDATE_GOLDEN` environment
+//! variable set.
+
+use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+
+use crate::{BuildScriptingRuntime, FuncArgs, Runtime, Script, ScriptingRuntimeBuilder};
+
+/// Name of the environment variable that makes [GoldenTest::assert_golden] write golden files
+/// instead of comparing with them.
+pub const UPDATE_GOLDEN_VAR_NAME: &str = "UPDATE_GOLDEN";
+
+type RecordedCalls<R> = Arc<
+    Mutex<
+        Vec<(
+            String,
+            Vec<<R as Runtime>::Value>,
+            <R as Runtime>::CallContext,
+        )>,
+    >,
+>;
+
+/// A resource that records all calls of registered functions from scripts of runtime `R`.
+/// It has to be inserted before the first update of the app.
+#[derive(Resource)]
+pub struct CallRecorder<R: Runtime> {
+    pub(crate) calls: RecordedCalls<R>,
+}
+
+impl<R: Runtime> Default for CallRecorder<R> {
+    fn default() -> Self {
+        Self {
+            calls: Default::default(),
+        }
+    }
+}
+
+impl<R: Runtime> CallRecorder<R> {
+    /// Returns recorded calls, one per line, formatted as `name(arg, ...)`.
+    pub fn transcript(&self, runtime: &R) -> String {
+        let calls = self.calls.lock().expect("Failed to lock call recorder");
+        let mut transcript = String::new();
+        for (name, args, context) in calls.iter() {
+            let args = args
+                .iter()
+                .map(|arg| runtime.format_value(arg, context))
+                .collect::<Vec<_>>()
+                .join(", ");
+            transcript.push_str(&format!("{name}({args})\n"));
+        }
+        transcript
+    }
+}
+
+/// A harness running a script within a minimal app and recording calls of registered
+/// functions, see [module documentation](self).
+pub struct GoldenTest<R: Runtime> {
+    app: App,
+    entity: Option<Entity>,
+    _phantom_data: PhantomData<R>,
+}
+
+impl<R: Runtime> GoldenTest<R> {
+    /// Maximum number of updates to wait for a script to be loaded.
+    const MAX_LOAD_UPDATES: usize = 100;
+
+    /// Creates a harness with runtime `R` set up using provided function.
+    pub fn new(f: impl Fn(ScriptingRuntimeBuilder<R>)) -> Self {
+        let mut app = App::new();
+        app.add_plugins((AssetPlugin::default(), TaskPoolPlugin::default()))
+            .init_resource::<CallRecorder<R>>()
+            .add_scripting::<R>(f);
+        Self {
+            app,
+            entity: None,
+            _phantom_data: PhantomData,
+        }
+    }
+
+    /// Provides access to the app, for example to insert resources used by registered functions.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Loads and evaluates script at provided asset path and processes calls it made.
+    /// Panics if the script can't be loaded or evaluated.
+    pub fn run_script(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        let handle = self
+            .app
+            .world()
+            .resource::<AssetServer>()
+            .load::<R::ScriptAsset>(path.clone());
+        let entity = self.app.world_mut().spawn(Script::new(handle)).id();
+        for _ in 0..Self::MAX_LOAD_UPDATES {
+            self.app.update();
+            if self.app.world().get::<R::ScriptData>(entity).is_some() {
+                self.entity = Some(entity);
+                return self;
+            }
+        }
+        panic!("script {path} has not been loaded");
+    }
+
+    /// Calls a function defined in the script and processes calls it made.
+    /// Panics if the call fails.
+    pub fn call_fn(mut self, name: &str, args: impl for<'a> FuncArgs<'a, R::Value, R>) -> Self {
+        let entity = self.entity.expect("run_script has to be called first");
+        self.app
+            .world_mut()
+            .resource_scope(|world, runtime: Mut<R>| {
+                let mut script_data = world
+                    .get_mut::<R::ScriptData>(entity)
+                    .expect("script data has to be present");
+                runtime.call_fn(name, &mut script_data, entity, args)
+            })
+            .unwrap_or_else(|e| panic!("calling {name} failed: {e}"));
+        self.app.update();
+        self
+    }
+
+    /// Returns all recorded calls, one per line.
+    pub fn transcript(&self) -> String {
+        let world = self.app.world();
+        world
+            .resource::<CallRecorder<R>>()
+            .transcript(world.resource::<R>())
+    }
+
+    /// Compares recorded calls with the golden file at provided path, panicking when they differ.
+    /// When [UPDATE_GOLDEN_VAR_NAME] environment variable is set, the file is written instead.
+    pub fn assert_golden(self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let transcript = self.transcript();
+        if std::env::var_os(UPDATE_GOLDEN_VAR_NAME).is_some() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).expect("Failed to create golden file directory");
+            }
+            std::fs::write(path, transcript).expect("Failed to write golden file");
+            return;
+        }
+        let expected = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            panic!(
+                "failed to read golden file {}: {e}, run with {UPDATE_GOLDEN_VAR_NAME}=1 to create it",
+                path.display()
+            )
+        });
+        assert!(
+            expected == transcript,
+            "recorded calls differ from golden file {}\n--- expected\n{expected}--- recorded\n{transcript}",
+            path.display()
+        );
+    }
+}
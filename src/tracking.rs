@@ -0,0 +1,126 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+thread_local! {
+    static CURRENT_TRACKER: RefCell<Option<CallTracker>> = const { RefCell::new(None) };
+}
+
+#[derive(Default)]
+struct TrackerState {
+    pending: usize,
+    wakers: Vec<Waker>,
+}
+
+/// Counts promises spawned by a tracked call that have not settled yet.
+#[derive(Clone, Default)]
+pub(crate) struct CallTracker(Arc<Mutex<TrackerState>>);
+
+impl CallTracker {
+    /// Makes promises created on this thread get tracked by this tracker,
+    /// until the returned guard is dropped.
+    pub(crate) fn enter(&self) -> TrackerGuard {
+        TrackerGuard(CURRENT_TRACKER.replace(Some(self.clone())))
+    }
+
+    /// Returns the tracker of the call that is currently running on this thread.
+    pub(crate) fn current() -> Option<Self> {
+        CURRENT_TRACKER.with_borrow(Clone::clone)
+    }
+
+    /// Registers a pending promise, which settles once the returned value is dropped.
+    pub(crate) fn track(&self) -> PendingPromise {
+        self.lock().pending += 1;
+        PendingPromise(self.clone())
+    }
+
+    fn is_settled(&self) -> bool {
+        self.lock().pending == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TrackerState> {
+        self.0.lock().expect("Failed to lock call tracker")
+    }
+}
+
+/// Restores previously entered tracker when dropped.
+pub(crate) struct TrackerGuard(Option<CallTracker>);
+
+impl Drop for TrackerGuard {
+    fn drop(&mut self) {
+        CURRENT_TRACKER.set(self.0.take());
+    }
+}
+
+/// A promise counted by a [CallTracker], which settles when this gets dropped.
+pub(crate) struct PendingPromise(CallTracker);
+
+impl PendingPromise {
+    pub(crate) fn tracker(&self) -> &CallTracker {
+        &self.0
+    }
+}
+
+impl Drop for PendingPromise {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.pending -= 1;
+        if state.pending == 0 {
+            state.wakers.drain(..).for_each(Waker::wake);
+        }
+    }
+}
+
+/// A handle to a call made with [Runtime::call_fn_tracked](crate::Runtime::call_fn_tracked),
+/// holding the value returned by the function and tracking promises it has spawned.
+pub struct TrackedCall<V> {
+    value: V,
+    tracker: CallTracker,
+}
+
+impl<V> TrackedCall<V> {
+    pub(crate) fn new(value: V, tracker: CallTracker) -> Self {
+        Self { value, tracker }
+    }
+
+    /// Returns the value returned by the function.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Returns the value returned by the function, consuming the handle.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+
+    /// Returns `true` once every promise spawned by the call has been resolved or cancelled,
+    /// including promises spawned by their callbacks.
+    pub fn is_finished(&self) -> bool {
+        self.tracker.is_settled()
+    }
+
+    /// Returns a future completing once [TrackedCall::is_finished] returns `true`.
+    pub fn finished(&self) -> CallFinished {
+        CallFinished(self.tracker.clone())
+    }
+}
+
+/// A future returned by [TrackedCall::finished].
+pub struct CallFinished(CallTracker);
+
+impl Future for CallFinished {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.0.lock();
+        if state.pending == 0 {
+            return Poll::Ready(());
+        }
+        state.wakers.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{EntityArgs, ScriptValue};
+
+/// Namespace of functions that scripts use to move and rotate entities.
+pub(crate) const TRANSFORM_NAMESPACE: &str = "transform";
+
+/// Returns the translation of an entity, or nil if it has no [Transform], registered as
+/// `transform.position`.
+pub(crate) fn position<R: EntityArgs>(
+    In((entity,)): In<(R::ScriptEntity,)>,
+    transforms: Query<&Transform>,
+) -> ScriptValue {
+    transforms
+        .get(R::script_entity(entity))
+        .map(|transform| ScriptValue::Vec3(transform.translation))
+        .unwrap_or_default()
+}
+
+/// Sets the translation of an entity, registered as `transform.set_position`.
+pub(crate) fn set_position<R: EntityArgs>(
+    In((entity, position)): In<(R::ScriptEntity, ScriptValue)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let Some(position) = position.as_vec3() else {
+        tracing::error!("position must be a Vec3, got {:?}", position);
+        return;
+    };
+    if let Ok(mut transform) = transforms.get_mut(R::script_entity(entity)) {
+        transform.translation = position;
+    }
+}
+
+/// Rotates an entity around the Y axis by provided angle in radians, registered as
+/// `transform.rotate_y`.
+pub(crate) fn rotate_y<R: EntityArgs>(
+    In((entity, angle)): In<(R::ScriptEntity, f64)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    if let Ok(mut transform) = transforms.get_mut(R::script_entity(entity)) {
+        transform.rotate_y(angle as f32);
+    }
+}
+
+/// Rotates an entity so that it faces provided point or entity, with its up direction kept as
+/// close to the Y axis as possible, registered as `transform.look_at`.
+pub(crate) fn look_at<R: EntityArgs>(
+    In((entity, target)): In<(R::ScriptEntity, ScriptValue)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let target = match target {
+        ScriptValue::Vec3(target) => target,
+        ScriptValue::Entity(target) => match transforms.get(target) {
+            Ok(transform) => transform.translation,
+            Err(_) => {
+                tracing::error!("can not look at entity {:?} without Transform", target);
+                return;
+            }
+        },
+        target => {
+            tracing::error!("target must be a Vec3 or an entity, got {:?}", target);
+            return;
+        }
+    };
+    if let Ok(mut transform) = transforms.get_mut(R::script_entity(entity)) {
+        transform.look_at(target, Vec3::Y);
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{EntityArgs, ScriptOnClick};
+
+/// Calls functions of [ScriptOnClick] components whose entities have just been pressed.
+pub(crate) fn call_script_on_click<R: EntityArgs>(
+    interactions: Query<(Entity, &Interaction, &ScriptOnClick<R>), Changed<Interaction>>,
+    mut scripted_entities: Query<&mut R::ScriptData>,
+    scripting_runtime: Res<R>,
+) {
+    for (entity, interaction, on_click) in &interactions {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let script = on_click.script.unwrap_or(entity);
+        let Ok(mut script_data) = scripted_entities.get_mut(script) else {
+            tracing::error!(
+                "entity {:?} pressed but {:?} has no script to call '{}' on",
+                entity,
+                script,
+                on_click.function
+            );
+            continue;
+        };
+        if let Err(e) = scripting_runtime.call_fn(
+            &on_click.function,
+            &mut script_data,
+            script,
+            R::entity_args(entity),
+        ) {
+            tracing::error!(
+                "error calling '{}' on entity {:?}: {}",
+                on_click.function,
+                script,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::{prelude::*, utils::tracing};
+
+use crate::{Callbacks, Runtime};
+
+impl<R: Runtime> Callbacks<R> {
+    /// Removes all overloads of provided function, along with calls of it that have not been
+    /// processed yet.
+    fn remove(&mut self, namespace: Option<&str>, name: &str) {
+        let other = |callback_namespace: &Option<String>, callback_name: &str| {
+            callback_namespace.as_deref() != namespace || callback_name != name
+        };
+        self.uninitialized_callbacks
+            .retain(|callback| other(&callback.namespace, &callback.name));
+        self.callbacks
+            .lock()
+            .expect("Failed to lock callbacks mutex")
+            .retain(|_, callback| other(&callback.namespace, &callback.name));
+        self.signatures
+            .retain(|(signature_namespace, signature_name, _)| {
+                other(signature_namespace, signature_name)
+            });
+    }
+}
+
+/// Removes the function with provided name registered in runtime `R`, within provided namespace
+/// if any, along with all of its overloads, so that scripts can no longer call it, e.g. to strip
+/// debug functions or functions of a mod that has been disabled. Calls of it that scripts have
+/// made but that have not run yet are dropped, so their promises never resolve.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_scriptum::prelude::*;
+/// use bevy_scriptum::remove_script_function;
+/// use bevy_scriptum::runtimes::lua::prelude::*;
+///
+/// fn strip_debug_functions(world: &mut World) {
+///     remove_script_function::<LuaRuntime>(world, Some("debug"), "spawn_enemy");
+/// }
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_scripting::<LuaRuntime>(|runtime| {
+///         runtime
+///             .namespace("debug")
+///             .add_function("spawn_enemy", || info!("enemy spawned"));
+///     })
+///     .add_systems(PostStartup, strip_debug_functions);
+/// ```
+pub fn remove_script_function<R: Runtime>(world: &mut World, namespace: Option<&str>, name: &str) {
+    if let Some(mut callbacks) = world.get_resource_mut::<Callbacks<R>>() {
+        callbacks.remove(namespace, name);
+    }
+    let Some(mut runtime) = world.get_resource_mut::<R>() else {
+        return;
+    };
+    if let Err(e) = runtime.unregister_fn(namespace, name) {
+        tracing::error!("error removing function `{}`: {}", name, e);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::entity::Entity, math::Vec3};
+
+/// A value that can be converted from and into values of every runtime, so that values set by
+/// scripts of one language can be read by scripts of another, e.g. through the
+/// [Blackboard](crate::Blackboard), and host code can handle values of scripts regardless of
+/// their language. Functions registered with
+/// [add_function](crate::ScriptingRuntimeBuilder::add_function) can take and return it, and
+/// values returned by script functions can be converted into it with
+/// [Runtime::to_script_value](crate::Runtime::to_script_value).
+///
+/// Lua tables whose keys are consecutive integers starting at 1 get converted into arrays and
+/// other tables into maps, with integer keys turned into strings. Rhai arrays and object maps
+/// get converted into arrays and maps respectively.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ScriptValue {
+    /// `nil` in Lua and `()` in Rhai.
+    #[default]
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<ScriptValue>),
+    Map(HashMap<String, ScriptValue>),
+    Entity(Entity),
+    Vec3(Vec3),
+}
+
+impl ScriptValue {
+    /// Returns `true` if the value is [ScriptValue::Nil].
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Self::Nil)
+    }
+
+    /// Returns the boolean held by the value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer held by the value.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number held by the value, converting integers into floats.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string held by the value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of the array held by the value.
+    pub fn as_array(&self) -> Option<&[ScriptValue]> {
+        match self {
+            Self::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the map held by the value.
+    pub fn as_map(&self) -> Option<&HashMap<String, ScriptValue>> {
+        match self {
+            Self::Map(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the entity held by the value.
+    pub fn as_entity(&self) -> Option<Entity> {
+        match self {
+            Self::Entity(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the vector held by the value.
+    pub fn as_vec3(&self) -> Option<Vec3> {
+        match self {
+            Self::Vec3(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl From<()> for ScriptValue {
+    fn from(_: ()) -> Self {
+        Self::Nil
+    }
+}
+
+impl From<bool> for ScriptValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for ScriptValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<i64> for ScriptValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f32> for ScriptValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value.into())
+    }
+}
+
+impl From<f64> for ScriptValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for ScriptValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for ScriptValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Entity> for ScriptValue {
+    fn from(value: Entity) -> Self {
+        Self::Entity(value)
+    }
+}
+
+impl From<Vec3> for ScriptValue {
+    fn from(value: Vec3) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl<T: Into<ScriptValue>> From<Option<T>> for ScriptValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Nil, Into::into)
+    }
+}
+
+impl<T: Into<ScriptValue>> From<Vec<T>> for ScriptValue {
+    fn from(value: Vec<T>) -> Self {
+        Self::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<ScriptValue>> From<HashMap<String, T>> for ScriptValue {
+    fn from(value: HashMap<String, T>) -> Self {
+        Self::Map(
+            value
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl From<serde_json::Value> for ScriptValue {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::Nil,
+            serde_json::Value::Bool(value) => Self::Bool(value),
+            serde_json::Value::Number(value) => match value.as_i64() {
+                Some(value) => Self::Int(value),
+                None => Self::Float(value.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(value) => Self::String(value),
+            serde_json::Value::Array(value) => value.into(),
+            serde_json::Value::Object(value) => Self::Map(
+                value
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -2,8 +2,17 @@ use std::sync::OnceLock;
 
 use bevy::ecs::system::RunSystemOnce as _;
 use bevy::prelude::*;
-use bevy_scriptum::{prelude::*, FuncArgs, Runtime};
-use mlua::Table;
+use bevy_scriptum::{
+    apply_script_calls, call_queue::DEFAULT_CALL_QUEUE_CAPACITY, decoders::*, prelude::*,
+    remove_script_function, resume_paused_calls, run_script_fn_on_all, run_script_fn_on_changed,
+    testing::GoldenTest, Blackboard, BlackboardChanged, CallProgress, CompiledScriptCache,
+    FuncArgs, FunctionConflictPolicy, Json, PromiseResolved, Runtime, ScriptActions,
+    ScriptActivationRange, ScriptActivator, ScriptApiRegistry, ScriptCallError, ScriptCallQueue,
+    ScriptCapabilities, ScriptComponents, ScriptDiagnostic, ScriptError, ScriptFixedUpdateSet,
+    ScriptFunction, ScriptIntrospection, ScriptLoadError, ScriptLoaderSettings,
+    ScriptMissingFunctions, ScriptReloaded, ScriptRng, ScriptSuspended, ScriptTags, ScriptValue,
+    ScriptingApiVersions, ScriptingError, ScriptingSettings,
+};
 
 static TRACING_SUBSCRIBER: OnceLock<()> = OnceLock::new();
 
@@ -48,6 +57,75 @@ fn call_script_on_update_from_rust<R: Runtime>(
         .unwrap();
 }
 
+/// Errors of scripts that failed to load, recorded by [record_load_errors].
+#[derive(Resource, Default)]
+struct LoadErrors(Vec<(Option<Entity>, UntypedHandle, String, ScriptLoadError)>);
+
+/// Records errors of scripts of runtime `R` that failed to load in [LoadErrors].
+fn record_load_errors<R: Runtime>(app: &mut App) {
+    app.init_resource::<LoadErrors>().add_systems(
+        Last,
+        |mut events: EventReader<ScriptError<R>>, mut errors: ResMut<LoadErrors>| {
+            for event in events.read() {
+                if let ScriptingError::LoadError(error) = &event.error {
+                    errors.0.push((
+                        event.entity,
+                        event.script.clone().untyped(),
+                        event.path.clone(),
+                        error.clone(),
+                    ));
+                }
+            }
+        },
+    );
+}
+
+/// Starts a HTTP server on a random port that responds to every request with a body holding
+/// the method, path and body of the request, returning its URL.
+#[cfg(feature = "http")]
+fn spawn_echo_http_server() -> String {
+    use std::io::{BufRead as _, BufReader, Read as _, Write as _};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header == "\r\n" {
+                    break;
+                }
+                if let Some(length) = header.strip_prefix("Content-Length: ") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let response = format!(
+                "{} {} {}",
+                parts.next().unwrap(),
+                parts.next().unwrap(),
+                String::from_utf8(body).unwrap()
+            );
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+        }
+    });
+    url
+}
+
 trait AssertStateKeyValue {
     type ScriptData;
     fn assert_state_key_value_i64(world: &World, entity_id: Entity, key: &str, value: i64);
@@ -259,7 +337,7 @@ macro_rules! scripting_tests {
                     let (entity, mut script_data) = scripted_entities.single_mut();
                     let result =
                         scripting_runtime.call_fn("test_func", &mut script_data, entity, ());
-                    assert!(result.is_err());
+                    assert!(matches!(result, Err(ScriptingError::RuntimeError(_))));
                 },
             );
         }
@@ -282,13 +360,13 @@ macro_rules! scripting_tests {
                     let (entity, mut script_data) = scripted_entities.single_mut();
                     let result =
                         scripting_runtime.call_fn("does_not_exist", &mut script_data, entity, ());
-                    assert!(result.is_err());
+                    assert!(matches!(result, Err(ScriptingError::FunctionNotFound(name)) if name == "does_not_exist"));
                 },
             );
         }
 
         #[test]
-        fn test_script_function_gets_called_from_rust() {
+        fn test_call_script_function_if_exists() {
             let mut app = build_test_app();
 
             app.add_scripting::<$runtime>(|_| {});
@@ -300,173 +378,5107 @@ macro_rules! scripting_tests {
                     $script, $extension
                 )
                 .to_string(),
-                call_script_on_update_from_rust::<$runtime>,
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    let result = scripting_runtime
+                        .call_fn_if_exists("does_not_exist", &mut script_data, entity, ())
+                        .unwrap();
+                    assert!(result.is_none());
+                    let result = scripting_runtime
+                        .call_fn_if_exists("test_func", &mut script_data, entity, ())
+                        .unwrap();
+                    assert!(result.is_some());
+                },
             );
 
             <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 1i64);
         }
 
         #[test]
-        fn test_promise() {
+        fn test_run_script_fn_on_all() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {});
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/script_function_gets_called_from_rust.{}",
+                    $script, $extension
+                )
+                .to_string(),
+                run_script_fn_on_all::<$runtime, _>("test_func", ()),
+            );
+
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 1i64);
+        }
+
+        #[test]
+        fn script_fn_runs_only_when_component_changed() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {}).add_systems(
+                Update,
+                run_script_fn_on_changed::<$runtime, Transform, _>("test_func", ()),
+            );
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/script_function_gets_called_from_rust.{}",
+                $script, $extension
+            ));
+            let entity_id = app
+                .world_mut()
+                .spawn((Script::new(handle), Transform::default()))
+                .id();
+            for _ in 0..3 {
+                app.update();
+            }
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 1i64);
+
+            app.world_mut()
+                .get_mut::<Transform>(entity_id)
+                .unwrap()
+                .translation
+                .x = 1.0;
+            for _ in 0..3 {
+                app.update();
+            }
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 2i64);
+        }
+
+        #[test]
+        fn tagged_scripts_run_only_in_schedules_of_their_tags() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<String>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((tag,)): In<(String,)>, mut records: ResMut<Records>| {
+                            records.0.push(tag);
+                        },
+                    )
+                    .run_tagged_scripts_in(PostUpdate, "ui", "on_ui")
+                    .run_tagged_scripts_in(Last, "sim", "on_sim");
+            });
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/tagged_scripts.{}",
+                $script, $extension
+            ));
+            app.world_mut()
+                .spawn((Script::new(handle.clone()), ScriptTags::new(["ui"])));
+            app.world_mut()
+                .spawn((Script::new(handle.clone()), ScriptTags::new(["ui", "sim"])));
+            app.world_mut().spawn(Script::new(handle));
+            // Calls made by scripts in the first frame get processed in the second one.
+            app.update();
+            app.update();
+
+            let mut records = app.world().resource::<Records>().0.clone();
+            records.sort();
+            assert_eq!(records, vec!["sim", "ui", "ui"]);
+        }
+
+        #[test]
+        fn on_fixed_update_gets_called_every_fixed_timestep() {
+            use bevy::time::TimeUpdateStrategy;
+            use std::time::Duration;
+
             let mut app = build_test_app();
 
+            #[derive(Default, Resource)]
+            struct Steps(Vec<f64>);
+
+            app.add_plugins(bevy::time::TimePlugin)
+                .insert_resource(Time::<Fixed>::from_seconds(0.25))
+                .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+                .init_resource::<Steps>();
             app.add_scripting::<$runtime>(|runtime| {
-                runtime.add_function(String::from("rust_func"), || 123);
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((dt,)): In<(f64,)>, mut steps: ResMut<Steps>| {
+                            steps.0.push(dt);
+                        },
+                    )
+                    .enable_fixed_update();
             });
+            app.add_systems(
+                FixedUpdate,
+                (|steps: Res<Steps>| assert!(!steps.0.is_empty())).after(ScriptFixedUpdateSet),
+            );
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/fixed_update.{}", $script, $extension),
+                || {},
+            );
+            for _ in 0..4 {
+                app.update();
+            }
+
+            assert_eq!(app.world().resource::<Steps>().0, vec![0.25, 0.25]);
+        }
+
+        #[test]
+        fn scripts_out_of_activation_range_get_suspended() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_script_activation();
+            })
+            .add_systems(
+                PostUpdate,
+                run_script_fn_on_all::<$runtime, _>("on_update", ()),
+            );
+
+            let activator = app
+                .world_mut()
+                .spawn((ScriptActivator, GlobalTransform::default()))
+                .id();
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/script_activation.{}",
+                $script, $extension
+            ));
+            let entity_id = app
+                .world_mut()
+                .spawn((
+                    Script::new(handle),
+                    ScriptActivationRange(10.0),
+                    GlobalTransform::from_translation(Vec3::new(100.0, 0.0, 0.0)),
+                ))
+                .id();
+            for _ in 0..3 {
+                app.update();
+            }
+            assert!(app.world().get::<ScriptSuspended>(entity_id).is_some());
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_suspended", 1i64);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_updated", 0i64);
+
+            *app.world_mut().get_mut::<GlobalTransform>(activator).unwrap() =
+                GlobalTransform::from_translation(Vec3::new(95.0, 0.0, 0.0));
+            app.update();
+            assert!(app.world().get::<ScriptSuspended>(entity_id).is_none());
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_resumed", 1i64);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_updated", 1i64);
+        }
+
+        #[test]
+        fn test_parallel_eval_evaluates_all_scripts() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {});
+            app.world_mut()
+                .resource_mut::<ScriptingSettings<$runtime>>()
+                .parallel_eval = true;
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/script_function_gets_called_from_rust.{}",
+                $script, $extension
+            ));
+            for _ in 0..8 {
+                app.world_mut().spawn(Script::new(handle.clone()));
+            }
+
+            for _ in 0..100 {
+                app.update();
+                if asset_server.is_loaded_with_dependencies(&handle) {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            app.update();
+
+            let evaluated = app
+                .world_mut()
+                .query::<&<$runtime as Runtime>::ScriptData>()
+                .iter(app.world())
+                .count();
+            assert_eq!(evaluated, 8);
+        }
+
+        #[test]
+        fn test_script_function_gets_called_from_rust() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {});
 
             let entity_id = run_script::<$runtime, _, _>(
                 &mut app,
-                format!("tests/{}/return_via_promise.{}", $script, $extension).to_string(),
+                format!(
+                    "tests/{}/script_function_gets_called_from_rust.{}",
+                    $script, $extension
+                )
+                .to_string(),
                 call_script_on_update_from_rust::<$runtime>,
             );
 
-            <$runtime>::assert_state_key_value_i32(&app.world(), entity_id, "x", 123i32);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 1i64);
         }
 
         #[test]
-        fn test_promise_runtime_error_does_not_panic() {
+        fn vectors_and_hash_maps_convert_to_and_from_script_values() {
             let mut app = build_test_app();
 
+            #[derive(Default, Resource)]
+            struct Received {
+                sum: i64,
+                scores: f64,
+            }
+
+            app.world_mut().init_resource::<Received>();
+
             app.add_scripting::<$runtime>(|runtime| {
-                runtime.add_function(String::from("rust_func"), || 123);
+                runtime
+                    .add_function(
+                        String::from("rust_sum"),
+                        |In((values,)): In<(Vec<i64>,)>, mut res: ResMut<Received>| {
+                            res.sum = values.iter().sum();
+                        },
+                    )
+                    .add_function(
+                        String::from("rust_scores"),
+                        |In((scores,)): In<(std::collections::HashMap<String, f64>,)>,
+                         mut res: ResMut<Received>| {
+                            res.scores = scores["a"] + scores["b"];
+                        },
+                    )
+                    .add_function(String::from("rust_vec"), || vec![1i64, 2, 3])
+                    .add_function(String::from("rust_map"), || {
+                        std::collections::HashMap::from([
+                            (String::from("a"), 1i64),
+                            (String::from("b"), 2i64),
+                        ])
+                    });
             });
 
-            run_script::<$runtime, _, _>(
+            let entity_id = run_script::<$runtime, _, _>(
                 &mut app,
-                format!("tests/{}/promise_runtime_error.{}", $script, $extension).to_string(),
+                format!("tests/{}/collections.{}", $script, $extension).to_string(),
                 call_script_on_update_from_rust::<$runtime>,
             );
+
+            let received = app.world().resource::<Received>();
+            assert_eq!(received.sum, 6);
+            assert_eq!(received.scores, 4.0);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "sum", 6);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "total", 3);
         }
 
         #[test]
-        fn test_side_effects() {
+        fn serde_types_convert_to_and_from_script_values() {
             let mut app = build_test_app();
 
-            #[derive(Component)]
-            struct MyTag;
+            #[derive(serde::Serialize, serde::Deserialize)]
+            struct Stats {
+                health: i64,
+                name: String,
+            }
 
             app.add_scripting::<$runtime>(|runtime| {
-                runtime.add_function(String::from("spawn_entity"), |mut commands: Commands| {
-                    commands.spawn(MyTag);
-                });
+                runtime.add_function(
+                    String::from("heal"),
+                    |In((Json(mut stats),)): In<(Json<Stats>,)>| {
+                        stats.health += 10;
+                        stats.name.push('!');
+                        Json(stats)
+                    },
+                );
             });
 
-            run_script::<$runtime, _, _>(
+            let entity_id = run_script::<$runtime, _, _>(
                 &mut app,
-                format!("tests/{}/side_effects.{}", $script, $extension).to_string(),
+                format!("tests/{}/json_struct.{}", $script, $extension).to_string(),
                 call_script_on_update_from_rust::<$runtime>,
             );
 
-            app.world_mut().run_system_once(|tagged: Query<&MyTag>| {
-                tagged.single();
-            });
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "health", 15);
+            <$runtime>::assert_state_key_value_string(&app.world(), entity_id, "name", "hero!");
         }
 
         #[test]
-        fn test_rust_function_gets_called_from_script() {
+        fn options_convert_to_and_from_nil() {
             let mut app = build_test_app();
 
             #[derive(Default, Resource)]
-            struct TimesCalled {
-                times_called: u8,
-            }
+            struct Recorded(Vec<Option<i64>>);
 
-            app.world_mut().init_resource::<TimesCalled>();
+            app.world_mut().init_resource::<Recorded>();
 
             app.add_scripting::<$runtime>(|runtime| {
-                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
-                    res.times_called += 1;
-                });
+                runtime
+                    .add_function(String::from("find"), |In((name,)): In<(String,)>| {
+                        (name == "a").then_some(5i64)
+                    })
+                    .add_function(
+                        String::from("record"),
+                        |In((value,)): In<(Option<i64>,)>, mut res: ResMut<Recorded>| {
+                            res.0.push(value);
+                        },
+                    );
             });
 
-            run_script::<$runtime, _, _>(
+            let entity_id = run_script::<$runtime, _, _>(
                 &mut app,
-                format!(
-                    "tests/{}/rust_function_gets_called_from_script.{}",
-                    $script, $extension
-                )
-                .to_string(),
+                format!("tests/{}/optional_values.{}", $script, $extension).to_string(),
                 call_script_on_update_from_rust::<$runtime>,
             );
 
-            assert_eq!(
-                app.world()
-                    .get_resource::<TimesCalled>()
-                    .unwrap()
-                    .times_called,
-                1
-            );
+            assert_eq!(app.world().resource::<Recorded>().0, vec![None, Some(7)]);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "found", 5);
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "missing", 1);
         }
-    };
-}
 
-#[cfg(feature = "rhai")]
-mod rhai_tests {
-    use bevy::prelude::*;
-    use bevy_scriptum::runtimes::rhai::prelude::*;
+        #[test]
+        fn script_function_passed_to_rust_can_be_called_later() {
+            let mut app = build_test_app();
 
-    impl AssertStateKeyValue for RhaiRuntime {
-        type ScriptData = RhaiScriptData;
+            #[derive(Default, Resource)]
+            struct Listeners(Vec<ScriptFunction<$runtime>>);
 
-        fn assert_state_key_value_i64(world: &World, entity_id: Entity, key: &str, value: i64) {
-            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
-            let state = script_data.scope.get_value::<rhai::Map>("state").unwrap();
-            assert_eq!(state[key].clone_cast::<i64>(), value);
-        }
+            app.world_mut().init_resource::<Listeners>();
 
-        fn assert_state_key_value_i32(world: &World, entity_id: Entity, key: &str, value: i32) {
-            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
-            let state = script_data.scope.get_value::<rhai::Map>("state").unwrap();
-            assert_eq!(state[key].clone_cast::<i32>(), value);
-        }
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("on_event"),
+                    |In((listener,)): In<(ScriptFunction<$runtime>,)>,
+                     mut listeners: ResMut<Listeners>| {
+                        listeners.0.push(listener);
+                    },
+                );
+            });
 
-        fn assert_state_key_value_string(world: &World, entity_id: Entity, key: &str, value: &str) {
-            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
-            let state = script_data.scope.get_value::<rhai::Map>("state").unwrap();
-            assert_eq!(state[key].clone_cast::<String>(), value);
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/script_function.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            app.world_mut()
+                .run_system_once(|listeners: Res<Listeners>, runtime: Res<$runtime>| {
+                    assert_eq!(listeners.0.len(), 1);
+                    listeners.0[0].call(&runtime, (42i64,)).unwrap();
+                });
+
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "received", 42);
         }
-    }
 
-    scripting_tests!(RhaiRuntime, "rhai", "rhai");
-}
+        #[test]
+        fn tracked_call_finishes_once_spawned_promises_settle() {
+            let mut app = build_test_app();
 
-#[cfg(feature = "lua")]
-mod lua_tests {
-    use bevy::prelude::*;
-    use bevy_scriptum::runtimes::lua::prelude::*;
+            #[derive(Default, Resource)]
+            struct TimesCalled(u8);
 
-    impl AssertStateKeyValue for LuaRuntime {
-        type ScriptData = LuaScriptData;
+            app.world_mut().init_resource::<TimesCalled>();
 
-        fn assert_state_key_value_i64(world: &World, _entity_id: Entity, key: &str, value: i64) {
-            let runtime = world.get_resource::<LuaRuntime>().unwrap();
-            runtime.with_engine(|engine| {
-                let state = engine.globals().get::<_, Table>("State").unwrap();
-                assert_eq!(state.get::<_, i64>(key).unwrap(), value);
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(String::from("rust_func"), || {})
+                    .add_function(
+                        String::from("rust_callback"),
+                        |mut res: ResMut<TimesCalled>| {
+                            res.0 += 1;
+                        },
+                    );
             });
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/tracked_call.{}",
+                $script, $extension
+            ));
+            app.world_mut().spawn(Script::new(handle));
+            app.update();
+
+            let call = app.world_mut().run_system_once(
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    scripting_runtime
+                        .call_fn_tracked("test_func", &mut script_data, entity, ())
+                        .unwrap()
+                },
+            );
+            assert!(!call.is_finished());
+
+            for _ in 0..3 {
+                app.update();
+            }
+
+            assert!(call.is_finished());
+            assert_eq!(app.world().resource::<TimesCalled>().0, 1);
+            bevy::tasks::block_on(call.finished());
         }
 
-        fn assert_state_key_value_i32(world: &World, _entity_id: Entity, key: &str, value: i32) {
-            let runtime = world.get_resource::<LuaRuntime>().unwrap();
-            runtime.with_engine(|engine| {
-                let state = engine.globals().get::<_, Table>("State").unwrap();
-                assert_eq!(state.get::<_, i32>(key).unwrap(), value);
+        #[test]
+        fn namespaced_functions_get_called_from_script() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Items(i64);
+
+            app.world_mut().init_resource::<Items>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .namespace("game")
+                    .add_function(
+                        "give_item",
+                        |In((count,)): In<(i64,)>, mut items: ResMut<Items>| {
+                            items.0 += count;
+                        },
+                    )
+                    .add_function("take_item", |mut items: ResMut<Items>| {
+                        items.0 -= 1;
+                    });
             });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/namespaced_function.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(app.world().resource::<Items>().0, 4);
         }
 
-        fn assert_state_key_value_string(
-            world: &World,
-            _entity_id: Entity,
-            key: &str,
-            value: &str,
-        ) {
-            let runtime = world.get_resource::<LuaRuntime>().unwrap();
-            runtime.with_engine(|engine| {
-                let state = engine.globals().get::<_, Table>("State").unwrap();
-                assert_eq!(state.get::<_, String>(key).unwrap(), value);
+        #[test]
+        fn overloaded_functions_get_called_by_argument_count_and_types() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Moves(Vec<String>);
+
+            app.world_mut().init_resource::<Moves>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("move_to"),
+                        |In((x, y)): In<(i64, i64)>, mut moves: ResMut<Moves>| {
+                            moves.0.push(format!("{x},{y}"));
+                        },
+                    )
+                    .add_function(
+                        String::from("move_to"),
+                        |In((x,)): In<(i64,)>, mut moves: ResMut<Moves>| {
+                            moves.0.push(format!("{x}"));
+                        },
+                    )
+                    .add_function(
+                        String::from("move_to"),
+                        |In((place,)): In<(String,)>, mut moves: ResMut<Moves>| {
+                            moves.0.push(place);
+                        },
+                    );
             });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/overloaded_function.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(app.world().resource::<Moves>().0, vec!["1,2", "3", "home"]);
         }
-    }
 
-    scripting_tests!(LuaRuntime, "lua", "lua");
+        #[test]
+        fn boot_scripts_complete_before_first_update() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Items(Vec<String>);
+
+            #[derive(Default, Resource)]
+            struct ItemsOnFirstUpdate(Option<usize>);
+
+            app.world_mut().init_resource::<Items>();
+            app.world_mut().init_resource::<ItemsOnFirstUpdate>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("define_item"),
+                        |In((name,)): In<(String,)>, mut items: ResMut<Items>| {
+                            items.0.push(name);
+                        },
+                    )
+                    .add_boot_script(format!("tests/{}/boot_script.{}", $script, $extension));
+            });
+            app.add_systems(
+                Update,
+                |items: Res<Items>, mut on_first_update: ResMut<ItemsOnFirstUpdate>| {
+                    on_first_update.0.get_or_insert(items.0.len());
+                },
+            );
+
+            app.update();
+
+            assert_eq!(app.world().resource::<ItemsOnFirstUpdate>().0, Some(2));
+            assert_eq!(app.world().resource::<Items>().0, vec!["sword", "shield"]);
+        }
+
+        #[test]
+        fn named_systems_get_run_from_script() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Respawns(u32);
+
+            app.world_mut().init_resource::<Respawns>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_named_system("respawn_all", |mut respawns: ResMut<Respawns>| {
+                        respawns.0 += 1;
+                    })
+                    .add_named_system("unused", || {});
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/run_named_system.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(app.world().resource::<Respawns>().0, 2);
+        }
+
+        #[test]
+        fn script_systems_run_every_frame_until_unregistered() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.add_plugins(bevy::time::TimePlugin)
+                .init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((event, _delta)): In<(String, f64)>, mut events: ResMut<Events>| {
+                            events.0.push(event);
+                        },
+                    )
+                    .add_script_systems();
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/script_systems.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+            app.update();
+            app.update();
+
+            assert_eq!(
+                app.world().resource::<Events>().0,
+                vec!["repeating", "once", "repeating"]
+            );
+        }
+
+        #[cfg(feature = "physics")]
+        #[test]
+        fn collision_events_get_forwarded_to_both_scripts() {
+            use bevy_scriptum::physics::{CollisionPhase, ScriptCollisionEvent};
+
+            let mut app = build_test_app();
+
+            #[derive(Event)]
+            struct Collision(Entity, Entity, CollisionPhase);
+
+            impl ScriptCollisionEvent for Collision {
+                fn collision(&self) -> (Entity, Entity, CollisionPhase) {
+                    (self.0, self.1, self.2)
+                }
+            }
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<(String, u32)>);
+
+            app.add_event::<Collision>().init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((event, other)): In<(String, u32)>, mut events: ResMut<Events>| {
+                            events.0.push((event, other));
+                        },
+                    )
+                    .forward_collision_events::<Collision>();
+            });
+
+            let asset_server = app.world().resource::<AssetServer>();
+            let script = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/collisions.{}",
+                $script, $extension
+            ));
+            let first = app.world_mut().spawn(Script::new(script.clone())).id();
+            let second = app.world_mut().spawn(Script::new(script)).id();
+            let unscripted = app.world_mut().spawn_empty().id();
+            app.update();
+
+            app.world_mut()
+                .send_event(Collision(first, second, CollisionPhase::Started));
+            app.world_mut()
+                .send_event(Collision(unscripted, first, CollisionPhase::Stopped));
+            app.update();
+            app.update();
+
+            assert_eq!(
+                app.world().resource::<Events>().0,
+                vec![
+                    ("start".to_string(), second.index()),
+                    ("start".to_string(), first.index()),
+                    ("stop".to_string(), unscripted.index()),
+                ]
+            );
+        }
+
+        #[cfg(feature = "physics")]
+        #[test]
+        fn spatial_queries_of_scripts_are_answered_by_physics_resource() {
+            use bevy_scriptum::physics::{RayHit, ScriptSpatialQuery};
+
+            let mut app = build_test_app();
+
+            /// Answers queries as if there was a single wall in front of the origin.
+            #[derive(Resource)]
+            struct Wall(Entity);
+
+            impl ScriptSpatialQuery for Wall {
+                fn cast_ray(
+                    &self,
+                    origin: Vec3,
+                    direction: Vec3,
+                    max_distance: f32,
+                ) -> Option<RayHit> {
+                    (direction.normalize().z < 0.0 && max_distance >= 5.0).then(|| RayHit {
+                        entity: self.0,
+                        point: origin - Vec3::Z * 5.0,
+                    })
+                }
+
+                fn overlap_sphere(&self, _center: Vec3, _radius: f32) -> Vec<Entity> {
+                    vec![self.0]
+                }
+            }
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<ScriptValue>);
+
+            let wall = app.world_mut().spawn_empty().id();
+            app.insert_resource(Wall(wall)).init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_spatial_queries::<Wall>().add_function(
+                    String::from("record"),
+                    |In((value,)): In<(ScriptValue,)>, mut records: ResMut<Records>| {
+                        records.0.push(value);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/spatial_queries.{}", $script, $extension),
+                || {},
+            );
+
+            assert_eq!(
+                app.world().resource::<Records>().0,
+                vec![
+                    ScriptValue::Map(
+                        [
+                            ("entity".to_string(), ScriptValue::Entity(wall)),
+                            ("point".to_string(), ScriptValue::Vec3(Vec3::new(0.0, 0.0, -5.0))),
+                        ]
+                        .into()
+                    ),
+                    ScriptValue::Nil,
+                    ScriptValue::Array(vec![ScriptValue::Entity(wall)]),
+                ]
+            );
+        }
+
+        #[cfg(feature = "ui")]
+        #[test]
+        fn script_on_click_calls_script_function_when_pressed() {
+            use bevy::ui::Interaction;
+            use bevy_scriptum::ScriptOnClick;
+
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Clicks(Vec<(String, u32)>);
+
+            app.init_resource::<Clicks>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((button, pressed)): In<(String, u32)>, mut clicks: ResMut<Clicks>| {
+                            clicks.0.push((button, pressed));
+                        },
+                    )
+                    .enable_script_on_click();
+            });
+
+            let script = app
+                .world()
+                .resource::<AssetServer>()
+                .load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                    "tests/{}/script_on_click.{}",
+                    $script, $extension
+                ));
+            let menu = app.world_mut().spawn(Script::new(script.clone())).id();
+            let button = app
+                .world_mut()
+                .spawn((
+                    Script::new(script),
+                    Interaction::None,
+                    ScriptOnClick::<$runtime>::new("on_play"),
+                ))
+                .id();
+            let other_button = app
+                .world_mut()
+                .spawn((
+                    Interaction::None,
+                    ScriptOnClick::<$runtime>::new("on_quit").on_script(menu),
+                ))
+                .id();
+            app.update();
+            app.update();
+
+            *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::Hovered;
+            app.update();
+            *app.world_mut().get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+            *app.world_mut().get_mut::<Interaction>(other_button).unwrap() = Interaction::Pressed;
+            app.update();
+            app.update();
+
+            let mut clicks = app.world().resource::<Clicks>().0.clone();
+            clicks.sort();
+            assert_eq!(
+                clicks,
+                vec![
+                    ("play".to_string(), button.index()),
+                    ("quit".to_string(), other_button.index()),
+                ]
+            );
+        }
+
+        #[cfg(feature = "animation")]
+        #[test]
+        fn animation_markers_trigger_script_events() {
+            use bevy::animation::{AnimationClip, AnimationPlugin, AnimationPlayer};
+            use bevy::time::TimeUpdateStrategy;
+            use bevy_scriptum::ScriptAnimationEvents;
+            use std::time::Duration;
+
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.add_plugins((bevy::time::TimePlugin, AnimationPlugin))
+                .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+                .init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                            events.0.push(event);
+                        },
+                    )
+                    .enable_animation_events();
+            });
+
+            let mut clip = AnimationClip::default();
+            clip.set_duration(1.0);
+            let clip = app.world_mut().resource_mut::<Assets<AnimationClip>>().add(clip);
+            let (graph, animation) = AnimationGraph::from_clip(clip);
+            let graph = app.world_mut().resource_mut::<Assets<AnimationGraph>>().add(graph);
+            let mut player = AnimationPlayer::default();
+            player.play(animation).repeat();
+            let script = app
+                .world()
+                .resource::<AssetServer>()
+                .load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                    "tests/{}/animation_events.{}",
+                    $script, $extension
+                ));
+            app.world_mut().spawn((
+                Script::new(script),
+                player,
+                graph,
+                ScriptAnimationEvents::<$runtime>::default()
+                    .with_marker(animation, 0.25, "footstep")
+                    .with_marker(animation, 0.75, "land"),
+            ));
+
+            for _ in 0..15 {
+                app.update();
+            }
+
+            assert_eq!(
+                app.world().resource::<Events>().0,
+                vec!["footstep", "land", "footstep"]
+            );
+        }
+
+        #[test]
+        fn script_values_are_passed_between_scripts_and_host() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Stored(ScriptValue);
+
+            app.init_resource::<Stored>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("store"),
+                    |In((value,)): In<(ScriptValue,)>, mut stored: ResMut<Stored>| {
+                        stored.0 = value;
+                    },
+                );
+            });
+
+            let entity = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/script_value.{}", $script, $extension),
+                || {},
+            );
+
+            let expected = ScriptValue::Map(
+                [
+                    (String::from("alive"), ScriptValue::Bool(true)),
+                    (String::from("level"), ScriptValue::Int(3)),
+                    (String::from("ratio"), ScriptValue::Float(0.5)),
+                    (String::from("name"), ScriptValue::from("hero")),
+                    (String::from("tags"), ScriptValue::from(vec!["brave", "tall"])),
+                    (
+                        String::from("position"),
+                        ScriptValue::Vec3(Vec3::new(1.0, 2.0, 3.0)),
+                    ),
+                    (String::from("owner"), ScriptValue::Entity(entity)),
+                ]
+                .into(),
+            );
+            assert_eq!(app.world().resource::<Stored>().0, expected);
+
+            app.world_mut()
+                .run_system_once(
+                    move |mut scripted_entities: Query<&mut <$runtime as Runtime>::ScriptData>,
+                          runtime: Res<$runtime>| {
+                        let mut script_data = scripted_entities.get_mut(entity).unwrap();
+                        let stats = runtime
+                            .call_fn("get_stats", &mut script_data, entity, ())
+                            .unwrap();
+                        let stats = runtime.to_script_value(&stats, &script_data);
+                        assert_eq!(
+                            stats.as_map().unwrap()["health"],
+                            ScriptValue::Int(10)
+                        );
+                        assert_eq!(
+                            stats.as_map().unwrap()["items"],
+                            ScriptValue::from(vec!["sword"])
+                        );
+                    },
+                );
+        }
+
+        #[derive(Default, Resource)]
+        struct Loaded(Vec<ScriptValue>);
+
+        fn load_data_files(path: String, count: usize) -> Vec<ScriptValue> {
+            let mut app = build_test_app();
+
+            app.init_resource::<Loaded>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_data_loading().add_function(
+                    String::from("record"),
+                    |In((value,)): In<(ScriptValue,)>, mut loaded: ResMut<Loaded>| {
+                        loaded.0.push(value);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(&mut app, path, || {});
+            for _ in 0..500 {
+                if app.world().resource::<Loaded>().0.len() == count {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                app.update();
+            }
+            app.world_mut().remove_resource::<Loaded>().unwrap().0
+        }
+
+        fn assert_data_file_is_loaded(path: String) {
+
+            let enemy = std::collections::HashMap::from([
+                (String::from("health"), ScriptValue::Int(100)),
+                (String::from("speed"), ScriptValue::Float(2.5)),
+                (String::from("boss"), ScriptValue::Bool(false)),
+            ]);
+            let balance = ScriptValue::Map(std::collections::HashMap::from([
+                (String::from("difficulty"), ScriptValue::from("normal")),
+                (String::from("enemy"), ScriptValue::Map(enemy)),
+                (String::from("waves"), ScriptValue::from(vec![3, 5, 8])),
+            ]));
+            assert_eq!(load_data_files(path, 1), vec![balance]);
+        }
+
+        #[test]
+        fn data_files_outside_of_assets_are_not_loaded() {
+            let loaded = load_data_files(
+                format!("tests/{}/data_outside_assets.{}", $script, $extension),
+                3,
+            );
+
+            assert_eq!(loaded, vec![ScriptValue::Nil; 3]);
+        }
+
+        #[test]
+        fn json_data_files_are_loaded_as_values() {
+            assert_data_file_is_loaded(format!("tests/{}/data_json.{}", $script, $extension));
+        }
+
+        #[cfg(feature = "toml")]
+        #[test]
+        fn toml_data_files_are_loaded_as_values() {
+            assert_data_file_is_loaded(format!("tests/{}/data_toml.{}", $script, $extension));
+        }
+
+        #[cfg(feature = "http")]
+        #[test]
+        fn http_requests_resolve_promises_with_responses() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Responses(Vec<ScriptValue>);
+
+            app.init_resource::<Responses>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .enable_http(["127.0.0.1"])
+                    .add_constant(String::from("SERVER_URL"), spawn_echo_http_server())
+                    .add_function(
+                        String::from("record"),
+                        |In((response,)): In<(ScriptValue,)>, mut responses: ResMut<Responses>| {
+                            responses.0.push(response);
+                        },
+                    );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/http.{}", $script, $extension),
+                || {},
+            );
+            for _ in 0..500 {
+                if app.world().resource::<Responses>().0.len() == 3 {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                app.update();
+            }
+
+            let response = |body: &str| {
+                ScriptValue::Map(std::collections::HashMap::from([
+                    (String::from("status"), ScriptValue::Int(200)),
+                    (String::from("body"), ScriptValue::from(body)),
+                ]))
+            };
+            let responses = &app.world().resource::<Responses>().0;
+            assert_eq!(responses.len(), 3);
+            assert_eq!(responses[0], ScriptValue::Nil);
+            assert!(responses.contains(&response("GET /scores ")));
+            assert!(responses.contains(&response("POST /scores 42")));
+        }
+
+        #[cfg(feature = "http")]
+        #[test]
+        fn http_requests_to_hosts_disguised_as_allowed_are_not_sent() {
+            use bevy_scriptum::http::{
+                HttpClient, HttpError, HttpRequest, HttpResponse, HttpTransport,
+            };
+            use std::sync::{Arc, Mutex};
+
+            struct RecordingTransport(Arc<Mutex<Vec<String>>>);
+
+            impl HttpTransport for RecordingTransport {
+                fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+                    self.0.lock().unwrap().push(request.url);
+                    Ok(HttpResponse {
+                        status: 200,
+                        body: String::new(),
+                    })
+                }
+            }
+
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Responses(Vec<ScriptValue>);
+
+            let sent = Arc::new(Mutex::new(Vec::new()));
+            app.init_resource::<Responses>();
+            app.insert_resource(HttpClient::new(RecordingTransport(sent.clone())));
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_http(["allowed.example.com", "example.com"]).add_function(
+                    String::from("record"),
+                    |In((response,)): In<(ScriptValue,)>, mut responses: ResMut<Responses>| {
+                        responses.0.push(response);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/http_bypass.{}", $script, $extension),
+                || {},
+            );
+            for _ in 0..500 {
+                if app.world().resource::<Responses>().0.len() == 4 {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                app.update();
+            }
+
+            assert_eq!(
+                *sent.lock().unwrap(),
+                vec![String::from("http://allowed.example.com/")]
+            );
+            let responses = &app.world().resource::<Responses>().0;
+            assert_eq!(responses.len(), 4);
+            assert_eq!(responses[..3], vec![ScriptValue::Nil; 3]);
+        }
+
+        #[cfg(feature = "http")]
+        #[test]
+        fn http_requests_over_limit_resolve_with_nil_without_waiting() {
+            use bevy_scriptum::http::{
+                HttpClient, HttpError, HttpRequest, HttpResponse, HttpTransport,
+            };
+            use std::sync::{mpsc, Arc, Mutex};
+
+            struct BlockingTransport {
+                release: Mutex<mpsc::Receiver<()>>,
+                sent: Arc<Mutex<Vec<String>>>,
+            }
+
+            impl HttpTransport for BlockingTransport {
+                fn send(&self, request: HttpRequest) -> Result<HttpResponse, HttpError> {
+                    self.sent.lock().unwrap().push(request.url);
+                    self.release.lock().unwrap().recv().unwrap();
+                    Ok(HttpResponse {
+                        status: 200,
+                        body: String::new(),
+                    })
+                }
+            }
+
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Responses(Vec<ScriptValue>);
+
+            let (release, receiver) = mpsc::channel();
+            let sent = Arc::new(Mutex::new(Vec::new()));
+            let transport = BlockingTransport {
+                release: Mutex::new(receiver),
+                sent: sent.clone(),
+            };
+            app.init_resource::<Responses>();
+            app.insert_resource(HttpClient::with_limits(transport, 1, 1));
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_http(["allowed.example.com"]).add_function(
+                    String::from("record"),
+                    |In((response,)): In<(ScriptValue,)>, mut responses: ResMut<Responses>| {
+                        responses.0.push(response);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/http_limit.{}", $script, $extension),
+                || {},
+            );
+            app.update();
+            let dropped = app.world().resource::<Responses>().0.len();
+            assert!(dropped >= 1);
+            assert!(app
+                .world()
+                .resource::<Responses>()
+                .0
+                .iter()
+                .all(|response| *response == ScriptValue::Nil));
+
+            for _ in 0..3 {
+                let _ = release.send(());
+            }
+            for _ in 0..500 {
+                if app.world().resource::<Responses>().0.len() == 3 {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                app.update();
+            }
+
+            assert_eq!(app.world().resource::<Responses>().0.len(), 3);
+            assert_eq!(sent.lock().unwrap().len(), 3 - dropped);
+        }
+
+        #[test]
+        fn conflicting_functions_follow_conflict_policy() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Greetings(Vec<String>);
+
+            app.init_resource::<Greetings>();
+            app.add_scripting::<$runtime>(|runtime| {
+                let runtime = runtime
+                    .set_function_conflict_policy(FunctionConflictPolicy::Error)
+                    .add_function(String::from("greet"), || String::from("first"));
+                let conflict = runtime
+                    .try_add_function(String::from("greet"), || String::from("second"))
+                    .err()
+                    .expect("function should conflict");
+                assert_eq!(conflict.name(), "greet");
+                conflict
+                    .into_builder()
+                    .set_function_conflict_policy(FunctionConflictPolicy::Namespace(
+                        String::from("plugin"),
+                    ))
+                    .add_function(String::from("greet"), || String::from("third"))
+                    .add_function(
+                        String::from("record"),
+                        |In((greeting,)): In<(String,)>, mut greetings: ResMut<Greetings>| {
+                            greetings.0.push(greeting);
+                        },
+                    );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/conflicting_functions.{}", $script, $extension),
+                || {},
+            );
+            app.update();
+
+            assert_eq!(app.world().resource::<Greetings>().0, vec!["first", "third"]);
+        }
+
+        #[test]
+        fn removed_functions_can_not_be_called() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Greetings(Vec<String>);
+
+            fn call(app: &mut App, name: &'static str) -> Result<(), ScriptingError> {
+                let result = app.world_mut().run_system_once(
+                    move |mut scripted_entities: Query<(
+                        Entity,
+                        &mut <$runtime as Runtime>::ScriptData,
+                    )>,
+                          scripting_runtime: Res<$runtime>| {
+                        let (entity, mut script_data) = scripted_entities.single_mut();
+                        scripting_runtime
+                            .call_fn(name, &mut script_data, entity, ())
+                            .map(|_| ())
+                    },
+                );
+                app.update();
+                app.update();
+                result
+            }
+
+            app.init_resource::<Greetings>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(String::from("greet"), || String::from("hello"))
+                    .add_function(
+                        String::from("record"),
+                        |In((greeting,)): In<(String,)>, mut greetings: ResMut<Greetings>| {
+                            greetings.0.push(greeting);
+                        },
+                    )
+                    .namespace("plugin")
+                    .add_function("greet", || String::from("hello from plugin"));
+            });
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/removed_functions.{}", $script, $extension),
+                || {},
+            );
+
+            call(&mut app, "call_greet").unwrap();
+            call(&mut app, "call_plugin_greet").unwrap();
+            assert_eq!(
+                app.world().resource::<Greetings>().0,
+                vec!["hello", "hello from plugin"]
+            );
+
+            remove_script_function::<$runtime>(app.world_mut(), None, "greet");
+            assert!(call(&mut app, "call_greet").is_err());
+            call(&mut app, "call_plugin_greet").unwrap();
+
+            app.add_scripting_api::<$runtime>(|runtime| {
+                runtime.namespace("plugin").remove_function("greet");
+            });
+            assert!(call(&mut app, "call_plugin_greet").is_err());
+            assert_eq!(
+                app.world().resource::<Greetings>().0,
+                vec!["hello", "hello from plugin", "hello from plugin"]
+            );
+        }
+
+        #[test]
+        fn panicking_functions_do_not_crash_the_app() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(String::from("panicking"), || -> i64 {
+                        panic!("bad argument")
+                    })
+                    .add_function(
+                        String::from("record"),
+                        |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                            events.0.push(event);
+                        },
+                    );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/panicking_function.{}", $script, $extension),
+                || {},
+            );
+            app.update();
+
+            assert_eq!(app.world().resource::<Events>().0, vec!["after"]);
+        }
+
+        #[test]
+        fn wrong_argument_types_raise_script_errors() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Messages(Vec<String>);
+
+            app.init_resource::<Messages>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(String::from("takes_number"), |In((_,)): In<(i64,)>| {})
+                    .add_function(
+                        String::from("record"),
+                        |In((message,)): In<(String,)>, mut messages: ResMut<Messages>| {
+                            messages.0.push(message);
+                        },
+                    );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/wrong_argument_type.{}", $script, $extension),
+                || {},
+            );
+
+            let messages = &app.world().resource::<Messages>().0;
+            assert_eq!(messages.len(), 1);
+            assert!(messages[0].contains("argument 1 of function `takes_number` has to be "));
+            assert!(messages[0].contains("got string"));
+        }
+
+        #[test]
+        fn removed_scripting_can_be_added_again() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            #[derive(Default, Resource)]
+            struct Mismatches(usize);
+
+            fn setup(runtime: bevy_scriptum::ScriptingRuntimeBuilder<$runtime>) {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((event, _delta)): In<(String, f64)>, mut events: ResMut<Events>| {
+                            events.0.push(event);
+                        },
+                    )
+                    .add_script_systems()
+                    .enable_signature_declarations();
+            }
+
+            app.add_plugins(bevy::time::TimePlugin)
+                .init_resource::<Events>()
+                .init_resource::<Mismatches>()
+                .add_systems(
+                    Last,
+                    |mut errors: EventReader<ScriptError<$runtime>>,
+                     mut mismatches: ResMut<Mismatches>| {
+                        mismatches.0 += errors.read().count();
+                    },
+                );
+            app.add_scripting::<$runtime>(setup);
+            let entity = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/restartable.{}", $script, $extension),
+                || {},
+            );
+            assert_eq!(app.world().resource::<Events>().0, vec!["evaluated", "tick"]);
+            assert_eq!(app.world().resource::<Mismatches>().0, 1);
+
+            app.remove_scripting::<$runtime>();
+            app.update();
+            assert!(!app.world().contains_resource::<$runtime>());
+            assert!(app
+                .world()
+                .get::<<$runtime as Runtime>::ScriptData>(entity)
+                .is_none());
+            assert_eq!(app.world().resource::<Events>().0, vec!["evaluated", "tick"]);
+
+            app.world_mut().resource_mut::<Events>().0.clear();
+            app.add_scripting::<$runtime>(setup);
+            app.update();
+            app.update();
+            assert!(app
+                .world()
+                .get::<<$runtime as Runtime>::ScriptData>(entity)
+                .is_some());
+            assert_eq!(app.world().resource::<Events>().0, vec!["evaluated", "tick"]);
+            assert_eq!(app.world().resource::<Mismatches>().0, 2);
+        }
+
+        #[test]
+        fn reloaded_scripts_send_events_and_run_hooks() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<String>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((record,)): In<(String,)>, mut records: ResMut<Records>| {
+                            records.0.push(record);
+                        },
+                    )
+                    .on_script_reloaded(
+                        |In(reloaded): In<ScriptReloaded<$runtime>>,
+                         mut records: ResMut<Records>| {
+                            records.0.push(format!(
+                                "hook {} -> {}",
+                                reloaded.old_version, reloaded.new_version
+                            ));
+                        },
+                    );
+            });
+            app.add_systems(
+                Last,
+                |mut events: EventReader<ScriptReloaded<$runtime>>,
+                 mut records: ResMut<Records>| {
+                    for event in events.read() {
+                        records.0.push(format!("event {}", event.new_version));
+                    }
+                },
+            );
+
+            let entity = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/reloaded.{}", $script, $extension),
+                || {},
+            );
+            assert_eq!(app.world().resource::<Records>().0, vec!["evaluated"]);
+
+            let handle = app
+                .world()
+                .get::<Script<<$runtime as Runtime>::ScriptAsset>>(entity)
+                .unwrap()
+                .script
+                .clone();
+            app.world_mut()
+                .resource_mut::<Assets<<$runtime as Runtime>::ScriptAsset>>()
+                .get_mut(&handle);
+            for _ in 0..3 {
+                app.update();
+            }
+
+            assert_eq!(
+                app.world().resource::<Records>().0,
+                vec!["evaluated", "evaluated", "hook 0 -> 1", "event 1"]
+            );
+        }
+
+        #[test]
+        fn compiled_scripts_are_cached_and_invalidated_on_reload() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {});
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/script_function_gets_called_from_rust.{}",
+                $script, $extension
+            ));
+            for _ in 0..3 {
+                app.world_mut().spawn(Script::new(handle.clone()));
+            }
+            app.update();
+
+            let cache = app
+                .world()
+                .resource::<CompiledScriptCache<$runtime>>()
+                .clone();
+            assert_eq!(cache.compilations(), 1);
+            assert_eq!(cache.len(), 1);
+
+            app.world_mut()
+                .resource_mut::<Assets<<$runtime as Runtime>::ScriptAsset>>()
+                .get_mut(&handle)
+                .unwrap()
+                .0
+                .push('\n');
+            for _ in 0..3 {
+                app.update();
+            }
+
+            // The changed script got compiled once more, replacing the entry of its old source.
+            assert_eq!(cache.compilations(), 2);
+            assert_eq!(cache.len(), 1);
+        }
+
+        #[test]
+        fn compiled_scripts_are_removed_with_their_assets() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {});
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/script_function_gets_called_from_rust.{}",
+                $script, $extension
+            ));
+            let entities = (0..3)
+                .map(|_| app.world_mut().spawn(Script::new(handle.clone())).id())
+                .collect::<Vec<_>>();
+            app.update();
+
+            let cache = app
+                .world()
+                .resource::<CompiledScriptCache<$runtime>>()
+                .clone();
+            assert_eq!(cache.len(), 1);
+
+            for entity in entities {
+                app.world_mut().despawn(entity);
+            }
+            drop(handle);
+            for _ in 0..3 {
+                app.update();
+            }
+
+            assert!(cache.is_empty());
+        }
+
+        #[test]
+        fn compiled_script_cache_can_be_disabled() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.disable_compiled_script_cache();
+            });
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/script_function_gets_called_from_rust.{}",
+                $script, $extension
+            ));
+            for _ in 0..3 {
+                app.world_mut().spawn(Script::new(handle.clone()));
+            }
+            app.update();
+
+            let cache = app.world().resource::<CompiledScriptCache<$runtime>>();
+            assert_eq!(cache.compilations(), 3);
+            assert!(cache.is_empty());
+        }
+
+        #[test]
+        fn promise_callbacks_over_limit_are_called_in_following_frames() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Steps(Vec<i64>);
+
+            app.init_resource::<Steps>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("next_step"),
+                        |mut step: Local<i64>| {
+                            *step += 1;
+                            *step
+                        },
+                    )
+                    .add_function(
+                        String::from("record"),
+                        |In((step,)): In<(i64,)>, mut steps: ResMut<Steps>| {
+                            steps.0.push(step);
+                        },
+                    );
+            });
+            app.world_mut()
+                .resource_mut::<ScriptingSettings<$runtime>>()
+                .max_promise_callbacks = Some(2);
+
+            let asset_server = app.world().resource::<AssetServer>();
+            let handle = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/promise_callbacks.{}",
+                $script, $extension
+            ));
+            app.world_mut().spawn(Script::new(handle));
+            for _ in 0..10 {
+                app.update();
+                if !app.world().resource::<Steps>().0.is_empty() {
+                    break;
+                }
+            }
+
+            assert_eq!(app.world().resource::<Steps>().0, vec![1, 2]);
+            app.update();
+            assert_eq!(app.world().resource::<Steps>().0, vec![1, 2, 3, 4]);
+        }
+
+        #[test]
+        fn promise_resolutions_are_sent_as_events() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Resolved(Vec<(Option<Entity>, String, ScriptValue)>);
+
+            app.init_resource::<Resolved>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .enable_promise_events()
+                    .add_function(String::from("add"), |In((a, b)): In<(i64, i64)>| a + b)
+                    .namespace("game")
+                    .add_function("greet", |In((name,)): In<(String,)>| {
+                        format!("hello {name}")
+                    });
+            });
+            app.add_systems(
+                Last,
+                |mut events: EventReader<PromiseResolved<$runtime>>,
+                 mut resolved: ResMut<Resolved>| {
+                    for event in events.read() {
+                        resolved.0.push((event.entity, event.function.clone(), event.value.clone()));
+                    }
+                },
+            );
+
+            let entity = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/promise_events.{}", $script, $extension),
+                || {},
+            );
+
+            assert_eq!(
+                app.world().resource::<Resolved>().0,
+                vec![
+                    (Some(entity), String::from("add"), ScriptValue::Int(3)),
+                    (
+                        Some(entity),
+                        String::from("game.greet"),
+                        ScriptValue::from("hello hero")
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn blackboard_values_are_shared_with_rust() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<String>);
+
+            #[derive(Default, Resource)]
+            struct Changes(Vec<BlackboardChanged>);
+
+            app.init_resource::<Records>()
+                .init_resource::<Changes>()
+                .add_systems(
+                    Last,
+                    |mut events: EventReader<BlackboardChanged>, mut changes: ResMut<Changes>| {
+                        changes.0.extend(events.read().cloned());
+                    },
+                );
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_blackboard().add_function(
+                    String::from("record"),
+                    |In((record,)): In<(String,)>, mut records: ResMut<Records>| {
+                        records.0.push(record);
+                    },
+                );
+            });
+            app.world_mut()
+                .resource_mut::<Blackboard>()
+                .set("target", "gate");
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/blackboard.{}", $script, $extension),
+                || {},
+            );
+            app.update();
+
+            assert_eq!(app.world().resource::<Records>().0, vec!["gate", "missing"]);
+            let blackboard = app.world().resource::<Blackboard>();
+            assert_eq!(blackboard.get("alarm"), Some(&ScriptValue::Bool(true)));
+            assert_eq!(
+                blackboard.get("patrol"),
+                Some(&ScriptValue::Map(
+                    [(String::from("speed"), ScriptValue::Int(2))].into()
+                ))
+            );
+            let keys = app
+                .world()
+                .resource::<Changes>()
+                .0
+                .iter()
+                .map(|change| change.key.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(keys, vec!["target", "alarm", "patrol"]);
+        }
+
+        #[test]
+        fn script_defined_components_are_stored_on_entities() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<bool>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_script_components().add_function(
+                    String::from("record"),
+                    |In((burning,)): In<(bool,)>, mut records: ResMut<Records>| {
+                        records.0.push(burning);
+                    },
+                );
+            });
+
+            let entity = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/script_components.{}", $script, $extension),
+                || {},
+            );
+            app.update();
+
+            assert_eq!(app.world().resource::<Records>().0, vec![true]);
+            let components = app.world().get::<ScriptComponents>(entity).unwrap();
+            assert_eq!(
+                components.get("Burning"),
+                Some(&ScriptValue::Map(
+                    [("duration".to_string(), ScriptValue::Int(3))].into()
+                ))
+            );
+
+            app.world_mut().run_system_once(
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    scripting_runtime
+                        .call_fn("extinguish", &mut script_data, entity, ())
+                        .unwrap();
+                },
+            );
+            app.update();
+
+            let components = app.world().get::<ScriptComponents>(entity).unwrap();
+            assert!(!components.has("Burning"));
+        }
+
+        #[test]
+        fn hierarchy_of_entities_is_accessible_to_scripts() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<ScriptValue>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_hierarchy().add_function(
+                    String::from("record"),
+                    |In((value,)): In<(ScriptValue,)>, mut records: ResMut<Records>| {
+                        records.0.push(value);
+                    },
+                );
+            });
+
+            let asset_server = app.world_mut().get_resource_mut::<AssetServer>().unwrap();
+            let asset = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/hierarchy.{}",
+                $script, $extension
+            ));
+            let parent = app.world_mut().spawn_empty().id();
+            let hand = app.world_mut().spawn(Name::new("Hand")).id();
+            let arm = app.world_mut().spawn(Name::new("Arm")).add_child(hand).id();
+            let entity = app.world_mut().spawn(Script::new(asset)).add_child(arm).id();
+            app.world_mut().entity_mut(parent).add_child(entity);
+            app.update();
+            app.update();
+
+            assert_eq!(
+                app.world().resource::<Records>().0,
+                vec![
+                    ScriptValue::Entity(parent),
+                    ScriptValue::Array(vec![ScriptValue::Entity(arm)]),
+                    ScriptValue::Entity(hand),
+                    ScriptValue::Nil,
+                ]
+            );
+        }
+
+        #[test]
+        fn transforms_of_entities_are_manipulated_by_scripts() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<ScriptValue>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_transform().add_function(
+                    String::from("record"),
+                    |In((value,)): In<(ScriptValue,)>, mut records: ResMut<Records>| {
+                        records.0.push(value);
+                    },
+                );
+            });
+
+            let asset_server = app.world_mut().get_resource_mut::<AssetServer>().unwrap();
+            let asset = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/transform.{}",
+                $script, $extension
+            ));
+            let entity = app
+                .world_mut()
+                .spawn((Script::new(asset), Transform::default()))
+                .id();
+            app.update();
+            app.update();
+
+            assert_eq!(
+                app.world().resource::<Records>().0,
+                vec![ScriptValue::Vec3(Vec3::new(1.0, 2.0, 3.0))]
+            );
+            let transform = app.world().get::<Transform>(entity).unwrap();
+            assert!(transform
+                .rotation
+                .abs_diff_eq(Quat::from_rotation_y(0.5), 1e-6));
+
+            app.world_mut().run_system_once(
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    scripting_runtime
+                        .call_fn("face", &mut script_data, entity, ())
+                        .unwrap();
+                },
+            );
+            app.update();
+
+            let transform = app.world().get::<Transform>(entity).unwrap();
+            assert!(transform.forward().abs_diff_eq(Vec3::Z, 1e-6));
+        }
+
+        #[test]
+        fn localized_texts_are_loaded_and_translated_for_scripts() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<String>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_localization().add_function(
+                    String::from("record"),
+                    |In((text,)): In<(String,)>, mut records: ResMut<Records>| {
+                        records.0.push(text);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/localization.{}", $script, $extension),
+                || {},
+            );
+
+            assert_eq!(
+                app.world().resource::<Records>().0,
+                vec!["Start game", "Score: 10", "menu.quit"]
+            );
+        }
+
+        #[test]
+        fn scripts_bind_actions_to_keys() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_action_bindings();
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/action_bindings.{}", $script, $extension),
+                || {},
+            );
+
+            let actions = app.world().resource::<ScriptActions>();
+            assert_eq!(actions.key("dash"), Some(KeyCode::Space));
+            assert_eq!(actions.key("jump"), Some(KeyCode::ArrowUp));
+            assert_eq!(actions.key("fly"), None);
+
+            let mut input = ButtonInput::<KeyCode>::default();
+            input.press(KeyCode::Space);
+            assert!(actions.just_pressed("dash", &input));
+            assert!(!actions.pressed("jump", &input));
+        }
+
+        #[test]
+        fn registered_functions_are_listed_by_api_registry() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(String::from("hello"), || {})
+                    .set_function_source(Some("inventory"))
+                    .namespace("inventory")
+                    .add_function("give", |In((_item, _count)): In<(String, i64)>| true)
+                    .finish();
+            });
+            app.update();
+
+            let functions = app.world_mut().run_system_once(
+                |registry: ScriptApiRegistry<$runtime>| registry.iter().collect::<Vec<_>>(),
+            );
+            let functions = functions
+                .iter()
+                .map(|function| {
+                    (
+                        function.qualified_name(),
+                        function.arg_type_names.clone(),
+                        function.return_type_name,
+                        function.source.as_deref(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert!(functions.contains(&(String::from("hello"), vec![], "()", None)));
+            assert!(functions.contains(&(
+                String::from("inventory.give"),
+                vec![std::any::type_name::<String>(), "i64"],
+                "bool",
+                Some("inventory")
+            )));
+        }
+
+        #[test]
+        fn functions_defined_by_scripts_and_their_bases_are_listed() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("record"), |In((_event,)): In<(String,)>| {});
+            });
+
+            let asset_server = app.world().resource::<AssetServer>();
+            let base = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/base_greeter.{}",
+                $script, $extension
+            ));
+            let derived = asset_server.load(format!(
+                "tests/{}/derived_greeter.{}",
+                $script, $extension
+            ));
+            let entity = app
+                .world_mut()
+                .spawn(Script::new(derived).with_base(base))
+                .id();
+            for _ in 0..3 {
+                app.update();
+            }
+
+            let (functions, defines_record) = app.world_mut().run_system_once(
+                move |introspection: ScriptIntrospection<$runtime>| {
+                    (
+                        introspection.functions(entity),
+                        introspection.defines(entity, "record"),
+                    )
+                },
+            );
+            assert_eq!(
+                functions,
+                Some(vec![String::from("describe"), String::from("greet")])
+            );
+            assert!(!defines_record);
+        }
+
+        #[test]
+        fn base_scripts_are_evaluated_before_derived_script() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("record"),
+                    |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                        events.0.push(event);
+                    },
+                );
+            });
+
+            let asset_server = app.world().resource::<AssetServer>();
+            let base = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/base_greeter.{}",
+                $script, $extension
+            ));
+            let derived = asset_server.load(format!(
+                "tests/{}/derived_greeter.{}",
+                $script, $extension
+            ));
+            app.world_mut()
+                .spawn(Script::new(derived).with_base(base));
+            for _ in 0..3 {
+                app.update();
+            }
+
+            assert_eq!(
+                app.world().resource::<Events>().0,
+                vec!["hello from derived"]
+            );
+        }
+
+        #[test]
+        fn scripts_get_attached_and_detached_with_commands() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("record"),
+                    |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                        events.0.push(event);
+                    },
+                );
+            });
+
+            let entity = app.world_mut().spawn_empty().id();
+            app.world_mut()
+                .commands()
+                .entity(entity)
+                .attach_script::<$runtime>(format!("tests/{}/attach_first.{}", $script, $extension));
+            app.world_mut().flush();
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(app.world().resource::<Events>().0, vec!["attached first"]);
+
+            app.world_mut()
+                .commands()
+                .entity(entity)
+                .attach_script::<$runtime>(format!("tests/{}/attach_second.{}", $script, $extension));
+            app.world_mut().flush();
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(
+                app.world().resource::<Events>().0,
+                vec!["attached first", "detached first", "attached second"]
+            );
+
+            app.world_mut()
+                .commands()
+                .entity(entity)
+                .detach_script::<$runtime>();
+            app.world_mut().flush();
+            app.update();
+            assert_eq!(
+                app.world().resource::<Events>().0,
+                vec![
+                    "attached first",
+                    "detached first",
+                    "attached second",
+                    "detached second"
+                ]
+            );
+            assert!(app.world().get_entity(entity).is_some());
+            assert!(app
+                .world()
+                .get::<<$runtime as Runtime>::ScriptData>(entity)
+                .is_none());
+            assert!(app
+                .world()
+                .get::<Script<<$runtime as Runtime>::ScriptAsset>>(entity)
+                .is_none());
+        }
+
+        #[cfg(feature = "inspector")]
+        #[test]
+        fn script_variables_are_mirrored_and_edited_through_component() {
+            use bevy_scriptum::inspector::{ScriptVariableValue, ScriptVariables};
+
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_inspector();
+            });
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/inspector_variables.{}", $script, $extension),
+                || {},
+            );
+            app.update();
+
+            let variable_name = |app: &App, field: &str| {
+                app.world()
+                    .get::<ScriptVariables>(entity_id)
+                    .unwrap()
+                    .variables
+                    .iter()
+                    .map(|variable| variable.name.clone())
+                    .find(|name| name.ends_with(&format!(".{field}")))
+                    .unwrap()
+            };
+            let speed = variable_name(&app, "speed");
+            let name = variable_name(&app, "name");
+            let variables = app.world().get::<ScriptVariables>(entity_id).unwrap();
+            assert_eq!(
+                variables.get(&speed),
+                Some(&ScriptVariableValue::Number(1.5))
+            );
+            assert_eq!(
+                variables.get(&variable_name(&app, "alive")),
+                Some(&ScriptVariableValue::Bool(true))
+            );
+
+            app.world_mut()
+                .get_mut::<ScriptVariables>(entity_id)
+                .unwrap()
+                .set(&name, ScriptVariableValue::String(String::from("enemy")));
+            app.update();
+
+            <$runtime>::assert_state_key_value_string(app.world(), entity_id, "name", "enemy");
+            assert_eq!(
+                app.world()
+                    .get::<ScriptVariables>(entity_id)
+                    .unwrap()
+                    .get(&name),
+                Some(&ScriptVariableValue::String(String::from("enemy")))
+            );
+        }
+
+        #[cfg(feature = "states")]
+        #[test]
+        fn state_scripts_run_on_state_transitions() {
+            let mut app = build_test_app();
+
+            #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+            enum GameState {
+                #[default]
+                Loading,
+                Playing,
+            }
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.add_plugins(bevy::state::app::StatesPlugin)
+                .init_state::<GameState>()
+                .init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("record"),
+                    |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                        events.0.push(event);
+                    },
+                );
+            });
+            app.add_state_script::<$runtime, GameState>(
+                GameState::Loading,
+                format!("tests/{}/state_script.{}", $script, $extension),
+            );
+
+            app.update();
+            assert_eq!(app.world().resource::<Events>().0, vec!["entered"]);
+
+            app.world_mut()
+                .resource_mut::<NextState<GameState>>()
+                .set(GameState::Playing);
+            app.update();
+            assert_eq!(app.world().resource::<Events>().0, vec!["entered", "exited"]);
+
+            let scripts = app
+                .world_mut()
+                .query::<&<$runtime as Runtime>::ScriptData>()
+                .iter(app.world())
+                .count();
+            assert_eq!(scripts, 0);
+        }
+
+        #[cfg(feature = "states")]
+        #[test]
+        fn exposed_states_get_read_and_set_from_script() {
+            let mut app = build_test_app();
+
+            #[derive(States, Reflect, Default, Debug, Clone, PartialEq, Eq, Hash)]
+            enum GameState {
+                #[default]
+                Running,
+                Paused,
+            }
+
+            #[derive(Default, Resource)]
+            struct Events(Vec<String>);
+
+            app.add_plugins(bevy::state::app::StatesPlugin)
+                .init_state::<GameState>()
+                .init_resource::<Events>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("record"),
+                        |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                            events.0.push(event);
+                        },
+                    )
+                    .expose_state::<GameState>();
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/exposed_state.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+            app.update();
+            app.update();
+
+            assert_eq!(app.world().resource::<Events>().0, vec!["Running"]);
+            assert_eq!(
+                app.world().resource::<State<GameState>>().get(),
+                &GameState::Paused
+            );
+        }
+
+        #[test]
+        fn test_promise() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), || 123);
+            });
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/return_via_promise.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            <$runtime>::assert_state_key_value_i32(&app.world(), entity_id, "x", 123i32);
+        }
+
+        #[test]
+        fn test_cancelled_promise_drops_call_and_callbacks() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled(u8);
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                        res.0 += 1;
+                    })
+                    .add_function(
+                        String::from("rust_callback"),
+                        |mut res: ResMut<TimesCalled>| {
+                            res.0 += 1;
+                        },
+                    );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/promise_cancel.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(app.world().resource::<TimesCalled>().0, 0);
+        }
+
+        #[test]
+        fn test_pending_call_of_despawned_entity_is_dropped() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled(u8);
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                    res.0 += 1;
+                });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/rust_function_gets_called_from_script.{}",
+                    $script, $extension
+                )
+                .to_string(),
+                |mut commands: Commands,
+                 mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    scripting_runtime
+                        .call_fn("test_func", &mut script_data, entity, ())
+                        .unwrap();
+                    commands.entity(entity).despawn();
+                },
+            );
+
+            assert_eq!(app.world().resource::<TimesCalled>().0, 0);
+        }
+
+        #[test]
+        fn test_promise_runtime_error_does_not_panic() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), || 123);
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/promise_runtime_error.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+        }
+
+        #[test]
+        fn test_side_effects() {
+            let mut app = build_test_app();
+
+            #[derive(Component)]
+            struct MyTag;
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("spawn_entity"), |mut commands: Commands| {
+                    commands.spawn(MyTag);
+                });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/side_effects.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            app.world_mut().run_system_once(|tagged: Query<&MyTag>| {
+                tagged.single();
+            });
+        }
+
+        #[test]
+        fn entities_spawned_by_callbacks_are_queryable_in_post_update() {
+            let mut app = build_test_app();
+
+            #[derive(Component)]
+            struct MyTag;
+
+            #[derive(Default, Resource)]
+            struct TaggedInPostUpdate(usize);
+
+            app.world_mut().init_resource::<TaggedInPostUpdate>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("spawn_entity"), |mut commands: Commands| {
+                    commands.spawn(MyTag);
+                });
+            });
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let path = format!("tests/{}/side_effects.{}", $script, $extension);
+            app.world_mut().spawn(Script::new(
+                asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path),
+            ));
+            app.update();
+
+            app.add_systems(Update, call_script_on_update_from_rust::<$runtime>);
+            app.add_systems(
+                PostUpdate,
+                |tagged: Query<&MyTag>, mut count: ResMut<TaggedInPostUpdate>| {
+                    count.0 = tagged.iter().count();
+                },
+            );
+            app.update();
+
+            assert_eq!(app.world().resource::<TaggedInPostUpdate>().0, 1);
+        }
+
+        #[test]
+        fn apply_script_calls_makes_callback_effects_visible_to_following_systems() {
+            let mut app = build_test_app();
+
+            #[derive(Component)]
+            struct MyTag;
+
+            #[derive(Default, Resource)]
+            struct TaggedInUpdate(usize);
+
+            app.world_mut().init_resource::<TaggedInUpdate>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("spawn_entity"), |mut commands: Commands| {
+                    commands.spawn(MyTag);
+                });
+            });
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let path = format!("tests/{}/side_effects.{}", $script, $extension);
+            app.world_mut().spawn(Script::new(
+                asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path),
+            ));
+            app.update();
+
+            app.add_systems(
+                Update,
+                (
+                    call_script_on_update_from_rust::<$runtime>,
+                    apply_script_calls::<$runtime>,
+                    |tagged: Query<&MyTag>, mut count: ResMut<TaggedInUpdate>| {
+                        count.0 = tagged.iter().count();
+                    },
+                )
+                    .chain(),
+            );
+            app.update();
+
+            assert_eq!(app.world().resource::<TaggedInUpdate>().0, 1);
+        }
+
+        #[test]
+        fn test_script_preprocessor_transforms_source() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_script_preprocessor(|source, _path| source.replace("__FUNC__", "rust_func"))
+                    .add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                        res.times_called += 1;
+                    });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/preprocessed_script.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(
+                app.world()
+                    .get_resource::<TimesCalled>()
+                    .unwrap()
+                    .times_called,
+                1
+            );
+        }
+
+        #[test]
+        fn test_script_decoder_decodes_asset() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_script_decoder("xor", XorDecoder::new("bevy"))
+                    .add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                        res.times_called += 1;
+                    });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/encoded_script.{}.xor", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(
+                app.world()
+                    .get_resource::<TimesCalled>()
+                    .unwrap()
+                    .times_called,
+                1
+            );
+        }
+
+        #[cfg(feature = "gzip")]
+        #[test]
+        fn test_gzip_decoder_decodes_asset() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_script_decoder("gz", GzipDecoder)
+                    .add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                        res.times_called += 1;
+                    });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/encoded_script.{}.gz", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(
+                app.world()
+                    .get_resource::<TimesCalled>()
+                    .unwrap()
+                    .times_called,
+                1
+            );
+        }
+
+        #[test]
+        fn syntax_error_sends_script_error_with_diagnostic() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Diagnostics(Vec<(Option<Entity>, String, ScriptDiagnostic)>);
+
+            app.add_scripting::<$runtime>(|_| {});
+            app.init_resource::<Diagnostics>().add_systems(
+                Last,
+                |mut events: EventReader<ScriptError<$runtime>>,
+                 mut diagnostics: ResMut<Diagnostics>| {
+                    for event in events.read() {
+                        if let ScriptingError::CompileError(diagnostic) = &event.error {
+                            diagnostics
+                                .0
+                                .push((event.entity, event.path.clone(), diagnostic.clone()));
+                        }
+                    }
+                },
+            );
+
+            let path = format!("tests/{}/syntax_error.{}", $script, $extension);
+            let entity = run_script::<$runtime, _, _>(&mut app, path.clone(), || {});
+
+            let diagnostics = &app.world().resource::<Diagnostics>().0;
+            assert_eq!(diagnostics.len(), 1);
+            let (error_entity, error_path, diagnostic) = &diagnostics[0];
+            assert_eq!(*error_entity, Some(entity));
+            assert_eq!(*error_path, path);
+            assert_eq!(diagnostic.file, path);
+            assert_eq!(diagnostic.line, Some(2));
+            assert!(diagnostic.snippet.as_deref().unwrap().contains("= = 1"));
+            assert!(!diagnostic.message.is_empty());
+            assert!(!diagnostic.message.contains(&path));
+        }
+
+        #[test]
+        fn calls_queued_from_other_threads_run_in_scripting_schedule() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_call_queue(DEFAULT_CALL_QUEUE_CAPACITY);
+            });
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/script_function_gets_called_from_rust_with_single_param.{}",
+                    $script, $extension
+                ),
+                || {},
+            );
+            let sender = app
+                .world()
+                .resource::<ScriptCallQueue<$runtime>>()
+                .sender();
+            std::thread::spawn(move || {
+                sender
+                    .call(entity_id, "test_func", vec![ScriptValue::Int(1)])
+                    .unwrap();
+            })
+            .join()
+            .unwrap();
+            app.update();
+
+            <$runtime>::assert_state_key_value_i32(&app.world(), entity_id, "a_value", 1i32);
+        }
+
+        #[test]
+        fn calls_queued_over_capacity_are_rejected() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_call_queue(1);
+            });
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/script_function_gets_called_from_rust.{}",
+                    $script, $extension
+                ),
+                || {},
+            );
+            let sender = app
+                .world()
+                .resource::<ScriptCallQueue<$runtime>>()
+                .sender();
+            sender.call(entity_id, "test_func", vec![]).unwrap();
+            assert_eq!(
+                sender.call(entity_id, "test_func", vec![]),
+                Err(ScriptCallError::Full)
+            );
+            app.update();
+            sender.call(entity_id, "test_func", vec![]).unwrap();
+            app.update();
+
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 2i64);
+        }
+
+        #[test]
+        fn calls_queued_for_suspended_entities_are_skipped() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.enable_call_queue(DEFAULT_CALL_QUEUE_CAPACITY);
+            });
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/script_function_gets_called_from_rust.{}",
+                    $script, $extension
+                ),
+                || {},
+            );
+            app.world_mut().entity_mut(entity_id).insert(ScriptSuspended);
+            let sender = app
+                .world()
+                .resource::<ScriptCallQueue<$runtime>>()
+                .sender();
+            sender.call(entity_id, "test_func", vec![]).unwrap();
+            sender.call_all("test_func", vec![]).unwrap();
+            app.update();
+
+            <$runtime>::assert_state_key_value_i64(&app.world(), entity_id, "times_called", 0i64);
+        }
+
+        #[test]
+        fn entity_variable_can_be_renamed() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<bool>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.set_entity_variable(Some("self_entity")).add_function(
+                    String::from("record"),
+                    |In((value,)): In<(bool,)>, mut records: ResMut<Records>| {
+                        records.0.push(value);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/entity_variable.{}", $script, $extension),
+                || {},
+            );
+
+            assert_eq!(app.world().resource::<Records>().0, vec![true]);
+        }
+
+        #[test]
+        fn entity_variable_can_be_disabled() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Records(Vec<bool>);
+
+            app.init_resource::<Records>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.set_entity_variable(None).add_function(
+                    String::from("record"),
+                    |In((value,)): In<(bool,)>, mut records: ResMut<Records>| {
+                        records.0.push(value);
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/no_entity_variable.{}", $script, $extension),
+                || {},
+            );
+
+            assert_eq!(app.world().resource::<Records>().0, vec![true]);
+        }
+
+        #[test]
+        fn script_without_required_functions_sends_event() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Missing(Vec<(Entity, String, Vec<String>)>);
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.require_functions(["on_init", "on_update"]);
+            });
+            app.init_resource::<Missing>().add_systems(
+                Last,
+                |mut events: EventReader<ScriptMissingFunctions<$runtime>>,
+                 mut missing: ResMut<Missing>| {
+                    for event in events.read() {
+                        missing
+                            .0
+                            .push((event.entity, event.path.clone(), event.missing.clone()));
+                    }
+                },
+            );
+
+            let path = format!("tests/{}/missing_functions.{}", $script, $extension);
+            let entity = run_script::<$runtime, _, _>(&mut app, path.clone(), || {});
+
+            assert_eq!(
+                app.world().resource::<Missing>().0,
+                vec![(entity, path, vec![String::from("on_update")])]
+            );
+        }
+
+        #[test]
+        fn required_functions_are_checked_after_runtime_is_added_back() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Missing(usize);
+
+            fn setup(runtime: bevy_scriptum::ScriptingRuntimeBuilder<$runtime>) {
+                runtime.require_functions(["on_update"]);
+            }
+
+            app.add_scripting::<$runtime>(setup);
+            app.init_resource::<Missing>().add_systems(
+                Last,
+                |mut events: EventReader<ScriptMissingFunctions<$runtime>>,
+                 mut missing: ResMut<Missing>| {
+                    missing.0 += events.read().count();
+                },
+            );
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/missing_functions.{}", $script, $extension),
+                || {},
+            );
+            assert_eq!(app.world().resource::<Missing>().0, 1);
+
+            app.remove_scripting::<$runtime>();
+            app.update();
+            app.add_scripting::<$runtime>(setup);
+            app.update();
+            app.update();
+
+            assert_eq!(app.world().resource::<Missing>().0, 2);
+        }
+
+        #[test]
+        fn mismatched_signature_declarations_send_script_errors() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Mismatches(Vec<(Option<Entity>, String, String)>);
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("give_item"),
+                        |In((_item, _count)): In<(String, i64)>| {},
+                    )
+                    .enable_signature_declarations();
+            });
+            app.init_resource::<Mismatches>().add_systems(
+                Last,
+                |mut events: EventReader<ScriptError<$runtime>>,
+                 mut mismatches: ResMut<Mismatches>| {
+                    for event in events.read() {
+                        if let ScriptingError::SignatureMismatch { function, reason } =
+                            &event.error
+                        {
+                            mismatches
+                                .0
+                                .push((event.entity, function.clone(), reason.clone()));
+                        }
+                    }
+                },
+            );
+
+            let entity = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/declare_signatures.{}", $script, $extension),
+                || {},
+            );
+
+            let mismatches = &app.world().resource::<Mismatches>().0;
+            assert_eq!(
+                *mismatches,
+                vec![
+                    (
+                        Some(entity),
+                        String::from("give_item"),
+                        String::from("argument 2 is declared as string, but it takes i64"),
+                    ),
+                    (
+                        Some(entity),
+                        String::from("take_item"),
+                        String::from("function is not registered"),
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn invalid_utf8_script_sends_script_error() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), || {});
+            });
+            record_load_errors::<$runtime>(&mut app);
+
+            let asset_server = app.world().resource::<AssetServer>();
+            let asset = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(format!(
+                "tests/{}/invalid_utf8.{}",
+                $script, $extension
+            ));
+            let entity = app.world_mut().spawn(Script::new(asset.clone())).id();
+            for _ in 0..3 {
+                app.update();
+            }
+
+            let errors = &app.world().resource::<LoadErrors>().0;
+            assert_eq!(errors.len(), 1);
+            let (error_entity, script, path, error) = &errors[0];
+            assert_eq!(*error_entity, Some(entity));
+            assert_eq!(*script, asset.untyped());
+            assert_eq!(
+                *path,
+                format!("tests/{}/invalid_utf8.{}", $script, $extension)
+            );
+            assert!(matches!(
+                error,
+                ScriptLoadError::InvalidUtf8 { line: 2, .. }
+            ));
+            assert!(app
+                .world()
+                .get::<<$runtime as Runtime>::ScriptData>(entity)
+                .is_none());
+        }
+
+        #[test]
+        fn lossy_utf8_setting_loads_invalid_utf8_script() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                    res.times_called += 1;
+                });
+            });
+
+            let asset_server = app.world().resource::<AssetServer>();
+            let asset = asset_server.load_with_settings::<<$runtime as Runtime>::ScriptAsset, _>(
+                format!("tests/{}/invalid_utf8.{}", $script, $extension),
+                |settings: &mut ScriptLoaderSettings| settings.lossy_utf8 = true,
+            );
+            app.world_mut().spawn(Script::new(asset));
+            app.update();
+            app.world_mut()
+                .run_system_once(call_script_on_update_from_rust::<$runtime>);
+            app.update();
+
+            assert_eq!(app.world().resource::<TimesCalled>().times_called, 1);
+        }
+
+        #[test]
+        fn byte_order_mark_is_only_stripped_with_accept_bom_setting() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                    res.times_called += 1;
+                });
+            });
+
+            let path = format!("tests/{}/byte_order_mark.{}", $script, $extension);
+            let asset_server = app.world().resource::<AssetServer>();
+            let accepted = asset_server
+                .load_with_settings::<<$runtime as Runtime>::ScriptAsset, _>(
+                    path.clone(),
+                    |settings: &mut ScriptLoaderSettings| settings.accept_bom = true,
+                );
+            app.world_mut().spawn(Script::new(accepted));
+            app.update();
+            app.world_mut()
+                .run_system_once(call_script_on_update_from_rust::<$runtime>);
+            app.update();
+
+            assert_eq!(app.world().resource::<TimesCalled>().times_called, 1);
+
+            let mut app = build_test_app();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), || {});
+            });
+            record_load_errors::<$runtime>(&mut app);
+            let asset_server = app.world().resource::<AssetServer>();
+            let rejected = asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path);
+            app.world_mut().spawn(Script::new(rejected));
+            for _ in 0..3 {
+                app.update();
+            }
+
+            let errors = &app.world().resource::<LoadErrors>().0;
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].3, ScriptLoadError::ByteOrderMark);
+        }
+
+        #[test]
+        fn test_script_from_pack_gets_loaded() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                    res.times_called += 1;
+                });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/script_pack.{}.pack#main.{}",
+                    $script, $extension, $extension
+                )
+                .to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(
+                app.world()
+                    .get_resource::<TimesCalled>()
+                    .unwrap()
+                    .times_called,
+                1
+            );
+        }
+
+        #[test]
+        fn test_api_version_is_available_to_scripts() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Version(String);
+
+            app.world_mut().init_resource::<Version>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.set_api_version(semver::Version::new(1, 3, 0)).add_function(
+                    String::from("rust_func"),
+                    |In((version,)): In<(String,)>, mut res: ResMut<Version>| {
+                        res.0 = version;
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/api_version.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(app.world().resource::<Version>().0, "1.3.0");
+            assert_eq!(
+                app.world()
+                    .resource::<ScriptingApiVersions>()
+                    .get::<$runtime>(),
+                Some(&semver::Version::new(1, 3, 0))
+            );
+        }
+
+        #[test]
+        fn test_function_requiring_capability_is_denied_without_grant() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime
+                    .add_function_with(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                        res.times_called += 1;
+                    })
+                    .requires("filesystem");
+            });
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let path = format!(
+                "tests/{}/rust_function_gets_called_from_script.{}",
+                $script, $extension
+            );
+            let denied = app
+                .world_mut()
+                .spawn(Script::new(asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path.clone())))
+                .id();
+            let granted = app
+                .world_mut()
+                .spawn((
+                    Script::new(asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path)),
+                    ScriptCapabilities::new(["filesystem"]),
+                ))
+                .id();
+            app.update();
+
+            let results = app.world_mut().run_system_once(
+                move |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                      scripting_runtime: ResMut<$runtime>| {
+                    [denied, granted].map(|entity| {
+                        let (entity, mut script_data) = scripted_entities.get_mut(entity).unwrap();
+                        scripting_runtime
+                            .call_fn("test_func", &mut script_data, entity, ())
+                            .map(|_| ())
+                    })
+                },
+            );
+            app.update();
+
+            let [denied_result, granted_result] = results;
+            let error = denied_result.expect_err("call without capability has to fail");
+            assert!(error.to_string().contains("requires filesystem capability"));
+            assert!(granted_result.is_ok());
+            assert_eq!(
+                app.world()
+                    .get_resource::<TimesCalled>()
+                    .unwrap()
+                    .times_called,
+                1
+            );
+        }
+
+        #[test]
+        fn test_restore_state_rolls_back_script_state() {
+            let mut app = build_test_app();
+
+            app.add_scripting::<$runtime>(|_| {});
+
+            let entity_id = run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/script_function_gets_called_from_rust.{}", $script, $extension)
+                    .to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            app.world_mut().run_system_once(
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    let snapshot = scripting_runtime.snapshot_state(&script_data).unwrap();
+                    for _ in 0..2 {
+                        scripting_runtime
+                            .call_fn("test_func", &mut script_data, entity, ())
+                            .unwrap();
+                    }
+                    scripting_runtime
+                        .restore_state(&mut script_data, &snapshot)
+                        .unwrap();
+                },
+            );
+
+            <$runtime>::assert_state_key_value_i64(app.world(), entity_id, "times_called", 1);
+        }
+
+        #[test]
+        fn test_deterministic_mode_uses_seeded_rng() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Drawn(i64);
+
+            app.world_mut().init_resource::<Drawn>();
+            app.insert_resource(ScriptRng::new(42));
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("rust_func"),
+                    |In((x,)): In<(i64,)>, mut res: ResMut<Drawn>| {
+                        res.0 = x;
+                    },
+                );
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/deterministic_random.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            let expected = ScriptRng::new(42).next_in_range(1, 1000000);
+            assert_eq!(app.world().resource::<Drawn>().0, expected);
+        }
+
+        #[test]
+        fn test_deterministic_mode_disables_wall_clock() {
+            let mut app = build_test_app();
+
+            app.insert_resource(ScriptRng::new(42));
+            app.add_scripting::<$runtime>(|_| {});
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let path = format!("tests/{}/deterministic_clock.{}", $script, $extension);
+            app.world_mut().spawn(Script::new(
+                asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path),
+            ));
+            app.update();
+
+            let result = app.world_mut().run_system_once(
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    scripting_runtime
+                        .call_fn("test_func", &mut script_data, entity, ())
+                        .map(|_| ())
+                },
+            );
+
+            let error = result.expect_err("reading wall clock has to fail");
+            assert!(error.to_string().contains("disabled in deterministic mode"));
+        }
+
+        #[test]
+        fn test_golden_test_records_calls() {
+            GoldenTest::<$runtime>::new(|runtime| {
+                runtime.add_function(
+                    String::from("log_event"),
+                    |In((_name, _value)): In<(String, i64)>| {},
+                );
+            })
+            .run_script(format!("tests/{}/golden_calls.{}", $script, $extension))
+            .call_fn("test_func", ())
+            .assert_golden(format!("tests/golden/{}_calls.txt", $script));
+        }
+
+        #[test]
+        fn test_script_info_is_available_to_scripts() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Info {
+                path: String,
+                without_mod: bool,
+            }
+
+            app.world_mut().init_resource::<Info>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("rust_func"),
+                    |In((path, without_mod)): In<(String, bool)>, mut res: ResMut<Info>| {
+                        res.path = path;
+                        res.without_mod = without_mod;
+                    },
+                );
+            });
+
+            let path = format!("tests/{}/script_info.{}", $script, $extension);
+            run_script::<$runtime, _, _>(
+                &mut app,
+                path.clone(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            let info = app.world().resource::<Info>();
+            assert_eq!(info.path, path);
+            assert!(info.without_mod);
+        }
+
+        #[test]
+        fn test_log_functions_are_available_to_scripts() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                    res.times_called += 1;
+                });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!("tests/{}/logging.{}", $script, $extension).to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(app.world().resource::<TimesCalled>().times_called, 1);
+        }
+
+        // Luau has no debug hooks, so the Lua runtime cannot be debugged with it.
+        #[cfg(all(feature = "debugger", not(feature = "luau")))]
+        #[test]
+        fn test_debugger_stops_at_breakpoint() {
+            use bevy_scriptum::debugger::ScriptDebugger;
+            use std::io::{BufRead, BufReader, Write};
+            use std::net::TcpStream;
+            use std::time::Duration;
+
+            let mut app = build_test_app();
+
+            let path = format!("tests/{}/debugger.{}", $script, $extension);
+            let debugger = ScriptDebugger::listen("127.0.0.1:0").unwrap();
+            debugger.set_breakpoint(path.clone(), 3);
+            let addr = debugger.local_addr();
+            app.insert_resource(debugger);
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |In((_x,)): In<(i64,)>| {});
+            });
+
+            let client = std::thread::spawn(move || {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                stream
+                    .set_read_timeout(Some(Duration::from_secs(10)))
+                    .unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut read_line = || {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    line.trim_end().to_string()
+                };
+                let mut lines = vec![read_line()];
+                writeln!(stream, "variables").unwrap();
+                loop {
+                    let line = read_line();
+                    if line == "end" {
+                        break;
+                    }
+                    lines.push(line);
+                }
+                writeln!(stream, "continue").unwrap();
+                lines
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                path.clone(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            let lines = client.join().unwrap();
+            assert_eq!(lines[0], format!("stopped {path} 3"));
+            assert!(lines.contains(&String::from("variable x 5")), "{lines:?}");
+        }
+
+        #[test]
+        fn test_resumable_call_is_spread_across_frames() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct Sum(i64);
+
+            app.world_mut().init_resource::<Sum>();
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(
+                    String::from("rust_func"),
+                    |In((x,)): In<(i64,)>, mut res: ResMut<Sum>| {
+                        res.0 = x;
+                    },
+                );
+            });
+            app.add_systems(Update, resume_paused_calls::<$runtime>(1000));
+
+            let asset_server = app.world().resource::<AssetServer>().clone();
+            let path = format!("tests/{}/resumable_call.{}", $script, $extension);
+            app.world_mut().spawn(Script::new(
+                asset_server.load::<<$runtime as Runtime>::ScriptAsset>(path),
+            ));
+            app.update();
+
+            let paused = app.world_mut().run_system_once(
+                |mut scripted_entities: Query<(Entity, &mut <$runtime as Runtime>::ScriptData)>,
+                 scripting_runtime: ResMut<$runtime>| {
+                    let (entity, mut script_data) = scripted_entities.single_mut();
+                    let progress = scripting_runtime
+                        .call_fn_resumable("test_func", &mut script_data, entity, (), 1000)
+                        .unwrap();
+                    matches!(progress, CallProgress::Paused)
+                },
+            );
+            assert!(paused);
+
+            let mut frames = 0;
+            while app.world().resource::<Sum>().0 == 0 {
+                app.update();
+                frames += 1;
+                assert!(frames < 10000, "call has not finished");
+            }
+            assert!(frames > 1);
+            assert_eq!(app.world().resource::<Sum>().0, 5000050000);
+        }
+
+        #[test]
+        fn test_rust_function_gets_called_from_script() {
+            let mut app = build_test_app();
+
+            #[derive(Default, Resource)]
+            struct TimesCalled {
+                times_called: u8,
+            }
+
+            app.world_mut().init_resource::<TimesCalled>();
+
+            app.add_scripting::<$runtime>(|runtime| {
+                runtime.add_function(String::from("rust_func"), |mut res: ResMut<TimesCalled>| {
+                    res.times_called += 1;
+                });
+            });
+
+            run_script::<$runtime, _, _>(
+                &mut app,
+                format!(
+                    "tests/{}/rust_function_gets_called_from_script.{}",
+                    $script, $extension
+                )
+                .to_string(),
+                call_script_on_update_from_rust::<$runtime>,
+            );
+
+            assert_eq!(
+                app.world()
+                    .get_resource::<TimesCalled>()
+                    .unwrap()
+                    .times_called,
+                1
+            );
+        }
+    };
+}
+
+#[cfg(feature = "rhai")]
+mod rhai_tests {
+    use bevy::{ecs::system::RunSystemOnce as _, prelude::*};
+    use bevy_scriptum::runtimes::rhai::prelude::*;
+
+    impl AssertStateKeyValue for RhaiRuntime {
+        type ScriptData = RhaiScriptData;
+
+        fn assert_state_key_value_i64(world: &World, entity_id: Entity, key: &str, value: i64) {
+            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
+            let state = script_data.scope.get_value::<rhai::Map>("state").unwrap();
+            assert_eq!(state[key].clone_cast::<i64>(), value);
+        }
+
+        fn assert_state_key_value_i32(world: &World, entity_id: Entity, key: &str, value: i32) {
+            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
+            let state = script_data.scope.get_value::<rhai::Map>("state").unwrap();
+            // Narrow integers passed to scripts become Rhai integers.
+            assert_eq!(state[key].clone_cast::<i64>(), i64::from(value));
+        }
+
+        fn assert_state_key_value_string(world: &World, entity_id: Entity, key: &str, value: &str) {
+            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
+            let state = script_data.scope.get_value::<rhai::Map>("state").unwrap();
+            assert_eq!(state[key].clone_cast::<String>(), value);
+        }
+    }
+
+    scripting_tests!(RhaiRuntime, "rhai", "rhai");
+
+    #[test]
+    fn scripts_can_define_entity_when_entity_variable_is_disabled() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Records(Vec<bool>);
+
+        app.init_resource::<Records>();
+        app.add_scripting::<RhaiRuntime>(|runtime| {
+            runtime.set_entity_variable(None).add_function(
+                String::from("record"),
+                |In((value,)): In<(bool,)>, mut records: ResMut<Records>| {
+                    records.0.push(value);
+                },
+            );
+        });
+
+        run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/define_entity.rhai".to_string(),
+            || {},
+        );
+
+        assert_eq!(app.world().resource::<Records>().0, vec![true]);
+    }
+
+    #[test]
+    fn scripts_can_not_define_renamed_entity_variable() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<RhaiRuntime>(|runtime| {
+            runtime.set_entity_variable(Some("me"));
+        });
+
+        let entity = run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/define_entity_variable.rhai".to_string(),
+            || {},
+        );
+        app.update();
+
+        assert!(app.world().get::<RhaiScriptData>(entity).is_none());
+    }
+
+    #[test]
+    fn calls_queued_for_all_scripts_skip_scripts_without_function() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<RhaiRuntime>(|runtime| {
+            runtime.enable_call_queue(DEFAULT_CALL_QUEUE_CAPACITY);
+        });
+
+        let asset_server = app.world().resource::<AssetServer>();
+        let with_function = asset_server
+            .load::<RhaiScript>("tests/rhai/script_function_gets_called_from_rust.rhai");
+        let without_function = asset_server.load::<RhaiScript>("tests/rhai/missing_functions.rhai");
+        let entity_id = app.world_mut().spawn(Script::new(with_function)).id();
+        app.world_mut().spawn(Script::new(without_function));
+        app.update();
+
+        let sender = app
+            .world()
+            .resource::<ScriptCallQueue<RhaiRuntime>>()
+            .sender();
+        sender.call_all("test_func", vec![]).unwrap();
+        app.update();
+
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entity_id, "times_called", 1);
+    }
+
+    #[test]
+    fn array_returned_from_script_function_holds_multiple_values() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<RhaiRuntime>(|_| {});
+
+        let entity_id = run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/multiple_returns.rhai".to_string(),
+            |_: Query<(Entity, &mut RhaiScriptData)>, _: ResMut<RhaiRuntime>| {},
+        );
+
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<&mut RhaiScriptData>,
+                  scripting_runtime: ResMut<RhaiRuntime>| {
+                let mut script_data = scripted_entities.get_mut(entity_id).unwrap();
+                let result = scripting_runtime
+                    .call_fn("pair", &mut script_data, entity_id, ())
+                    .unwrap();
+                let values = result.get::<rhai::Array>().unwrap();
+                assert_eq!(values[0].clone_cast::<i64>(), 1);
+                assert_eq!(values[1].clone_cast::<String>(), "two");
+            },
+        );
+    }
+
+    #[test]
+    fn native_modules_are_registered_alongside_functions() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<RhaiRuntime>(|runtime| {
+            let mut dice = rhai::Module::new();
+            dice.set_native_fn("double", |value: i64| Ok(value * 2));
+            let mut global = rhai::Module::new();
+            global.set_native_fn("triple", |value: i64| Ok(value * 3));
+            runtime
+                .namespace("dice")
+                .add_function("roll", || 4)
+                .finish()
+                .register_module("dice", dice)
+                .register_global_module(global.into());
+        });
+
+        let entity_id = run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/native_modules.rhai".to_string(),
+            |_: Query<(Entity, &mut RhaiScriptData)>, _: ResMut<RhaiRuntime>| {},
+        );
+
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entity_id, "doubled", 42);
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entity_id, "tripled", 6);
+    }
+
+    #[test]
+    fn cached_scripts_keep_scope_per_entity() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<RhaiRuntime>(|_| {});
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle = asset_server
+            .load::<RhaiScript>("tests/rhai/script_function_gets_called_from_rust.rhai");
+        let entities = (0..3)
+            .map(|_| app.world_mut().spawn(Script::new(handle.clone())).id())
+            .collect::<Vec<_>>();
+        app.update();
+
+        let first = entities[0];
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<(Entity, &mut RhaiScriptData)>,
+                  scripting_runtime: ResMut<RhaiRuntime>| {
+                let (entity, mut script_data) = scripted_entities.get_mut(first).unwrap();
+                scripting_runtime
+                    .call_fn("test_func", &mut script_data, entity, ())
+                    .unwrap();
+            },
+        );
+
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entities[0], "times_called", 1);
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entities[1], "times_called", 0);
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entities[2], "times_called", 0);
+    }
+
+    #[test]
+    fn imported_modules_are_read_through_asset_server() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<RhaiRuntime>(|_| {});
+
+        let entity_id = run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/import_module.rhai".to_string(),
+            |_: Query<(Entity, &mut RhaiScriptData)>, _: ResMut<RhaiRuntime>| {},
+        );
+
+        RhaiRuntime::assert_state_key_value_i64(app.world(), entity_id, "doubled", 42);
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<&mut RhaiScriptData>,
+                  scripting_runtime: ResMut<RhaiRuntime>| {
+                let mut script_data = scripted_entities.get_mut(entity_id).unwrap();
+                let result = scripting_runtime
+                    .call_fn("test_func", &mut script_data, entity_id, ())
+                    .unwrap();
+                assert_eq!(result.get::<i64>().unwrap(), 10);
+            },
+        );
+    }
+
+    #[test]
+    fn numbers_out_of_range_of_rhai_numbers_are_not_passed_to_scripts() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Records(Vec<String>);
+
+        app.init_resource::<Records>();
+        app.add_scripting::<RhaiRuntime>(|runtime| {
+            runtime
+                .add_constant(String::from("EXACT"), i64::MAX as u64)
+                .add_constant(String::from("BIG"), u64::MAX)
+                .add_function(String::from("too_big"), || u64::MAX)
+                .add_function(String::from("largest"), || i64::MAX as u64)
+                .add_function(
+                    String::from("record"),
+                    |In((record,)): In<(String,)>, mut records: ResMut<Records>| {
+                        records.0.push(record);
+                    },
+                );
+        });
+
+        run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/out_of_range_numbers.rhai".to_string(),
+            || {},
+        );
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<Records>().0,
+            vec!["true", "false", &i64::MAX.to_string()]
+        );
+    }
+
+    #[test]
+    fn narrow_numbers_are_converted_to_rhai_numbers() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Records(Vec<String>);
+
+        app.init_resource::<Records>();
+        app.add_scripting::<RhaiRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("double"), |In((value,)): In<(u8,)>| {
+                    u16::from(value) * 2
+                })
+                .add_function(String::from("half"), |In((value,)): In<(f32,)>| value / 2.0)
+                .add_function(
+                    String::from("record"),
+                    |In((record,)): In<(String,)>, mut records: ResMut<Records>| {
+                        records.0.push(record);
+                    },
+                );
+        });
+
+        let entity_id = run_script::<RhaiRuntime, _, _>(
+            &mut app,
+            "tests/rhai/narrow_numbers.rhai".to_string(),
+            || {},
+        );
+        app.update();
+
+        app.update();
+
+        let records = &app.world().resource::<Records>().0;
+        assert_eq!(records.len(), 3);
+        assert!(records[0].contains(
+            "argument 1 of function `double` has to be integer in range of u8, got i64 256"
+        ));
+        assert_eq!(records[1..], ["401", "3.0"]);
+
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<&mut RhaiScriptData>,
+                  scripting_runtime: ResMut<RhaiRuntime>| {
+                let mut script_data = scripted_entities.get_mut(entity_id).unwrap();
+                let result = scripting_runtime
+                    .call_fn("increment", &mut script_data, entity_id, (254u8,))
+                    .unwrap();
+                assert_eq!(result.get::<u8>(), Some(255));
+                let result = scripting_runtime
+                    .call_fn("increment", &mut script_data, entity_id, (255u8,))
+                    .unwrap();
+                assert_eq!(result.get::<u8>(), None);
+                assert_eq!(result.get::<i32>(), Some(256));
+            },
+        );
+    }
+}
+
+#[cfg(any(feature = "lua", feature = "lua54", feature = "luau"))]
+// Which fields `LuaRuntimeSettings` has depends on the selected Lua implementation.
+#[allow(clippy::needless_update)]
+mod lua_tests {
+    use bevy::{ecs::system::RunSystemOnce as _, prelude::*};
+    use bevy_scriptum::mods::{ModError, ModManager, ModManagerPlugin};
+    use bevy_scriptum::runtimes::lua::mlua::{self, Table};
+    use bevy_scriptum::runtimes::lua::prelude::*;
+
+    impl AssertStateKeyValue for LuaRuntime {
+        type ScriptData = LuaScriptData;
+
+        fn assert_state_key_value_i64(world: &World, entity_id: Entity, key: &str, value: i64) {
+            let runtime = world.get_resource::<LuaRuntime>().unwrap();
+            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
+            runtime.with_script_globals(script_data, |_, globals| {
+                let state = globals.get::<_, Table>("State").unwrap();
+                assert_eq!(state.get::<_, i64>(key).unwrap(), value);
+            });
+        }
+
+        fn assert_state_key_value_i32(world: &World, entity_id: Entity, key: &str, value: i32) {
+            let runtime = world.get_resource::<LuaRuntime>().unwrap();
+            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
+            runtime.with_script_globals(script_data, |_, globals| {
+                let state = globals.get::<_, Table>("State").unwrap();
+                assert_eq!(state.get::<_, i32>(key).unwrap(), value);
+            });
+        }
+
+        fn assert_state_key_value_string(world: &World, entity_id: Entity, key: &str, value: &str) {
+            let runtime = world.get_resource::<LuaRuntime>().unwrap();
+            let script_data = world.get::<Self::ScriptData>(entity_id).unwrap();
+            runtime.with_script_globals(script_data, |_, globals| {
+                let state = globals.get::<_, Table>("State").unwrap();
+                assert_eq!(state.get::<_, String>(key).unwrap(), value);
+            });
+        }
+    }
+
+    scripting_tests!(LuaRuntime, "lua", "lua");
+
+    #[test]
+    fn calls_queued_for_all_scripts_skip_scripts_without_function() {
+        let mut app = build_test_app();
+
+        // Scripts sharing global variables would all see the function.
+        app.insert_resource(LuaRuntimeSettings {
+            variables: ScriptVariables::Environment,
+            ..Default::default()
+        });
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.enable_call_queue(DEFAULT_CALL_QUEUE_CAPACITY);
+        });
+
+        let asset_server = app.world().resource::<AssetServer>();
+        let with_function =
+            asset_server.load::<LuaScript>("tests/lua/script_function_gets_called_from_rust.lua");
+        let without_function = asset_server.load::<LuaScript>("tests/lua/missing_functions.lua");
+        let entity_id = app.world_mut().spawn(Script::new(with_function)).id();
+        app.world_mut().spawn(Script::new(without_function));
+        app.update();
+
+        let sender = app
+            .world()
+            .resource::<ScriptCallQueue<LuaRuntime>>()
+            .sender();
+        sender.call_all("test_func", vec![]).unwrap();
+        app.update();
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "times_called", 1);
+    }
+
+    #[test]
+    fn required_modules_are_read_through_asset_server() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let entity_id = run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/require_module.lua".to_string(),
+            |_: Query<(Entity, &mut LuaScriptData)>, _: ResMut<LuaRuntime>| {},
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "doubled", 42);
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "incremented", 42);
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "missing_blocked", 1);
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "loadlib_removed", 1);
+    }
+
+    #[test]
+    fn pack_modules_can_be_required_with_computed_names() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let entity_id = run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/dynamic_require.lua.pack#main.lua".to_string(),
+            |_: Query<(Entity, &mut LuaScriptData)>, _: ResMut<LuaRuntime>| {},
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "answer", 42);
+    }
+
+    #[test]
+    fn required_modules_are_loaded_per_script_in_its_environment() {
+        let mut app = build_test_app();
+
+        app.insert_resource(LuaRuntimeSettings {
+            variables: ScriptVariables::Environment,
+            ..Default::default()
+        });
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle = asset_server.load::<LuaScript>("tests/lua/require_owner_module.lua");
+        let entities = (0..2)
+            .map(|_| app.world_mut().spawn(Script::new(handle.clone())).id())
+            .collect::<Vec<_>>();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        for entity in entities {
+            let index = i64::from(entity.index());
+            LuaRuntime::assert_state_key_value_i64(app.world(), entity, "owner_index", index);
+            LuaRuntime::assert_state_key_value_i64(
+                app.world(),
+                entity,
+                "owner_global_index",
+                index,
+            );
+        }
+        let (owner_leaked, module_cached) =
+            app.world().resource::<LuaRuntime>().with_engine(|engine| {
+                let loaded = engine
+                    .globals()
+                    .get::<_, Table>("package")
+                    .unwrap()
+                    .get::<_, Table>("loaded")
+                    .unwrap();
+                (
+                    !engine
+                        .globals()
+                        .get::<_, mlua::Value>("Owner")
+                        .unwrap()
+                        .is_nil(),
+                    !loaded
+                        .get::<_, mlua::Value>("modules.owner")
+                        .unwrap()
+                        .is_nil(),
+                )
+            });
+        assert!(!owner_leaked);
+        assert!(!module_cached);
+    }
+
+    #[test]
+    fn chained_promise_callbacks_over_limit_are_called_in_following_frames() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Steps(Vec<i64>);
+
+        app.init_resource::<Steps>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("first_step"), || 1i64)
+                .add_function(
+                    String::from("record"),
+                    |In((step,)): In<(i64,)>, mut steps: ResMut<Steps>| {
+                        steps.0.push(step);
+                    },
+                );
+        });
+        app.world_mut()
+            .resource_mut::<ScriptingSettings<LuaRuntime>>()
+            .max_promise_callbacks = Some(2);
+
+        let asset_server = app.world().resource::<AssetServer>();
+        let handle = asset_server.load::<LuaScript>("tests/lua/promise_chain.lua");
+        app.world_mut().spawn(Script::new(handle));
+        for _ in 0..10 {
+            app.update();
+            if !app.world().resource::<Steps>().0.is_empty() {
+                break;
+            }
+        }
+
+        assert_eq!(app.world().resource::<Steps>().0, vec![1, 2]);
+        app.update();
+        assert_eq!(app.world().resource::<Steps>().0, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn multiple_values_returned_from_rust_function_are_passed_to_promise_callback() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.add_function(String::from("rust_func"), || (123, String::from("abc")));
+        });
+
+        let entity_id = run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/multiple_returns.lua".to_string(),
+            call_script_on_update_from_rust::<LuaRuntime>,
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "x", 123);
+        LuaRuntime::assert_state_key_value_string(app.world(), entity_id, "y", "abc");
+    }
+
+    #[test]
+    fn multiple_values_returned_from_script_function_convert_into_tuple() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let entity_id = run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/multiple_returns.lua".to_string(),
+            |_: Query<(Entity, &mut LuaScriptData)>, _: ResMut<LuaRuntime>| {},
+        );
+
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<&mut LuaScriptData>,
+                  scripting_runtime: ResMut<LuaRuntime>| {
+                let mut script_data = scripted_entities.get_mut(entity_id).unwrap();
+                let result = scripting_runtime
+                    .call_fn("pair", &mut script_data, entity_id, ())
+                    .unwrap();
+                scripting_runtime.with_script_engine(&script_data, |engine| {
+                    let (number, text) = result.get::<(i64, String)>(engine).unwrap();
+                    assert_eq!(number, 1);
+                    assert_eq!(text, "two");
+                    assert_eq!(result.get::<i64>(engine).unwrap(), 1);
+                });
+            },
+        );
+    }
+
+    #[cfg(feature = "lua")]
+    #[test]
+    fn disable_jit_setting_turns_jit_compiler_off() {
+        let runtime = LuaRuntime::with_settings(LuaRuntimeSettings {
+            disable_jit: true,
+            ..Default::default()
+        });
+
+        let enabled = runtime
+            .with_engine(|engine| engine.load("return jit.status()").eval::<bool>())
+            .unwrap();
+
+        assert!(!enabled);
+    }
+
+    #[cfg(feature = "luau")]
+    #[test]
+    fn sandbox_setting_makes_libraries_read_only() {
+        let runtime = LuaRuntime::with_settings(LuaRuntimeSettings {
+            sandbox: true,
+            ..Default::default()
+        });
+
+        let result = runtime.with_engine(|engine| engine.load("math.answer = 42").exec());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn arguments_failing_conversion_do_not_crash_the_app() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Events(Vec<String>);
+
+        app.init_resource::<Events>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_function(
+                    String::from("takes_numbers"),
+                    |In((_,)): In<(Vec<i64>,)>| {},
+                )
+                .add_function(
+                    String::from("record"),
+                    |In((event,)): In<(String,)>, mut events: ResMut<Events>| {
+                        events.0.push(event);
+                    },
+                );
+        });
+
+        run_script::<LuaRuntime, _, _>(&mut app, String::from("tests/lua/bad_argument.lua"), || {});
+        app.update();
+
+        assert_eq!(app.world().resource::<Events>().0, vec!["after"]);
+    }
+
+    #[test]
+    fn safe_stdlib_setting_leaves_out_file_and_os_access() {
+        let runtime = LuaRuntime::with_settings(LuaRuntimeSettings {
+            stdlib: LuaStdLib::Safe,
+            ..Default::default()
+        });
+
+        let (removed, kept) = runtime
+            .with_engine(|engine| {
+                engine
+                    .load(
+                        "return io == nil and os == nil and dofile == nil and loadfile == nil, \
+                         string ~= nil and math ~= nil and table ~= nil and require ~= nil",
+                    )
+                    .eval::<(bool, bool)>()
+            })
+            .unwrap();
+
+        assert!(removed);
+        assert!(kept);
+    }
+
+    #[test]
+    fn safe_stdlib_setting_prevents_loading_bytecode() {
+        #[cfg(not(feature = "luau"))]
+        let bytecode = LuaRuntime::with_settings(LuaRuntimeSettings::default())
+            .with_engine(|engine| {
+                engine
+                    .load("return string.dump(function() return 42 end)")
+                    .eval::<mlua::String>()
+                    .map(|bytecode| bytecode.as_bytes().to_vec())
+            })
+            .unwrap();
+        #[cfg(feature = "luau")]
+        let bytecode = mlua::Compiler::new().compile("return 42");
+        let runtime = LuaRuntime::with_settings(LuaRuntimeSettings {
+            stdlib: LuaStdLib::Safe,
+            ..Default::default()
+        });
+
+        let (loaded, dump_removed, source_loaded) = runtime
+            .with_engine(|engine| {
+                engine
+                    .globals()
+                    .set("bytecode", engine.create_string(&bytecode)?)?;
+                engine
+                    .load(
+                        "local ok, chunk = pcall(load, bytecode) \
+                         return ok and chunk ~= nil, string.dump == nil, \
+                         load('return 42')() == 42",
+                    )
+                    .eval::<(bool, bool, bool)>()
+            })
+            .unwrap();
+
+        assert!(!loaded);
+        assert!(dump_removed);
+        assert!(source_loaded);
+    }
+
+    #[test]
+    fn custom_stdlib_setting_loads_only_provided_libraries() {
+        let runtime = LuaRuntime::with_settings(LuaRuntimeSettings {
+            stdlib: LuaStdLib::Custom(mlua::StdLib::STRING | mlua::StdLib::MATH),
+            ..Default::default()
+        });
+
+        let (loaded, missing) = runtime
+            .with_engine(|engine| {
+                engine
+                    .load("return string ~= nil and math ~= nil, table == nil and package == nil")
+                    .eval::<(bool, bool)>()
+            })
+            .unwrap();
+
+        assert!(loaded);
+        assert!(missing);
+    }
+
+    #[test]
+    fn log_functions_name_script_evaluated_in_its_own_environment() {
+        use bevy::utils::tracing::{
+            field::{Field, Visit},
+            subscriber, Event, Subscriber,
+        };
+        use bevy_scriptum::{ScriptInfo, SCRIPT_LOG_TARGET};
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+        #[derive(Clone, Default)]
+        struct LoggedScripts(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for LoggedScripts {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                if field.name() == "script" {
+                    self.0.lock().unwrap().push(value.to_string());
+                }
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        impl<S: Subscriber> Layer<S> for LoggedScripts {
+            fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+                if event.metadata().target() == SCRIPT_LOG_TARGET {
+                    event.record(&mut self.clone());
+                }
+            }
+        }
+
+        let runtime = LuaRuntime::with_settings(LuaRuntimeSettings {
+            variables: ScriptVariables::Environment,
+            ..Default::default()
+        });
+        let script = LuaScript::from(String::from(
+            "log.info('evaluated')\nfunction test_func() log.warn('called') end",
+        ));
+        let info = ScriptInfo {
+            path: String::from("scripts/logging.lua"),
+            ..Default::default()
+        };
+        let logged = LoggedScripts::default();
+
+        subscriber::with_default(tracing_subscriber::registry().with(logged.clone()), || {
+            let entity = Entity::from_raw(1);
+            let mut script_data = runtime.eval(&script, entity, &info).unwrap();
+            runtime
+                .call_fn("test_func", &mut script_data, entity, ())
+                .unwrap();
+        });
+
+        assert_eq!(
+            *logged.0.lock().unwrap(),
+            vec!["scripts/logging.lua", "scripts/logging.lua"]
+        );
+    }
+
+    #[test]
+    fn per_entity_isolation_keeps_script_globals_separate() {
+        let mut app = build_test_app();
+
+        app.insert_resource(LuaRuntimeSettings {
+            isolation: IsolationMode::PerEntity,
+            ..Default::default()
+        });
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle =
+            asset_server.load::<LuaScript>("tests/lua/script_function_gets_called_from_rust.lua");
+        let first = app.world_mut().spawn(Script::new(handle.clone())).id();
+        let second = app.world_mut().spawn(Script::new(handle.clone())).id();
+        app.update();
+
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<(Entity, &mut LuaScriptData)>,
+                  scripting_runtime: ResMut<LuaRuntime>| {
+                let (entity, mut script_data) = scripted_entities.get_mut(first).unwrap();
+                for _ in 0..2 {
+                    scripting_runtime
+                        .call_fn("test_func", &mut script_data, entity, ())
+                        .unwrap();
+                }
+            },
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), first, "times_called", 2);
+        LuaRuntime::assert_state_key_value_i64(app.world(), second, "times_called", 0);
+    }
+
+    #[test]
+    fn cached_scripts_keep_globals_per_entity() {
+        let mut app = build_test_app();
+
+        app.insert_resource(LuaRuntimeSettings {
+            isolation: IsolationMode::PerEntity,
+            ..Default::default()
+        });
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle =
+            asset_server.load::<LuaScript>("tests/lua/script_function_gets_called_from_rust.lua");
+        let entities = (0..3)
+            .map(|_| app.world_mut().spawn(Script::new(handle.clone())).id())
+            .collect::<Vec<_>>();
+        app.update();
+
+        let first = entities[0];
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<(Entity, &mut LuaScriptData)>,
+                  scripting_runtime: ResMut<LuaRuntime>| {
+                let (entity, mut script_data) = scripted_entities.get_mut(first).unwrap();
+                scripting_runtime
+                    .call_fn("test_func", &mut script_data, entity, ())
+                    .unwrap();
+            },
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entities[0], "times_called", 1);
+        LuaRuntime::assert_state_key_value_i64(app.world(), entities[1], "times_called", 0);
+        LuaRuntime::assert_state_key_value_i64(app.world(), entities[2], "times_called", 0);
+    }
+
+    #[test]
+    fn functions_registered_in_batch_get_installed_once_it_finishes() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let is_installed = |runtime: &LuaRuntime| {
+            runtime.with_engine(|engine| {
+                engine
+                    .globals()
+                    .get::<_, Option<mlua::Function>>("batched")
+                    .unwrap()
+                    .is_some()
+            })
+        };
+        let mut runtime = app.world_mut().resource_mut::<LuaRuntime>();
+        runtime.start_registering_fns();
+        runtime
+            .register_fn(
+                None,
+                String::from("batched"),
+                Vec::new(),
+                |_, _| unreachable!(),
+            )
+            .unwrap();
+        assert!(!is_installed(&runtime));
+
+        runtime.finish_registering_fns().unwrap();
+        assert!(is_installed(&runtime));
+    }
+
+    #[test]
+    fn scripts_sharing_lua_state_keep_globals_per_entity() {
+        let mut app = build_test_app();
+
+        app.insert_resource(LuaRuntimeSettings {
+            variables: ScriptVariables::Environment,
+            ..Default::default()
+        });
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle =
+            asset_server.load::<LuaScript>("tests/lua/script_function_gets_called_from_rust.lua");
+        let entities = (0..2)
+            .map(|_| app.world_mut().spawn(Script::new(handle.clone())).id())
+            .collect::<Vec<_>>();
+        app.update();
+
+        let first = entities[0];
+        app.world_mut().run_system_once(
+            move |mut scripted_entities: Query<(Entity, &mut LuaScriptData)>,
+                  scripting_runtime: ResMut<LuaRuntime>| {
+                let (entity, mut script_data) = scripted_entities.get_mut(first).unwrap();
+                scripting_runtime
+                    .call_fn("test_func", &mut script_data, entity, ())
+                    .unwrap();
+            },
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entities[0], "times_called", 1);
+        LuaRuntime::assert_state_key_value_i64(app.world(), entities[1], "times_called", 0);
+    }
+
+    #[test]
+    fn promise_callbacks_see_entity_of_their_script() {
+        let mut app = build_test_app();
+
+        app.insert_resource(LuaRuntimeSettings {
+            variables: ScriptVariables::Environment,
+            ..Default::default()
+        });
+        #[derive(Default, Resource)]
+        struct Entities(Vec<Entity>);
+
+        app.init_resource::<Entities>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("wait"), || {})
+                .add_function(
+                    String::from("record_entity"),
+                    |In((entity,)): In<(BevyEntity,)>, mut entities: ResMut<Entities>| {
+                        entities.0.push(entity.0);
+                    },
+                );
+        });
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle = asset_server.load::<LuaScript>("tests/lua/promise_entity.lua");
+        let entities = (0..2)
+            .map(|_| app.world_mut().spawn(Script::new(handle.clone())).id())
+            .collect::<Vec<_>>();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let mut recorded = app.world().resource::<Entities>().0.clone();
+        recorded.sort();
+        assert_eq!(recorded, entities);
+    }
+
+    #[test]
+    fn global_script_variables_are_cleared_after_calls() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        let entity_id = run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/script_function_gets_called_from_rust.lua".to_string(),
+            call_script_on_update_from_rust::<LuaRuntime>,
+        );
+
+        LuaRuntime::assert_state_key_value_i64(app.world(), entity_id, "times_called", 1);
+        let entity_cleared = app.world().resource::<LuaRuntime>().with_engine(|engine| {
+            engine
+                .globals()
+                .get::<_, mlua::Value>("entity")
+                .unwrap()
+                .is_nil()
+        });
+        assert!(entity_cleared);
+    }
+
+    #[test]
+    fn per_entity_isolation_supports_functions_and_promises() {
+        let mut app = build_test_app();
+
+        app.insert_resource(LuaRuntimeSettings {
+            isolation: IsolationMode::PerEntity,
+            ..Default::default()
+        });
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.add_function(String::from("rust_func"), || 123);
+        });
+
+        let entity_id = run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/return_via_promise.lua".to_string(),
+            call_script_on_update_from_rust::<LuaRuntime>,
+        );
+
+        LuaRuntime::assert_state_key_value_i32(app.world(), entity_id, "x", 123);
+    }
+
+    #[test]
+    fn restore_state_removes_new_globals_and_keeps_functions() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|_| {});
+
+        run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/script_function_gets_called_from_rust.lua".to_string(),
+            call_script_on_update_from_rust::<LuaRuntime>,
+        );
+
+        app.world_mut().run_system_once(
+            |mut scripted_entities: Query<&mut LuaScriptData>, scripting_runtime: ResMut<LuaRuntime>| {
+                let mut script_data = scripted_entities.single_mut();
+                let snapshot = scripting_runtime.snapshot_state(&script_data).unwrap();
+                scripting_runtime.with_script_globals(&script_data, |engine, globals| {
+                    engine
+                        .load("Added = 1; State.extra = { 1, 2 }; State.times_called = 5")
+                        .set_environment(globals)
+                        .exec()
+                        .unwrap();
+                });
+
+                scripting_runtime
+                    .restore_state(&mut script_data, &snapshot)
+                    .unwrap();
+
+                scripting_runtime.with_script_globals(&script_data, |engine, globals| {
+                    let result = engine
+                        .load(
+                            "return Added == nil and State.extra == nil and State.times_called == 1 \
+                             and type(test_func) == 'function' and string.format('%d', 1) == '1'",
+                        )
+                        .set_environment(globals)
+                        .eval::<bool>()
+                        .unwrap();
+                    assert!(result);
+                });
+            },
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn lua_annotations_describe_registered_functions() {
+        let mut app = build_test_app();
+        let path = std::env::temp_dir().join(format!(
+            "bevy_scriptum_annotations_{}.d.lua",
+            std::process::id()
+        ));
+
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .write_lua_annotations(&path)
+                .add_function(
+                    String::from("spawn_at"),
+                    |In((_entity, _position)): In<(BevyEntity, BevyVec3)>| {},
+                )
+                .set_function_source(Some("inventory"))
+                .namespace("inventory")
+                .add_function("count", |In((_item,)): In<(String,)>| 3_i64)
+                .finish();
+        });
+        app.update();
+
+        let annotations = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(annotations.starts_with("---@meta"));
+        assert!(annotations.contains("function Promise:and_then(callback) end"));
+        assert!(annotations.contains(
+            "---@param arg1 BevyEntity\n---@param arg2 Vec3\n\
+             ---@return Promise # resolves with nil\nfunction spawn_at(arg1, arg2) end"
+        ));
+        assert!(annotations.contains("\ninventory = {}\n"));
+        assert!(annotations.contains(
+            "---Registered by inventory.\n---@param arg1 string\n\
+             ---@return Promise # resolves with integer\nfunction inventory.count(arg1) end"
+        ));
+    }
+
+    #[test]
+    fn mod_manager_loads_and_toggles_mods() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct LoadedMods(Vec<String>);
+
+        app.world_mut().init_resource::<LoadedMods>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.add_function(
+                String::from("mod_loaded"),
+                |In((name,)): In<(String,)>, mut loaded: ResMut<LoadedMods>| {
+                    loaded.0.push(name);
+                },
+            );
+        });
+        app.add_plugins(ModManagerPlugin::new("tests/mods").with_language::<LuaRuntime>("lua"));
+
+        let names = |app: &App| {
+            app.world()
+                .resource::<ModManager>()
+                .mods()
+                .iter()
+                .map(|m| m.manifest.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&app), vec!["base", "addon"]);
+
+        for _ in 0..10 {
+            app.update();
+        }
+        let mut loaded = app.world().resource::<LoadedMods>().0.clone();
+        loaded.sort();
+        assert_eq!(loaded, vec!["addon", "base"]);
+
+        app.world_mut()
+            .resource_mut::<ModManager>()
+            .disable("base")
+            .unwrap();
+        app.update();
+        let manager = app.world().resource::<ModManager>();
+        assert!(manager.mods().iter().all(|m| !m.is_enabled()));
+        assert_eq!(
+            app.world_mut()
+                .query::<&Script<LuaScript>>()
+                .iter(app.world())
+                .count(),
+            0
+        );
+
+        app.world_mut()
+            .resource_mut::<ModManager>()
+            .enable("addon")
+            .unwrap();
+        app.update();
+        let manager = app.world().resource::<ModManager>();
+        assert!(manager.mods().iter().all(|m| m.is_enabled()));
+        assert_eq!(
+            app.world_mut()
+                .query::<&Script<LuaScript>>()
+                .iter(app.world())
+                .count(),
+            2
+        );
+
+        assert!(matches!(
+            app.world_mut()
+                .resource_mut::<ModManager>()
+                .enable("missing"),
+            Err(ModError::UnknownMod(_))
+        ));
+    }
+
+    #[test]
+    fn mod_manager_only_discovers_listed_mods() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.add_function(String::from("mod_loaded"), |In((_,)): In<(String,)>| {});
+        });
+        app.add_plugins(
+            ModManagerPlugin::new("tests/mods")
+                .with_mods(["base"])
+                .with_language::<LuaRuntime>("lua"),
+        );
+
+        let manager = app.world().resource::<ModManager>();
+        assert_eq!(manager.mods().len(), 1);
+        assert_eq!(manager.mods()[0].manifest.name, "base");
+        assert_eq!(manager.mods()[0].path, "tests/mods/base");
+    }
+
+    #[test]
+    fn mod_manager_loads_mods_created_in_watched_directory() {
+        let directory =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/tests/mods_watched");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct LoadedMods(Vec<String>);
+
+        #[derive(Component)]
+        struct Modded;
+
+        app.world_mut().init_resource::<LoadedMods>();
+        app.add_plugins(bevy::time::TimePlugin);
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.add_function(
+                String::from("mod_loaded"),
+                |In((name,)): In<(String,)>, mut loaded: ResMut<LoadedMods>| {
+                    loaded.0.push(name);
+                },
+            );
+        });
+        app.add_plugins(
+            ModManagerPlugin::new("tests/mods_watched")
+                .with_language::<LuaRuntime>("lua")
+                .with_template(|entity| {
+                    entity.insert(Modded);
+                })
+                .watch(std::time::Duration::ZERO),
+        );
+        app.update();
+        assert!(app.world().resource::<ModManager>().mods().is_empty());
+
+        let mod_directory = directory.join("dropped");
+        std::fs::create_dir(&mod_directory).unwrap();
+        std::fs::write(mod_directory.join("main.lua"), "mod_loaded(\"dropped\")").unwrap();
+        std::fs::write(
+            mod_directory.join("mod.json"),
+            r#"{ "name": "dropped", "version": "1.0.0", "entry": "main.lua" }"#,
+        )
+        .unwrap();
+        for _ in 0..10 {
+            app.update();
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let manager = app.world().resource::<ModManager>();
+        assert_eq!(manager.mods().len(), 1);
+        let entity = manager.get("dropped").unwrap().entity().unwrap();
+        assert!(app.world().get::<Modded>(entity).is_some());
+        assert_eq!(app.world().resource::<LoadedMods>().0, vec!["dropped"]);
+    }
+
+    #[derive(Default, Resource)]
+    struct Records(Vec<String>);
+
+    fn record(In((record,)): In<(String,)>, mut records: ResMut<Records>) {
+        records.0.push(record);
+    }
+
+    #[test]
+    fn integer_arguments_out_of_range_raise_script_errors() {
+        let mut app = build_test_app();
+
+        app.init_resource::<Records>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("takes_byte"), |In((_,)): In<(u8,)>| {})
+                .add_function(String::from("record"), record);
+        });
+
+        run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/integer_arguments.lua".to_string(),
+            || {},
+        );
+
+        let records = &app.world().resource::<Records>().0;
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0], "ok");
+        for (record, got) in records[1..]
+            .iter()
+            .zip(["integer 256", "integer -1", "number 2.5"])
+        {
+            assert!(record.contains(&format!(
+                "argument 1 of function `takes_byte` has to be integer in range of u8, got {got}"
+            )));
+        }
+    }
+
+    // LuaJIT stores every number as a double, which holds integers of up to 53 bits exactly.
+    #[cfg(feature = "lua")]
+    #[test]
+    fn returned_integers_are_not_rounded() {
+        let mut app = build_test_app();
+
+        app.init_resource::<Records>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("exact"), || -> i64 { 1 << 53 })
+                .add_function(String::from("rounded"), || -> i64 { (1 << 53) + 1 })
+                .add_function(String::from("record"), record);
+        });
+
+        run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/rounded_integers.lua".to_string(),
+            || {},
+        );
+        app.update();
+
+        assert_eq!(app.world().resource::<Records>().0, vec!["exact"]);
+    }
+
+    #[cfg(feature = "lua")]
+    #[test]
+    fn constants_out_of_range_are_not_registered() {
+        let mut app = build_test_app();
+
+        app.init_resource::<Records>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_constant(String::from("EXACT"), 1i64 << 53)
+                .add_constant(String::from("ROUNDED"), (1i64 << 53) + 1)
+                .add_function(String::from("record"), record);
+        });
+
+        run_script::<LuaRuntime, _, _>(
+            &mut app,
+            "tests/lua/out_of_range_constant.lua".to_string(),
+            || {},
+        );
+
+        assert_eq!(
+            app.world().resource::<Records>().0,
+            vec!["9.007199254741e+15", "nil"]
+        );
+    }
+
+    #[cfg(feature = "lua54")]
+    #[test]
+    fn integers_and_floats_keep_their_types() {
+        let mut app = build_test_app();
+
+        app.init_resource::<Records>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime
+                .add_function(String::from("integer"), || -> i64 { 2 })
+                .add_function(String::from("float"), || -> f64 { 2.0 })
+                .add_function(String::from("echo"), |In((value,)): In<(ScriptValue,)>| {
+                    value
+                })
+                .add_function(String::from("record"), record);
+        });
+
+        run_script::<LuaRuntime, _, _>(&mut app, "tests/lua/number_types.lua".to_string(), || {});
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<Records>().0,
+            vec!["integer", "float", "integer", "float"]
+        );
+    }
+}
+
+mod mods_tests {
+    use bevy_scriptum::mods::*;
+
+    fn manifest(name: &str, dependencies: &[&str]) -> ModManifest {
+        ModManifest {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            entry: "main.lua".to_string(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            language: None,
+            requires_api: None,
+        }
+    }
+
+    #[test]
+    fn load_order_puts_dependencies_first() {
+        let (ordered, errors) = resolve_load_order([
+            manifest("c", &["b"]),
+            manifest("a", &[]),
+            manifest("b", &["a"]),
+        ]);
+        let names = ordered.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn load_order_skips_missing_dependencies_and_cycles() {
+        let (ordered, errors) = resolve_load_order([
+            manifest("a", &["b"]),
+            manifest("b", &["a"]),
+            manifest("c", &["missing"]),
+            manifest("d", &[]),
+        ]);
+        let names = ordered.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["d"]);
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ModError::DependencyCycle(name) if name == "a")));
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, ModError::MissingDependency(name, _) if name == "c")));
+    }
+
+    #[test]
+    fn language_defaults_to_entry_extension() {
+        assert_eq!(manifest("a", &[]).language(), "lua");
+    }
+}
+
+#[cfg(all(
+    any(feature = "lua", feature = "lua54", feature = "luau"),
+    feature = "rhai"
+))]
+mod multi_runtime_tests {
+    use bevy::prelude::*;
+    use bevy_scriptum::{
+        runtimes::{lua::prelude::*, rhai::prelude::*},
+        DynScriptingRuntimes, ScriptExtensionPolicy, MAX_DYN_FUNCTION_ARITY,
+    };
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn mirrored_function_and_constant_are_available_in_both_runtimes() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Sum(i64);
+
+        app.world_mut().init_resource::<Sum>();
+
+        app.add_scripting::<LuaRuntime>(|_| {})
+            .add_scripting::<RhaiRuntime>(|_| {})
+            .add_mirrored_scripting_api::<LuaRuntime, RhaiRuntime>(|runtime| {
+                runtime
+                    .add_function(
+                        String::from("rust_func"),
+                        |In((x,)): In<(i64,)>, mut sum: ResMut<Sum>| {
+                            sum.0 += x;
+                        },
+                    )
+                    .add_constant(String::from("MAGIC"), 5i64);
+            });
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn(Script::<LuaScript>::new(
+            asset_server.load("tests/lua/mirrored_function_with_constant.lua"),
+        ));
+        app.world_mut().spawn(Script::<RhaiScript>::new(
+            asset_server.load("tests/rhai/mirrored_function_with_constant.rhai"),
+        ));
+
+        for _ in 0..100 {
+            app.update();
+            if app.world().resource::<Sum>().0 == 10 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(app.world().resource::<Sum>().0, 10);
+    }
+
+    #[test]
+    fn blackboard_is_shared_between_runtimes() {
+        let mut app = build_test_app();
+
+        #[derive(Default, Resource)]
+        struct Records(Vec<String>);
+
+        app.init_resource::<Records>();
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.enable_blackboard();
+        })
+        .add_scripting::<RhaiRuntime>(|runtime| {
+            runtime.enable_blackboard().add_function(
+                String::from("record"),
+                |In((record,)): In<(String,)>, mut records: ResMut<Records>| {
+                    records.0.push(record);
+                },
+            );
+        });
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn(Script::<LuaScript>::new(
+            asset_server.load("tests/lua/raise_alarm.lua"),
+        ));
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<Blackboard>().get("patrol"),
+            Some(&ScriptValue::Map(
+                [
+                    (
+                        String::from("points"),
+                        ScriptValue::Array(vec![
+                            ScriptValue::Int(1),
+                            ScriptValue::Int(2),
+                            ScriptValue::Int(3)
+                        ])
+                    ),
+                    (String::from("speed"), ScriptValue::Float(1.5)),
+                ]
+                .into()
+            ))
+        );
+
+        app.world_mut().spawn(Script::<RhaiScript>::new(
+            asset_server.load("tests/rhai/check_alarm.rhai"),
+        ));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world().resource::<Records>().0,
+            vec!["raised", "3 points at 1.5"]
+        );
+    }
+
+    #[test]
+    fn extension_policy_assigns_extension_to_runtime() {
+        let policy = ScriptExtensionPolicy::default().assign::<RhaiRuntime>("script");
+
+        assert!(policy.handles::<RhaiRuntime>("script"));
+        assert!(!policy.handles::<LuaRuntime>("script"));
+        assert!(policy.handles::<LuaRuntime>("lua"));
+        assert_eq!(
+            policy.extensions_for::<RhaiRuntime>(),
+            vec!["rhai", "script"]
+        );
+    }
+
+    #[test]
+    fn dyn_runtimes_call_scripts_of_every_runtime() {
+        let mut app = build_test_app();
+
+        app.add_scripting::<LuaRuntime>(|runtime| {
+            runtime.enable_dyn_runtime();
+        })
+        .add_scripting::<RhaiRuntime>(|runtime| {
+            runtime.enable_dyn_runtime();
+        });
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        app.world_mut()
+            .resource_scope(|world, runtimes: Mut<DynScriptingRuntimes>| {
+                assert_eq!(runtimes.iter().count(), 2);
+                assert!(runtimes.get::<LuaRuntime>().is_some());
+                for runtime in runtimes.iter() {
+                    let records = records.clone();
+                    runtime
+                        .add_function(
+                            world,
+                            String::from("record"),
+                            1,
+                            Arc::new(move |args: Vec<ScriptValue>| {
+                                records.lock().unwrap().push(args[0].clone());
+                                ScriptValue::Nil
+                            }),
+                        )
+                        .unwrap();
+                    assert!(matches!(
+                        runtime.add_function(
+                            world,
+                            String::from("too_many"),
+                            MAX_DYN_FUNCTION_ARITY + 1,
+                            Arc::new(|_| ScriptValue::Nil),
+                        ),
+                        Err(ScriptingError::UnsupportedArity { .. })
+                    ));
+                }
+            });
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        app.world_mut().spawn(Script::<LuaScript>::new(
+            asset_server.load("tests/lua/dyn_runtime.lua"),
+        ));
+        app.world_mut().spawn(Script::<RhaiScript>::new(
+            asset_server.load("tests/rhai/dyn_runtime.rhai"),
+        ));
+        for _ in 0..100 {
+            app.update();
+            let mut evaluated = app
+                .world_mut()
+                .query_filtered::<(), Or<(With<LuaScriptData>, With<RhaiScriptData>)>>();
+            if evaluated.iter(app.world()).count() == 2 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        app.world_mut()
+            .resource_scope(|world, runtimes: Mut<DynScriptingRuntimes>| {
+                for runtime in runtimes.iter() {
+                    assert!(runtime
+                        .functions(world)
+                        .iter()
+                        .any(|function| function.name == "record"));
+                    let entities = runtime.scripted_entities(world);
+                    assert_eq!(entities.len(), 1, "{}", runtime.name());
+                    let entity = entities[0];
+
+                    let sum = runtime
+                        .call_fn(world, entity, "add", vec![2.into(), 3.into()])
+                        .unwrap();
+                    assert_eq!(sum.as_float(), Some(5.0));
+                    assert_eq!(
+                        runtime
+                            .call_fn_if_exists(world, entity, "missing", vec![])
+                            .unwrap(),
+                        None
+                    );
+                    runtime
+                        .call_fn(world, entity, "notify", vec!["hello".into()])
+                        .unwrap();
+
+                    runtime.call_fn(world, entity, "bump", vec![]).unwrap();
+                    let counter = runtime.call_fn(world, entity, "bump", vec![]).unwrap();
+                    assert_eq!(counter.as_float(), Some(2.0));
+                    runtime.eval(world, entity).unwrap();
+                    let counter = runtime.call_fn(world, entity, "bump", vec![]).unwrap();
+                    assert_eq!(counter.as_float(), Some(1.0));
+                }
+            });
+        app.update();
+
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![ScriptValue::from("hello"), ScriptValue::from("hello")]
+        );
+    }
+}
+
+mod decoder_tests {
+    use bevy_scriptum::decoders::*;
+
+    #[test]
+    fn xor_decoder_roundtrips() {
+        let decoder = XorDecoder::new("key");
+        let source = b"print('hello')".to_vec();
+        let encoded = decoder.decode(source.clone()).unwrap();
+        assert_ne!(encoded, source);
+        assert_eq!(decoder.decode(encoded).unwrap(), source);
+    }
+
+    #[cfg(feature = "aes")]
+    #[test]
+    fn aes_decoder_roundtrips() {
+        let decoder = Aes256CtrDecoder::new([7; 32]);
+        let source = b"print('hello')".to_vec();
+        let encoded = decoder.encode([3; 16], source.clone());
+        assert_eq!(encoded.len(), source.len() + 16);
+        assert_eq!(decoder.decode(encoded).unwrap(), source);
+    }
+
+    #[cfg(feature = "aes")]
+    #[test]
+    fn aes_decoder_rejects_truncated_input() {
+        let decoder = Aes256CtrDecoder::new([7; 32]);
+        assert!(decoder.decode(vec![0; 8]).is_err());
+    }
+}
+
+#[cfg(feature = "http")]
+mod http_transport_tests {
+    use std::io::{BufRead as _, BufReader, Write as _};
+
+    use bevy_scriptum::http::{HttpError, HttpRequest, HttpTransport, PlainHttpTransport};
+
+    /// Spawns a server answering a single request with provided raw response.
+    fn spawn_http_server(response: Vec<u8>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while line != "\r\n" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            let _ = stream.write_all(&response);
+        });
+        url
+    }
+
+    fn get(url: String) -> Result<String, HttpError> {
+        PlainHttpTransport::default()
+            .send(HttpRequest {
+                method: String::from("GET"),
+                url,
+                body: String::new(),
+            })
+            .map(|response| response.body)
+    }
+
+    #[test]
+    fn chunks_are_split_on_bytes() {
+        let mut response =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nz\xc3".to_vec();
+        response.extend_from_slice(b"\xb3\r\n5\r\n\xc5\x82ty\xff\r\n0\r\n\r\n");
+
+        let body = get(spawn_http_server(response)).unwrap();
+
+        assert_eq!(body, "z\u{f3}\u{142}ty\u{fffd}");
+    }
+
+    #[test]
+    fn responses_over_limit_are_not_read() {
+        let mut response = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        response.resize(PlainHttpTransport::MAX_RESPONSE_BYTES as usize + 1, b'a');
+
+        let error = get(spawn_http_server(response)).unwrap_err();
+
+        assert!(matches!(
+            error,
+            HttpError::ResponseTooLarge(PlainHttpTransport::MAX_RESPONSE_BYTES)
+        ));
+    }
+
+    #[test]
+    fn responses_of_maximum_size_are_read() {
+        let mut response = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        let head = response.len();
+        response.resize(PlainHttpTransport::MAX_RESPONSE_BYTES as usize, b'a');
+
+        let body = get(spawn_http_server(response)).unwrap();
+
+        assert_eq!(
+            body.len(),
+            PlainHttpTransport::MAX_RESPONSE_BYTES as usize - head
+        );
+    }
+}
+
+mod scaffold_tests {
+    use std::any::TypeId;
+
+    use bevy_scriptum::scaffold::{starter_script, ScaffoldLanguage};
+    use bevy_scriptum::ScriptFunctionInfo;
+
+    fn give_item() -> ScriptFunctionInfo {
+        ScriptFunctionInfo {
+            name: String::from("give"),
+            namespace: Some(String::from("inventory")),
+            arg_types: vec![TypeId::of::<String>(), TypeId::of::<Option<i64>>()],
+            arg_type_names: vec![
+                std::any::type_name::<String>(),
+                std::any::type_name::<Option<i64>>(),
+            ],
+            return_type_name: "bool",
+            capabilities: vec![],
+            source: Some(String::from("inventory_plugin")),
+        }
+    }
+
+    #[test]
+    fn lua_starter_script_lists_functions_and_defines_hooks() {
+        let script = starter_script(ScaffoldLanguage::Lua, &[give_item()]);
+        assert!(script.contains(
+            "--   inventory.give(String, Option<i64>) -> bool, registered by inventory_plugin\n"
+        ));
+        assert!(script.contains("function on_fixed_update(dt)\nend\n"));
+        assert!(script.contains("function on_collision_start(other)\nend\n"));
+    }
+
+    #[test]
+    fn rhai_starter_script_lists_functions_and_defines_hooks() {
+        let script = starter_script(ScaffoldLanguage::Rhai, &[give_item()]);
+        assert!(script.contains("//   inventory::give(String, Option<i64>) -> bool"));
+        assert!(script.contains("fn on_detach() {\n}\n"));
+        assert!(!script.contains("--"));
+    }
+}
+
+#[cfg(any(feature = "lua", feature = "lua54", feature = "luau"))]
+mod lua_conformance_tests {
+    use bevy_scriptum::runtimes::lua::prelude::*;
+
+    bevy_scriptum::conformance_tests!(LuaRuntime, "conformance/lua", "lua");
+}
+
+#[cfg(feature = "rhai")]
+mod rhai_conformance_tests {
+    use bevy_scriptum::runtimes::rhai::prelude::*;
+
+    bevy_scriptum::conformance_tests!(RhaiRuntime, "conformance/rhai", "rhai");
 }